@@ -13,61 +13,201 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// #[derive(Clone)]
-// pub struct Backend {
-// config: Config,
-// }
-//
-// impl Backend {
-// pub fn new(config: Config) -> Backend {
-// Backend { config }
-// }
-//
-// fn ldap_settings(&self) -> LdapConnSettings {
-// LdapConnSettings::new()
-// .set_conn_timeout(*self.config.conn_timeout)
-// .set_starttls(self.config.starttls)
-// .set_no_tls_verify(self.config.insecure_skip_tls_verify)
-// }
-//
-// Spawns a new LDAP connection to the server and spawns the Tokio task associated with
-// it, does the stuff you need in the function body, and destroys the connection
-// altogether. pub async fn conn<F, Fut, Res, Err>(&self, f: F) -> Result<Res, Err>
-// where
-// F: FnOnce(Ldap) -> Fut + Send,
-// Fut: Future<Output = Result<Res, Err>>,
-// Err: From<ldap3::LdapError>,
-// {
-// let (conn, ldap) =
-// LdapConnAsync::from_url_with_settings(self.ldap_settings(),
-// &Url::parse(&self.config.host).unwrap()) .await?;
-//
-// tokio::spawn(async move {
-// if let Err(e) = conn.drive().await {
-// error!(error = %e, "failed to drive LDAP connection to completion");
-// sentry::capture_error(&e);
-// }
-// });
-//
-// f(ldap).await
-// }
-// }
-//
-// impl Authenticator for Backend {
-// fn authenticate<'u>(&'u self, user: &'u User, password: String) -> BoxedFuture<'u,
-// eyre::Result<()>> { Box::pin(async move {
-// let bind_dn = self.config.bind_dn.clone();
-// self.conn(|mut conn| async move {
-// match conn
-// .simple_bind(bind_dn.replace("%u", &user.username).as_str(), password.as_str())
-// .await
-// {
-// Ok(res) if res.rc == 0 => Ok(()),
-// Ok(res) if res.rc == 6 => Err(InvalidPassword.into()),
-// Ok(res) => Err(eyre!("received unexpected error from LDAP service: {res:?}")),
-// Err(e) => Err(e.into()),
-// }
-// })
-// .await
-// })
-// }
+//! # 🐻‍❄️📦 `charted_authz_ldap`
+//! This crate implements the [`Authenticator`] trait for authenticating users against
+//! a LDAP server.
+//!
+//! Users are authenticated by binding as the DN that is templated from the `bind_dn`
+//! setting (`%u` is replaced by the user's username). If the DN is a distinguished
+//! name, then the user's entry must also match the `filter_query` setting.
+//!
+//! LDAP users still require a local user to exist; the [`sync`] module allows
+//! provisioning and updating local users from the LDAP server's entries if
+//! `schedule_new_users` or `schedule_user_updates` are enabled.
+
+#[cfg(test)]
+mod tests;
+
+pub mod sync;
+
+use charted_authz::{Authenticator, InvalidPassword, Request};
+use charted_config::sessions::ldap::Config;
+use charted_core::BoxedFuture;
+use eyre::{bail, eyre};
+use ldap3::{Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry, dn_escape, ldap_escape};
+use std::ops::Deref;
+use tracing::{debug, error, instrument, trace};
+
+/// LDAP result code when the credentials given in a bind request are invalid.
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// A entry that was queried from the LDAP server, mapped from the configured attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub dn: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Backend {
+    config: Config,
+}
+
+impl Backend {
+    pub fn new(config: Config) -> Backend {
+        Backend { config }
+    }
+
+    /// Returns the configuration of this backend.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Establishes a new connection to the LDAP server. The connection is driven in a
+    /// background task until the returned [`Ldap`] handle is unbound or dropped.
+    async fn connect(&self) -> eyre::Result<Ldap> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(*self.config.connect_timeout.deref())
+            .set_starttls(self.config.starttls)
+            .set_no_tls_verify(self.config.insecure_skip_tls_verify);
+
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.config.server).await?;
+        tokio::spawn(async move {
+            if let Err(e) = conn.drive().await {
+                error!(error = %e, "failed to drive LDAP connection to completion");
+                sentry::capture_error(&e);
+            }
+        });
+
+        Ok(ldap)
+    }
+
+    /// Returns the DN to bind as for `username`.
+    fn bind_dn(&self, username: &str) -> String {
+        self.config.bind_dn.replace("%u", &dn_escape(username))
+    }
+
+    /// Returns the base DN that users live in, which is the `bind_dn` without its first
+    /// relative distinguished name: `uid=%u,ou=people,dc=domain,dc=com` becomes
+    /// `ou=people,dc=domain,dc=com`.
+    ///
+    /// Returns [`None`] if `bind_dn` isn't a distinguished name, like Active Directory's
+    /// `%u@domain` form.
+    fn search_base(&self) -> Option<&str> {
+        let (rdn, base) = self.config.bind_dn.split_once(',')?;
+        (rdn.contains("%u") && rdn.contains('=') && !base.is_empty()).then_some(base.trim())
+    }
+
+    /// Returns the search filter from `filter_query` for `username`. If `username` is
+    /// [`None`], then the filter will match all users.
+    fn filter(&self, username: Option<&str>) -> String {
+        let value = match username {
+            Some(username) => ldap_escape(username).into_owned(),
+            None => String::from("*"),
+        };
+
+        let filter = self
+            .config
+            .filter_query
+            .replace("<username>", &self.config.attributes.username)
+            .replace("%u", &value);
+
+        match filter.starts_with('(') {
+            true => filter,
+            false => format!("({filter})"),
+        }
+    }
+
+    fn attributes(&self) -> [&str; 3] {
+        let attrs = &self.config.attributes;
+        [&attrs.username, &attrs.display_name, &attrs.email]
+    }
+
+    fn to_entry(&self, entry: SearchEntry) -> Option<Entry> {
+        let attrs = &self.config.attributes;
+        let first = |attr: &str| entry.attrs.get(attr).and_then(|values| values.first()).cloned();
+
+        Some(Entry {
+            username: first(&attrs.username)?,
+            display_name: first(&attrs.display_name),
+            email: first(&attrs.email),
+            dn: entry.dn.clone(),
+        })
+    }
+
+    /// Queries all users from the LDAP server that match `filter_query`.
+    #[instrument(name = "charted.authz.ldap.entries", skip_all)]
+    pub async fn entries(&self) -> eyre::Result<Vec<Entry>> {
+        let Some(base) = self.search_base() else {
+            bail!(
+                "unable to determine which DN to search users in from `bind_dn` [{}]",
+                self.config.bind_dn
+            );
+        };
+
+        let mut ldap = self.connect().await?;
+        let filter = self.filter(None);
+        trace!(%base, %filter, "searching for users");
+
+        let (entries, _) = ldap
+            .search(base, Scope::Subtree, &filter, self.attributes())
+            .await?
+            .success()?;
+
+        let _ = ldap.unbind().await;
+        Ok(entries
+            .into_iter()
+            .map(SearchEntry::construct)
+            .filter_map(|entry| self.to_entry(entry))
+            .collect())
+    }
+}
+
+impl Authenticator for Backend {
+    #[instrument(name = "charted.authz.ldap.authenticate", skip_all, fields(%user.username, %user.id))]
+    fn authenticate<'a>(
+        &'a self,
+        Request { user, password, .. }: Request<'a>,
+    ) -> BoxedFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            // an empty password is an unauthenticated bind, which LDAP servers will
+            // happily accept.
+            if password.is_empty() {
+                return Err(InvalidPassword.into());
+            }
+
+            let mut ldap = self.connect().await?;
+            let dn = self.bind_dn(user.username.as_str());
+
+            let result = ldap.simple_bind(&dn, &password).await?;
+            match result.rc {
+                0 => {}
+                INVALID_CREDENTIALS => return Err(InvalidPassword.into()),
+                _ => return Err(eyre!("received unexpected result from LDAP server: {result}")),
+            }
+
+            // If we can search, then make sure the user's entry is allowed by the filter.
+            if self.search_base().is_some() {
+                let filter = self.filter(Some(user.username.as_str()));
+                let (entries, _) = ldap
+                    .search(&dn, Scope::Base, &filter, self.attributes())
+                    .await?
+                    .success()?;
+
+                // users that the filter rejects are refused like a wrong password, so
+                // that it isn't reported as a server error.
+                if entries.is_empty() {
+                    let _ = ldap.unbind().await;
+                    debug!(%user.username, "user is not allowed by the LDAP server's filter query");
+
+                    return Err(InvalidPassword.into());
+                }
+            }
+
+            let _ = ldap.unbind().await;
+            Ok(())
+        })
+    }
+}
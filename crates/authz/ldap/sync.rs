@@ -0,0 +1,177 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provisioning of local users from the entries of a LDAP server.
+
+use crate::{Backend, Entry};
+use charted_core::ulid::Generator;
+use charted_database::entities::{OrganizationEntity, UserEntity, organization, user};
+use charted_types::{Ulid, name::Name};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+};
+use tracing::{instrument, warn};
+
+/// Outcome of a [`Backend::sync`] call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// List of users that were created.
+    pub created: Vec<Ulid>,
+
+    /// Amount of users that had their email or display name updated.
+    pub updated: usize,
+}
+
+impl Backend {
+    /// Synchronizes local users with all entries that are available from the LDAP
+    /// server.
+    ///
+    /// * If `schedule_new_users` is enabled, then entries without a local user will
+    ///   be created without a password, so they can only be authenticated by this backend.
+    /// * If `schedule_user_updates` is enabled, then the email and display name of
+    ///   existing users will be updated from the entry's attributes.
+    #[instrument(name = "charted.authz.ldap.sync", skip_all)]
+    pub async fn sync(&self, db: &DatabaseConnection, ulid: &Generator) -> eyre::Result<Report> {
+        let mut report = Report::default();
+        if !self.config.schedule_new_users && !self.config.schedule_user_updates {
+            return Ok(report);
+        }
+
+        for entry in self.entries().await? {
+            let Ok(username) = entry.username.parse::<Name>() else {
+                warn!(
+                    dn = entry.dn,
+                    username = entry.username,
+                    "skipping entry with invalid username"
+                );
+                continue;
+            };
+
+            match UserEntity::find()
+                .filter(user::Column::Username.eq(username.clone()))
+                .one(db)
+                .await?
+            {
+                Some(model) if self.config.schedule_user_updates => {
+                    if update(db, model, &entry).await? {
+                        report.updated += 1;
+                    }
+                }
+
+                Some(_) => {}
+                None if self.config.schedule_new_users => {
+                    if let Some(id) = create(db, ulid, username, &entry).await? {
+                        report.created.push(id);
+                    }
+                }
+
+                None => {}
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+async fn create(
+    db: &DatabaseConnection,
+    ulid: &Generator,
+    username: Name,
+    entry: &Entry,
+) -> eyre::Result<Option<Ulid>> {
+    let Some(ref email) = entry.email else {
+        warn!(dn = entry.dn, %username, "skipping new user as entry has no email");
+        return Ok(None);
+    };
+
+    if UserEntity::find()
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        warn!(dn = entry.dn, %username, "skipping new user as email is taken by another user");
+        return Ok(None);
+    }
+
+    // users and organizations share a namespace, so an organization's name can't be
+    // taken over by a user.
+    if OrganizationEntity::find()
+        .filter(organization::Column::Name.eq(username.clone()))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        warn!(dn = entry.dn, %username, "skipping new user as username is taken by an organization");
+        return Ok(None);
+    }
+
+    let id: Ulid = ulid.generate()?.into();
+    let model = user::Model {
+        verified_publisher: false,
+        prefers_gravatar: false,
+        gravatar_email: None,
+        description: None,
+        avatar_hash: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        password: None,
+        username,
+        email: email.to_owned(),
+        admin: false,
+        name: entry.display_name.clone(),
+        id,
+    };
+
+    UserEntity::insert(model.into_active_model()).exec(db).await?;
+    Ok(Some(id))
+}
+
+async fn update(db: &DatabaseConnection, model: user::Model, entry: &Entry) -> eyre::Result<bool> {
+    let mut email = entry.email.as_ref().filter(|email| **email != model.email);
+    if let Some(new) = email &&
+        UserEntity::find()
+            .filter(user::Column::Email.eq(new))
+            .one(db)
+            .await?
+            .is_some()
+    {
+        warn!(dn = entry.dn, username = %model.username, "not updating email as it is taken by another user");
+        email = None;
+    }
+
+    let name = entry
+        .display_name
+        .as_ref()
+        .filter(|name| model.name.as_ref() != Some(*name));
+    if email.is_none() && name.is_none() {
+        return Ok(false);
+    }
+
+    let mut active = model.into_active_model();
+    if let Some(email) = email {
+        active.email = ActiveValue::set(email.to_owned());
+    }
+
+    if let Some(name) = name {
+        active.name = ActiveValue::set(Some(name.to_owned()));
+    }
+
+    active.updated_at = ActiveValue::set(Utc::now());
+    active.update(db).await?;
+
+    Ok(true)
+}
@@ -0,0 +1,398 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the LDAP backend, which run against a small in-process LDAP server
+//! that only understands the bind, search, and unbind operations.
+
+use super::*;
+use bytes::BytesMut;
+use charted_authz::InvalidPassword;
+use charted_config::{database, sessions::ldap::Attributes};
+use charted_core::serde::Duration;
+use charted_database::entities::{UserEntity, user};
+use charted_types::User;
+use ldap3::asn1::{
+    ASNTag, Enumerated, Integer, OctetString, PL, Sequence, Set, StructureTag, Tag, TagClass, parse_tag, write,
+};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use std::{borrow::Cow, sync::Arc};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const BIND_DN: &str = "uid=%u,ou=people,dc=charts,dc=dev";
+
+struct DirEntry {
+    dn: &'static str,
+    password: &'static str,
+    attrs: Vec<(&'static str, &'static str)>,
+}
+
+fn directory() -> Vec<DirEntry> {
+    vec![
+        DirEntry {
+            dn: "uid=noel,ou=people,dc=charts,dc=dev",
+            password: "noeliscutieuwu",
+            attrs: vec![
+                ("uid", "noel"),
+                ("displayName", "Noel Towa"),
+                ("mail", "noel@noelware.org"),
+                ("memberOf", "cn=charted,ou=groups,dc=charts,dc=dev"),
+            ],
+        },
+        DirEntry {
+            dn: "uid=boel,ou=people,dc=charts,dc=dev",
+            password: "thetwinofboel",
+            attrs: vec![("uid", "boel"), ("mail", "boel@noelware.org")],
+        },
+        DirEntry {
+            dn: "uid=nomail,ou=people,dc=charts,dc=dev",
+            password: "nomailnomail",
+            attrs: vec![("uid", "nomail")],
+        },
+    ]
+}
+
+/// Spawns the LDAP server in the background and returns the URL to connect to it.
+async fn server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let directory = Arc::new(directory());
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(connection(stream, directory.clone()));
+        }
+    });
+
+    format!("ldap://{addr}")
+}
+
+async fn connection(mut stream: TcpStream, directory: Arc<Vec<DirEntry>>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    loop {
+        let (consumed, message) = match parse_tag(&buf) {
+            Ok((rest, tag)) => (buf.len() - rest.len(), tag),
+            Err(_) => match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => {
+                    buf.extend_from_slice(&chunk[..n]);
+                    continue;
+                }
+            },
+        };
+
+        buf.drain(..consumed);
+
+        let mut message = message.expect_constructed().unwrap().into_iter();
+        let id = integer(message.next().unwrap());
+        let op = message.next().unwrap();
+
+        let responses = match op.id {
+            0 => vec![bind(&directory, op)],
+            3 => search(&directory, op),
+            _ => return,
+        };
+
+        let mut out = BytesMut::new();
+        for response in responses {
+            let message = Sequence {
+                inner: vec![
+                    Tag::Integer(Integer {
+                        inner: id,
+                        ..Default::default()
+                    }),
+                    Tag::StructureTag(response),
+                ],
+                ..Default::default()
+            };
+
+            write::encode_into(&mut out, message.into_structure()).unwrap();
+        }
+
+        if stream.write_all(&out).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn bind(directory: &[DirEntry], op: StructureTag) -> StructureTag {
+    let mut op = op.expect_constructed().unwrap().into_iter().skip(1);
+    let dn = string(op.next().unwrap());
+    let password = string(op.next().unwrap());
+
+    let ok = (dn.is_empty() && password.is_empty()) ||
+        directory
+            .iter()
+            .any(|entry| entry.dn.eq_ignore_ascii_case(&dn) && entry.password == password);
+
+    result(1, if ok { 0 } else { INVALID_CREDENTIALS as i64 })
+}
+
+fn search(directory: &[DirEntry], op: StructureTag) -> Vec<StructureTag> {
+    let op = op.expect_constructed().unwrap();
+    let base = string(op[0].clone()).to_lowercase();
+    let scope = integer(op[1].clone());
+    let filter = op[6].clone();
+
+    let mut responses = directory
+        .iter()
+        .filter(|entry| {
+            let dn = entry.dn.to_lowercase();
+            match scope {
+                0 => dn == base,
+                _ => dn.ends_with(&base),
+            }
+        })
+        .filter(|entry| matches(entry, &filter))
+        .map(|entry| {
+            let attrs = entry
+                .attrs
+                .iter()
+                .map(|(name, value)| {
+                    Tag::Sequence(Sequence {
+                        inner: vec![
+                            octet_string(name),
+                            Tag::Set(Set {
+                                inner: vec![octet_string(value)],
+                                ..Default::default()
+                            }),
+                        ],
+                        ..Default::default()
+                    })
+                })
+                .collect();
+
+            Sequence {
+                class: TagClass::Application,
+                id: 4,
+                inner: vec![
+                    octet_string(entry.dn),
+                    Tag::Sequence(Sequence {
+                        inner: attrs,
+                        ..Default::default()
+                    }),
+                ],
+            }
+            .into_structure()
+        })
+        .collect::<Vec<_>>();
+
+    responses.push(result(5, 0));
+    responses
+}
+
+fn matches(entry: &DirEntry, filter: &StructureTag) -> bool {
+    let has = |name: &str, value: Option<&str>| {
+        entry
+            .attrs
+            .iter()
+            .any(|(n, v)| n.eq_ignore_ascii_case(name) && value.is_none_or(|value| v.eq_ignore_ascii_case(value)))
+    };
+
+    match (filter.id, &filter.payload) {
+        (0, PL::C(filters)) => filters.iter().all(|filter| matches(entry, filter)),
+        (1, PL::C(filters)) => filters.iter().any(|filter| matches(entry, filter)),
+        (2, PL::C(filters)) => !matches(entry, &filters[0]),
+        (3, PL::C(ava)) => has(&string(ava[0].clone()), Some(&string(ava[1].clone()))),
+        (7, PL::P(name)) => has(&String::from_utf8_lossy(name), None),
+        _ => false,
+    }
+}
+
+fn result(id: u64, code: i64) -> StructureTag {
+    Sequence {
+        class: TagClass::Application,
+        id,
+        inner: vec![
+            Tag::Enumerated(Enumerated {
+                inner: code,
+                ..Default::default()
+            }),
+            octet_string(""),
+            octet_string(""),
+        ],
+    }
+    .into_structure()
+}
+
+fn octet_string(value: &str) -> Tag {
+    Tag::OctetString(OctetString {
+        inner: value.as_bytes().to_vec(),
+        ..Default::default()
+    })
+}
+
+fn string(tag: StructureTag) -> String {
+    String::from_utf8(tag.expect_primitive().unwrap()).unwrap()
+}
+
+fn integer(tag: StructureTag) -> i64 {
+    tag.expect_primitive()
+        .unwrap()
+        .into_iter()
+        .fold(0, |acc, byte| (acc << 8) | byte as i64)
+}
+
+fn config(server: String) -> Config {
+    Config {
+        insecure_skip_tls_verify: false,
+        schedule_user_updates: true,
+        schedule_new_users: true,
+        connect_timeout: Duration::from_secs(1),
+        filter_query: String::from("<username>=%u"),
+        attributes: Attributes::default(),
+        starttls: false,
+        bind_dn: BIND_DN.to_owned(),
+        server,
+    }
+}
+
+fn build_request<'s>(username: &'s str, password: &'s str) -> Request<'s> {
+    let model = user::Model {
+        verified_publisher: Default::default(),
+        prefers_gravatar: Default::default(),
+        gravatar_email: Default::default(),
+        description: Default::default(),
+        avatar_hash: Default::default(),
+        created_at: Default::default(),
+        updated_at: Default::default(),
+        username: username.parse().unwrap(),
+        password: None,
+        email: format!("{username}@noelware.org"),
+        admin: Default::default(),
+        name: Default::default(),
+        id: Default::default(),
+    };
+
+    Request {
+        password: Cow::Borrowed(password),
+        user: User::from(model.clone()),
+        model,
+    }
+}
+
+async fn create_db() -> DatabaseConnection {
+    charted_database::create_pool(&database::Config::SQLite(database::sqlite::Config {
+        common: Default::default(),
+        path: String::from(":memory:").into(),
+    }))
+    .await
+    .expect("failed to create database pool")
+}
+
+#[tokio::test]
+async fn authenticate() {
+    let backend = Backend::new(config(server().await));
+    assert!(
+        backend
+            .authenticate(build_request("noel", "noeliscutieuwu"))
+            .await
+            .is_ok()
+    );
+
+    let err = backend
+        .authenticate(build_request("noel", "thetwinofboel"))
+        .await
+        .unwrap_err();
+
+    assert!(err.is::<InvalidPassword>());
+
+    let err = backend.authenticate(build_request("noel", "")).await.unwrap_err();
+    assert!(err.is::<InvalidPassword>());
+}
+
+#[tokio::test]
+async fn authenticate_with_filter_query() {
+    let mut config = config(server().await);
+    config.filter_query = String::from("(&(<username>=%u)(memberOf=cn=charted,ou=groups,dc=charts,dc=dev))");
+
+    let backend = Backend::new(config);
+    assert!(
+        backend
+            .authenticate(build_request("noel", "noeliscutieuwu"))
+            .await
+            .is_ok()
+    );
+
+    let err = backend
+        .authenticate(build_request("boel", "thetwinofboel"))
+        .await
+        .unwrap_err();
+
+    assert!(!err.is::<InvalidPassword>());
+}
+
+#[tokio::test]
+async fn entries() {
+    let backend = Backend::new(config(server().await));
+    let entries = backend.entries().await.unwrap();
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0], Entry {
+        dn: String::from("uid=noel,ou=people,dc=charts,dc=dev"),
+        username: String::from("noel"),
+        display_name: Some(String::from("Noel Towa")),
+        email: Some(String::from("noel@noelware.org")),
+    });
+
+    assert_eq!(entries[2].email, None);
+}
+
+#[tokio::test]
+async fn sync() {
+    let db = create_db().await;
+    let ulid = charted_core::ulid::Generator::new();
+    let mut backend = Backend::new(config(server().await));
+
+    // `nomail` doesn't have an email, so it can't be created.
+    let report = backend.sync(&db, &ulid).await.unwrap();
+    assert_eq!(report.created.len(), 2);
+    assert_eq!(report.updated, 0);
+
+    let noel = UserEntity::find_by_id(report.created[0])
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(noel.username.as_str(), "noel");
+    assert_eq!(noel.name.as_deref(), Some("Noel Towa"));
+    assert_eq!(noel.password, None);
+
+    // syncing again shouldn't do anything as nothing has changed
+    assert_eq!(backend.sync(&db, &ulid).await.unwrap(), sync::Report::default());
+
+    let mut active = user::ActiveModel::from(noel.clone());
+    active.email = sea_orm::ActiveValue::set(String::from("noel@example.com"));
+    active.name = sea_orm::ActiveValue::set(None);
+    sea_orm::ActiveModelTrait::update(active, &db).await.unwrap();
+
+    let report = backend.sync(&db, &ulid).await.unwrap();
+    assert!(report.created.is_empty());
+    assert_eq!(report.updated, 1);
+
+    let noel = UserEntity::find_by_id(noel.id).one(&db).await.unwrap().unwrap();
+    assert_eq!(noel.email, "noel@noelware.org");
+    assert_eq!(noel.name.as_deref(), Some("Noel Towa"));
+
+    // with both schedules disabled, nothing is synced
+    backend.config.schedule_new_users = false;
+    backend.config.schedule_user_updates = false;
+
+    assert_eq!(backend.sync(&db, &ulid).await.unwrap(), sync::Report::default());
+}
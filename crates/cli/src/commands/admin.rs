@@ -14,6 +14,7 @@
 // limitations under the License.
 
 mod authz;
+mod repair;
 mod user;
 
 /// Administrative commands.
@@ -24,11 +25,15 @@ pub enum Subcommand {
 
     #[command(subcommand)]
     Authz(authz::Subcmd),
+
+    #[command(name = "repair-latest-versions")]
+    RepairLatestVersions(repair::Args),
 }
 
 pub async fn run(subcmd: Subcommand) -> eyre::Result<()> {
     match subcmd {
         Subcommand::User(subcmd) => user::run(subcmd).await,
         Subcommand::Authz(subcmd) => authz::run(subcmd),
+        Subcommand::RepairLatestVersions(args) => repair::run(args).await,
     }
 }
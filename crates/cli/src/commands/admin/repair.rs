@@ -0,0 +1,81 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::commands::server::load_config;
+use charted_database::entities::RepositoryEntity;
+use charted_server::Env;
+use cli_table::{Cell, Table, format::Justify};
+use sea_orm_migration::sea_orm::EntityTrait;
+use std::path::PathBuf;
+use tracing::{error, info};
+
+#[derive(Table)]
+struct CliTable {
+    #[table(title = "Repository", justify = "Justify::Left")]
+    repository: String,
+
+    #[table(title = "Latest", justify = "Justify::Left")]
+    latest: String,
+
+    #[table(title = "Latest Pre-release", justify = "Justify::Left")]
+    prerelease: String,
+}
+
+/// Recomputes the cached latest versions of every repository from the chart tarballs
+/// in the datastore.
+///
+/// This lists every chart tarball of every repository, so it should only be used
+/// after upgrading or if the cached versions are out of date.
+#[derive(Debug, clap::Parser)]
+pub struct Args {
+    /// Path to a charted `config.toml` configuration file.
+    #[arg(long, short = 'c', env = "CHARTED_CONFIG_FILE")]
+    config: Option<PathBuf>,
+}
+
+pub async fn run(Args { config }: Args) -> eyre::Result<()> {
+    let config = load_config(config)?;
+    let env = Env::new(config).await?;
+
+    let repositories = RepositoryEntity::find().all(&env.db).await?;
+    let mut cells = Vec::with_capacity(repositories.len());
+
+    for repository in &repositories {
+        match charted_server::ops::latest::repair(&env, repository).await {
+            Ok((latest, prerelease)) => cells.push(CliTable {
+                repository: format!("{} ({})", repository.name, repository.id),
+                latest: latest.map(|v| v.to_string()).unwrap_or_else(|| String::from("-")),
+                prerelease: prerelease.map(|v| v.to_string()).unwrap_or_else(|| String::from("-")),
+            }),
+
+            Err(e) => {
+                error!(error = %e, repository.id = %repository.id, "failed to repair latest versions");
+            }
+        }
+    }
+
+    let _ = cli_table::print_stdout(cells.table().title([
+        "Repository".cell(),
+        "Latest".cell(),
+        "Latest Pre-release".cell(),
+    ]));
+
+    info!(
+        repaired = cells.len(),
+        repositories = repositories.len(),
+        "finished repairing latest versions"
+    );
+
+    env.close().await
+}
@@ -30,8 +30,8 @@ pub enum Subcmd {
 impl Subcmd {
     pub async fn run(self) -> eyre::Result<()> {
         match self {
-            Self::ApiKey(_) => Ok(()),
-            Self::Storage(_) => Ok(()),
+            Self::ApiKey(args) => apikey::run(args).await,
+            Self::Storage(args) => storage::run(args).await,
         }
     }
 }
@@ -13,8 +13,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::commands::Tokio;
+use crate::commands::{Tokio, server::load_config};
+use charted_apikey_worker::Worker;
+use charted_server::Env;
 use std::path::PathBuf;
+use tracing::info;
 
 #[derive(Debug, clap::Parser)]
 pub struct Args {
@@ -22,6 +25,30 @@ pub struct Args {
     #[arg(long, short = 'c', env = "CHARTED_CONFIG_FILE")]
     config: Option<PathBuf>,
 
+    /// Runs the worker once and prints how many API keys it took care of instead of
+    /// running it every `workers.apikeys.interval`.
+    #[arg(long)]
+    once: bool,
+
     #[command(flatten)]
     pub tokio: Tokio,
 }
+
+pub async fn run(Args { config, once, .. }: Args) -> eyre::Result<()> {
+    let config = load_config(config)?;
+    let env = Env::new(config).await?;
+    let worker = Worker::new(env.config.workers.apikeys.clone(), env.db.clone());
+
+    if !once {
+        worker.run().await;
+        return env.close().await;
+    }
+
+    let report = worker.run_once(chrono::Utc::now()).await?;
+    info!(
+        "{} api keys were {}d and {} owners were told that theirs are about to expire",
+        report.expired, env.config.workers.apikeys.action, report.notified
+    );
+
+    env.close().await
+}
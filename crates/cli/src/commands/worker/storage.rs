@@ -13,8 +13,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::commands::Tokio;
+use crate::commands::{Tokio, server::load_config};
+use charted_feature_gc::Feature;
+use charted_server::Env;
+use cli_table::{Cell, Table, format::Justify};
+use eyre::OptionExt;
 use std::path::PathBuf;
+use tracing::info;
+
+#[derive(Table)]
+struct CliTable {
+    #[table(title = "Rule", justify = "Justify::Left")]
+    rule: &'static str,
+
+    #[table(title = "Garbage", justify = "Justify::Left")]
+    garbage: String,
+
+    #[table(title = "Collected")]
+    collected: &'static str,
+}
 
 #[derive(Debug, clap::Parser)]
 pub struct Args {
@@ -22,6 +39,67 @@ pub struct Args {
     #[arg(long, short = 'c', env = "CHARTED_CONFIG_FILE")]
     config: Option<PathBuf>,
 
+    /// Only report what would've been collected without deleting anything.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Runs the garbage collector once and prints its report instead of running it
+    /// on the schedule in `features.gc.schedule`.
+    #[arg(long)]
+    once: bool,
+
     #[command(flatten)]
     pub tokio: Tokio,
 }
+
+pub async fn run(
+    Args {
+        config, dry_run, once, ..
+    }: Args,
+) -> eyre::Result<()> {
+    let mut config = load_config(config)?;
+
+    // the worker is ran explicitly, so it doesn't matter if the feature is disabled
+    // for the API server.
+    config.features.gc.enable = true;
+    config.features.gc.dry_run |= dry_run;
+
+    let env = Env::new(config).await?;
+    if !once {
+        info!("starting data storage pruner");
+        charted_server::ops::gc::job(env.clone()).await;
+
+        return env.close().await;
+    }
+
+    let collector = env
+        .features
+        .get::<Feature>()
+        .map(Feature::collector)
+        .ok_or_eyre("garbage collection feature was not registered")?;
+
+    let report = charted_server::ops::gc::run(&env, collector, env.config.features.gc.dry_run).await?;
+    let cells = report
+        .items
+        .iter()
+        .map(|item| CliTable {
+            rule: item.rule,
+            garbage: item.garbage.to_string(),
+            collected: if item.collected { "Yes" } else { "No" },
+        })
+        .collect::<Vec<_>>();
+
+    let _ = cli_table::print_stdout(
+        cells
+            .table()
+            .title(["Rule".cell(), "Garbage".cell(), "Collected".cell()]),
+    );
+    info!(
+        dry_run = report.dry_run,
+        found = report.items.len(),
+        collected = report.collected(),
+        "finished garbage collection"
+    );
+
+    env.close().await
+}
@@ -0,0 +1,66 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_DECOMPRESSED_SIZE: &str = "CHARTED_CHARTS_MAX_DECOMPRESSED_SIZE";
+pub const MAX_ENTRIES: &str = "CHARTED_CHARTS_MAX_ENTRIES";
+
+/// ## `[charts]` table
+/// Configures the limits that are enforced on chart tarballs when they are uploaded.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Maximum amount of bytes, in total, that a chart tarball can decompress to.
+    #[serde(default = "__max_decompressed_size")]
+    pub max_decompressed_size: u64,
+
+    /// Maximum amount of files and directories that a chart tarball can contain.
+    #[serde(default = "__max_entries")]
+    pub max_entries: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_decompressed_size: __max_decompressed_size(),
+            max_entries: __max_entries(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            max_decompressed_size: env::try_parse_or_else(MAX_DECOMPRESSED_SIZE, __max_decompressed_size())?,
+            max_entries: env::try_parse_or_else(MAX_ENTRIES, __max_entries())?,
+        })
+    }
+}
+
+// Helm itself refuses to load charts that decompress to more than 100MiB.
+const fn __max_decompressed_size() -> u64 {
+    100 * 1024 * 1024
+}
+
+const fn __max_entries() -> u64 {
+    1000
+}
@@ -13,4 +13,51 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod auditlog;
+pub mod gc;
+pub mod oci;
 pub mod totp;
+pub mod webhooks;
+
+use azalia::config::{env::TryFromEnv, merge::Merge};
+use serde::{Deserialize, Serialize};
+
+/// ## `[features]` table
+/// Configures the server features that extend **charted-server**.
+#[derive(Debug, Clone, Default, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Configures the audit logs feature.
+    #[serde(default)]
+    pub auditlog: auditlog::Config,
+
+    /// Configures the garbage collection feature.
+    #[serde(default)]
+    pub gc: gc::Config,
+
+    /// Configures the OCI registry feature.
+    #[serde(default)]
+    pub oci: oci::Config,
+
+    /// Configures the TOTP two-factor authentication feature.
+    #[serde(default)]
+    pub totp: totp::Config,
+
+    /// Configures the webhooks feature.
+    #[serde(default)]
+    pub webhooks: webhooks::Config,
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            auditlog: auditlog::Config::try_from_env()?,
+            gc: gc::Config::try_from_env()?,
+            oci: oci::Config::try_from_env()?,
+            totp: totp::Config::try_from_env()?,
+            webhooks: webhooks::Config::try_from_env()?,
+        })
+    }
+}
@@ -12,3 +12,203 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv, TryParseError},
+    merge::Merge,
+};
+use eyre::{bail, eyre};
+use serde::{Deserialize, Serialize};
+use std::{env::VarError, path::PathBuf};
+use url::Url;
+
+pub const ENABLE: &str = "CHARTED_FEATURES_AUDITLOG_ENABLE";
+pub const SINK: &str = "CHARTED_FEATURES_AUDITLOG_SINK";
+
+pub const CLICKHOUSE_URL: &str = "CHARTED_FEATURES_AUDITLOG_CLICKHOUSE_URL";
+pub const CLICKHOUSE_DATABASE: &str = "CHARTED_FEATURES_AUDITLOG_CLICKHOUSE_DATABASE";
+pub const CLICKHOUSE_TABLE: &str = "CHARTED_FEATURES_AUDITLOG_CLICKHOUSE_TABLE";
+pub const CLICKHOUSE_USERNAME: &str = "CHARTED_FEATURES_AUDITLOG_CLICKHOUSE_USERNAME";
+pub const CLICKHOUSE_PASSWORD: &str = "CHARTED_FEATURES_AUDITLOG_CLICKHOUSE_PASSWORD";
+
+pub const JSONL_PATH: &str = "CHARTED_FEATURES_AUDITLOG_JSONL_PATH";
+
+/// ## `[features.auditlog]` table
+/// Records who changed what in repositories and organizations, which can be viewed by
+/// the people that are allowed to modify them.
+#[derive(Debug, Clone, Default, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if the audit logs feature is enabled.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub enable: bool,
+
+    /// Where audit log records are stored.
+    #[serde(default)]
+    pub sink: Sink,
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            enable: util::bool_env(ENABLE)?,
+            sink: Sink::try_from_env()?,
+        })
+    }
+}
+
+/// ## `[features.auditlog.sink]` table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "lowercase")]
+pub enum Sink {
+    /// Stores audit log records in the `audit_logs` table of the server's database.
+    #[default]
+    Database,
+
+    /// Stores audit log records in a [ClickHouse](https://clickhouse.com) table over its
+    /// HTTP interface. This is recommended for production use.
+    ClickHouse(ClickHouse),
+
+    /// Appends audit log records as JSON lines to a file. This is mainly useful for
+    /// development and testing.
+    Jsonl(Jsonl),
+}
+
+impl Merge for Sink {
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (Self::ClickHouse(c1), Self::ClickHouse(c2)) => {
+                c1.merge(c2);
+            }
+
+            (Self::Jsonl(j1), Self::Jsonl(j2)) => {
+                j1.merge(j2);
+            }
+
+            (me, other) => {
+                *me = other;
+            }
+        }
+    }
+}
+
+impl TryFromEnv for Sink {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        match env::try_parse::<_, String>(SINK) {
+            Ok(s) => match &*s.to_ascii_lowercase() {
+                "database" | "db" => Ok(Sink::Database),
+                "clickhouse" => Ok(Sink::ClickHouse(ClickHouse::try_from_env()?)),
+                "jsonl" => Ok(Sink::Jsonl(Jsonl::try_from_env()?)),
+                s => Err(eyre!("unknown variant for `${}`: {}", SINK, s)),
+            },
+
+            Err(TryParseError::System(VarError::NotPresent)) => Ok(Sink::Database),
+            Err(TryParseError::System(VarError::NotUnicode(_))) => {
+                Err(eyre!("received non-unicode in `${}` environment variable", SINK))
+            }
+
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// ## `[features.auditlog.sink.clickhouse]` table
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClickHouse {
+    /// URL to the ClickHouse server's HTTP interface.
+    pub url: Url,
+
+    /// Database that the audit logs table lives in.
+    #[serde(default = "__default_clickhouse_database")]
+    pub database: String,
+
+    /// Name of the table that audit log records are inserted into. The table is created
+    /// if it doesn't exist.
+    #[serde(default = "__default_clickhouse_table")]
+    pub table: String,
+
+    /// Username to authenticate as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Password of the user to authenticate as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl TryFromEnv for ClickHouse {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(ClickHouse {
+            url: match env::try_parse::<_, Url>(CLICKHOUSE_URL) {
+                Ok(url) => url,
+
+                Err(TryParseError::Parse(err)) => bail!("failed to parse url: {}", err),
+                Err(TryParseError::System(VarError::NotPresent)) => bail!(
+                    "environment variable `${}` is required when environment variable `${}` is set to \"clickhouse\"",
+                    CLICKHOUSE_URL,
+                    SINK,
+                ),
+
+                Err(TryParseError::System(VarError::NotUnicode(_))) => {
+                    bail!(
+                        "environment variable `${}` contained invalid unicode characters",
+                        CLICKHOUSE_URL
+                    )
+                }
+            },
+
+            database: env::try_parse_or_else(CLICKHOUSE_DATABASE, __default_clickhouse_database())?,
+            table: env::try_parse_or_else(CLICKHOUSE_TABLE, __default_clickhouse_table())?,
+            username: env::try_parse_optional(CLICKHOUSE_USERNAME)?,
+            password: env::try_parse_optional(CLICKHOUSE_PASSWORD)?,
+        })
+    }
+}
+
+/// ## `[features.auditlog.sink.jsonl]` table
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Jsonl {
+    /// Path to the file that audit log records are appended to.
+    #[serde(default = "__default_jsonl_path")]
+    pub path: PathBuf,
+}
+
+impl Default for Jsonl {
+    fn default() -> Self {
+        Jsonl {
+            path: __default_jsonl_path(),
+        }
+    }
+}
+
+impl TryFromEnv for Jsonl {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Jsonl {
+            path: env::try_parse_or_else(JSONL_PATH, __default_jsonl_path())?,
+        })
+    }
+}
+
+fn __default_clickhouse_database() -> String {
+    String::from("charted")
+}
+
+fn __default_clickhouse_table() -> String {
+    String::from("audit_logs")
+}
+
+fn __default_jsonl_path() -> PathBuf {
+    PathBuf::from("./data/audit-logs.jsonl")
+}
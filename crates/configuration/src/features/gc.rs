@@ -12,3 +12,115 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use charted_core::serde::Duration;
+use serde::{Deserialize, Serialize};
+
+pub const ENABLE: &str = "CHARTED_FEATURES_GC_ENABLE";
+pub const DRY_RUN: &str = "CHARTED_FEATURES_GC_DRY_RUN";
+pub const SCHEDULE: &str = "CHARTED_FEATURES_GC_SCHEDULE";
+pub const GRACE_PERIOD: &str = "CHARTED_FEATURES_GC_GRACE_PERIOD";
+
+/// ## `[features.gc]` table
+/// Allows **charted-server** to periodically collect garbage from the database and the
+/// datastore, like tarballs of releases that no longer exist.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if the garbage collection feature is enabled.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub enable: bool,
+
+    /// If enabled, scheduled runs only report what would've been collected without
+    /// deleting anything.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub dry_run: bool,
+
+    /// Cron expression of when the garbage collector runs, with a leading seconds
+    /// field. By default, it runs every day at 04:00 UTC.
+    #[serde(default = "__default_schedule")]
+    pub schedule: String,
+
+    /// Objects in the datastore that were modified within this period are never
+    /// collected, so that uploads that are still in-flight aren't seen as orphans.
+    #[serde(default = "__default_grace_period")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub grace_period: Duration,
+
+    /// List of rules that the garbage collector runs, in order.
+    #[serde(default = "__default_rules")]
+    #[merge(strategy = __merge_rules)]
+    pub rules: Vec<Rule>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enable: false,
+            dry_run: false,
+            schedule: __default_schedule(),
+            grace_period: __default_grace_period(),
+            rules: __default_rules(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            enable: util::bool_env(ENABLE)?,
+            dry_run: util::bool_env(DRY_RUN)?,
+            schedule: env::try_parse_or_else(SCHEDULE, __default_schedule())?,
+            grace_period: env::try_parse_or_else(GRACE_PERIOD, __default_grace_period())?,
+            rules: __default_rules(),
+        })
+    }
+}
+
+/// ## `[[features.gc.rules]]`
+/// A rule that finds a kind of garbage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Rule {
+    /// Deletes repositories whose owner, a user or organization, no longer exists.
+    OrphanedRepositories,
+
+    /// Deletes chart tarballs and provenance files under `repositories/{owner}/{repo}/tarballs`
+    /// that don't have a matching release.
+    OrphanedTarballs,
+
+    /// Deletes user avatars, and organization and repository icons that are no longer
+    /// referenced by their owner's `avatar_hash` or `icon_hash`.
+    UnreferencedAvatars,
+
+    /// Deletes the prereleases of every repository except for the last `keep`
+    /// prereleases that were published.
+    Prereleases { keep: usize },
+}
+
+fn __default_schedule() -> String {
+    String::from("0 0 4 * * *")
+}
+
+const fn __default_grace_period() -> Duration {
+    Duration::from_secs(60 * 60)
+}
+
+fn __default_rules() -> Vec<Rule> {
+    vec![Rule::OrphanedRepositories, Rule::OrphanedTarballs, Rule::UnreferencedAvatars]
+}
+
+fn __merge_rules(me: &mut Vec<Rule>, other: Vec<Rule>) {
+    if other != __default_rules() {
+        *me = other;
+    }
+}
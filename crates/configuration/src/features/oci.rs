@@ -12,3 +12,31 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{env::TryFromEnv, merge::Merge};
+use serde::{Deserialize, Serialize};
+
+pub const ENABLE: &str = "CHARTED_FEATURES_OCI_ENABLE";
+
+/// ## `[features.oci]` table
+/// Allows **charted-server** to act as an OCI registry for Helm charts under the `/v2`
+/// endpoints.
+#[derive(Debug, Clone, Default, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if the OCI registry feature is enabled.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub enable: bool,
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            enable: util::bool_env(ENABLE)?,
+        })
+    }
+}
@@ -12,3 +12,78 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use serde::{Deserialize, Serialize};
+
+pub const ENABLE: &str = "CHARTED_FEATURES_TOTP_ENABLE";
+pub const ISSUER: &str = "CHARTED_FEATURES_TOTP_ISSUER";
+pub const SKEW: &str = "CHARTED_FEATURES_TOTP_SKEW";
+pub const RECOVERY_CODES: &str = "CHARTED_FEATURES_TOTP_RECOVERY_CODES";
+
+/// ## `[features.totp]` table
+/// Allows local users to protect their accounts with time-based one-time passwords
+/// ([RFC 6238]) from an authenticator app.
+///
+/// [RFC 6238]: https://datatracker.ietf.org/doc/html/rfc6238
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if the TOTP feature is enabled.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub enable: bool,
+
+    /// Name of the issuer that authenticator apps display next to the account.
+    #[serde(default = "__default_issuer")]
+    pub issuer: String,
+
+    /// How many 30 second time steps before and after the current one that a code
+    /// is still accepted for, to allow clock drift between the server and devices.
+    #[serde(default = "__default_skew")]
+    pub skew: u8,
+
+    /// How many recovery codes are generated when a user enrolls.
+    #[serde(default = "__default_recovery_codes")]
+    pub recovery_codes: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enable: false,
+            issuer: __default_issuer(),
+            skew: __default_skew(),
+            recovery_codes: __default_recovery_codes(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            enable: util::bool_env(ENABLE)?,
+            issuer: env::try_parse_or_else(ISSUER, __default_issuer())?,
+            skew: env::try_parse_or_else(SKEW, __default_skew())?,
+            recovery_codes: env::try_parse_or_else(RECOVERY_CODES, __default_recovery_codes())?,
+        })
+    }
+}
+
+fn __default_issuer() -> String {
+    String::from("charted-server")
+}
+
+const fn __default_skew() -> u8 {
+    1
+}
+
+const fn __default_recovery_codes() -> u8 {
+    10
+}
@@ -12,3 +12,123 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use charted_core::serde::Duration;
+use serde::{Deserialize, Serialize};
+
+pub const ENABLE: &str = "CHARTED_FEATURES_WEBHOOKS_ENABLE";
+pub const MAX_ATTEMPTS: &str = "CHARTED_FEATURES_WEBHOOKS_MAX_ATTEMPTS";
+pub const BACKOFF: &str = "CHARTED_FEATURES_WEBHOOKS_BACKOFF";
+pub const MAX_BACKOFF: &str = "CHARTED_FEATURES_WEBHOOKS_MAX_BACKOFF";
+pub const TIMEOUT: &str = "CHARTED_FEATURES_WEBHOOKS_TIMEOUT";
+pub const ALLOWED_HOSTS: &str = "CHARTED_FEATURES_WEBHOOKS_ALLOWED_HOSTS";
+
+/// ## `[features.webhooks]` table
+/// Allows repositories and organizations to register HTTP endpoints that receive
+/// events as [Standard Webhooks](https://www.standardwebhooks.com).
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if the webhooks feature is enabled.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub enable: bool,
+
+    /// Maximum amount of times that a event is delivered to a webhook before it
+    /// is given up on.
+    #[serde(default = "__default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry of a failed delivery. Every retry afterwards
+    /// doubles the delay until `max_backoff` is reached.
+    #[serde(default = "__default_backoff")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub backoff: Duration,
+
+    /// The longest delay between two delivery attempts.
+    #[serde(default = "__default_max_backoff")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub max_backoff: Duration,
+
+    /// How long a webhook endpoint has to respond before the attempt is considered
+    /// as failed.
+    #[serde(default = "__default_timeout")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub timeout: Duration,
+
+    /// Hosts that webhooks are allowed to be delivered to even if they resolve to a
+    /// loopback, private, or link-local address. By default, those addresses are
+    /// refused so that a webhook can't be used to reach services that are only
+    /// available inside of charted-server's network.
+    ///
+    /// When configured with the `CHARTED_FEATURES_WEBHOOKS_ALLOWED_HOSTS` environment
+    /// variable, hosts are separated by commas.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[merge(strategy = __merge_allowed_hosts)]
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enable: false,
+            max_attempts: __default_max_attempts(),
+            backoff: __default_backoff(),
+            max_backoff: __default_max_backoff(),
+            timeout: __default_timeout(),
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            enable: util::bool_env(ENABLE)?,
+            max_attempts: env::try_parse_or_else(MAX_ATTEMPTS, __default_max_attempts())?,
+            backoff: env::try_parse_or_else(BACKOFF, __default_backoff())?,
+            max_backoff: env::try_parse_or_else(MAX_BACKOFF, __default_max_backoff())?,
+            timeout: env::try_parse_or_else(TIMEOUT, __default_timeout())?,
+            allowed_hosts: util::env_from_result(
+                std::env::var(ALLOWED_HOSTS).map(|hosts| {
+                    hosts
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|host| !host.is_empty())
+                        .map(String::from)
+                        .collect()
+                }),
+                Vec::new(),
+            )?,
+        })
+    }
+}
+
+const fn __default_max_attempts() -> u32 {
+    5
+}
+
+const fn __default_backoff() -> Duration {
+    Duration::from_secs(5)
+}
+
+const fn __default_max_backoff() -> Duration {
+    Duration::from_secs(10 * 60)
+}
+
+const fn __default_timeout() -> Duration {
+    Duration::from_secs(15)
+}
+
+fn __merge_allowed_hosts(me: &mut Vec<String>, other: Vec<String>) {
+    if !other.is_empty() {
+        *me = other;
+    }
+}
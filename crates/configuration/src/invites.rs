@@ -0,0 +1,57 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use charted_core::serde::Duration;
+use serde::{Deserialize, Serialize};
+
+pub const EXPIRES_IN: &str = "CHARTED_INVITES_EXPIRES_IN";
+
+/// ## `[invites]` table
+/// Configures invites that are sent to users to become a repository or organization
+/// member.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// How long an invite can be accepted for before it expires.
+    #[serde(default = "__default_expires_in")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub expires_in: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            expires_in: __default_expires_in(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            expires_in: env::try_parse_or_else(EXPIRES_IN, __default_expires_in())?,
+        })
+    }
+}
+
+const fn __default_expires_in() -> Duration {
+    Duration::from_secs(7 * 24 * 60 * 60)
+}
@@ -0,0 +1,112 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv},
+    merge::Merge,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+pub const SIGNING_KEY: &str = "CHARTED_JWT_SIGNING_KEY";
+pub const ACCEPT_LEGACY_TOKENS: &str = "CHARTED_JWT_ACCEPT_LEGACY_TOKENS";
+
+/// Asymmetric algorithm that a key signs JWTs with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
+pub enum Algorithm {
+    /// RSASSA-PKCS1-v1_5 using SHA-256. The key must be a PEM-encoded RSA private key.
+    #[display("RS256")]
+    RS256,
+
+    /// EdDSA using Ed25519. The key must be a PEM-encoded PKCS#8 Ed25519 private key.
+    #[display("EdDSA")]
+    EdDSA,
+}
+
+impl Merge for Algorithm {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+/// ## `[jwt.keys.<kid>]` table
+/// A key that JWTs are signed or verified with, where `<kid>` is the `kid` header
+/// that JWTs signed with this key will have.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Key {
+    /// Algorithm that the key signs JWTs with.
+    pub algorithm: Algorithm,
+
+    /// Location to the PEM-encoded private key.
+    pub private_key: PathBuf,
+}
+
+/// ## `[jwt]` table
+/// Configures the keys that session tokens are signed and verified with.
+///
+/// Every key in `keys` can verify tokens and is published in `/.well-known/jwks.json`,
+/// but only `signing_key` signs new tokens. Keys can be rotated by adding a new key,
+/// switching `signing_key` over to it, and removing the old key once the sessions
+/// it signed have expired.
+///
+/// If no keys are configured, tokens are signed with `jwt_secret_key` using HS512.
+/// Once `signing_key` is set, tokens signed that way are still accepted so that
+/// existing sessions keep working; set `accept_legacy_tokens` to `false` after they
+/// have expired (the refresh token's lifetime after switching) to stop accepting them.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The `kid` of the key in `keys` that new tokens are signed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+
+    /// Keys that tokens can be verified with, keyed by their `kid`.
+    ///
+    /// Keys can only be configured in the configuration file.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, Key>,
+
+    /// Whether if tokens without a `kid` header, which are signed with `jwt_secret_key`,
+    /// are accepted. This can only be disabled if `signing_key` is set.
+    #[serde(default = "crate::util::truthy")]
+    pub accept_legacy_tokens: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            signing_key: None,
+            keys: BTreeMap::new(),
+            accept_legacy_tokens: true,
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            signing_key: env::try_parse_optional(SIGNING_KEY)?,
+            keys: BTreeMap::new(),
+            accept_legacy_tokens: util::env_from_result(
+                std::env::var(ACCEPT_LEGACY_TOKENS).map(|x| azalia::TRUTHY_REGEX.is_match(&x)),
+                true,
+            )?,
+        })
+    }
+}
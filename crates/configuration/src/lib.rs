@@ -13,8 +13,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod charts;
 pub mod database;
 pub mod features;
+pub mod invites;
+pub mod jwt;
 pub mod logging;
 pub mod metrics;
 pub mod server;
@@ -51,7 +54,10 @@ pub struct Config {
     /// It is recommended to set this as the `CHARTED_JWT_SECRET_KEY` environment
     /// variable and **charted-server** will load it into this property.
     ///
-    /// If this is ever messed with, sessions that are on-going will be permanently
+    /// This is only used to sign tokens if no keys were configured in the `[jwt]`
+    /// table. Tokens that were signed with it without a `kid` header are still
+    /// accepted after switching to asymmetric keys, so that on-going sessions aren't
+    /// logged out. If this is ever messed with, those sessions will be permanently
     /// corrupted.
     #[serde(default)]
     #[merge(strategy = azalia::config::merge::strategy::strings::overwrite_empty)]
@@ -88,9 +94,21 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub base_url: Option<Url>,
 
+    #[serde(default)]
+    pub charts: charts::Config,
+
     #[serde(default)]
     pub database: database::Config,
 
+    #[serde(default)]
+    pub features: features::Config,
+
+    #[serde(default)]
+    pub invites: invites::Config,
+
+    #[serde(default)]
+    pub jwt: jwt::Config,
+
     #[serde(default)]
     pub logging: logging::Config,
 
@@ -108,6 +126,9 @@ pub struct Config {
 
     #[serde(default)]
     pub sessions: sessions::Config,
+
+    #[serde(default)]
+    pub workers: workers::Config,
 }
 
 impl Config {
@@ -183,12 +204,17 @@ impl TryFromEnv for Config {
             single_org: util::bool_env(SINGLE_ORG)?,
             sentry_dsn: env::try_parse_optional(SENTRY_DSN)?,
             base_url: env::try_parse_optional(BASE_URL)?,
+            charts: charts::Config::try_from_env()?,
             database: database::Config::try_from_env()?,
+            features: features::Config::try_from_env()?,
+            invites: invites::Config::try_from_env()?,
+            jwt: jwt::Config::try_from_env()?,
             sessions: sessions::Config::try_from_env()?,
             logging: logging::Config::try_from_env()?,
             storage: storage::Config::try_from_env()?,
             metrics: metrics::Config::try_from_env()?,
             server: server::Config::try_from_env()?,
+            workers: workers::Config::try_from_env()?,
 
             tracing: match util::bool_env(tracing::ENABLED) {
                 Ok(true) => Some(tracing::Config::try_from_env()?),
@@ -200,6 +226,26 @@ impl TryFromEnv for Config {
 }
 
 fn update_config(config: &mut Config) -> eyre::Result<()> {
+    if let Some(kid) = config.jwt.signing_key.as_deref() &&
+        !config.jwt.keys.contains_key(kid)
+    {
+        eyre::bail!("`jwt.signing_key` was set to `{kid}`, but no key was configured in `[jwt.keys.{kid}]`");
+    }
+
+    if config.jwt.signing_key.is_none() && !config.jwt.keys.is_empty() {
+        eyre::bail!("`jwt.signing_key` must be set to one of the keys in `[jwt.keys]`");
+    }
+
+    if !config.jwt.accept_legacy_tokens && config.jwt.signing_key.is_none() {
+        eyre::bail!("`jwt.accept_legacy_tokens` can only be disabled once `jwt.signing_key` is set");
+    }
+
+    // only used to verify old sessions if keys were configured, so there is
+    // nothing to warn about.
+    if config.jwt_secret_key.is_empty() && config.jwt.signing_key.is_some() {
+        config.jwt_secret_key = charted_core::rand_string(16);
+    }
+
     if config.jwt_secret_key.is_empty() {
         let key = charted_core::rand_string(16);
         eprintln!(
@@ -47,6 +47,11 @@ pub struct Config {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ssl: Option<ssl::Config>,
+
+    /// Limits how many requests clients can send. Rate limiting is disabled if this
+    /// table isn't present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratelimits: Option<ratelimits::Config>,
 }
 
 impl Default for Config {
@@ -56,6 +61,7 @@ impl Default for Config {
             host: __default_host(),
             port: __default_port(),
             ssl: None,
+            ratelimits: None,
         }
     }
 }
@@ -79,6 +85,11 @@ impl TryFromEnv for Config {
                 Ok(false) => None,
                 Err(e) => return Err(e),
             },
+            ratelimits: match util::bool_env(ratelimits::ENABLED) {
+                Ok(true) => ratelimits::Config::try_from_env().map(Some)?,
+                Ok(false) => None,
+                Err(e) => return Err(e),
+            },
         })
     }
 }
@@ -12,3 +12,214 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv, TryParseError},
+    merge::Merge,
+};
+use charted_core::serde::Duration;
+use eyre::{bail, eyre};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env::VarError, net::IpAddr};
+
+pub const ENABLED: &str = "CHARTED_SERVER_RATELIMITS";
+pub const BACKEND: &str = "CHARTED_SERVER_RATELIMITS_BACKEND";
+pub const IP: &str = "CHARTED_SERVER_RATELIMITS_IP";
+pub const USER: &str = "CHARTED_SERVER_RATELIMITS_USER";
+pub const APIKEY: &str = "CHARTED_SERVER_RATELIMITS_APIKEY";
+pub const TRUSTED_PROXIES: &str = "CHARTED_SERVER_RATELIMITS_TRUSTED_PROXIES";
+
+/// Where rate limit counters are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// Counters are kept in memory, so each instance of charted-server has its own.
+    #[default]
+    #[display("memory")]
+    Memory,
+
+    /// Counters are kept in the database, so they are shared between every instance
+    /// of charted-server that uses the same database.
+    #[display("database")]
+    Database,
+}
+
+impl Merge for Backend {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+impl TryFromEnv for Backend {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        match env::try_parse_or_else::<_, String>(BACKEND, Default::default()) {
+            Ok(input) => match &*input.to_ascii_lowercase() {
+                "memory" | "" => Ok(Backend::Memory),
+                "database" | "db" => Ok(Backend::Database),
+                input => bail!(
+                    "unexpected input given from environment variable `${}`: expected `memory` or `database`; received {} instead",
+                    BACKEND,
+                    input
+                ),
+            },
+
+            Err(TryParseError::System(VarError::NotUnicode(_))) => bail!(
+                "environment variable `${}` couldn't be loaded due to invalid unicode",
+                BACKEND
+            ),
+
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// How many requests can be sent in a window of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Limit {
+    /// How many requests can be sent in each window.
+    pub requests: u32,
+
+    /// How long each window lasts.
+    #[serde(default = "__default_window")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub window: Duration,
+}
+
+impl Limit {
+    pub const fn per_minute(requests: u32) -> Limit {
+        Limit {
+            requests,
+            window: __default_window(),
+        }
+    }
+}
+
+/// ## `[server.ratelimits]` table
+/// Limits how many requests clients can send. Requests are counted in buckets:
+///
+/// * requests with an API key are counted per API key,
+/// * requests with a session token are counted per user,
+/// * every other request is counted per IP address.
+///
+/// Routes in `routes` have their own buckets and limits, so that a limit on logging in
+/// doesn't eat into the limit that the rest of the API has.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Where rate limit counters are kept.
+    #[serde(default)]
+    pub backend: Backend,
+
+    /// Limit of requests that are counted per IP address.
+    #[serde(default = "__default_ip")]
+    pub ip: Limit,
+
+    /// Limit of requests that are counted per user.
+    #[serde(default = "__default_user")]
+    pub user: Limit,
+
+    /// Limit of requests that are counted per API key.
+    #[serde(default = "__default_apikey")]
+    pub apikey: Limit,
+
+    /// Limits of specific routes, keyed by the HTTP method and the route's path as
+    /// it was registered, like `"POST /v1/users/login"` or
+    /// `"GET /v1/repositories/{id}/releases"`.
+    ///
+    /// The latest API version is also served without its `/v1` prefix, so those
+    /// routes have to be listed separately.
+    #[serde(default = "__default_routes", skip_serializing_if = "BTreeMap::is_empty")]
+    pub routes: BTreeMap<String, Limit>,
+
+    /// IP addresses of reverse proxies that charted-server is deployed behind.
+    ///
+    /// Requests from these addresses are counted by the client's IP address in the
+    /// `X-Forwarded-For` header instead, which is the right-most address in it that
+    /// isn't a trusted proxy. The header is ignored on requests from anyone else, since
+    /// clients could otherwise pick a fresh IP address for every request.
+    ///
+    /// When configured with the `CHARTED_SERVER_RATELIMITS_TRUSTED_PROXIES` environment
+    /// variable, addresses are separated by commas.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[merge(strategy = __merge_trusted_proxies)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            backend: Backend::default(),
+            ip: __default_ip(),
+            user: __default_user(),
+            apikey: __default_apikey(),
+            routes: __default_routes(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            backend: Backend::try_from_env()?,
+            ip: Limit::per_minute(env::try_parse_or_else(IP, __default_ip().requests)?),
+            user: Limit::per_minute(env::try_parse_or_else(USER, __default_user().requests)?),
+            apikey: Limit::per_minute(env::try_parse_or_else(APIKEY, __default_apikey().requests)?),
+            routes: __default_routes(),
+            trusted_proxies: util::env_from_result(
+                std::env::var(TRUSTED_PROXIES).map(|proxies| {
+                    proxies
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|proxy| !proxy.is_empty())
+                        .map(String::from)
+                        .collect::<Vec<_>>()
+                }),
+                Vec::new(),
+            )?
+            .into_iter()
+            .map(|proxy| {
+                proxy.parse::<IpAddr>().map_err(|e| {
+                    eyre!("failed to parse `{proxy}` from environment variable `${TRUSTED_PROXIES}`: {e}")
+                })
+            })
+            .collect::<eyre::Result<_>>()?,
+        })
+    }
+}
+
+const fn __default_window() -> Duration {
+    Duration::from_secs(60)
+}
+
+const fn __default_ip() -> Limit {
+    Limit::per_minute(120)
+}
+
+const fn __default_user() -> Limit {
+    Limit::per_minute(600)
+}
+
+const fn __default_apikey() -> Limit {
+    Limit::per_minute(1200)
+}
+
+fn __default_routes() -> BTreeMap<String, Limit> {
+    // logging in is limited on its own to slow down brute-forcing passwords
+    BTreeMap::from([
+        (String::from("POST /users/login"), Limit::per_minute(10)),
+        (String::from("POST /v1/users/login"), Limit::per_minute(10)),
+    ])
+}
+
+fn __merge_trusted_proxies(me: &mut Vec<IpAddr>, other: Vec<IpAddr>) {
+    if !other.is_empty() {
+        *me = other;
+    }
+}
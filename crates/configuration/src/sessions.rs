@@ -14,6 +14,7 @@
 // limitations under the License.
 
 pub mod ldap;
+pub mod oidc;
 
 use crate::util;
 use azalia::config::{
@@ -98,6 +99,13 @@ pub struct Config {
 
     #[serde(default)]
     pub backend: Backend,
+
+    /// OpenID Connect providers that users can log in with and link their account to,
+    /// keyed by the provider's name.
+    ///
+    /// Providers can only be configured in the configuration file.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub oidc: BTreeMap<String, oidc::Provider>,
 }
 
 pub const ENABLE_BASIC_AUTH: &str = "CHARTED_SESSIONS_ENABLE_BASIC_AUTH";
@@ -109,6 +117,7 @@ impl TryFromEnv for Config {
         Ok(Config {
             enable_basic_auth: util::bool_env(ENABLE_BASIC_AUTH)?,
             backend: Backend::try_from_env()?,
+            oidc: BTreeMap::new(),
         })
     }
 }
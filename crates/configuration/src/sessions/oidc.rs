@@ -0,0 +1,87 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use azalia::config::merge::Merge;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Which of a user's connections that an OpenID Connect provider's accounts are linked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum Connection {
+    /// Links the provider's accounts as a [Noelware](https://account.noelware.org) account,
+    /// which requires the `sub` claim to be a ULID.
+    #[display("noelware")]
+    Noelware,
+
+    /// Links the provider's accounts as a [Google](https://google.com) account.
+    #[display("google")]
+    Google,
+
+    /// Links the provider's accounts as a [GitHub](https://github.com) account.
+    #[display("github")]
+    GitHub,
+
+    /// Links the provider's accounts as a [GitLab](https://gitlab.com) account.
+    #[display("gitlab")]
+    GitLab,
+}
+
+impl Merge for Connection {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+/// ## `[sessions.oidc.<name>]` table
+/// An OpenID Connect provider that users can log in with once they linked their
+/// account to it. Users are authenticated with the authorization code flow with
+/// [PKCE](https://www.rfc-editor.org/rfc/rfc7636).
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Provider {
+    /// Which of a user's connections that the provider's `sub` claim is linked to.
+    pub connection: Connection,
+
+    /// URL of the provider's issuer. The provider's metadata is discovered from
+    /// `{issuer}/.well-known/openid-configuration`.
+    pub issuer: Url,
+
+    /// Client ID that charted-server was registered as with the provider.
+    pub client_id: String,
+
+    /// Client secret that charted-server was registered with, if the provider requires
+    /// one for confidential clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+
+    /// URL that the provider redirects back to once the user authorized charted-server,
+    /// which should point to the `/v1/users/login/oidc/<name>/callback` endpoint.
+    pub redirect_url: Url,
+
+    /// Scopes to request from the provider. The `openid` scope is always requested.
+    #[serde(default = "__default_scopes")]
+    #[merge(strategy = __merge_scopes)]
+    pub scopes: Vec<String>,
+}
+
+fn __default_scopes() -> Vec<String> {
+    vec![String::from("openid")]
+}
+
+fn __merge_scopes(me: &mut Vec<String>, other: Vec<String>) {
+    if other != __default_scopes() {
+        *me = other;
+    }
+}
@@ -0,0 +1,130 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::util;
+use azalia::config::{
+    env::{self, TryFromEnv, TryParseError},
+    merge::Merge,
+};
+use charted_core::serde::Duration;
+use eyre::bail;
+use serde::{Deserialize, Serialize};
+use std::env::VarError;
+
+pub const STANDALONE: &str = "CHARTED_WORKERS_APIKEYS_STANDALONE";
+pub const INTERVAL: &str = "CHARTED_WORKERS_APIKEYS_INTERVAL";
+pub const ACTION: &str = "CHARTED_WORKERS_APIKEYS_ACTION";
+pub const NOTIFY_BEFORE: &str = "CHARTED_WORKERS_APIKEYS_NOTIFY_BEFORE";
+
+/// What happens to API keys once they expire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Expired API keys are kept so that their owners can still see them, but they
+    /// can no longer be used.
+    #[default]
+    #[display("deactivate")]
+    Deactivate,
+
+    /// Expired API keys are deleted.
+    #[display("delete")]
+    Delete,
+}
+
+impl Merge for Action {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+impl TryFromEnv for Action {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        match env::try_parse_or_else::<_, String>(ACTION, Default::default()) {
+            Ok(input) => match &*input.to_ascii_lowercase() {
+                "deactivate" | "" => Ok(Action::Deactivate),
+                "delete" => Ok(Action::Delete),
+                input => bail!(
+                    "unexpected input given from environment variable `${}`: expected `deactivate` or `delete`; received {} instead",
+                    ACTION,
+                    input
+                ),
+            },
+
+            Err(TryParseError::System(VarError::NotUnicode(_))) => bail!(
+                "environment variable `${}` couldn't be loaded due to invalid unicode",
+                ACTION
+            ),
+
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// ## `[workers.apikeys]` table
+/// The **API Key Expiration** worker, which takes care of API keys once they expire
+/// and can tell their owners beforehand.
+#[derive(Debug, Clone, Merge, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether if this worker is only ran on its own with `charted worker apikey`. If
+    /// not, then `charted server` runs it alongside the API server.
+    #[serde(default)]
+    #[merge(strategy = azalia::config::merge::strategy::bool::only_if_falsy)]
+    pub standalone: bool,
+
+    /// How often expired API keys are looked for.
+    #[serde(default = "__default_interval")]
+    #[merge(strategy = crate::util::merge_duration)]
+    pub interval: Duration,
+
+    /// What happens to API keys once they expire.
+    #[serde(default)]
+    pub action: Action,
+
+    /// How long before an API key expires that its owner is told about it, like
+    /// `"168h"` for a week. Owners aren't told if this isn't set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_before: Option<Duration>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            standalone: false,
+            interval: __default_interval(),
+            action: Action::default(),
+            notify_before: None,
+        }
+    }
+}
+
+impl TryFromEnv for Config {
+    type Error = eyre::Report;
+
+    fn try_from_env() -> Result<Self, Self::Error> {
+        Ok(Config {
+            standalone: util::bool_env(STANDALONE)?,
+            interval: env::try_parse_or_else(INTERVAL, __default_interval())?,
+            action: Action::try_from_env()?,
+            notify_before: env::try_parse_optional(NOTIFY_BEFORE)?,
+        })
+    }
+}
+
+const fn __default_interval() -> Duration {
+    Duration::from_secs(60 * 60)
+}
@@ -304,6 +304,10 @@ pub enum ErrorCode {
     /// invalid input was given
     InvalidInput,
 
+    /// sent too many requests and was rate limited, the `Retry-After` header tells
+    /// how many seconds to wait before sending requests again.
+    RateLimited,
+
     // ~ PATH PARAMETERS
     /// unable to parse a path parameter.
     UnableToParsePathParameter,
@@ -348,12 +352,22 @@ pub enum ErrorCode {
     /// Session already expired.
     SessionExpired,
 
+    /// api key had expired or was deactivated.
+    ApiKeyExpired,
+
     /// unknown session.
     UnknownSession,
 
     /// a refresh token is required in this request.
     RefreshTokenRequired,
 
+    /// the user has two-factor authentication enabled and a code from their
+    /// authenticator app, or a recovery code, is required.
+    TwoFactorRequired,
+
+    /// the two-factor authentication code given was invalid or was already used.
+    InvalidTwoFactorCode,
+
     // ~ PAGINATION
     /// the `?per_page` query parameter is maxed out to 100
     MaxPerPageExceeded,
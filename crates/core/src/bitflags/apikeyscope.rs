@@ -39,7 +39,7 @@ crate::bitflags! {
         /// GitHub or GitLab accounts, this is usually for OIDC providers that can query
         /// a user by an identifier that can identify a user into charted-server.
         ///
-        /// As of v0.1.0-beta, this scope is not used at all.
+        /// This also allows linking and unlinking OpenID Connect providers.
         UserConnections["user:connections"] => 1u64 << 3u64;
 
         /// Allows the current authenticated user to update their user avatar.
@@ -14,15 +14,30 @@
 // limitations under the License.
 
 pub mod apikey;
+pub mod audit_log;
+pub mod gpg_key;
+pub mod member_invite;
+pub mod oidc_authorization;
 pub mod organization;
+pub mod ratelimit;
 pub mod repository;
 pub mod session;
 pub mod user;
 pub mod user_connections;
+pub mod user_totp;
+pub mod webhook;
 
 pub use apikey::Entity as ApiKeyEntity;
-pub use organization::Entity as OrganizationEntity;
-pub use repository::{Entity as RepositoryEntity, release::Entity as RepositoryReleaseEntity};
+pub use audit_log::Entity as AuditLogEntity;
+pub use gpg_key::Entity as GpgKeyEntity;
+pub use member_invite::Entity as MemberInviteEntity;
+pub use oidc_authorization::Entity as OidcAuthorizationEntity;
+pub use organization::{Entity as OrganizationEntity, member::Entity as OrganizationMemberEntity};
+pub use ratelimit::Entity as RateLimitEntity;
+pub use repository::{
+    Entity as RepositoryEntity, member::Entity as RepositoryMemberEntity,
+    release::Entity as RepositoryReleaseEntity,
+};
 use sea_orm::{
     DeriveIden,
     prelude::Expr,
@@ -32,6 +47,8 @@ use sea_orm_migration::schema::{text, timestamp};
 pub use session::Entity as SessionEntity;
 pub use user::Entity as UserEntity;
 pub use user_connections::Entity as UserConnectionsEntity;
+pub use user_totp::Entity as UserTotpEntity;
+pub use webhook::{Entity as WebhookEntity, delivery::Entity as WebhookDeliveryEntity};
 
 #[derive(DeriveIden)]
 #[sea_orm(rename_all = "snake_case")]
@@ -24,6 +24,11 @@ pub struct Model {
     pub display_name: Option<String>,
     pub description: Option<String>,
     pub expires_in: Option<ChronoDateTimeUtc>,
+    pub deactivated_at: Option<ChronoDateTimeUtc>,
+
+    /// When the owner was told that this api key is about to expire.
+    pub expiry_notified_at: Option<ChronoDateTimeUtc>,
+
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
     pub scopes: i64,
@@ -41,6 +46,7 @@ impl From<Model> for ApiKey {
             display_name: model.display_name,
             description: model.description,
             expires_in: model.expires_in.map(Into::into),
+            deactivated_at: model.deactivated_at.map(Into::into),
             created_at: model.created_at.into(),
             updated_at: model.updated_at.into(),
             scopes: model.scopes,
@@ -0,0 +1,79 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{create_table, id};
+use charted_types::Ulid;
+use sea_orm::{entity::prelude::*, sea_query::TableCreateStatement};
+use sea_orm_migration::schema::*;
+
+/// A single record of the audit logs feature that is stored via the database sink.
+///
+/// Records don't reference the repository or users they are about since they have to
+/// outlive them.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub request_id: Option<String>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub repository: Option<Ulid>,
+
+    /// Fields that were changed by this action, as a JSON object of `{ "before": ..., "after": ... }`.
+    #[sea_orm(column_type = "Json")]
+    pub changes: Json,
+
+    #[sea_orm(column_type = "Text")]
+    pub action: String,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub actor: Option<Ulid>,
+
+    #[sea_orm(column_type = "Text")]
+    pub owner: Ulid,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub ip: Option<String>,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "audit_logs")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(text_null(Column::RequestId))
+        .col(text_null(Column::Repository))
+        .col(json(Column::Changes))
+        .col(text(Column::Action))
+        .col(text_null(Column::Actor))
+        .col(text(Column::Owner))
+        .col(text_null(Column::Ip))
+        .col(id())
+        .to_owned()
+}
@@ -0,0 +1,81 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{create_table, id};
+use charted_types::{GpgKey, Ulid};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Index, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "gpg_keys")]
+pub struct Model {
+    pub display_name: Option<String>,
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+    pub fingerprint: String,
+
+    #[sea_orm(column_type = "Text")]
+    pub public_key: String,
+
+    /// ID of the user or organization that this key belongs to.
+    pub owner: Ulid,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+impl From<Model> for GpgKey {
+    fn from(model: Model) -> Self {
+        GpgKey {
+            display_name: model.display_name,
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+            fingerprint: model.fingerprint,
+            public_key: model.public_key,
+            owner: model.owner,
+            id: model.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "gpg_keys")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(string_len_null(Column::DisplayName, 32))
+        .col(string_len(Column::Fingerprint, 64))
+        .col(text(Column::PublicKey))
+        .col(text(Column::Owner))
+        .col(id())
+        .index(
+            Index::create()
+                .name("idx_gpg_keys_owner_fingerprint")
+                .col(Column::Owner)
+                .col(Column::Fingerprint)
+                .unique(),
+        )
+        .to_owned()
+}
@@ -0,0 +1,181 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::entities::{create_table, id};
+use charted_types::{MemberInvite, Ulid};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{ForeignKey, Index, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+/// A pending invite for a user to become a member of a repository or an organization.
+/// Exactly one of `repository` or `organization` is set.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "member_invites")]
+pub struct Model {
+    /// Bitfield of `MemberPermission`s that the member will have.
+    pub permissions: i64,
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+    pub expires_at: ChronoDateTimeUtc,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub repository: Option<Ulid>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub organization: Option<Ulid>,
+
+    #[sea_orm(column_type = "Text")]
+    pub inviter: Ulid,
+
+    #[sea_orm(column_type = "Text")]
+    pub account: Ulid,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+impl From<Model> for MemberInvite {
+    fn from(model: Model) -> Self {
+        MemberInvite {
+            permissions: model.permissions as u64,
+            created_at: model.created_at.into(),
+            expires_at: model.expires_at.into(),
+            repository: model.repository,
+            organization: model.organization,
+            inviter: model.inviter,
+            account: model.account,
+            id: model.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "crate::entities::repository::Entity",
+        from = "Column::Repository",
+        to = "crate::entities::repository::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Repository,
+
+    #[sea_orm(
+        belongs_to = "crate::entities::organization::Entity",
+        from = "Column::Organization",
+        to = "crate::entities::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
+
+    #[sea_orm(
+        belongs_to = "crate::entities::user::Entity",
+        from = "Column::Account",
+        to = "crate::entities::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<crate::entities::repository::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Repository.def()
+    }
+}
+
+impl Related<crate::entities::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<crate::entities::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "member_invites")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(big_integer(Column::Permissions).default(0))
+        .col(timestamp(Column::ExpiresAt))
+        .col(text_null(Column::Repository))
+        .col(text_null(Column::Organization))
+        .col(text(Column::Inviter))
+        .col(text(Column::Account))
+        .col(id())
+        // a user can only have one invite per repository or organization
+        .index(
+            Index::create()
+                .name("idx_member_invite_repository_account")
+                .col(Column::Repository)
+                .col(Column::Account)
+                .unique(),
+        )
+        .index(
+            Index::create()
+                .name("idx_member_invite_organization_account")
+                .col(Column::Organization)
+                .col(Column::Account)
+                .unique(),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_member_invite_repository")
+                .from(Idens::Table, Column::Repository)
+                .to(
+                    crate::entities::repository::Idens::Table,
+                    crate::entities::repository::Column::Id,
+                )
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_member_invite_organization")
+                .from(Idens::Table, Column::Organization)
+                .to(
+                    crate::entities::organization::Idens::Table,
+                    crate::entities::organization::Column::Id,
+                )
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_member_invite_inviter")
+                .from(Idens::Table, Column::Inviter)
+                .to(crate::entities::user::Idens::Table, crate::entities::user::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_member_invite_account")
+                .from(Idens::Table, Column::Account)
+                .to(crate::entities::user::Idens::Table, crate::entities::user::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .to_owned()
+}
@@ -0,0 +1,89 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Pending OpenID Connect authorization requests, which are kept until the provider
+//! redirects the user back to charted-server.
+
+use super::{create_table, id};
+use charted_types::Ulid;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Index, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "oidc_authorizations")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+    pub expires_at: ChronoDateTimeUtc,
+
+    /// Name of the provider, as it was configured in `sessions.oidc`.
+    pub provider: String,
+
+    /// Opaque `state` parameter that was sent to the provider.
+    pub state: String,
+
+    /// PKCE code verifier that the token request is made with.
+    #[sea_orm(column_type = "Text")]
+    pub code_verifier: String,
+
+    /// Value of the `nonce` claim that the ID token has to have.
+    pub nonce: String,
+
+    /// ID of the user that is linking their account, or [`None`] if the user is
+    /// logging in instead.
+    ///
+    /// For a pending two-factor login, this is the user that is logging in.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub account: Option<Ulid>,
+
+    /// Whether if this is a login that is waiting for a code from the user's
+    /// authenticator app, in which case `state` is the ticket that the code has to
+    /// be sent back with.
+    pub totp_pending: bool,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "oidc_authorizations")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(timestamp(Column::ExpiresAt))
+        .col(text(Column::Provider))
+        .col(string_len(Column::State, 64))
+        .col(text(Column::CodeVerifier))
+        .col(string_len(Column::Nonce, 64))
+        .col(text_null(Column::Account))
+        .col(id())
+        .index(
+            Index::create()
+                .name("idx_oidc_authorizations_state")
+                .col(Column::State)
+                .unique(),
+        )
+        .to_owned()
+}
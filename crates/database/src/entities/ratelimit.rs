@@ -0,0 +1,60 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Rate limit counters that are shared between every instance of charted-server,
+//! which are used by the `database` rate limit backend.
+
+use super::create_table;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Index, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "ratelimits")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    /// When the window that this counter is for ends.
+    pub expires_at: ChronoDateTimeUtc,
+
+    /// How many requests were sent in the window.
+    pub hits: i64,
+
+    /// The bucket and window that this counter is for.
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "ratelimits")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(timestamp(Column::ExpiresAt))
+        .col(big_integer(Column::Hits).default(0))
+        .col(text(Column::Key).primary_key())
+        .index(Index::create().name("idx_ratelimits_expires_at").col(Column::ExpiresAt))
+        .to_owned()
+}
@@ -13,10 +13,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod member;
 pub mod release;
 
 use super::{create_table, id};
-use charted_types::{ChartType, Repository, Ulid, name::Name};
+use charted_types::{ChartType, Repository, Ulid, Version, name::Name};
 use sea_orm::{entity::prelude::*, sea_query::TableCreateStatement};
 use sea_orm_migration::schema::*;
 
@@ -35,6 +36,12 @@ pub struct Model {
     pub owner: Ulid,
     pub name: Name,
 
+    #[sea_orm(column_type = "Text", nullable)]
+    pub latest_version: Option<Version>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub latest_prerelease: Option<Version>,
+
     #[sea_orm(rename = "type")]
     pub type_: ChartType,
 
@@ -53,6 +60,8 @@ impl From<Model> for Repository {
             private: model.private,
             creator: model.creator,
             owner: model.owner,
+            latest_version: model.latest_version,
+            latest_prerelease: model.latest_prerelease,
             type_: model.type_,
             name: model.name,
             id: model.id,
@@ -0,0 +1,130 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::entities::{create_table, id};
+use charted_types::{RepositoryMember, Ulid};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{ForeignKey, Index, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+/// A user that is a member of a repository. Members are allowed to do what their
+/// `permissions` allow them to, while the repository's owner is allowed to do everything.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "repository_members")]
+pub struct Model {
+    #[sea_orm(column_type = "Text", nullable)]
+    pub display_name: Option<String>,
+
+    /// Bitfield of `MemberPermission`s.
+    pub permissions: i64,
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    #[sea_orm(column_type = "Text")]
+    pub repository: Ulid,
+
+    #[sea_orm(column_type = "Text")]
+    pub account: Ulid,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+impl From<Model> for RepositoryMember {
+    fn from(model: Model) -> Self {
+        RepositoryMember {
+            display_name: model.display_name,
+            permissions: model.permissions as u64,
+            updated_at: model.updated_at.into(),
+            joined_at: model.created_at.into(),
+            account: model.account,
+            id: model.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::Entity",
+        from = "Column::Repository",
+        to = "super::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Repository,
+
+    #[sea_orm(
+        belongs_to = "crate::entities::user::Entity",
+        from = "Column::Account",
+        to = "crate::entities::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Repository.def()
+    }
+}
+
+impl Related<crate::entities::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "repository_members")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(text_null(Column::DisplayName))
+        .col(big_integer(Column::Permissions).default(0))
+        .col(text(Column::Repository))
+        .col(text(Column::Account))
+        .col(id())
+        .index(
+            Index::create()
+                .name("idx_repository_member_account")
+                .col(Column::Repository)
+                .col(Column::Account)
+                .unique(),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_repository_member_repository")
+                .from(Idens::Table, Column::Repository)
+                .to(super::Idens::Table, super::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_repository_member_account")
+                .from(Idens::Table, Column::Account)
+                .to(crate::entities::user::Idens::Table, crate::entities::user::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .to_owned()
+}
@@ -16,7 +16,7 @@
 use charted_types::{RepositoryRelease, Ulid, Version};
 use sea_orm::{
     entity::prelude::*,
-    sea_query::{ForeignKey, TableCreateStatement},
+    sea_query::{ForeignKey, Index, IndexCreateStatement, TableCreateStatement},
 };
 use sea_orm_migration::schema::*;
 
@@ -31,6 +31,12 @@ pub struct Model {
     pub yanked: bool,
     pub title: Option<String>,
 
+    #[sea_orm(column_type = "Json", nullable)]
+    pub chart: Option<Json>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub digest: Option<String>,
+
     #[sea_orm(column_type = "Text")]
     pub tag: Version,
 
@@ -96,3 +102,19 @@ pub(crate) fn table() -> TableCreateStatement {
         )
         .to_owned()
 }
+
+/// Name of the unique index on `(repository, tag)`.
+pub(crate) const TAG_INDEX: &str = "idx_repository_release_tag";
+
+/// Unique index on `(repository, tag)`, so that a version can only be released once
+/// in a repository.
+pub(crate) fn tag_index() -> IndexCreateStatement {
+    Index::create()
+        .if_not_exists()
+        .name(TAG_INDEX)
+        .table(Idens::Table)
+        .col(Column::Repository)
+        .col(Column::Tag)
+        .unique()
+        .to_owned()
+}
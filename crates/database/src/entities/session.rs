@@ -24,6 +24,16 @@ use sea_orm_migration::schema::*;
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "sessions")]
 pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+    pub last_used_at: Option<ChronoDateTimeUtc>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub user_agent: Option<String>,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub ip: Option<String>,
+
     #[sea_orm(column_type = "Text")]
     pub refresh_token: String,
 
@@ -42,6 +52,10 @@ impl From<Model> for Session {
         Session {
             refresh_token: Some(model.refresh_token),
             access_token: Some(model.access_token),
+            user_agent: model.user_agent,
+            ip: model.ip,
+            created_at: model.created_at.into(),
+            last_used_at: model.last_used_at.map(Into::into),
             owner: model.account,
             id: model.id,
         }
@@ -0,0 +1,96 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{create_table, id};
+use charted_types::Ulid;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{ForeignKey, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+/// TOTP enrollment of a user. A row is created when a user starts enrolling and
+/// is only used to authenticate the user once `enabled` was set by verifying a
+/// code from their authenticator app.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "user_totp")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    /// List of SHA-256 hashes of the recovery codes that haven't been used yet.
+    #[sea_orm(column_type = "Json")]
+    pub recovery_codes: Json,
+
+    /// The last time step that a code was accepted for, so that a code can't be
+    /// used twice.
+    #[sea_orm(nullable)]
+    pub last_used_step: Option<i64>,
+
+    /// Base32-encoded shared secret.
+    #[sea_orm(column_type = "Text")]
+    pub secret: String,
+    pub enabled: bool,
+
+    #[sea_orm(column_type = "Text", unique)]
+    pub account: Ulid,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::Account",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "user_totp")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(json(Column::RecoveryCodes))
+        .col(big_integer_null(Column::LastUsedStep))
+        .col(text(Column::Secret))
+        .col(boolean(Column::Enabled))
+        .col(text_uniq(Column::Account))
+        .col(id())
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_user_totp_account")
+                .from(Idens::Table, Column::Account)
+                .to(super::user::Idens::Table, super::user::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .to_owned()
+}
@@ -0,0 +1,107 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod delivery;
+
+use super::{create_table, id};
+use charted_types::Ulid;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{ForeignKey, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+/// A HTTP endpoint that receives events from a repository, or from all repositories of a
+/// user or organization if `repository` is `NULL`.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "webhooks")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    #[sea_orm(column_type = "Text", nullable)]
+    pub repository: Option<Ulid>,
+
+    /// List of event types that this webhook is subscribed to.
+    #[sea_orm(column_type = "Json")]
+    pub events: Json,
+
+    #[sea_orm(column_type = "Text")]
+    pub secret: String,
+    pub enabled: bool,
+
+    #[sea_orm(column_type = "Text")]
+    pub owner: Ulid,
+
+    #[sea_orm(column_type = "Text")]
+    pub url: String,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::repository::Entity",
+        from = "Column::Repository",
+        to = "super::repository::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Repository,
+
+    #[sea_orm(has_many = "delivery::Entity")]
+    Delivery,
+}
+
+impl Related<super::repository::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Repository.def()
+    }
+}
+
+impl Related<delivery::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Delivery.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "webhooks")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(text_null(Column::Repository))
+        .col(json(Column::Events))
+        .col(text(Column::Secret))
+        .col(boolean(Column::Enabled))
+        .col(text(Column::Owner))
+        .col(text(Column::Url))
+        .col(id())
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_webhook_repository")
+                .from(Idens::Table, Column::Repository)
+                .to(super::repository::Idens::Table, super::repository::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .to_owned()
+}
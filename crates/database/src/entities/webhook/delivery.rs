@@ -0,0 +1,100 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::entities::{create_table, id};
+use charted_types::Ulid;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{ForeignKey, TableCreateStatement},
+};
+use sea_orm_migration::schema::*;
+
+/// A single attempt of delivering an event to a webhook.
+///
+/// Every attempt of the same event shares the same `message`, which is sent as the
+/// `webhook-id` header so that receivers can deduplicate retries.
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "webhook_deliveries")]
+pub struct Model {
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+
+    /// HTTP status code that the endpoint responded with, if it responded at all.
+    pub status_code: Option<i32>,
+
+    /// Why the attempt failed, if it didn't succeed.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub success: bool,
+    pub attempt: i32,
+
+    #[sea_orm(column_type = "Text")]
+    pub event: String,
+
+    #[sea_orm(column_type = "Text")]
+    pub message: Ulid,
+
+    #[sea_orm(column_type = "Text")]
+    pub webhook: Ulid,
+
+    #[sea_orm(column_type = "Text", primary_key, auto_increment = false)]
+    pub id: Ulid,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::Entity",
+        from = "Column::Webhook",
+        to = "super::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Webhook,
+}
+
+impl Related<super::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhook.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(DeriveIden)]
+pub(crate) enum Idens {
+    #[sea_orm(iden = "webhook_deliveries")]
+    Table,
+}
+
+pub(crate) fn table() -> TableCreateStatement {
+    create_table(Idens::Table)
+        .col(integer_null(Column::StatusCode))
+        .col(text_null(Column::Error))
+        .col(boolean(Column::Success))
+        .col(integer(Column::Attempt))
+        .col(text(Column::Event))
+        .col(text(Column::Message))
+        .col(text(Column::Webhook))
+        .col(id())
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk_webhook_delivery_webhook")
+                .from(Idens::Table, Column::Webhook)
+                .to(super::Idens::Table, super::Column::Id)
+                .on_delete(ForeignKeyAction::Cascade),
+        )
+        .to_owned()
+}
@@ -16,11 +16,41 @@
 use sea_orm_migration::MigratorTrait;
 
 pub(crate) mod m02_02_2025_000001_init;
+pub(crate) mod m17_10_2026_000001_release_chart_metadata;
+pub(crate) mod m17_10_2026_000002_webhooks;
+pub(crate) mod m17_10_2026_000003_audit_logs;
+pub(crate) mod m17_10_2026_000004_user_totp;
+pub(crate) mod m17_10_2026_000005_members;
+pub(crate) mod m17_10_2026_000006_member_invites;
+pub(crate) mod m17_10_2026_000007_repository_latest_versions;
+pub(crate) mod m17_10_2026_000008_gpg_keys;
+pub(crate) mod m17_10_2026_000009_oidc_authorizations;
+pub(crate) mod m17_10_2026_000010_session_metadata;
+pub(crate) mod m17_10_2026_000011_ratelimits;
+pub(crate) mod m17_10_2026_000012_apikey_expiry;
+pub(crate) mod m17_10_2026_000013_release_tag_index;
+pub(crate) mod m17_10_2026_000014_oidc_totp_pending;
 
 pub struct Migrator;
 
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn sea_orm_migration::MigrationTrait>> {
-        vec![Box::new(m02_02_2025_000001_init::migration())]
+        vec![
+            Box::new(m02_02_2025_000001_init::migration()),
+            Box::new(m17_10_2026_000001_release_chart_metadata::migration()),
+            Box::new(m17_10_2026_000002_webhooks::migration()),
+            Box::new(m17_10_2026_000003_audit_logs::migration()),
+            Box::new(m17_10_2026_000004_user_totp::migration()),
+            Box::new(m17_10_2026_000005_members::migration()),
+            Box::new(m17_10_2026_000006_member_invites::migration()),
+            Box::new(m17_10_2026_000007_repository_latest_versions::migration()),
+            Box::new(m17_10_2026_000008_gpg_keys::migration()),
+            Box::new(m17_10_2026_000009_oidc_authorizations::migration()),
+            Box::new(m17_10_2026_000010_session_metadata::migration()),
+            Box::new(m17_10_2026_000011_ratelimits::migration()),
+            Box::new(m17_10_2026_000012_apikey_expiry::migration()),
+            Box::new(m17_10_2026_000013_release_tag_index::migration()),
+            Box::new(m17_10_2026_000014_oidc_totp_pending::migration()),
+        ]
     }
 }
@@ -0,0 +1,76 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adds the parsed `Chart.yaml` and the tarball's SHA-256 digest to repository
+//! releases, so that they don't need to be read from the tarball every time.
+
+use crate::entities::repository::release;
+use sea_orm_migration::{prelude::*, schema::*};
+
+pub fn migration() -> impl MigrationTrait {
+    Impl
+}
+
+struct Impl;
+
+impl MigrationName for Impl {
+    fn name(&self) -> &str {
+        "release_chart_metadata"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Impl {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one change per `ALTER TABLE` statement.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(release::Idens::Table)
+                    .add_column(json_null(release::Column::Chart))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(release::Idens::Table)
+                    .add_column(text_null(release::Column::Digest))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(release::Idens::Table)
+                    .drop_column(release::Column::Chart)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(release::Idens::Table)
+                    .drop_column(release::Column::Digest)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,59 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adds the `repository_members` and `organization_members` tables.
+
+use crate::entities::{organization, repository};
+use sea_orm_migration::prelude::*;
+
+pub fn migration() -> impl MigrationTrait {
+    Impl
+}
+
+struct Impl;
+
+impl MigrationName for Impl {
+    fn name(&self) -> &str {
+        "members"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Impl {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(repository::member::table()).await?;
+        manager.create_table(organization::member::table()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(organization::member::Idens::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(repository::member::Idens::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
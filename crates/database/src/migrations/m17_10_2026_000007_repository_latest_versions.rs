@@ -0,0 +1,78 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Caches the latest stable and pre-release versions of a repository, so that
+//! downloading the `latest` version doesn't need to list every chart tarball in
+//! the datastore.
+//!
+//! Existing repositories are backfilled with `charted admin repair-latest-versions`.
+
+use crate::entities::repository;
+use sea_orm_migration::{prelude::*, schema::*};
+
+pub fn migration() -> impl MigrationTrait {
+    Impl
+}
+
+struct Impl;
+
+impl MigrationName for Impl {
+    fn name(&self) -> &str {
+        "repository_latest_versions"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Impl {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one change per `ALTER TABLE` statement.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(repository::Idens::Table)
+                    .add_column(text_null(repository::Column::LatestVersion))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(repository::Idens::Table)
+                    .add_column(text_null(repository::Column::LatestPrerelease))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(repository::Idens::Table)
+                    .drop_column(repository::Column::LatestVersion)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(repository::Idens::Table)
+                    .drop_column(repository::Column::LatestPrerelease)
+                    .to_owned(),
+            )
+            .await
+    }
+}
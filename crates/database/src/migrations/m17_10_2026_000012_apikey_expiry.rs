@@ -0,0 +1,67 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Adds the columns that the API key expiration worker keeps track of API keys with.
+
+use crate::entities::apikey;
+use sea_orm_migration::{prelude::*, schema::*};
+
+pub fn migration() -> impl MigrationTrait {
+    Impl
+}
+
+struct Impl;
+
+impl MigrationName for Impl {
+    fn name(&self) -> &str {
+        "apikey_expiry"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Impl {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one change per `ALTER TABLE` statement.
+        for column in [
+            timestamp_null(apikey::Column::DeactivatedAt),
+            timestamp_null(apikey::Column::ExpiryNotifiedAt),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(apikey::Idens::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [apikey::Column::DeactivatedAt, apikey::Column::ExpiryNotifiedAt] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(apikey::Idens::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
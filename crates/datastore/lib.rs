@@ -48,6 +48,7 @@
 //! # });
 //! ```
 
+mod upload;
 // Export the main `remi` crate.
 use azalia_remi::{
     StorageService,
@@ -65,9 +66,16 @@ use std::{
     path::{Path, PathBuf},
 };
 use tracing::debug;
+pub use upload::PART_SIZE;
 
 #[derive(Clone)]
-pub struct DataStore(azalia_remi::StorageService);
+pub struct DataStore {
+    service: azalia_remi::StorageService,
+
+    /// Client that uploads files to Amazon S3 in parts, since the storage service
+    /// doesn't expose its own.
+    s3: Option<upload::S3>,
+}
 impl DataStore {
     /// Creates a new [`DataStore`] instance and initializes the datastore itself.
     pub async fn new(config: &storage::Config) -> eyre::Result<Self> {
@@ -82,22 +90,27 @@ impl DataStore {
             ),
         };
 
+        let s3 = match config {
+            storage::Config::S3(s3) => Some(upload::S3::new(s3.to_owned())),
+            _ => None,
+        };
+
         remi::StorageService::init(&service).await.into_report()?;
-        Ok(Self(service))
+        Ok(Self { service, s3 })
     }
 
     /// Return a [`Namespace`] object that allows to group persisted data to make it
     /// easier to handle the datastore itself.
     pub fn namespace<'storage, N: Into<Cow<'storage, str>>>(&'storage self, namespace: N) -> Namespace<'storage> {
         Namespace {
-            handle: &self.0,
+            handle: &self.service,
             namespace: namespace.into(),
         }
     }
 
     /// Returns `true` if the datastore is a local filesystem path.
     pub const fn is_filesystem(&self) -> bool {
-        matches!(self, DataStore(StorageService::Filesystem(_)))
+        matches!(self.service, StorageService::Filesystem(_))
     }
 }
 
@@ -105,7 +118,7 @@ impl Deref for DataStore {
     type Target = azalia_remi::StorageService;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.service
     }
 }
 
@@ -117,6 +130,20 @@ pub struct Namespace<'storage> {
     namespace: Cow<'storage, str>,
 }
 
+impl Namespace<'_> {
+    /// Returns the path of `path` within this namespace, as the storage service sees it.
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        match self.handle {
+            StorageService::Filesystem(_) => PathBuf::from(format!("./{}", self.namespace)).join(path),
+            StorageService::Azure(_) | StorageService::S3(_) => {
+                PathBuf::from(self.namespace.to_string()).join(path)
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
 impl Debug for Namespace<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("Namespace").field(&self.namespace).finish()
@@ -0,0 +1,230 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Uploads of files that are too big to be held in memory.
+//!
+//! [`StorageService::upload`](azalia_remi::core::StorageService::upload) takes the
+//! whole object in memory, so files are uploaded in parts of [`PART_SIZE`] instead
+//! with the multipart upload APIs of Amazon S3 and Azure Blob Storage, which keeps
+//! the memory that an upload takes bounded regardless of how big the file is.
+
+use crate::{DataStore, azure::core::storage::blobs::prelude::*, s3};
+use azalia_remi::{StorageService, core::Bytes};
+use eyre::{Context, OptionExt};
+use s3::aws::s3::{
+    Client,
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart, ObjectCannedAcl},
+};
+use std::path::Path;
+use tokio::{
+    fs::{self, File},
+    io::{AsyncRead, AsyncReadExt},
+};
+use tracing::{instrument, warn};
+
+/// Size of every part of an upload but the last one. Amazon S3 requires every part
+/// but the last one to be at least 5 MiB.
+pub const PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// Amazon S3 client that is built from the same configuration as the storage service.
+#[derive(Clone)]
+pub(crate) struct S3 {
+    client: Client,
+    config: s3::StorageConfig,
+}
+
+impl S3 {
+    pub(crate) fn new(config: s3::StorageConfig) -> Self {
+        S3 {
+            client: Client::from_conf(config.clone().into()),
+            config,
+        }
+    }
+
+    /// Resolves `path` into an object key the same way that [`s3::StorageService`] does.
+    fn key(&self, path: &Path) -> eyre::Result<String> {
+        let path = path
+            .to_str()
+            .ok_or_eyre("expected path to be valid utf-8")?
+            .trim_start_matches("~/")
+            .trim_start_matches("./");
+
+        Ok(match self.config.prefix.as_deref().unwrap_or_default() {
+            "" => path.to_owned(),
+            prefix => format!("{}/{path}", prefix.trim_start_matches("~/").trim_start_matches("./")),
+        })
+    }
+
+    async fn upload(&self, path: &Path, mut file: File, content_type: &str) -> eyre::Result<()> {
+        let key = self.key(path)?;
+        let output = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(&key)
+            .acl(
+                self.config
+                    .default_object_acl
+                    .clone()
+                    .unwrap_or(ObjectCannedAcl::BucketOwnerFullControl),
+            )
+            .content_type(content_type)
+            .send()
+            .await
+            .context("failed to create multipart upload")?;
+
+        let upload_id = output.upload_id().ok_or_eyre("multipart upload has no upload id")?;
+        let result = async {
+            let mut parts = Vec::new();
+            let mut number = 1;
+            loop {
+                let part = read_part(&mut file).await?;
+                let len = part.len();
+
+                let output = self
+                    .client
+                    .upload_part()
+                    .bucket(&self.config.bucket)
+                    .key(&key)
+                    .upload_id(upload_id)
+                    .part_number(number)
+                    .body(ByteStream::from(part))
+                    .send()
+                    .await
+                    .with_context(|| format!("failed to upload part #{number}"))?;
+
+                parts.push(
+                    CompletedPart::builder()
+                        .part_number(number)
+                        .set_e_tag(output.e_tag().map(ToOwned::to_owned))
+                        .build(),
+                );
+
+                if len < PART_SIZE {
+                    break;
+                }
+
+                number += 1;
+            }
+
+            self.client
+                .complete_multipart_upload()
+                .bucket(&self.config.bucket)
+                .key(&key)
+                .upload_id(upload_id)
+                .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+                .send()
+                .await
+                .context("failed to complete multipart upload")?;
+
+            Ok::<_, eyre::Report>(())
+        }
+        .await;
+
+        if result.is_err() &&
+            let Err(e) = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&self.config.bucket)
+                .key(&key)
+                .upload_id(upload_id)
+                .send()
+                .await
+        {
+            warn!(error = %e, key, "failed to abort multipart upload");
+        }
+
+        result
+    }
+}
+
+impl DataStore {
+    /// Uploads the file at `file` to `path`, which is relative to the root of the
+    /// datastore, without ever holding more than [`PART_SIZE`] of it in memory.
+    ///
+    /// Use [`Namespace::resolve`](crate::Namespace::resolve) to upload into a namespace.
+    #[instrument(name = "charted.datastore.uploadFile", skip_all, fields(path = %path.as_ref().display()))]
+    pub async fn upload_file<P: AsRef<Path>>(&self, path: P, file: &Path, content_type: &str) -> eyre::Result<()> {
+        let path = path.as_ref();
+        match &self.service {
+            StorageService::Filesystem(service) => {
+                let dest = service
+                    .normalize(path)?
+                    .ok_or_eyre("unable to normalize path to upload file to")?;
+
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+
+                fs::copy(file, dest).await?;
+                Ok(())
+            }
+
+            StorageService::S3(_) => {
+                let s3 = self.s3.as_ref().ok_or_eyre("amazon s3 client was not initialized")?;
+                s3.upload(path, File::open(file).await?, content_type).await
+            }
+
+            StorageService::Azure(service) => {
+                let path = path
+                    .to_str()
+                    .ok_or_eyre("expected path to be valid utf-8")?
+                    .trim_start_matches("./")
+                    .trim_start_matches("~/");
+
+                let client = service.blob_client(path);
+                let mut file = File::open(file).await?;
+                let mut blocks = BlockList::default();
+
+                // block ids have to be the same length within a blob
+                for number in 0u32.. {
+                    let part = read_part(&mut file).await?;
+                    let len = part.len();
+                    let id = BlockId::new(format!("{number:08}"));
+
+                    client
+                        .put_block(id.clone(), Bytes::from(part))
+                        .await
+                        .with_context(|| format!("failed to put block #{number}"))?;
+
+                    blocks.blocks.push(BlobBlockType::new_uncommitted(id));
+                    if len < PART_SIZE {
+                        break;
+                    }
+                }
+
+                client
+                    .put_block_list(blocks)
+                    .content_type(content_type.to_owned())
+                    .await
+                    .context("failed to commit block list")?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Reads up to [`PART_SIZE`] bytes from `reader`. The part is only shorter than that
+/// if the end of `reader` was reached.
+async fn read_part<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut part = Vec::with_capacity(PART_SIZE);
+    reader.take(PART_SIZE as u64).read_to_end(&mut part).await?;
+
+    Ok(part)
+}
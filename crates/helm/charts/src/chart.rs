@@ -0,0 +1,185 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::http::StatusCode;
+use charted_core::api;
+use charted_helm_types::{Chart, ChartFile};
+use charted_types::{Repository, Version};
+use eyre::Context;
+use flate2::bufread::MultiGzDecoder;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    path::{Component, Path},
+};
+use tar::Archive;
+
+/// A chart tarball that was validated and stored in the datastore.
+#[derive(Debug, Clone)]
+pub struct UploadedChart {
+    /// The chart's parsed `Chart.yaml` file.
+    pub chart: Chart,
+
+    /// SHA-256 digest of the chart tarball, see [`digest`].
+    pub digest: String,
+}
+
+/// Reads the top-level `Chart.yaml` file from a packaged Helm chart.
+///
+/// Packaged charts have all of their files under a single directory that is named
+/// after the chart (i.e, `hello-world/Chart.yaml`), so `Chart.yaml` files from
+/// subcharts in `charts/` are never picked up. Returns [`None`] if the tarball
+/// doesn't have one.
+pub fn read_chart_metadata(tarball: &[u8]) -> eyre::Result<Option<Chart>> {
+    let mut archive = Archive::new(MultiGzDecoder::new(tarball));
+    for entry in archive.entries().context("failed to read tarball entries")? {
+        let mut entry = entry.context("failed to compute tar entry")?;
+        if !is_chart_manifest(&entry.path()?) {
+            continue;
+        }
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .context("failed to read `Chart.yaml`")?;
+
+        return serde_yaml_ng::from_slice(&contents)
+            .context("failed to parse `Chart.yaml`")
+            .map(Some);
+    }
+
+    Ok(None)
+}
+
+/// Extracts every file in a packaged Helm chart, sorted by their paths which are relative
+/// to the chart's root directory.
+pub fn extract_chart_files(tarball: &[u8]) -> eyre::Result<Vec<(ChartFile, Vec<u8>)>> {
+    let mut archive = Archive::new(MultiGzDecoder::new(tarball));
+    let mut files = Vec::new();
+
+    for entry in archive.entries().context("failed to read tarball entries")? {
+        let mut entry = entry.context("failed to compute tar entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let Some(path) = relative_path(&entry.path()?) else {
+            continue;
+        };
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .with_context(|| format!("failed to read `{path}`"))?;
+
+        files.push((
+            ChartFile {
+                path,
+                size: entry.size(),
+            },
+            contents,
+        ));
+    }
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// Reads a single file from a packaged Helm chart, where `path` is relative to the
+/// chart's root directory. Returns [`None`] if the tarball doesn't have it.
+pub fn read_chart_file(tarball: &[u8], path: &str) -> eyre::Result<Option<Vec<u8>>> {
+    let mut archive = Archive::new(MultiGzDecoder::new(tarball));
+    for entry in archive.entries().context("failed to read tarball entries")? {
+        let mut entry = entry.context("failed to compute tar entry")?;
+        if !entry.header().entry_type().is_file() || relative_path(&entry.path()?).as_deref() != Some(path) {
+            continue;
+        }
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .with_context(|| format!("failed to read `{path}`"))?;
+
+        return Ok(Some(contents));
+    }
+
+    Ok(None)
+}
+
+/// Computes the SHA-256 digest of a chart tarball as a lowercase hex string, which
+/// is what Helm expects in the `digest` field of a `index.yaml` entry.
+pub fn digest(tarball: &[u8]) -> String {
+    hex::encode(Sha256::digest(tarball))
+}
+
+/// Validates that the `Chart.yaml` of a chart that is being uploaded agrees with the
+/// repository it's being published to and the release tag it's being published as.
+pub(crate) fn validate_chart(
+    chart: &Chart,
+    repository: &Repository,
+    version: &Version,
+) -> Result<(), api::Response> {
+    if chart.name != repository.name.as_str() {
+        return Err(mismatch("name", repository.name.as_str(), &chart.name));
+    }
+
+    if chart.version != *version {
+        return Err(mismatch("version", version, &chart.version));
+    }
+
+    if chart.type_ != repository.type_ {
+        return Err(mismatch("type", &repository.type_, &chart.type_));
+    }
+
+    Ok(())
+}
+
+fn mismatch(path: &'static str, expected: impl ToString, received: impl ToString) -> api::Response {
+    api::err(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        (
+            api::ErrorCode::ValidationFailed,
+            format!("`{path}` in `Chart.yaml` doesn't match the repository or release"),
+            json!({
+                "path": path,
+                "expected": expected.to_string(),
+                "received": received.to_string(),
+            }),
+        ),
+    )
+}
+
+/// Strips the chart's root directory from `path`, returning [`None`] for anything
+/// that isn't a plain path within it.
+fn relative_path(path: &Path) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_str()?),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    match components.split_first() {
+        Some((_, rest)) if !rest.is_empty() => Some(rest.join("/")),
+        _ => None,
+    }
+}
+
+fn is_chart_manifest(path: &Path) -> bool {
+    path.components().count() == 2 && path.file_name().is_some_and(|name| name == "Chart.yaml")
+}
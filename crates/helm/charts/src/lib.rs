@@ -19,31 +19,38 @@ pub mod testutil;
 #[cfg(test)]
 mod tests;
 
+mod chart;
 mod ext;
+pub mod provenance;
+mod staging;
+mod validate;
 
 use axum::http::StatusCode;
-use charted_core::{BoxedFuture, ResultExt, api};
+pub use chart::*;
+use charted_core::{ResultExt, api, rand_string};
 use charted_datastore::{
     DataStore, Namespace,
-    remi::{Blob, File, StorageService, UploadRequest},
+    remi::{Blob, Bytes, File, StorageService, UploadRequest},
 };
-use charted_helm_types::ChartIndex;
-use charted_types::{QueryableVersion, Ulid, Version};
+use charted_helm_types::{ChartFile, ChartIndex};
+use charted_types::{Repository, Ulid, Version};
 pub use ext::*;
-use eyre::{Context, bail};
-use flate2::bufread::MultiGzDecoder;
+use eyre::{bail, eyre};
 use futures_util::future::FutureExt;
 use itertools::Itertools;
 use multer::Multipart;
-use tar::Archive;
-use tracing::{error, info, instrument, trace, warn};
+use provenance::{Provenance, ProvenanceError, SignedPublicKey};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, instrument, warn};
+pub use validate::{Limits, Violation};
 
 /// Accepted content types that are allowed to be sent as a tarball
 pub(crate) const ACCEPTABLE_CONTENT_TYPES: &[&str] = &["application/gzip", "application/tar+gzip"];
 
-/// Exempted files that aren't usually in a Helm chart, but they are allowed to be in one.
-pub(crate) const EXEMPTED_FILES: &[&str] = &["values.schema.json", "README.md", "LICENSE"];
-pub(crate) const ALLOWED_FILES: &[&str] = &["README.md", "LICENSE", "values.yaml", "Chart.yaml", "Chart.lock"];
+/// Accepted content types that are allowed to be sent as a provenance file.
+pub(crate) const ACCEPTABLE_PROVENANCE_CONTENT_TYPES: &[&str] =
+    &["application/pgp-signature", "text/plain", "application/octet-stream"];
 
 /// Newtype wrapper for the `metadata` namespace.
 #[derive(Clone, derive_more::Display, derive_more::Deref)]
@@ -79,6 +86,25 @@ impl<'storage> MetadataNamespace<'storage> {
         }
     }
 
+    /// Overwrites the [`ChartIndex`] of the specified owner.
+    pub async fn put_chart_index(&self, owner: Ulid, index: &ChartIndex) -> eyre::Result<()> {
+        let request = UploadRequest::default()
+            .with_data(serde_yaml_ng::to_string(index)?)
+            .with_content_type(Some("application/yaml; charset=utf-8"));
+
+        self.upload(format!("{owner}/index.yaml"), request).await.into_report()
+    }
+
+    /// Deletes the [`ChartIndex`] of the specified owner, if it exists.
+    pub async fn delete_chart_index(&self, owner: Ulid) -> eyre::Result<()> {
+        let key = format!("{owner}/index.yaml");
+        if self.exists(&key).await.into_report()? {
+            self.delete(key).await.into_report()?;
+        }
+
+        Ok(())
+    }
+
     /// Creates a [`ChartIndex`] for the specified user. If the folder doesn't exist
     /// if we are on the filesystem, then that will take care of it.
     pub async fn create_chart_index(&self, owner: Ulid) -> eyre::Result<ChartIndex> {
@@ -93,13 +119,7 @@ impl<'storage> MetadataNamespace<'storage> {
         }
 
         let index = ChartIndex::default();
-        let request = UploadRequest::default()
-            .with_data(serde_yaml_ng::to_string(&index)?)
-            .with_content_type(Some("application/yaml; charset=utf-8"));
-
-        self.upload(format!("{owner}/index.yaml"), request)
-            .await
-            .into_report()?;
+        self.put_chart_index(owner, &index).await?;
 
         Ok(index)
     }
@@ -190,13 +210,11 @@ impl<'storage> OwnerRepoNamespace<'storage> {
         Ok(versions)
     }
 
-    /// Retrieve a chart index for a specified version or the latest version.
+    /// Retrieve a chart tarball for a specified version.
     ///
-    /// ## Rationale
-    /// If the latest version is being queried, unfortunately, all the versions of the
-    /// repository have to be sorted by ascending order to find the latest version. For now,
-    /// it works but we would probably add a `latest` field in the repository's metadata
-    /// or cache the results.
+    /// The `latest` version is resolved by the caller from the latest versions that
+    /// are cached in the repository's metadata, so that this is only a single lookup
+    /// in the datastore.
     ///
     /// If `prereleases` is true, this will also allow querying pre-releases as well.
     #[instrument(
@@ -209,44 +227,20 @@ impl<'storage> OwnerRepoNamespace<'storage> {
             repository.id = %self.repo,
         )
     )]
-    pub fn get_chart<'asyncfn>(
-        &'asyncfn self,
-        version: QueryableVersion,
-        prereleases: bool,
-    ) -> BoxedFuture<'asyncfn, eyre::Result<Option<File>>> {
-        Box::pin(async move {
-            if version.is_latest() {
-                let sorted = self.sort_versions(prereleases).await?;
-                if sorted.is_empty() {
-                    return Ok(None);
-                }
-
-                return self
-                    .get_chart(QueryableVersion::Version(sorted[0].clone()), prereleases)
-                    .await;
-            }
-
-            info!("querying chart tarball from datastore");
-
-            // Safety: We are under `QueryableVersion::Version` now and if we are
-            // still `QueryableVersion::Latest`, it's a bug.
-            let version = unsafe { version.as_version_unchecked() };
-            if !version.pre.is_empty() && !prereleases {
-                bail!("version being queried is a prerelease version but prereleases are not allowed?")
-            }
+    pub async fn get_chart(&self, version: &Version, prereleases: bool) -> eyre::Result<Option<File>> {
+        info!("querying chart tarball from datastore");
+        if !version.pre.is_empty() && !prereleases {
+            bail!("version being queried is a prerelease version but prereleases are not allowed?")
+        }
 
-            match self.blob(format!("tarballs/{version}.tgz")).await.into_report()? {
-                Some(Blob::File(file)) => Ok(Some(file)),
-                _ => Ok(None),
-            }
-        })
+        match self.blob(format!("tarballs/{version}.tgz")).await.into_report()? {
+            Some(Blob::File(file)) => Ok(Some(file)),
+            _ => Ok(None),
+        }
     }
 
     /// Retrieves a Helm chart's [provenance](https://helm.sh/docs/topics/provenance/) for
-    /// a specific version or the latest version of the chart.
-    ///
-    /// Read the **Rationale** header in [`OwnerRepoNamespace::get_chart`] for more
-    /// information on how we query tarballs from the datastore.
+    /// a specific version of the chart.
     #[instrument(
         name = "charted.helm.getChartProvenance",
         skip_all,
@@ -257,37 +251,125 @@ impl<'storage> OwnerRepoNamespace<'storage> {
             %version
         )
     )]
-    pub fn get_chart_provenance<'asyncfn>(
-        &'asyncfn self,
-        version: QueryableVersion,
-        prereleases: bool,
-    ) -> BoxedFuture<'asyncfn, eyre::Result<Option<File>>> {
-        Box::pin(async {
-            if version.is_latest() {
-                let sorted = self.sort_versions(prereleases).await?;
-                if sorted.is_empty() {
-                    return Ok(None);
-                }
+    pub async fn get_chart_provenance(&self, version: &Version, prereleases: bool) -> eyre::Result<Option<File>> {
+        info!("querying chart provenance from datastore");
+        if !version.pre.is_empty() && !prereleases {
+            bail!("version being queried is a prerelease version but prereleases are not allowed?")
+        }
 
-                return self
-                    .get_chart_provenance(QueryableVersion::Version(sorted[0].clone()), prereleases)
-                    .await;
+        match self.blob(format!("tarballs/{version}.prov.tgz")).await.into_report()? {
+            Some(Blob::File(file)) => Ok(Some(file)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Lists every file in the chart tarball of a specific version. Returns [`None`]
+    /// if the version doesn't have a chart tarball.
+    ///
+    /// The listing is cached in `files/{version}.json` once the tarball was read, and
+    /// the contents of every file next to it in `files/{version}.{index}`, where `index`
+    /// is the position of the file in the listing. Later reads don't need to decompress
+    /// the tarball again.
+    #[instrument(
+        name = "charted.helm.getChartFiles",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+            %version
+        )
+    )]
+    pub async fn get_chart_files(&self, version: &Version) -> eyre::Result<Option<Vec<ChartFile>>> {
+        let key = format!("files/{version}.json");
+        if let Some(bytes) = self.open(&key).await.into_report()? {
+            match serde_json::from_slice(&bytes) {
+                Ok(files) => return Ok(Some(files)),
+                Err(e) => warn!(error = %e, "cached file listing is malformed; listing chart tarball again"),
             }
+        }
 
-            info!("querying chart tarball from datastore");
+        let Some(file) = self.get_chart(version, true).await? else {
+            return Ok(None);
+        };
 
-            // Safety: We are under `QueryableVersion::Version` now and if we are
-            // still `QueryableVersion::Latest`, it's a bug.
-            let version = unsafe { version.as_version_unchecked() };
-            if !version.pre.is_empty() && !prereleases {
-                bail!("version being queried is a prerelease version but prereleases are not allowed?")
-            }
+        let (files, contents): (Vec<_>, Vec<_>) = extract_chart_files(&file.data)?.into_iter().unzip();
+
+        // the listing is cached last so that it's only ever cached with the contents
+        // of every file in it.
+        let cached = async {
+            for (index, data) in contents.into_iter().enumerate() {
+                let request = UploadRequest::default()
+                    .with_content_type(Some("application/octet-stream"))
+                    .with_data(data);
 
-            match self.blob(format!("tarballs/{version}.prov.tgz")).await.into_report()? {
-                Some(Blob::File(file)) => Ok(Some(file)),
-                _ => Ok(None),
+                self.upload(format!("files/{version}.{index}"), request)
+                    .await
+                    .into_report()?;
             }
-        })
+
+            let request = UploadRequest::default()
+                .with_content_type(Some("application/json"))
+                .with_data(serde_json::to_vec(&files)?);
+
+            self.upload(key, request).await.into_report()
+        }
+        .await;
+
+        if let Err(e) = cached {
+            warn!(error = %e, "failed to cache files of chart tarball");
+        }
+
+        Ok(Some(files))
+    }
+
+    /// Reads a single file from the chart tarball of a specific version, where `path` is
+    /// relative to the chart's root directory. Returns [`None`] if the file doesn't exist.
+    ///
+    /// The file is read from the contents that [`get_chart_files`](Self::get_chart_files)
+    /// cached, and only from the tarball if they weren't.
+    #[instrument(
+        name = "charted.helm.getChartFile",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+            %version,
+            %path
+        )
+    )]
+    pub async fn get_chart_file(&self, version: &Version, path: &str) -> eyre::Result<Option<Vec<u8>>> {
+        let Some(files) = self.get_chart_files(version).await? else {
+            return Ok(None);
+        };
+
+        let Some(index) = files.iter().position(|file| file.path == path) else {
+            return Ok(None);
+        };
+
+        let key = format!("files/{version}.{index}");
+        if let Some(contents) = self.open(&key).await.into_report()? {
+            return Ok(Some(contents.to_vec()));
+        }
+
+        // listings that were cached before the contents were have to be read from
+        // the tarball itself.
+        let Some(file) = self.get_chart(version, true).await? else {
+            return Ok(None);
+        };
+
+        let Some(contents) = read_chart_file(&file.data, path)? else {
+            return Ok(None);
+        };
+
+        let request = UploadRequest::default()
+            .with_content_type(Some("application/octet-stream"))
+            .with_data(contents.clone());
+
+        if let Err(e) = self.upload(key, request).await {
+            warn!(error = %e, "failed to cache file of chart tarball");
+        }
+
+        Ok(Some(contents))
     }
 
     /// Deletes a Helm chart from the datastore.
@@ -306,7 +388,7 @@ impl<'storage> OwnerRepoNamespace<'storage> {
 
     /// Deletes a Helm chart's [provenance](https://helm.sh/docs/topics/provenance/) from the datastore.
     #[instrument(
-        name = "charted.helm.deleteChart",
+        name = "charted.helm.deleteChartProvenance",
         skip_all,
         fields(
             owner.id = %self.owner,
@@ -318,7 +400,64 @@ impl<'storage> OwnerRepoNamespace<'storage> {
         &self,
         version: Version,
     ) -> impl Future<Output = eyre::Result<()>> + Send + use<'_> {
-        self.delete(format!("tarballs/{version}.tgz")).map(|x| x.into_report())
+        self.delete(format!("tarballs/{version}.prov.tgz"))
+            .map(|x| x.into_report())
+    }
+
+    /// Deletes the cached file listing and file contents of a Helm chart, if they
+    /// were ever cached.
+    #[instrument(
+        name = "charted.helm.deleteChartFiles",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+            %version,
+        )
+    )]
+    pub async fn delete_chart_files(&self, version: &Version) -> eyre::Result<()> {
+        let key = format!("files/{version}.json");
+        let Some(bytes) = self.open(&key).await.into_report()? else {
+            return Ok(());
+        };
+
+        let files = serde_json::from_slice::<Vec<ChartFile>>(&bytes).unwrap_or_default();
+        for index in 0..files.len() {
+            let key = format!("files/{version}.{index}");
+            if self.exists(&key).await.into_report()? {
+                self.delete(key).await.into_report()?;
+            }
+        }
+
+        self.delete(key).await.into_report()
+    }
+
+    /// Deletes every chart tarball, provenance file, and cached file of this repository.
+    #[instrument(
+        name = "charted.helm.deleteAll",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+        )
+    )]
+    pub async fn delete_all(&self) -> eyre::Result<()> {
+        for dir in ["tarballs", "files"] {
+            if self.ds.is_filesystem() && !self.exists(dir).await.into_report()? {
+                continue;
+            }
+
+            for blob in self.namespace.blobs(Some(dir), None).await? {
+                // the S3 driver returns the whole key as the file's name
+                if let Blob::File(file) = blob &&
+                    let Some(name) = file.name.split('/').next_back()
+                {
+                    self.delete(format!("{dir}/{name}")).await.into_report()?;
+                }
+            }
+        }
+
+        Ok(())
     }
 
     //// upload \\\\
@@ -331,7 +470,13 @@ impl<'storage> OwnerRepoNamespace<'storage> {
             %version,
         )
     )]
-    pub async fn upload_chart<'m>(&self, mut multipart: Multipart<'m>, version: Version) -> api::Result<()> {
+    pub async fn upload_chart<'m>(
+        &self,
+        mut multipart: Multipart<'m>,
+        repository: &Repository,
+        version: Version,
+        limits: Limits,
+    ) -> Result<UploadedChart, api::Response> {
         // Find the first field in the multipart stream. We don't really care about
         // the file name since we're going to be doing a bit of paranoia validation
         // on the stream itself once we find the field.
@@ -377,138 +522,270 @@ impl<'storage> OwnerRepoNamespace<'storage> {
             ));
         }
 
-        info!("validating multipart stream to check if it's a valid Helm chart");
-
-        // temporary variable for rustc to shut up as well
-        //     error[E0505]: cannot move out of `field` because it is borrowed
-        //     --> crates/helm/charts/src/lib.rs:396:21
-        //      |
-        //  340 |         let field = multipart
-        //      |             ----- binding `field` declared here
-        //  ...
-        //  358 |         let ct = field.content_type().ok_or_else(|| {
-        //      |                  ----- borrow of `field` occurs here
-        //  ...
-        //  396 |         let bytes = field.bytes().await.map_err(api::system_failure)?;
-        //      |                     ^^^^^ move out of `field` occurs here
-        //  ...
-        //  491 |             .with_content_type(Some(ct.as_ref()))
-        //      |                                     -- borrow later used here
-        let ct = ct.to_owned();
-
-        // Now, this is the paranoia stage on where things can fail but tests
-        // should cover this. The structure that we want inside of the tarball
-        // is:
-        //
-        //       >> charted-0.1.0.tgz
-        //       --> templates/**/*.[yaml|yml]
-        //       --> charts/**/*.tgz
-        //       ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
-        //       --> Chart.lock
-        //       --> Chart.yaml
-        //       --> README.md, LICENSE
-        //       --> values.yaml, values.schema.json
-        let bytes = field.bytes().await.map_err(api::system_failure)?;
+        info!("streaming multipart field into staging to check if it's a valid Helm chart");
+
+        // The field is streamed into a staging file while a blocking task validates
+        // the tarball from the same chunks, so neither side ever holds the whole
+        // tarball in memory. The staging file is only promoted to `tarballs/{version}.tgz`
+        // once the tarball has been validated.
+        let ct = ct.to_string();
+        let staging = self.staging_path().map_err(api::system_failure_from_report)?;
+        let (tx, rx) = tokio::sync::mpsc::channel(staging::CHANNEL_CAPACITY);
+        let validator =
+            tokio::task::spawn_blocking(move || validate::validate(staging::ChannelReader::new(rx), limits));
+
+        let staged = staging::stage(field, staging, tx).await;
+        let validated = validator.await.map_err(api::system_failure)?;
+        let staged = staged.map_err(api::system_failure_from_report)?;
+
+        let chart = match validated {
+            Ok(chart) => chart,
+            Err(violation) => {
+                warn!(%violation, "rejecting chart tarball");
+                staging::remove(&staged.path).await;
+
+                return Err(violation_to_response(violation));
+            }
+        };
 
-        // temporary variable so that rustc can shut up
-        //
-        //     error[E0716]: temporary value dropped while borrowed
-        //     --> crates/helm/charts/src/lib.rs:397:65
-        //      |
-        //  397 |         let mut archive = Archive::new(MultiGzDecoder::new(&mut bytes.as_ref()));
-        //      |                                                                 ^^^^^^^^^^^^^^  - temporary value is freed at the end of this statement
-        //      |                                                                 |
-        //      |                                                                 creates a temporary value which is freed while still in use
-        //  398 |
-        //  399 |         for entry in archive.entries().map_err(api::system_failure)? {}
-        //      |                      ------- borrow later used here
-        //      |
-        //      = note: consider using a `let` binding to create a longer lived value
-        let mut bytes_as_slice = bytes.as_ref();
-        let mut archive = Archive::new(MultiGzDecoder::new(&mut bytes_as_slice));
-
-        for entry in archive.entries().map_err(api::system_failure)? {
-            let entry = entry
-                .context("failed to compute tar entry")
-                .map_err(api::system_failure_from_report)?;
-
-            // Retrieve the entry's metadata.
-            let header = entry.header();
-
-            // On Unix, `tar` will never call `Err(...)` on the path, so it's sound
-            // to use `unwrap_unchecked()` here.
-            //
-            // https://github.com/alexcrichton/tar-rs/blob/d5c546e2e72271746fb0ab19db152f5b4ab4b36a/src/header.rs#L1709-L1718
-            #[cfg(unix)]
-            let path = unsafe { entry.path().unwrap_unchecked() };
-
-            // On non-Unix (Windows), this can fail if the path is not
-            // valid unicode.
-            #[cfg(not(unix))]
-            let path = entry
-                .path()
-                .context("path was not in valid unicode")
-                .map_err(api::system_failure_from_report)?;
-
-            trace!(path = %path.display(), "validating entry in archive");
-
-            // If the entry in the archive is a directory, we only want the
-            // "templates" and "charts" directories to be avaliable but it's
-            // also ok if they're not.
-            if header.entry_type().is_dir() {
-                if path.ends_with("charts") || path.ends_with("templates") {
-                    continue;
-                }
+        if let Err(e) = validate_chart(&chart, repository, &version) {
+            staging::remove(&staged.path).await;
+            return Err(e);
+        }
 
-                return Err(api::err(
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    (
-                        api::ErrorCode::InvalidInput,
-                        format!(
-                            "expected either a `charts/` or `templates/` directory to be avaliable, received {} instead",
-                            path.display()
-                        ),
-                    ),
-                ));
+        info!("paranoia checks: done; assuming this is a valid archive. promoting into datastore");
+
+        self.promote(&staged.path, format!("tarballs/{version}.tgz"), &ct)
+            .await
+            .map_err(api::system_failure_from_report)?;
+
+        Ok(UploadedChart {
+            chart,
+            digest: staged.digest,
+        })
+    }
+
+    /// Validates and stores a chart tarball that is already in memory, like the chart
+    /// layer of a Helm chart that was pushed to the OCI registry.
+    #[instrument(
+        name = "charted.helm.uploadChartBytes",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+            %version,
+        )
+    )]
+    pub async fn upload_chart_bytes(
+        &self,
+        tarball: Bytes,
+        repository: &Repository,
+        version: Version,
+        limits: Limits,
+    ) -> Result<UploadedChart, api::Response> {
+        let data = tarball.clone();
+        let chart = tokio::task::spawn_blocking(move || validate::validate(data.as_ref(), limits))
+            .await
+            .map_err(api::system_failure)?
+            .map_err(|violation| {
+                warn!(%violation, "rejecting chart tarball");
+                violation_to_response(violation)
+            })?;
+
+        validate_chart(&chart, repository, &version)?;
+
+        let digest = digest(&tarball);
+        let request = UploadRequest::default()
+            .with_content_type(Some("application/gzip"))
+            .with_data(tarball);
+
+        self.upload(format!("tarballs/{version}.tgz"), request)
+            .await
+            .map_err(api::system_failure)?;
+
+        Ok(UploadedChart { chart, digest })
+    }
+
+    /// Returns a new path to stage a chart tarball at.
+    ///
+    /// On the filesystem datastore, this is within the repository's namespace so that
+    /// promoting it is a rename. Otherwise, it's in the system's temporary directory.
+    fn staging_path(&self) -> eyre::Result<PathBuf> {
+        let name = format!("{}.staging", rand_string(16));
+        if let Some(fs) = self.ds.as_filesystem() {
+            return fs
+                .normalize(format!(
+                    "./repositories/{}/{}/tarballs/.staging/{name}",
+                    self.owner, self.repo
+                ))?
+                .ok_or_else(|| eyre!("unable to normalize staging path"));
+        }
+
+        Ok(std::env::temp_dir()
+            .join("charted-staging")
+            .join(format!("{}-{}-{name}", self.owner, self.repo)))
+    }
+
+    /// Promotes a staging file to `key` in this namespace and removes the staging file.
+    ///
+    /// On the filesystem datastore, this is a rename. Otherwise, the staging file is
+    /// uploaded in parts so that it's never read into memory as a whole.
+    async fn promote(&self, staged: &Path, key: String, content_type: &str) -> eyre::Result<()> {
+        if let Some(fs) = self.ds.as_filesystem() {
+            let dest = fs
+                .normalize(format!("./repositories/{}/{}/{key}", self.owner, self.repo))?
+                .ok_or_else(|| eyre!("unable to normalize path to promote staging file to"))?;
+
+            if let Some(parent) = dest.parent() {
+                tokio::fs::create_dir_all(parent).await?;
             }
 
-            if !header.entry_type().is_file() {
-                return Err(api::err(
-                    StatusCode::UNPROCESSABLE_ENTITY,
+            return tokio::fs::rename(staged, dest).await.map_err(Into::into);
+        }
+
+        let result = self
+            .ds
+            .upload_file(self.namespace.resolve(key), staged, content_type)
+            .await;
+
+        staging::remove(staged).await;
+        result
+    }
+
+    /// Uploads a Helm chart's [provenance](https://helm.sh/docs/topics/provenance/) file
+    /// for a specific version.
+    ///
+    /// The checksum in the provenance file has to match `digest`, the SHA-256 digest of the
+    /// chart tarball that was uploaded for `version`. If `keyring` isn't empty, then the
+    /// provenance file also has to be signed by one of its public keys.
+    #[instrument(
+        name = "charted.helm.uploadChartProvenance",
+        skip_all,
+        fields(
+            owner.id = %self.owner,
+            repository.id = %self.repo,
+            %version,
+        )
+    )]
+    pub async fn upload_chart_provenance<'m>(
+        &self,
+        mut multipart: Multipart<'m>,
+        repository: &Repository,
+        version: Version,
+        digest: &str,
+        keyring: &[SignedPublicKey],
+    ) -> api::Result<()> {
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(api::system_failure)?
+            .ok_or_else(|| {
+                api::err(
+                    StatusCode::PRECONDITION_FAILED,
                     (
-                        api::ErrorCode::InvalidInput,
-                        format!("excepted a file in path {}", path.display()),
+                        api::ErrorCode::MissingMultipartField,
+                        "multipart stream was missing a field",
                     ),
-                ));
-            }
+                )
+            })?;
 
-            let name = path.file_name().ok_or_else(|| {
+        let ct = field
+            .content_type()
+            .map(|ct| ct.essence_str().to_owned())
+            .ok_or_else(|| {
                 api::err(
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    (api::ErrorCode::InvalidInput, "path was not valid utf-8"),
+                    StatusCode::PRECONDITION_FAILED,
+                    (
+                        api::ErrorCode::MissingContentType,
+                        "missing `Content-Type` header in field",
+                    ),
                 )
             })?;
 
-            if !EXEMPTED_FILES.iter().any(|x| name == *x) || !ALLOWED_FILES.iter().any(|x| name == *x) {
-                return Err(api::err(
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    (
-                        api::ErrorCode::AccessNotPermitted,
-                        format!("path '{}' is not allowed", path.display()),
+        if !ACCEPTABLE_PROVENANCE_CONTENT_TYPES.contains(&ct.as_str()) {
+            return Err(api::err(
+                StatusCode::PRECONDITION_FAILED,
+                (
+                    api::ErrorCode::InvalidHttpHeaderValue,
+                    format!(
+                        "invalid `Content-Type` header: {}; wanted either: {}",
+                        ct,
+                        ACCEPTABLE_PROVENANCE_CONTENT_TYPES.join(", ")
                     ),
-                ));
-            }
+                ),
+            ));
         }
 
-        info!("paranoia checks: done; assuming this is a valid archive. uploading to datastore");
+        let bytes = field.bytes().await.map_err(api::system_failure)?;
+
+        let provenance = Provenance::parse(&bytes).map_err(provenance_error_to_response)?;
+        provenance
+            .verify_checksum(&format!("{}-{version}.tgz", repository.name), digest)
+            .map_err(provenance_error_to_response)?;
+
+        if !keyring.is_empty() {
+            let key = provenance
+                .verify_signature(keyring)
+                .map_err(provenance_error_to_response)?;
+
+            info!(key.fingerprint = %provenance::fingerprint(key), "provenance file signature was verified");
+        }
 
         let request = UploadRequest::default()
-            .with_content_type(Some(ct.as_ref()))
+            .with_content_type(Some("application/pgp-signature"))
             .with_data(bytes);
 
-        self.upload(format!("tarballs/{version}.tgz"), request)
+        self.upload(format!("tarballs/{version}.prov.tgz"), request)
             .await
             .map(|_| api::no_content())
             .map_err(api::system_failure)
     }
 }
+
+fn provenance_error_to_response(error: ProvenanceError) -> api::Response {
+    match error {
+        ProvenanceError::ChecksumMismatch {
+            ref file,
+            ref expected,
+            ref received,
+        } => api::err(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            (
+                api::ErrorCode::ValidationFailed,
+                error.to_string(),
+                json!({"file":file,"expected":expected,"received":received}),
+            ),
+        ),
+
+        ProvenanceError::Unverified => api::err(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            (api::ErrorCode::AccessNotPermitted, error.to_string()),
+        ),
+
+        _ => api::err(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            (api::ErrorCode::InvalidInput, error.to_string()),
+        ),
+    }
+}
+
+fn violation_to_response(violation: Violation) -> api::Response {
+    let (status, code) = match violation {
+        Violation::Corrupted(_) => (StatusCode::UNPROCESSABLE_ENTITY, api::ErrorCode::InvalidInput),
+        Violation::TooManyEntries(_) | Violation::TooLarge(_) => {
+            (StatusCode::PAYLOAD_TOO_LARGE, api::ErrorCode::InvalidInput)
+        }
+
+        Violation::NotAllowed(_) | Violation::Link(_) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, api::ErrorCode::AccessNotPermitted)
+        }
+
+        Violation::MissingChartManifest | Violation::InvalidChartManifest(_) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, api::ErrorCode::ValidationFailed)
+        }
+
+        _ => (StatusCode::UNPROCESSABLE_ENTITY, api::ErrorCode::InvalidInput),
+    };
+
+    api::err(status, (code, violation.to_string()))
+}
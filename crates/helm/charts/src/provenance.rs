@@ -0,0 +1,157 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Parsing and verification of [provenance files].
+//!
+//! A provenance file is a PGP clear-signed document that is made up of the chart's
+//! `Chart.yaml` and a YAML document of the SHA-256 checksums of the chart tarballs
+//! that it was signed for, which looks like:
+//!
+//! ```text
+//! -----BEGIN PGP SIGNED MESSAGE-----
+//! Hash: SHA512
+//!
+//! apiVersion: v2
+//! name: hello-world
+//! version: 0.1.0
+//!
+//! ...
+//! files:
+//!   hello-world-0.1.0.tgz: sha256:3bb6cb2a9ec2d4b4c4f7bd1a0a8f2b6d...
+//! -----BEGIN PGP SIGNATURE-----
+//! ...
+//! -----END PGP SIGNATURE-----
+//! ```
+//!
+//! [provenance files]: https://helm.sh/docs/topics/provenance/
+
+use pgp::{
+    Deserializable,
+    cleartext::CleartextSignedMessage,
+    types::PublicKeyTrait,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+pub use pgp::SignedPublicKey;
+
+/// Reason why a provenance file was rejected.
+#[derive(Debug, derive_more::Display)]
+pub enum ProvenanceError {
+    /// The provenance file wasn't a PGP clear-signed document.
+    #[display("provenance file was not a PGP clear-signed document: {_0}")]
+    Malformed(String),
+
+    /// The signed document didn't have the checksums of the files it was signed for.
+    #[display("provenance file has no `files` section")]
+    MissingFiles,
+
+    /// There was no checksum for the chart tarball.
+    #[display("provenance file has no checksum for `{_0}`")]
+    MissingChecksum(String),
+
+    /// The checksum of the chart tarball didn't match.
+    #[display("checksum of `{file}` in the provenance file doesn't match the chart tarball")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        received: String,
+    },
+
+    /// None of the public keys in the keyring have signed the document.
+    #[display("provenance file was not signed by any public key in the keyring")]
+    Unverified,
+}
+
+impl std::error::Error for ProvenanceError {}
+
+#[derive(Deserialize)]
+struct Files {
+    files: BTreeMap<String, String>,
+}
+
+/// A parsed provenance file.
+#[derive(Debug)]
+pub struct Provenance {
+    message: CleartextSignedMessage,
+    files: BTreeMap<String, String>,
+}
+
+impl Provenance {
+    /// Parses a provenance file.
+    pub fn parse(data: &[u8]) -> Result<Self, ProvenanceError> {
+        let contents = std::str::from_utf8(data).map_err(|e| ProvenanceError::Malformed(e.to_string()))?;
+        let (message, _) =
+            CleartextSignedMessage::from_string(contents).map_err(|e| ProvenanceError::Malformed(e.to_string()))?;
+
+        // the `Chart.yaml` and the checksums are seperated by a YAML document end marker.
+        let text = message.signed_text();
+        let Some((_, files)) = text.split_once("\n...\n") else {
+            return Err(ProvenanceError::MissingFiles);
+        };
+
+        let Files { files } = serde_yaml_ng::from_str(files).map_err(|_| ProvenanceError::MissingFiles)?;
+        Ok(Provenance { message, files })
+    }
+
+    /// Returns the SHA-256 checksum of `file` as a lowercase hex string, if the
+    /// provenance file was signed for it.
+    pub fn checksum(&self, file: &str) -> Option<&str> {
+        self.files.get(file).map(|sum| sum.strip_prefix("sha256:").unwrap_or(sum))
+    }
+
+    /// Checks that the checksum of `file` is `digest`, which is computed by [`digest`][crate::digest].
+    pub fn verify_checksum(&self, file: &str, digest: &str) -> Result<(), ProvenanceError> {
+        let Some(checksum) = self.checksum(file) else {
+            return Err(ProvenanceError::MissingChecksum(file.to_owned()));
+        };
+
+        if !checksum.eq_ignore_ascii_case(digest) {
+            return Err(ProvenanceError::ChecksumMismatch {
+                file: file.to_owned(),
+                expected: digest.to_owned(),
+                received: checksum.to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the signature against every public key, and its subkeys, in `keyring`
+    /// and returns the key that signed it.
+    pub fn verify_signature<'k>(&self, keyring: &'k [SignedPublicKey]) -> Result<&'k SignedPublicKey, ProvenanceError> {
+        keyring
+            .iter()
+            .find(|key| {
+                self.message.verify(*key).is_ok() ||
+                    key.public_subkeys
+                        .iter()
+                        .any(|subkey| self.message.verify(subkey).is_ok())
+            })
+            .ok_or(ProvenanceError::Unverified)
+    }
+}
+
+/// Reads an ASCII-armored OpenPGP public key and checks its self-signatures.
+pub fn read_public_key(armored: &str) -> eyre::Result<SignedPublicKey> {
+    let (key, _) = SignedPublicKey::from_string(armored)?;
+    key.verify()?;
+
+    Ok(key)
+}
+
+/// Returns the fingerprint of `key` as an uppercase hex string.
+pub fn fingerprint(key: &SignedPublicKey) -> String {
+    hex::encode_upper(key.fingerprint())
+}
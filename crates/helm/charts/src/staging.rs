@@ -0,0 +1,128 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Staging of chart tarballs while they're being uploaded.
+//!
+//! The multipart field is streamed chunk by chunk into a staging file while every
+//! chunk is hashed and sent to a blocking task that validates the tarball as it's
+//! being read. Only a bounded amount of chunks are ever held in memory, so the
+//! size of a chart doesn't affect how much memory an upload takes.
+
+use charted_datastore::remi::Bytes;
+use multer::Field;
+use sha2::{Digest, Sha256};
+use std::{
+    cmp,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, File},
+    io::AsyncWriteExt,
+    sync::mpsc,
+};
+use tracing::warn;
+
+/// Amount of chunks that can be buffered before the validator catches up.
+pub(crate) const CHANNEL_CAPACITY: usize = 8;
+
+/// A tarball that was streamed into a staging file.
+#[derive(Debug)]
+pub(crate) struct Staged {
+    /// Path to the staging file.
+    pub(crate) path: PathBuf,
+
+    /// Hex-encoded SHA-256 digest of the tarball.
+    pub(crate) digest: String,
+}
+
+/// Streams `field` into a staging file at `path`, sending each chunk to `tx` as well.
+///
+/// `tx` is dropped once the field is exhausted (or when streaming fails), which lets
+/// the receiving end know that the tarball was fully read. Chunks that can no longer
+/// be sent since the validator finished early are still written to the staging file.
+pub(crate) async fn stage(mut field: Field<'_>, path: PathBuf, tx: mpsc::Sender<Bytes>) -> eyre::Result<Staged> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let mut file = File::create(&path).await?;
+    let mut hasher = Sha256::new();
+
+    let result = async {
+        while let Some(chunk) = field.chunk().await? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+
+            let _ = tx.send(chunk).await;
+        }
+
+        file.sync_all().await?;
+        Ok::<_, eyre::Report>(())
+    }
+    .await;
+
+    drop(tx);
+    if let Err(e) = result {
+        remove(&path).await;
+        return Err(e);
+    }
+
+    Ok(Staged {
+        path,
+        digest: hex::encode(hasher.finalize()),
+    })
+}
+
+/// Removes a staging file, only logging if it couldn't be removed.
+pub(crate) async fn remove(path: &Path) {
+    if let Err(e) = fs::remove_file(path).await &&
+        e.kind() != io::ErrorKind::NotFound
+    {
+        warn!(error = %e, path = %path.display(), "failed to remove staging file");
+    }
+}
+
+/// Blocking [`Read`] implementation over chunks that are received from a channel.
+///
+/// This must only be used within a blocking context, like [`tokio::task::spawn_blocking`].
+pub(crate) struct ChannelReader {
+    rx: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl ChannelReader {
+    pub(crate) fn new(rx: mpsc::Receiver<Bytes>) -> Self {
+        ChannelReader {
+            rx,
+            current: Bytes::new(),
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let len = cmp::min(buf.len(), self.current.len());
+        buf[..len].copy_from_slice(&self.current.split_to(len));
+
+        Ok(len)
+    }
+}
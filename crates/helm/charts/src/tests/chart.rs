@@ -0,0 +1,120 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{OwnerRepoNamespace, tests::fixture, testutil};
+use charted_config::storage::Config;
+use charted_datastore::{
+    DataStore,
+    remi::{StorageService, UploadRequest},
+};
+use charted_types::{Ulid, Version};
+use tempfile::TempDir;
+
+#[test]
+fn read_chart_metadata() {
+    let tarball = std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap();
+    let chart = crate::read_chart_metadata(&tarball).unwrap().unwrap();
+
+    assert_eq!(chart.name, "hello-world");
+}
+
+#[test]
+fn read_chart_metadata_from_invalid_tarball() {
+    assert!(crate::read_chart_metadata(b"not a tarball").is_err());
+}
+
+#[test]
+fn digest() {
+    // $ printf 'hello world' | sha256sum
+    assert_eq!(
+        crate::digest(b"hello world"),
+        "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+    );
+}
+
+#[test]
+fn read_chart_file() {
+    let tarball = std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap();
+    let contents = crate::read_chart_file(&tarball, "Chart.yaml").unwrap().unwrap();
+
+    assert!(String::from_utf8(contents).unwrap().contains("name: hello-world"));
+    assert!(
+        crate::read_chart_file(&tarball, "does/not/exist.yaml")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn extract_chart_files() {
+    let tarball = std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap();
+    let files = crate::extract_chart_files(&tarball).unwrap();
+
+    assert!(files.iter().any(|(file, _)| file.path == "Chart.yaml"));
+    assert!(files.iter().any(|(file, _)| file.path == "values.yaml"));
+    assert!(files.iter().all(|(file, _)| !file.path.starts_with("hello-world/")));
+    assert!(files.is_sorted_by(|a, b| a.0 <= b.0));
+
+    for (file, contents) in files {
+        assert_eq!(contents.len() as u64, file.size);
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(
+    windows,
+    ignore = "fails to run, probably an issue within either us or `tempdir`: Filesystem(Os { code: 123, kind: InvalidFilename, message: \"The filename, directory name, or volume label syntax is incorrect.\" })"
+)]
+async fn chart_files_are_cached() {
+    let _log_guard = testutil::setup_tracing();
+    let tmpdir = TempDir::new().unwrap();
+    let ds = DataStore::new(&Config::Filesystem(charted_datastore::fs::StorageConfig::new(
+        tmpdir.path(),
+    )))
+    .await
+    .unwrap();
+
+    let owner = Ulid::new("01J5SG1FXT019M8Q2TB84QVV8V").unwrap();
+    let repo = Ulid::new("01J5SG1JAEG4RJCGYC5KJ6QYS2").unwrap();
+    let ns = OwnerRepoNamespace::new(&ds, owner, repo);
+    let version = Version::parse("0.1.0").unwrap();
+
+    let request = UploadRequest::default()
+        .with_content_type(Some("application/tar+gzip"))
+        .with_data(std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap());
+
+    ns.upload("tarballs/0.1.0.tgz", request).await.unwrap();
+
+    let files = ns.get_chart_files(&version).await.unwrap().unwrap();
+    assert!(ns.exists("files/0.1.0.json").await.unwrap());
+
+    // once cached, files are read without the tarball
+    ns.delete_chart(version.clone()).await.unwrap();
+    let index = files.iter().position(|file| file.path == "Chart.yaml").unwrap();
+    let contents = ns.get_chart_file(&version, "Chart.yaml").await.unwrap().unwrap();
+
+    assert!(ns.exists(format!("files/0.1.0.{index}")).await.unwrap());
+    assert!(String::from_utf8(contents).unwrap().contains("name: hello-world"));
+    assert!(
+        ns.get_chart_file(&version, "does/not/exist.yaml")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    ns.delete_chart_files(&version).await.unwrap();
+    assert!(!ns.exists("files/0.1.0.json").await.unwrap());
+    assert!(!ns.exists(format!("files/0.1.0.{index}")).await.unwrap());
+}
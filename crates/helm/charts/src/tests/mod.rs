@@ -13,7 +13,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod chart;
+mod provenance;
 mod sort_versions;
+mod staging;
+mod upload;
+mod validate;
 
 macro_rules! fixture {
     ($path:literal) => {
@@ -0,0 +1,74 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    provenance::{self, Provenance, ProvenanceError},
+    tests::fixture,
+};
+use std::path::PathBuf;
+
+fn provenance() -> Provenance {
+    let data = std::fs::read(fixture!("provenance/hello-world-0.1.0.tgz.prov")).unwrap();
+    Provenance::parse(&data).unwrap()
+}
+
+fn key(path: PathBuf) -> provenance::SignedPublicKey {
+    provenance::read_public_key(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn verify_checksum() {
+    let tarball = std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap();
+    let provenance = provenance();
+
+    assert!(
+        provenance
+            .verify_checksum("hello-world-0.1.0.tgz", &crate::digest(&tarball))
+            .is_ok()
+    );
+
+    assert!(matches!(
+        provenance.verify_checksum("hello-world-0.1.0.tgz", &crate::digest(b"not the tarball")),
+        Err(ProvenanceError::ChecksumMismatch { .. })
+    ));
+
+    assert!(matches!(
+        provenance.verify_checksum("hello-world-0.2.0.tgz", &crate::digest(&tarball)),
+        Err(ProvenanceError::MissingChecksum(_))
+    ));
+}
+
+#[test]
+fn verify_signature() {
+    let provenance = provenance();
+    let signer = key(fixture!("provenance/public.asc"));
+    let other = key(fixture!("provenance/other.asc"));
+
+    let keyring = [other.clone(), signer.clone()];
+    let key = provenance.verify_signature(&keyring).unwrap();
+    assert_eq!(provenance::fingerprint(key), provenance::fingerprint(&signer));
+
+    assert!(matches!(
+        provenance.verify_signature(&[other]),
+        Err(ProvenanceError::Unverified)
+    ));
+}
+
+#[test]
+fn parse_malformed() {
+    assert!(matches!(
+        Provenance::parse(b"not a provenance file"),
+        Err(ProvenanceError::Malformed(_))
+    ));
+}
@@ -147,4 +147,8 @@ async fn do_test(ds: DataStore) {
         semver::Version::parse("0.2.1").unwrap().into(),
         semver::Version::parse("0.1.0-beta").unwrap().into(),
     ]);
+
+    // yanked versions can still be queried by their exact version
+    let yanked: charted_types::Version = semver::Version::parse("2024.3.24").unwrap().into();
+    assert!(ns.get_chart(&yanked, false).await.unwrap().is_some());
 }
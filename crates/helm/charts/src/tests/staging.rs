@@ -0,0 +1,51 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    Limits,
+    staging::{CHANNEL_CAPACITY, ChannelReader},
+    tests::fixture,
+    validate::validate,
+};
+use charted_datastore::remi::Bytes;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn validates_from_chunks() {
+    let tarball = Bytes::from(std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap());
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let validator = tokio::task::spawn_blocking(move || validate(ChannelReader::new(rx), Limits::default()));
+
+    // small chunks so that the validator has to wait on the channel a bunch of times
+    for start in (0..tarball.len()).step_by(64) {
+        let _ = tx.send(tarball.slice(start..(start + 64).min(tarball.len()))).await;
+    }
+
+    drop(tx);
+
+    let chart = validator.await.unwrap().unwrap();
+    assert_eq!(chart.name, "hello-world");
+}
+
+#[tokio::test]
+async fn truncated_stream_is_rejected() {
+    let tarball = Bytes::from(std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap());
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let validator = tokio::task::spawn_blocking(move || validate(ChannelReader::new(rx), Limits::default()));
+
+    tx.send(tarball.slice(..tarball.len() / 2)).await.unwrap();
+    drop(tx);
+
+    assert!(validator.await.unwrap().is_err());
+}
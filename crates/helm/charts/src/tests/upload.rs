@@ -0,0 +1,201 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Limits, OwnerRepoNamespace, tests::fixture, testutil};
+use charted_config::storage::Config;
+use charted_datastore::{
+    DataStore, PART_SIZE,
+    remi::{Bytes, StorageService},
+};
+use charted_types::{DateTime, Repository, Ulid, Version};
+use futures_util::stream;
+use multer::Multipart;
+use tempfile::TempDir;
+
+const BOUNDARY: &str = "charted-upload-boundary";
+
+fn repository(owner: Ulid) -> Repository {
+    Repository {
+        description: None,
+        deprecated: false,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+        icon_hash: None,
+        creator: None,
+        private: false,
+        owner,
+        latest_version: None,
+        latest_prerelease: None,
+        type_: Default::default(),
+        name: "hello-world".parse().unwrap(),
+        id: Ulid::new("01J5SG1JAEG4RJCGYC5KJ6QYS2").unwrap(),
+    }
+}
+
+/// Builds a multipart stream with `tarball` as its only field, which is sent in small
+/// chunks so that it's streamed like a request body would be.
+fn multipart(tarball: &[u8]) -> Multipart<'static> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"tarball\"; filename=\"hello-world-0.1.0.tgz\"\r\nContent-Type: application/gzip\r\n\r\n"
+    )
+    .into_bytes();
+
+    body.extend_from_slice(tarball);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+    let chunks = body
+        .chunks(1024)
+        .map(|chunk| Ok::<_, std::io::Error>(Bytes::copy_from_slice(chunk)))
+        .collect::<Vec<_>>();
+
+    Multipart::new(stream::iter(chunks), BOUNDARY)
+}
+
+#[tokio::test]
+#[cfg_attr(
+    windows,
+    ignore = "fails to run, probably an issue within either us or `tempdir`: Filesystem(Os { code: 123, kind: InvalidFilename, message: \"The filename, directory name, or volume label syntax is incorrect.\" })"
+)]
+async fn filesystem() {
+    let _log_guard = testutil::setup_tracing();
+    let tmpdir = TempDir::new().unwrap();
+    let ds = DataStore::new(&Config::Filesystem(charted_datastore::fs::StorageConfig::new(
+        tmpdir.path(),
+    )))
+    .await
+    .unwrap();
+
+    do_test(ds).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn s3() {
+    use charted_datastore::s3::{self, aws};
+
+    // Always succeed the test if Docker tests are disabled.
+    if super::docker_tests_disabled() {
+        return;
+    }
+
+    let _log_guard = testutil::setup_tracing();
+    let minio = testutil::minio().await.unwrap();
+    let port = minio.get_host_port_ipv4(9000).await.unwrap();
+
+    let config = s3::StorageConfig {
+        enforce_path_access_style: true,
+        default_bucket_acl: Some(aws::s3::types::BucketCannedAcl::Private),
+        default_object_acl: Some(aws::s3::types::ObjectCannedAcl::BucketOwnerFullControl),
+        secret_access_key: "somedummysecretkey".into(),
+        access_key_id: "somedummyaccesskey".into(),
+        endpoint: Some(format!("http://localhost:{port}")),
+        region: Some(aws::config::Region::from_static("us-east-1")),
+        bucket: "test-bucket".into(),
+
+        ..Default::default()
+    };
+
+    let ds = DataStore::new(&Config::S3(config)).await.unwrap();
+    do_test(ds.clone()).await;
+    upload_in_parts(ds).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn azure() {
+    use charted_datastore::azure;
+
+    // Always succeed the test if Docker tests are disabled.
+    if super::docker_tests_disabled() {
+        return;
+    }
+
+    let _log_guard = testutil::setup_tracing();
+    let azurite = testutil::azurite().await.unwrap();
+    let port = azurite.get_host_port_ipv4(10000).await.unwrap();
+
+    let config = azure::StorageConfig {
+        container: "test-container".into(),
+        location: azure::CloudLocation::Emulator {
+            address: "0.0.0.0".into(),
+            port,
+        },
+
+        credentials: azure::Credential::AccessKey {
+            account: String::from("devstoreaccount1"),
+            access_key: String::from(
+                "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==",
+            ),
+        },
+    };
+
+    let ds = DataStore::new(&Config::Azure(config)).await.unwrap();
+    do_test(ds.clone()).await;
+    upload_in_parts(ds).await;
+}
+
+async fn do_test(ds: DataStore) {
+    let tarball = std::fs::read(fixture!("tarballs/hello-world.tgz")).unwrap();
+    let owner = Ulid::new("01J5SG1FXT019M8Q2TB84QVV8V").unwrap();
+    let repository = repository(owner);
+    let ns = OwnerRepoNamespace::new(&ds, owner, repository.id);
+
+    let uploaded = ns
+        .upload_chart(
+            multipart(&tarball),
+            &repository,
+            Version::parse("0.1.0").unwrap(),
+            Limits::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(uploaded.chart.name, "hello-world");
+    assert_eq!(uploaded.digest, crate::digest(&tarball));
+
+    let stored = ns.open("tarballs/0.1.0.tgz").await.unwrap().unwrap();
+    assert_eq!(stored.as_ref(), tarball.as_slice());
+
+    // a chart that doesn't match the repository is never promoted
+    let result = ns
+        .upload_chart(
+            multipart(&tarball),
+            &repository,
+            Version::parse("0.2.0").unwrap(),
+            Limits::default(),
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(!ns.exists("tarballs/0.2.0.tgz").await.unwrap());
+}
+
+/// Uploads a file that doesn't fit in a single part and checks that it's put
+/// back together.
+async fn upload_in_parts(ds: DataStore) {
+    let tmpdir = TempDir::new().unwrap();
+    let path = tmpdir.path().join("file");
+    let contents = (0..(PART_SIZE * 2 + 1)).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    std::fs::write(&path, &contents).unwrap();
+
+    let ns = ds.namespace("parts");
+    ds.upload_file(ns.resolve("file"), &path, "application/octet-stream")
+        .await
+        .unwrap();
+
+    let stored = ns.open("file").await.unwrap().unwrap();
+    assert_eq!(stored.len(), contents.len());
+    assert!(stored.as_ref() == contents.as_slice());
+}
@@ -0,0 +1,88 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    Limits, Violation,
+    tests::fixture,
+    testutil::{self, CHART_YAML, Entry},
+    validate::validate,
+};
+
+#[test]
+fn corpus() {
+    for case in testutil::corpus() {
+        let result = validate(case.tarball.as_slice(), Limits::default());
+        assert_eq!(
+            result.is_ok(),
+            case.valid,
+            "case `{}` should've been {}: {:?}",
+            case.name,
+            if case.valid { "valid" } else { "invalid" },
+            result.err()
+        );
+    }
+}
+
+#[test]
+fn fixtures() {
+    let fixtures = [
+        ("hello-world", fixture!("tarballs/hello-world.tgz")),
+        ("youtrack", fixture!("tarballs/youtrack.tgz")),
+    ];
+
+    for (name, path) in fixtures {
+        let tarball = std::fs::read(path).unwrap();
+        if let Err(e) = validate(tarball.as_slice(), Limits::default()) {
+            panic!("fixture `{name}` should be valid: {e}");
+        }
+    }
+}
+
+#[test]
+fn max_entries() {
+    let tarball = testutil::archive(&[
+        Entry::File("hello/Chart.yaml", CHART_YAML),
+        Entry::File("hello/values.yaml", b""),
+        Entry::File("hello/README.md", b""),
+    ]);
+
+    let limits = Limits {
+        max_entries: 2,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        validate(tarball.as_slice(), limits),
+        Err(Violation::TooManyEntries(2))
+    ));
+}
+
+#[test]
+fn max_decompressed_size() {
+    let tarball = testutil::archive(&[
+        Entry::File("hello/Chart.yaml", CHART_YAML),
+        Entry::File("hello/values.yaml", &[b'a'; 64]),
+    ]);
+
+    let limits = Limits {
+        max_decompressed_size: 64,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        validate(tarball.as_slice(), limits),
+        Err(Violation::TooLarge(64))
+    ));
+}
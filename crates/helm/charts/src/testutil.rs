@@ -14,7 +14,9 @@
 // limitations under the License.
 
 use azalia::log::{WriteLayer, writers::default::Writer};
+use flate2::{Compression, write::GzEncoder};
 use std::{io, time::Duration};
+use tar::{Builder, EntryType, Header};
 use testcontainers::{
     ContainerAsync, GenericImage, ImageExt,
     core::{ContainerPort, WaitFor, logs::consumer::logging_consumer::LoggingConsumer},
@@ -73,3 +75,154 @@ pub fn setup_tracing() -> DefaultGuard {
         .with(EnvFilter::from_env("INTEGTEST_LOG"))
         .set_default()
 }
+
+/// A single entry of a tarball that is built with [`archive`].
+#[derive(Debug, Clone, Copy)]
+pub enum Entry {
+    File(&'static str, &'static [u8]),
+    Dir(&'static str),
+    Symlink(&'static str, &'static str),
+    Hardlink(&'static str, &'static str),
+    Fifo(&'static str),
+}
+
+/// A minimal `Chart.yaml` that is used by the archives in [`corpus`].
+pub const CHART_YAML: &[u8] = b"apiVersion: v2\nname: hello\nversion: 0.1.0\n";
+
+/// Builds a gzip-compressed tarball out of `entries`.
+///
+/// Paths are written as-is into the tar header rather than going through
+/// [`Header::set_path`], since it refuses to write the absolute and `..` paths
+/// that the corpus needs.
+pub fn archive(entries: &[Entry]) -> Vec<u8> {
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for entry in entries {
+        let (path, ty, data, link): (&str, EntryType, &[u8], Option<&str>) = match *entry {
+            Entry::File(path, data) => (path, EntryType::Regular, data, None),
+            Entry::Dir(path) => (path, EntryType::Directory, &[], None),
+            Entry::Symlink(path, target) => (path, EntryType::Symlink, &[], Some(target)),
+            Entry::Hardlink(path, target) => (path, EntryType::Link, &[], Some(target)),
+            Entry::Fifo(path) => (path, EntryType::Fifo, &[], None),
+        };
+
+        let mut header = Header::new_gnu();
+        let name = &mut header.as_old_mut().name;
+        name[..path.len()].copy_from_slice(path.as_bytes());
+
+        if let Some(target) = link {
+            header.set_link_name(target).unwrap();
+        }
+
+        header.set_entry_type(ty);
+        header.set_mode(0o644);
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+
+        builder.append(&header, data).unwrap();
+    }
+
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// A tarball in the [`corpus`] and whether it should pass validation.
+pub struct Case {
+    pub name: &'static str,
+    pub tarball: Vec<u8>,
+    pub valid: bool,
+}
+
+/// Table of good and bad chart tarballs.
+pub fn corpus() -> Vec<Case> {
+    macro_rules! case {
+        ($name:literal, $valid:literal, [$($entry:expr),* $(,)?]) => {
+            Case {
+                name: $name,
+                tarball: archive(&[$($entry),*]),
+                valid: $valid,
+            }
+        };
+    }
+
+    vec![
+        // good archives
+        case!("minimal", true, [Entry::File("hello/Chart.yaml", CHART_YAML)]),
+        case!("full layout", true, [
+            Entry::Dir("hello/"),
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("hello/Chart.lock", b""),
+            Entry::File("hello/values.yaml", b""),
+            Entry::File("hello/values.schema.json", b"{}"),
+            Entry::File("hello/README.md", b""),
+            Entry::File("hello/LICENSE", b""),
+            Entry::File("hello/NOTES.txt", b""),
+            Entry::File("hello/.helmignore", b""),
+            Entry::Dir("hello/templates/"),
+            Entry::File("hello/templates/deployment.yaml", b""),
+            Entry::File("hello/templates/NOTES.txt", b""),
+            Entry::File("hello/templates/tests/test-connection.yaml", b""),
+            Entry::Dir("hello/crds/"),
+            Entry::File("hello/crds/crd.yaml", b""),
+            Entry::Dir("hello/charts/"),
+            Entry::File("hello/charts/subchart-0.1.0.tgz", b""),
+        ]),
+        case!("leading `./`", true, [Entry::File("./hello/Chart.yaml", CHART_YAML)]),
+        case!("unpacked subchart", true, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::Dir("hello/charts/sub/"),
+            Entry::File("hello/charts/sub/Chart.yaml", CHART_YAML),
+            Entry::Dir("hello/charts/sub/templates/"),
+            Entry::File("hello/charts/sub/templates/_helpers.tpl", b""),
+        ]),
+        // bad archives
+        case!("empty", false, []),
+        case!("missing Chart.yaml", false, [Entry::File("hello/values.yaml", b"")]),
+        case!("invalid Chart.yaml", false, [Entry::File(
+            "hello/Chart.yaml",
+            b"name: ["
+        )]),
+        case!("file outside of chart directory", false, [Entry::File(
+            "Chart.yaml",
+            CHART_YAML
+        )]),
+        case!("multiple chart directories", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("world/values.yaml", b""),
+        ]),
+        case!("unknown top-level file", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("hello/main.go", b""),
+        ]),
+        case!("unknown directory", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::Dir("hello/src/"),
+        ]),
+        case!("unknown file in unpacked subchart", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("hello/charts/sub/main.go", b""),
+        ]),
+        case!("non-tarball subchart", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("hello/charts/sub.zip", b""),
+        ]),
+        case!("path traversal", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("hello/templates/../../etc/passwd", b""),
+        ]),
+        case!("absolute path", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::File("/etc/passwd", b""),
+        ]),
+        case!("symlink", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::Symlink("hello/templates/passwd", "/etc/passwd"),
+        ]),
+        case!("hardlink", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::Hardlink("hello/templates/passwd", "/etc/passwd"),
+        ]),
+        case!("fifo", false, [
+            Entry::File("hello/Chart.yaml", CHART_YAML),
+            Entry::Fifo("hello/templates/fifo"),
+        ]),
+    ]
+}
@@ -0,0 +1,244 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the layout of packaged Helm charts.
+//!
+//! A packaged chart has every file under a single directory that is named after
+//! the chart, which looks like:
+//!
+//! ```text
+//! hello-world/
+//! ├── Chart.yaml
+//! ├── Chart.lock
+//! ├── values.yaml
+//! ├── values.schema.json
+//! ├── README.md, LICENSE, NOTES.txt, .helmignore
+//! ├── charts/*.tgz, charts/<subchart>/**
+//! ├── crds/**
+//! └── templates/**
+//! ```
+
+use charted_helm_types::Chart;
+use flate2::read::MultiGzDecoder;
+use std::{
+    ffi::OsString,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+use tar::{Archive, EntryType};
+
+/// Files that are allowed in the chart's root directory.
+pub(crate) const TOPLEVEL_FILES: &[&str] = &[
+    "Chart.yaml",
+    "Chart.lock",
+    "values.yaml",
+    "values.schema.json",
+    "requirements.yaml",
+    "requirements.lock",
+    "README.md",
+    "LICENSE",
+    "NOTES.txt",
+    ".helmignore",
+];
+
+/// Limits that are enforced when validating a chart tarball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum amount of bytes, in total, that a chart tarball can decompress to.
+    pub max_decompressed_size: u64,
+
+    /// Maximum amount of files and directories that a chart tarball can contain.
+    pub max_entries: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::from(&charted_config::charts::Config::default())
+    }
+}
+
+impl From<&charted_config::charts::Config> for Limits {
+    fn from(config: &charted_config::charts::Config) -> Self {
+        Limits {
+            max_decompressed_size: config.max_decompressed_size,
+            max_entries: config.max_entries,
+        }
+    }
+}
+
+/// Reason why a chart tarball was rejected.
+#[derive(Debug, derive_more::Display)]
+pub enum Violation {
+    /// The tarball couldn't be read.
+    #[display("tarball is corrupted: {_0}")]
+    Corrupted(std::io::Error),
+
+    /// An entry's path was absolute.
+    #[display("path '{}' is absolute", _0.display())]
+    AbsolutePath(PathBuf),
+
+    /// An entry's path escapes the chart directory.
+    #[display("path '{}' contains a `..` component", _0.display())]
+    PathTraversal(PathBuf),
+
+    /// An entry was a symbolic or hard link.
+    #[display("path '{}' is a link, which are not allowed", _0.display())]
+    Link(PathBuf),
+
+    /// An entry was something other than a file or directory.
+    #[display("path '{}' is not a file or directory", _0.display())]
+    UnsupportedEntry(PathBuf),
+
+    /// An entry was outside of the chart's root directory.
+    #[display("path '{}' is outside of the chart directory", _0.display())]
+    OutsideChartDirectory(PathBuf),
+
+    /// An entry isn't a part of a Helm chart's layout.
+    #[display("path '{}' is not allowed", _0.display())]
+    NotAllowed(PathBuf),
+
+    /// The tarball had more entries than [`Limits::max_entries`].
+    #[display("tarball has more than {_0} entries")]
+    TooManyEntries(u64),
+
+    /// The tarball decompressed to more than [`Limits::max_decompressed_size`] bytes.
+    #[display("tarball decompresses to more than {_0} bytes")]
+    TooLarge(u64),
+
+    /// The tarball didn't have a `Chart.yaml` file.
+    #[display("chart tarball is missing a `Chart.yaml` file")]
+    MissingChartManifest,
+
+    /// The `Chart.yaml` file couldn't be parsed.
+    #[display("failed to parse `Chart.yaml`: {_0}")]
+    InvalidChartManifest(serde_yaml_ng::Error),
+}
+
+impl std::error::Error for Violation {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Violation::Corrupted(err) => Some(err),
+            Violation::InvalidChartManifest(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Validates the layout of a packaged chart and returns its parsed `Chart.yaml`.
+///
+/// The tarball is read from `tarball` as it's being validated, so it doesn't need to be
+/// fully buffered in memory.
+pub fn validate<R: Read>(tarball: R, limits: Limits) -> Result<Chart, Violation> {
+    let mut archive = Archive::new(MultiGzDecoder::new(tarball));
+    let mut root = None::<OsString>;
+    let mut chart = None;
+    let mut entries = 0u64;
+    let mut size = 0u64;
+
+    for entry in archive.entries().map_err(Violation::Corrupted)? {
+        let mut entry = entry.map_err(Violation::Corrupted)?;
+
+        entries += 1;
+        if entries > limits.max_entries {
+            return Err(Violation::TooManyEntries(limits.max_entries));
+        }
+
+        size = size.saturating_add(entry.header().entry_size().map_err(Violation::Corrupted)?);
+        if size > limits.max_decompressed_size {
+            return Err(Violation::TooLarge(limits.max_decompressed_size));
+        }
+
+        // `Entry::path` is avoided since it'll happily give back paths with
+        // `..` in them, which is what we want to check for.
+        let path = PathBuf::from(String::from_utf8_lossy(&entry.path_bytes()).into_owned());
+        let components = normalize(&path)?;
+
+        let Some((first, rest)) = components.split_first() else {
+            return Err(Violation::OutsideChartDirectory(path));
+        };
+
+        match root {
+            Some(ref root) if root != first => return Err(Violation::OutsideChartDirectory(path)),
+            Some(_) => {}
+            None => root = Some(first.clone()),
+        }
+
+        let entry_type = entry.header().entry_type();
+        match entry_type {
+            EntryType::Symlink | EntryType::Link => return Err(Violation::Link(path)),
+            EntryType::Directory => {
+                if !is_allowed_directory(rest) {
+                    return Err(Violation::NotAllowed(path));
+                }
+
+                continue;
+            }
+
+            EntryType::Regular | EntryType::Continuous => {}
+            _ => return Err(Violation::UnsupportedEntry(path)),
+        }
+
+        if !is_allowed_file(rest) {
+            return Err(Violation::NotAllowed(path));
+        }
+
+        if rest.len() == 1 && rest[0] == "Chart.yaml" {
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).map_err(Violation::Corrupted)?;
+
+            chart = Some(serde_yaml_ng::from_slice::<Chart>(&contents).map_err(Violation::InvalidChartManifest)?);
+        }
+    }
+
+    chart.ok_or(Violation::MissingChartManifest)
+}
+
+/// Splits `path` into its normal components, rejecting absolute paths and
+/// anything that tries to escape the archive.
+fn normalize(path: &Path) -> Result<Vec<OsString>, Violation> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_owned()),
+            Component::CurDir => {}
+            Component::ParentDir => return Err(Violation::PathTraversal(path.to_path_buf())),
+            Component::RootDir | Component::Prefix(_) => return Err(Violation::AbsolutePath(path.to_path_buf())),
+        }
+    }
+
+    Ok(components)
+}
+
+fn is_allowed_directory(rest: &[OsString]) -> bool {
+    match rest {
+        // the chart's root directory
+        [] => true,
+        [dir] => dir == "templates" || dir == "crds" || dir == "charts",
+
+        // subcharts that are unpacked in `charts/` follow the same layout
+        [dir, _subchart, inner @ ..] if dir == "charts" => is_allowed_directory(inner),
+        [dir, ..] => dir == "templates" || dir == "crds",
+    }
+}
+
+fn is_allowed_file(rest: &[OsString]) -> bool {
+    match rest {
+        [] => false,
+        [file] => TOPLEVEL_FILES.iter().any(|name| file == name),
+        [dir, file] if dir == "charts" => Path::new(file).extension().is_some_and(|ext| ext == "tgz"),
+        [dir, _subchart, inner @ ..] if dir == "charts" => is_allowed_file(inner),
+        [dir, ..] => dir == "templates" || dir == "crds",
+    }
+}
@@ -241,6 +241,18 @@ pub struct Chart {
     pub annotations: HashMap<String, String>,
 }
 
+/// A single file within a packaged chart.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ChartFile {
+    /// Path of the file, relative to the chart's root directory (i.e, `templates/deployment.yaml`).
+    pub path: String,
+
+    /// Size of the file, in bytes.
+    pub size: u64,
+}
+
 /// Specification of the `index.yaml` file used for Helm chart repositories.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
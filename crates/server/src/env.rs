@@ -19,7 +19,11 @@ mod prometheus;
 #[cfg(all(feature = "libsystemd", target_os = "linux"))]
 mod systemd;
 
-use crate::{feature, routing};
+use crate::{
+    feature,
+    ops::invites::{LogNotifier, Notifier},
+    routing,
+};
 use axum::Extension;
 use axum_server::Handle;
 use charted_authz::Authenticator;
@@ -39,6 +43,9 @@ pub struct Env {
     pub features: feature::Collection,
     pub config: Config,
     pub authz: Arc<dyn Authenticator>,
+    pub keys: Arc<crate::ops::jwt::Keys>,
+    pub ratelimits: Option<Arc<dyn crate::middleware::ratelimit::Store>>,
+    pub notifier: Arc<dyn Notifier>,
     pub ulid: ulid::Generator,
     pub http: reqwest::Client,
     pub db: DatabaseConnection,
@@ -70,6 +77,14 @@ impl Env {
         let authz: Arc<dyn Authenticator> = build_authz_backend(&config.sessions);
         debug!("authenticator: initialized [{}]", Duration::from(now.elapsed()));
 
+        let keys = Arc::new(crate::ops::jwt::Keys::new(&config)?);
+        debug!("jwt keys: loaded {} key(s)", config.jwt.keys.len());
+
+        let ratelimits =
+            config.server.ratelimits.as_ref().map(|ratelimits| {
+                crate::middleware::ratelimit::store::from_backend(ratelimits.backend, pool.clone())
+            });
+
         let prometheus = match &config.metrics {
             metrics::Config::Disabled => None,
             metrics::Config::Prometheus(config) => charted_metrics::init_prometheus(config).map(Some)?,
@@ -92,9 +107,6 @@ impl Env {
             }
         }
 
-        #[allow(unused_mut)]
-        let mut features = feature::Collection::new();
-
         let http = reqwest::Client::builder()
             .use_rustls_tls()
             .user_agent(format!(
@@ -104,12 +116,63 @@ impl Env {
             ))
             .build()?;
 
+        let mut features = feature::Collection::new();
+        if config.features.gc.enable {
+            features.add(charted_feature_gc::Feature::new(charted_feature_gc::Collector::new(
+                config.features.gc.clone(),
+                pool.clone(),
+                ds.clone(),
+            )?));
+        }
+
+        if config.features.oci.enable {
+            features.add(charted_feature_oci::Feature);
+        }
+
+        if config.features.auditlog.enable {
+            let sink = charted_feature_audit_logs::sink::from_config(
+                &config.features.auditlog.sink,
+                pool.clone(),
+                http.clone(),
+            );
+
+            sink.init().await?;
+            features.add(charted_feature_audit_logs::Feature::new(sink));
+        }
+
+        if config.features.totp.enable {
+            features.add(charted_feature_totp::Feature::new(config.features.totp.clone()));
+        }
+
+        if config.features.webhooks.enable {
+            features.add(charted_feature_webhooks::Feature::new(
+                charted_feature_webhooks::Dispatcher::new(
+                    config.features.webhooks.clone(),
+                    pool.clone(),
+                    // redirects aren't followed since they could point to a host that
+                    // wasn't checked by the dispatcher
+                    reqwest::Client::builder()
+                        .use_rustls_tls()
+                        .user_agent(format!(
+                            "Noelware/charted-server (+{}; {})",
+                            env!("CARGO_PKG_REPOSITORY"),
+                            charted_core::version()
+                        ))
+                        .redirect(reqwest::redirect::Policy::none())
+                        .build()?,
+                ),
+            ));
+        }
+
         debug!("built environment in {}", Duration::from(original.elapsed()));
         Ok(Self {
             prometheus,
             features,
             config,
             authz,
+            keys,
+            ratelimits,
+            notifier: Arc::new(LogNotifier),
             http,
             ulid: ulid::Generator::new(),
             db: pool,
@@ -126,6 +189,23 @@ impl Env {
 
     /// Starts the API server.
     pub async fn drive(&self) -> eyre::Result<()> {
+        if crate::ops::ldap::enabled(self) {
+            tokio::spawn(crate::ops::ldap::sync_job(self.clone()));
+        }
+
+        if self.features.has::<charted_feature_gc::Feature>() {
+            tokio::spawn(crate::ops::gc::job(self.clone()));
+        }
+
+        if self.ratelimits.is_some() {
+            tokio::spawn(crate::middleware::ratelimit::prune_job(self.clone()));
+        }
+
+        if !self.config.workers.apikeys.standalone {
+            let worker = charted_apikey_worker::Worker::new(self.config.workers.apikeys.clone(), self.db.clone());
+            tokio::spawn(worker.run());
+        }
+
         let router = routing::create_router(self)
             .layer(Extension(self.prometheus.clone()))
             .with_state(self.clone());
@@ -143,10 +223,7 @@ fn build_authz_backend(config: &sessions::Config) -> Arc<dyn Authenticator> {
     match config.backend {
         Backend::Local => Arc::new(charted_authz_local::Backend::default()),
         Backend::Static(ref users) => Arc::new(charted_authz_static::Backend::new(users.to_owned())),
-        Backend::Ldap(_) => {
-            warn!("as of this build, the LDAP authenticator is not supported, switching to local backend");
-            Arc::new(charted_authz_local::Backend::default())
-        }
+        Backend::Ldap(ref config) => Arc::new(charted_authz_ldap::Backend::new(config.to_owned())),
     }
 }
 
@@ -21,6 +21,7 @@ use axum::Router;
 use axum_server::{Handle, tls_rustls::RustlsConfig};
 use charted_config::server::{self, ssl};
 use charted_core::ResultExt;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
 
 /// Starts the API server.
@@ -46,7 +47,7 @@ async fn start_https(config: &server::Config, ssl: &ssl::Config, router: Router)
     info!(address = %addr, "binding to address");
     axum_server::bind_rustls(addr, rustls)
         .handle(handle)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .into_report()
 }
@@ -61,7 +62,7 @@ async fn start_http(config: &server::Config, router: Router) -> eyre::Result<()>
     systemd::notify_ready();
 
     info!(target: "charted_server", address = %addr, "binding to socket address");
-    axum::serve(listener, router.into_make_service())
+    axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal(None))
         .await
         .into_report()
@@ -40,6 +40,9 @@ pub mod pagination;
 pub mod routing;
 pub mod util;
 
+#[cfg(test)]
+pub(crate) mod testutil;
+
 // Private module to aid in macro development
 #[doc(hidden)]
 pub mod __macro_support {
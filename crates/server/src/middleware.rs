@@ -15,8 +15,9 @@
 
 pub mod authn;
 mod log;
+pub mod ratelimit;
 mod request_id;
 
 pub use log::log;
-pub(in crate::middleware) use request_id::XRequestId;
-pub use request_id::request_id;
+pub use ratelimit::ratelimit;
+pub use request_id::{XRequestId, request_id};
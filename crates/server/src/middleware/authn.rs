@@ -29,7 +29,7 @@ use charted_core::{
     bitflags::{ApiKeyScope, ApiKeyScopes},
 };
 use charted_database::entities::{ApiKeyEntity, SessionEntity, UserEntity, apikey, session, user};
-use charted_types::{ApiKey, User, name::Name};
+use charted_types::{ApiKey, DateTime, User, name::Name};
 use error::Error;
 pub use extract::Session;
 use jsonwebtoken::TokenData;
@@ -240,6 +240,18 @@ impl Authn {
 
         match env.authz.authenticate(request).await {
             Ok(()) => {
+                // `Basic` authentication has no way to pass a code from an authenticator
+                // app, so it would bypass two-factor authentication.
+                if ops::totp::enabled(&env, user.id).await.map_err(as_response)? {
+                    bail!(api::err(
+                        StatusCode::FORBIDDEN,
+                        (
+                            api::ErrorCode::UnsupportedAuthorizationKind,
+                            "cannot use `Basic` authentication with an account that has two-factor authentication enabled",
+                        ),
+                    ))
+                }
+
                 sentry::configure_scope(|scope| {
                     scope.set_user(Some(sentry::User {
                         username: Some(user.username.as_str().to_owned()),
@@ -293,6 +305,8 @@ impl Authn {
             bail!(Error::RefreshTokenRequired)
         }
 
+        ops::db::session::touch(&env, &session).await;
+
         let Some(user) = UserEntity::find_by_id(claims.uid)
             .one(&env.db)
             .await
@@ -347,6 +361,17 @@ impl Authn {
             ))
         };
 
+        if apikey.is_expired(DateTime::now()) {
+            bail!(api::err(
+                StatusCode::UNAUTHORIZED,
+                (
+                    api::ErrorCode::ApiKeyExpired,
+                    "api key has expired",
+                    json!({"expires_in":apikey.expires_in}),
+                ),
+            ))
+        }
+
         let scopes = apikey.bitfield();
         for (scope, bit) in self.options.scopes.flags() {
             debug!(%apikey.name, %apikey.owner, %scope, "checking if api key has scope enabled");
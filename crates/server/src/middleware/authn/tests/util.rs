@@ -16,6 +16,7 @@
 use crate::{
     Env,
     middleware::authn::{Factory, Options},
+    testutil,
 };
 use axum::{
     Router,
@@ -23,54 +24,13 @@ use axum::{
     response::IntoResponse,
     routing,
 };
-use charted_config::{
-    Config, database, metrics,
-    sessions::{self, Backend},
-};
-use sentry::protocol::Url;
 
 async fn echo(req: axum::extract::Request) -> impl IntoResponse {
     (StatusCode::OK, Response::new(req.into_body()))
 }
 
-// so that sessions are "consistent" enough between tests
-const JWT_SECRET_KEY: &str =
-    "ahashthatshouldbeavalidhashfromopensslbutidontwanttodothatandnooneshouldusethisvaluetobeginwithuwu";
-
-pub async fn create_environment(ov: impl FnOnce(&mut Env)) -> Env {
-    let mut env = Env::new(Config {
-        jwt_secret_key: JWT_SECRET_KEY.to_owned(),
-        registrations: true,
-        single_user: false,
-        single_org: false,
-        sentry_dsn: None,
-        base_url: Some(Url::parse("http://localhost:3651").unwrap()),
-        logging: Default::default(),
-        storage: Default::default(),
-        tracing: None,
-        metrics: metrics::Config::Disabled,
-        server: Default::default(),
-
-        sessions: sessions::Config {
-            enable_basic_auth: false,
-            backend: Backend::Static(azalia::btreemap! {
-                // echo "noeliscutieuwu" | cargo cli admin authz hash-password --stdin
-                "noel" => "$argon2id$v=19$m=19456,t=2,p=1$gIcVA4mVHgr8ZWkmDrtJlw$sb5ypFAvphFCGrJXy9fRI1Gb/2vGIH1FTzDax458+xY"
-            })
-        },
-
-        database: database::Config::SQLite(database::sqlite::Config {
-            common: Default::default(),
-            path: String::from(":memory:").into(),
-        }),
-    }).await.expect("failed to create server environment");
-
-    ov(&mut env);
-    env
-}
-
 pub async fn create_router(options: Options, ov: impl FnOnce(&mut Env)) -> Router {
-    let env = create_environment(ov).await;
+    let env = testutil::create_environment(ov).await;
 
     Router::new()
         .route("/echo", routing::post(echo).layer(env.authn(options)))
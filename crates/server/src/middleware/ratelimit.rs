@@ -0,0 +1,224 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate limiting of requests, which is enabled by the `[server.ratelimits]` table.
+//!
+//! Requests are counted in fixed windows per bucket: the API key or user that sent the
+//! request if it was authenticated with one, or the client's IP address otherwise. API
+//! keys that don't resolve to an active key are counted by IP address, so that made up
+//! keys can't be used to get a fresh limit for every request.
+//! Routes that have their own limit are counted separately from the rest of the API.
+//!
+//! Behind reverse proxies that are listed in `trusted_proxies`, the client's IP address
+//! is taken from the `X-Forwarded-For` header that they append to.
+
+#[cfg(test)]
+mod tests;
+
+pub mod store;
+
+use crate::Env;
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use charted_config::server::ratelimits::{Config, Limit};
+use charted_core::api;
+use charted_database::entities::{ApiKeyEntity, apikey};
+use charted_types::ApiKey;
+use chrono::{DateTime, TimeZone, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+pub use store::Store;
+
+pub const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Returns the index of the window that `now` is in, and when that window ends.
+pub(crate) fn window(now: DateTime<Utc>, limit: &Limit) -> (i64, DateTime<Utc>) {
+    let secs = i64::try_from(limit.window.as_secs()).unwrap_or(i64::MAX).max(1);
+    let index = now.timestamp().div_euclid(secs);
+
+    (
+        index,
+        Utc.timestamp_opt(index.saturating_add(1).saturating_mul(secs), 0)
+            .single()
+            .unwrap_or(DateTime::<Utc>::MAX_UTC),
+    )
+}
+
+/// Returns which bucket a request is counted in and that bucket's limit, or [`None`]
+/// if the request can't be attributed to anyone.
+async fn bucket(env: &Env, config: &Config, req: &Request<Body>) -> Option<(String, Limit)> {
+    if let Some((scheme, token)) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+    {
+        match &*scheme.to_ascii_lowercase() {
+            "apikey" => {
+                if let Some(apikey) = resolve(env, token).await {
+                    return Some((format!("apikey:{}", apikey.id), config.apikey));
+                }
+            }
+
+            "bearer" => {
+                if let Ok(data) = env.keys.decode(token) {
+                    return Some((format!("user:{}", data.claims.uid), config.user));
+                }
+            }
+
+            // basic authentication isn't verified until the request is handled, so
+            // it's counted by IP address so that nobody can use up another user's limit.
+            _ => {}
+        }
+    }
+
+    let ConnectInfo(addr) = req.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    Some((format!("ip:{}", client_ip(config, addr.ip(), req.headers())), config.ip))
+}
+
+/// Resolves the IP address of the client that sent a request, which `peer` forwarded
+/// if it's a trusted proxy.
+///
+/// Every proxy appends the address that it received the request from to
+/// `X-Forwarded-For`, so the addresses are walked from the right until one isn't a
+/// trusted proxy. Anything to the left of it was sent by the client and can't be
+/// trusted.
+pub(crate) fn client_ip(config: &Config, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    let mut ip = peer;
+    let forwarded = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+
+    for addr in forwarded.into_iter().rev() {
+        if !config.trusted_proxies.contains(&ip) {
+            break;
+        }
+
+        let Ok(addr) = addr.trim().parse::<IpAddr>() else {
+            break;
+        };
+
+        ip = addr;
+    }
+
+    ip
+}
+
+/// Looks up the API key that `token` belongs to, if it can still be used.
+async fn resolve(env: &Env, token: &str) -> Option<ApiKey> {
+    ApiKeyEntity::find()
+        .filter(apikey::Column::Token.eq(token))
+        .one(&env.db)
+        .await
+        .inspect_err(|e| warn!(error = %e, "failed to look up api key for its rate limit"))
+        .ok()
+        .flatten()
+        .map(ApiKey::from)
+        .filter(|apikey| !apikey.is_expired(charted_types::DateTime::now()))
+}
+
+fn headers(limit: &Limit, hits: u64, reset: i64) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(limit.requests));
+    headers.insert(
+        RATELIMIT_REMAINING,
+        HeaderValue::from(u64::from(limit.requests).saturating_sub(hits)),
+    );
+
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(reset));
+    headers
+}
+
+#[cfg_attr(debug_assertions, axum::debug_middleware)]
+pub async fn ratelimit(State(env): State<Env>, req: Request<Body>, next: Next) -> Response {
+    let (Some(config), Some(store)) = (env.config.server.ratelimits.as_ref(), env.ratelimits.as_deref()) else {
+        return next.run(req).await;
+    };
+
+    let Some((mut key, mut limit)) = bucket(&env, config, &req).await else {
+        return next.run(req).await;
+    };
+
+    if let Some(path) = req.extensions().get::<MatchedPath>() {
+        let route = format!("{} {}", req.method(), path.as_str());
+        if let Some(over) = config.routes.get(&route) {
+            key = format!("{route}:{key}");
+            limit = *over;
+        }
+    }
+
+    let now = Utc::now();
+    let (index, resets_at) = window(now, &limit);
+    let hits = match store.hit(&format!("{key}:{index}"), resets_at).await {
+        Ok(hits) => hits,
+        Err(e) => {
+            // a broken store shouldn't take the whole API down with it
+            warn!(error = %e, "failed to count request towards its rate limit");
+
+            return next.run(req).await;
+        }
+    };
+
+    let reset = (resets_at - now).num_seconds().max(0);
+    let mut headers = headers(&limit, hits, reset);
+
+    if hits > u64::from(limit.requests) {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(reset));
+        return (
+            headers,
+            api::err(
+                StatusCode::TOO_MANY_REQUESTS,
+                (
+                    api::ErrorCode::RateLimited,
+                    "too many requests were sent, try again later",
+                    json!({"limit":limit.requests,"retry_after":reset}),
+                ),
+            ),
+        )
+            .into_response();
+    }
+
+    let mut res = next.run(req).await;
+    res.headers_mut().extend(headers);
+
+    res
+}
+
+/// Forgets about windows that have ended every minute.
+pub async fn prune_job(env: Env) {
+    let Some(store) = env.ratelimits.clone() else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        if let Err(e) = store.prune().await {
+            warn!(error = %e, "failed to prune rate limit windows");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use charted_config::server::ratelimits::Backend;
+use charted_core::BoxedFuture;
+use charted_database::entities::{RateLimitEntity, ratelimit};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::{Expr, OnConflict},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Keeps how many requests were sent in each bucket's window.
+pub trait Store: Send + Sync {
+    /// Counts a request towards `key`'s window, which ends at `resets_at`, and
+    /// returns how many requests were counted in the window so far.
+    fn hit<'a>(&'a self, key: &'a str, resets_at: DateTime<Utc>) -> BoxedFuture<'a, eyre::Result<u64>>;
+
+    /// Forgets about windows that have already ended.
+    fn prune(&self) -> BoxedFuture<'_, eyre::Result<()>>;
+}
+
+/// Creates the [`Store`] that `backend` refers to.
+pub fn from_backend(backend: Backend, db: DatabaseConnection) -> Arc<dyn Store> {
+    match backend {
+        Backend::Memory => Arc::new(Memory::default()),
+        Backend::Database => Arc::new(Database(db)),
+    }
+}
+
+/// [`Store`] that keeps windows in memory.
+#[derive(Debug, Default)]
+pub struct Memory(Mutex<HashMap<String, (DateTime<Utc>, u64)>>);
+
+impl Store for Memory {
+    fn hit<'a>(&'a self, key: &'a str, resets_at: DateTime<Utc>) -> BoxedFuture<'a, eyre::Result<u64>> {
+        Box::pin(async move {
+            let mut windows = self.0.lock().unwrap();
+            let (_, hits) = windows.entry(key.to_owned()).or_insert((resets_at, 0));
+            *hits += 1;
+
+            Ok(*hits)
+        })
+    }
+
+    fn prune(&self) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            let now = Utc::now();
+            self.0.lock().unwrap().retain(|_, (resets_at, _)| *resets_at > now);
+
+            Ok(())
+        })
+    }
+}
+
+/// [`Store`] that keeps windows in the `ratelimits` table, so that they are shared
+/// between every instance of charted-server that uses the same database.
+#[derive(Debug)]
+pub struct Database(DatabaseConnection);
+
+impl Store for Database {
+    fn hit<'a>(&'a self, key: &'a str, resets_at: DateTime<Utc>) -> BoxedFuture<'a, eyre::Result<u64>> {
+        Box::pin(async move {
+            let now = Utc::now();
+            RateLimitEntity::insert(ratelimit::ActiveModel {
+                created_at: ActiveValue::set(now),
+                updated_at: ActiveValue::set(now),
+                expires_at: ActiveValue::set(resets_at),
+                hits: ActiveValue::set(1),
+                key: ActiveValue::set(key.to_owned()),
+            })
+            .on_conflict(
+                OnConflict::column(ratelimit::Column::Key)
+                    .value(
+                        ratelimit::Column::Hits,
+                        Expr::col((RateLimitEntity, ratelimit::Column::Hits)).add(1),
+                    )
+                    .value(ratelimit::Column::UpdatedAt, now)
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.0)
+            .await?;
+
+            let hits = RateLimitEntity::find_by_id(key)
+                .one(&self.0)
+                .await?
+                .map_or(1, |model| model.hits);
+
+            Ok(hits.try_into().unwrap_or(u64::MAX))
+        })
+    }
+
+    fn prune(&self) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            RateLimitEntity::delete_many()
+                .filter(ratelimit::Column::ExpiresAt.lte(Utc::now()))
+                .exec(&self.0)
+                .await?;
+
+            Ok(())
+        })
+    }
+}
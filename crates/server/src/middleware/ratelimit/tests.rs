@@ -0,0 +1,167 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    RATELIMIT_LIMIT, RATELIMIT_REMAINING, RATELIMIT_RESET, X_FORWARDED_FOR, bucket, client_ip, headers,
+    store::{Memory, Store},
+    window,
+};
+use crate::testutil::{self, create_apikey, create_user};
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request},
+    http::{HeaderMap, HeaderValue, header},
+};
+use charted_config::server::ratelimits::{Config, Limit};
+use charted_core::serde::Duration;
+use chrono::{TimeDelta, TimeZone, Utc};
+use std::net::{IpAddr, SocketAddr};
+
+#[test]
+fn windows_are_aligned() {
+    let limit = Limit::per_minute(10);
+    let start = Utc.timestamp_opt(1_800_000_000 - 1_800_000_000 % 60, 0).unwrap();
+
+    let (index, resets_at) = window(start, &limit);
+    assert_eq!(resets_at, start + TimeDelta::minutes(1));
+
+    // the last second of the window is still in it
+    assert_eq!(window(start + TimeDelta::seconds(59), &limit), (index, resets_at));
+
+    let (next, _) = window(resets_at, &limit);
+    assert_eq!(next, index + 1);
+}
+
+#[test]
+fn zero_second_windows_are_one_second_long() {
+    let limit = Limit {
+        requests: 10,
+        window: Duration::from_secs(0),
+    };
+
+    let now = Utc.timestamp_opt(1_800_000_000, 0).unwrap();
+    assert_eq!(window(now, &limit).1, now + TimeDelta::seconds(1));
+}
+
+#[tokio::test]
+async fn memory_store_counts_per_key() {
+    let store = Memory::default();
+    let resets_at = Utc::now() + TimeDelta::minutes(1);
+
+    assert_eq!(store.hit("ip:127.0.0.1:0", resets_at).await.unwrap(), 1);
+    assert_eq!(store.hit("ip:127.0.0.1:0", resets_at).await.unwrap(), 2);
+    assert_eq!(store.hit("ip:127.0.0.2:0", resets_at).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn memory_store_prunes_ended_windows() {
+    let store = Memory::default();
+    store.hit("ended", Utc::now() - TimeDelta::seconds(1)).await.unwrap();
+    store.hit("current", Utc::now() + TimeDelta::minutes(1)).await.unwrap();
+
+    store.prune().await.unwrap();
+    assert_eq!(store.hit("ended", Utc::now()).await.unwrap(), 1);
+    assert_eq!(
+        store.hit("current", Utc::now() + TimeDelta::minutes(1)).await.unwrap(),
+        2
+    );
+}
+
+#[test]
+fn headers_never_underflow() {
+    let limit = Limit::per_minute(2);
+
+    let headers = headers(&limit, 1, 30);
+    assert_eq!(headers[RATELIMIT_LIMIT], "2");
+    assert_eq!(headers[RATELIMIT_REMAINING], "1");
+    assert_eq!(headers[RATELIMIT_RESET], "30");
+
+    assert_eq!(headers(&limit, 5, 30)[RATELIMIT_REMAINING], "0");
+}
+
+fn request(authorization: &str) -> Request<Body> {
+    let mut req = Request::get("/v1/users/@me").body(Body::empty()).unwrap();
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 3651))));
+
+    req.headers_mut()
+        .insert(header::AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+
+    req
+}
+
+#[tokio::test]
+async fn apikeys_are_only_counted_once_they_resolve() {
+    let env = testutil::create_environment(|_| {}).await;
+    let config = Config::default();
+    let noel = create_user(&env, "noel").await;
+
+    let apikey = create_apikey(&env, noel.id, "ci", None).await;
+    let expired = create_apikey(&env, noel.id, "old", Some(Utc::now() - TimeDelta::days(1))).await;
+
+    let key = |authorization: String| {
+        let env = env.clone();
+        let config = config.clone();
+        async move {
+            bucket(&env, &config, &request(&authorization))
+                .await
+                .map(|(key, _)| key)
+        }
+    };
+
+    assert_eq!(
+        key(format!("ApiKey {}", apikey.token)).await,
+        Some(format!("apikey:{}", apikey.id))
+    );
+
+    // made up and expired keys would otherwise get a fresh limit every time
+    assert_eq!(
+        key(String::from("ApiKey madeup")).await,
+        Some(String::from("ip:127.0.0.1"))
+    );
+    assert_eq!(
+        key(format!("ApiKey {}", expired.token)).await,
+        Some(String::from("ip:127.0.0.1"))
+    );
+}
+
+#[test]
+fn forwarded_for_is_only_trusted_from_trusted_proxies() {
+    let proxy = IpAddr::from([10, 0, 0, 1]);
+    let client = IpAddr::from([203, 0, 113, 7]);
+    let config = Config {
+        trusted_proxies: vec![proxy],
+        ..Config::default()
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("198.51.100.1, 203.0.113.7"));
+
+    // the left-most address was made up by the client
+    assert_eq!(client_ip(&config, proxy, &headers), client);
+
+    // anyone else could make up the header
+    assert_eq!(client_ip(&config, client, &headers), client);
+    assert_eq!(client_ip(&Config::default(), proxy, &headers), proxy);
+
+    // every trusted proxy that forwarded the request is skipped
+    headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+    assert_eq!(client_ip(&config, proxy, &headers), client);
+
+    // requests that don't say who they were forwarded for are counted by the proxy
+    assert_eq!(client_ip(&config, proxy, &HeaderMap::new()), proxy);
+    headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("unknown"));
+    assert_eq!(client_ip(&config, proxy, &headers), proxy);
+}
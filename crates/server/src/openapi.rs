@@ -18,9 +18,12 @@ mod types;
 
 use addons::{IncludeDefaultVersionWithoutPrefix, IncludeErrorProneSchemas};
 pub use types::{
-    ApiErrorResponse, ApiKeyResponse, EmptyApiResponse, ListApiKeyResponse, ListOrganizationResponse,
-    ListRepositoryResponse, OrganizationResponse, RepositoryResponse, SessionResponse, Url, UrlResponse,
-    UserResponse,
+    ApiErrorResponse, ApiKeyResponse, EmptyApiResponse, GpgKeyResponse, ListApiKeyResponse, ListAuditLogResponse,
+    ListGpgKeyResponse, ListMemberInviteResponse, ListOrganizationMemberResponse, ListOrganizationResponse,
+    ListRepositoryMemberResponse, ListRepositoryResponse, ListSessionResponse, ListWebhookDeliveryResponse,
+    ListWebhookResponse, MemberInviteResponse, OrganizationResponse, RepositoryReleaseResponse,
+    RepositoryResponse, SessionResponse, TotpChallengeResponse, TotpEnrollmentResponse, TotpStatusResponse, Url,
+    UrlResponse, UserConnectionsResponse, UserResponse, WebhookResponse,
 };
 use utoipa::{
     Modify, OpenApi,
@@ -53,12 +56,16 @@ use utoipa::{
             //                          request bodies                          \\
             charted_types::payloads::CreateRepositoryReleasePayload,
             charted_types::payloads::PatchRepositoryReleasePayload,
+            charted_types::payloads::CreateMemberPayload,
+            charted_types::payloads::PatchMemberPayload,
             charted_types::payloads::CreateOrganizationPayload,
             charted_types::payloads::PatchOrganizationPayload,
             charted_types::payloads::CreateRepositoryPayload,
             charted_types::payloads::PatchRepositoryPayload,
             charted_types::payloads::CreateApiKeyPayload,
             charted_types::payloads::PatchApiKeyPayload,
+            charted_types::payloads::CreateGpgKeyPayload,
+            charted_types::payloads::PatchGpgKeyPayload,
             charted_types::payloads::CreateUserPayload,
             charted_types::payloads::PatchUserPayload,
 
@@ -73,6 +80,7 @@ use utoipa::{
             charted_helm_types::ChartIndexSpec,
             charted_helm_types::ImportValue,
             charted_helm_types::ChartIndex,
+            charted_helm_types::ChartFile,
             charted_helm_types::ChartType,
             charted_helm_types::Chart,
 
@@ -84,9 +92,12 @@ use utoipa::{
             charted_types::OrganizationMember,
             charted_types::Organization,
 
+            charted_types::MemberInvite,
+
             charted_types::UserConnections,
             charted_types::Session,
             charted_types::ApiKey,
+            charted_types::GpgKey,
             charted_types::User,
 
             charted_core::api::ErrorCode,
@@ -105,6 +116,25 @@ use utoipa::{
             charted_feature::Metadata,
             charted_feature::Deprecation,
 
+            //                              audit logs                          \\
+            charted_feature_audit_logs::AuditLog,
+            charted_feature_audit_logs::Action,
+            charted_feature_audit_logs::Change,
+
+            //                                 totp                             \\
+            charted_feature_totp::TotpCodePayload,
+            charted_feature_totp::TotpEnrollment,
+            charted_feature_totp::TotpStatus,
+            crate::ops::oidc::TotpChallenge,
+            crate::routing::v1::user::sessions::OidcTotpPayload,
+
+            //                               webhooks                           \\
+            charted_feature_webhooks::CreateWebhookPayload,
+            charted_feature_webhooks::PatchWebhookPayload,
+            charted_feature_webhooks::event::EventKind,
+            charted_feature_webhooks::WebhookDelivery,
+            charted_feature_webhooks::Webhook,
+
             crate::routing::v1::main::Main,
             crate::routing::v1::Entrypoint,
             crate::pagination::Ordering,
@@ -115,31 +145,112 @@ use utoipa::{
             ApiKeyResponse,
             EmptyApiResponse,
             OrganizationResponse,
+            RepositoryReleaseResponse,
             RepositoryResponse,
             UserResponse,
             ListApiKeyResponse,
             ListOrganizationResponse,
+            ListOrganizationMemberResponse,
             ListRepositoryResponse,
+            ListRepositoryMemberResponse,
             UrlResponse,
             SessionResponse,
+            WebhookResponse,
+            TotpStatusResponse,
+            TotpEnrollmentResponse,
+            TotpChallengeResponse,
+            ListAuditLogResponse,
+            ListWebhookResponse,
+            ListWebhookDeliveryResponse,
+            MemberInviteResponse,
+            ListMemberInviteResponse,
+            GpgKeyResponse,
+            ListGpgKeyResponse,
+            ListSessionResponse,
+            UserConnectionsResponse,
 
             crate::routing::v1::main::MainResponse,
+            crate::routing::v1::repository::releases::ChartResponse,
+            crate::routing::v1::repository::releases::ListChartFileResponse,
             crate::routing::v1::indexes::ChartIndexResponse,
             crate::routing::v1::EntrypointResponse,
         )
     ),
     paths(
+        crate::routing::v1::repository::releases::upload_release_provenance,
+        crate::routing::v1::repository::releases::upload_release_tarball,
         crate::routing::v1::repository::releases::get_single_release_provenance,
+        crate::routing::v1::repository::releases::get_single_release_chart,
+        crate::routing::v1::repository::releases::list_release_files,
+        crate::routing::v1::repository::releases::get_release_file,
         crate::routing::v1::repository::releases::get_single_release_tarball,
         crate::routing::v1::repository::releases::get_single_release,
         crate::routing::v1::repository::releases::fetch_releases,
+        crate::routing::v1::repository::releases::patch_release,
+        crate::routing::v1::repository::releases::yank_release,
+        crate::routing::v1::repository::releases::unyank_release,
+        crate::routing::v1::repository::releases::delete_release,
+        crate::routing::v1::repository::audit_logs::list,
+        crate::routing::v1::repository::invites::revoke,
+        crate::routing::v1::repository::invites::create,
+        crate::routing::v1::repository::invites::list,
+        crate::routing::v1::repository::members::kick,
+        crate::routing::v1::repository::members::patch,
+        crate::routing::v1::repository::members::list,
+        crate::routing::v1::repository::webhooks::deliveries,
+        crate::routing::v1::repository::webhooks::create,
+        crate::routing::v1::repository::webhooks::delete,
+        crate::routing::v1::repository::webhooks::patch,
+        crate::routing::v1::repository::webhooks::fetch,
+        crate::routing::v1::repository::webhooks::list,
+        crate::routing::v1::repository::index,
         crate::routing::v1::repository::fetch,
         crate::routing::v1::repository::main,
 
+        crate::routing::v1::organization::icon::upload_org_icon,
+        crate::routing::v1::organization::icon::get_org_icon_by_hash,
+        crate::routing::v1::organization::icon::get_org_icon,
+
+        crate::routing::v1::organization::invites::revoke,
+        crate::routing::v1::organization::invites::create,
+        crate::routing::v1::organization::invites::list,
+        crate::routing::v1::organization::members::kick,
+        crate::routing::v1::organization::members::patch,
+        crate::routing::v1::organization::members::list,
+        crate::routing::v1::organization::webhooks::deliveries,
+        crate::routing::v1::organization::webhooks::create,
+        crate::routing::v1::organization::webhooks::delete,
+        crate::routing::v1::organization::webhooks::patch,
+        crate::routing::v1::organization::webhooks::fetch,
+        crate::routing::v1::organization::webhooks::list,
+        crate::routing::v1::organization::delete,
+        crate::routing::v1::organization::patch,
+        crate::routing::v1::organization::fetch,
+        crate::routing::v1::organization::create,
+        crate::routing::v1::organization::list,
+
         crate::routing::v1::user::sessions::login,
         crate::routing::v1::user::sessions::logout,
         crate::routing::v1::user::sessions::fetch,
         crate::routing::v1::user::sessions::refresh_session,
+        crate::routing::v1::user::sessions::list_sessions,
+        crate::routing::v1::user::sessions::revoke_session,
+        crate::routing::v1::user::sessions::revoke_other_sessions,
+        crate::routing::v1::user::sessions::oidc_login,
+        crate::routing::v1::user::sessions::oidc_callback,
+        crate::routing::v1::user::sessions::oidc_totp,
+        crate::routing::v1::user::connections::fetch,
+        crate::routing::v1::user::connections::link,
+        crate::routing::v1::user::connections::unlink,
+
+        crate::routing::v1::user::totp::status,
+        crate::routing::v1::user::totp::enroll,
+        crate::routing::v1::user::totp::verify,
+        crate::routing::v1::user::totp::disable,
+
+        crate::routing::v1::user::invites::list,
+        crate::routing::v1::user::invites::accept,
+        crate::routing::v1::user::invites::decline,
 
         crate::routing::v1::user::avatars::get_self_user_avatar_by_hash,
         crate::routing::v1::user::avatars::get_user_avatar_by_hash,
@@ -147,6 +258,11 @@ use utoipa::{
         crate::routing::v1::user::avatars::upload_user_avatar,
         crate::routing::v1::user::avatars::get_user_avatar,
 
+        crate::routing::v1::user::gpg_keys::list,
+        crate::routing::v1::user::gpg_keys::fetch,
+        crate::routing::v1::user::gpg_keys::create,
+        crate::routing::v1::user::gpg_keys::patch,
+        crate::routing::v1::user::gpg_keys::delete,
         crate::routing::v1::user::apikeys::delete,
         crate::routing::v1::user::apikeys::create,
         crate::routing::v1::user::apikeys::patch,
@@ -184,6 +300,10 @@ use utoipa::{
             name = "Users/Sessions",
             description = "Endpoints that allow to login as a user and get an access token."
         ),
+        (
+            name = "Users/Two-Factor Authentication",
+            description = "Endpoints that enroll users into two-factor authentication, only available if the TOTP feature is enabled"
+        ),
         (
             name = "API Keys",
             description = "Endpoints that allow authenticating users with a secret key that is trusted by the server."
@@ -204,10 +324,22 @@ use utoipa::{
             name = "Organizations",
             description = "Endpoints that create, modify, delete, or fetch organization metadata"
         ),
+        (
+            name = "Organization/Icons",
+            description = "Endpoints that can create, modify, delete, and fetch organization icons"
+        ),
         (
             name = "Organization/Members",
             description = "Endpoints that create, modify, delete, or fetch organization members"
         ),
+        (
+            name = "Audit Logs",
+            description = "Endpoints that list audit logs, only available if the audit logs feature is enabled"
+        ),
+        (
+            name = "Webhooks",
+            description = "Endpoints that manage webhooks and their deliveries, only available if the webhooks feature is enabled"
+        ),
     ),
     servers(
         (
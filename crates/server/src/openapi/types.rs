@@ -13,7 +13,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use charted_types::{ApiKey, Organization, Repository, Session, User};
+use crate::ops::oidc::TotpChallenge;
+use charted_feature_audit_logs::AuditLog;
+use charted_feature_totp::{TotpEnrollment, TotpStatus};
+use charted_feature_webhooks::{Webhook, WebhookDelivery};
+use charted_types::{
+    ApiKey, GpgKey, MemberInvite, Organization, OrganizationMember, Repository, RepositoryMember,
+    RepositoryRelease, Session, User, UserConnections,
+};
 use serde_json::Value;
 use utoipa::{
     PartialSchema, ToResponse, ToSchema,
@@ -280,16 +287,32 @@ macro_rules! mk_list_based_api_response_types {
 }
 
 mk_api_response_types! {
+    RepositoryRelease
     Organization
     Repository
     Session
     ApiKey
     User
     Url
+    Webhook
+    TotpStatus
+    TotpEnrollment
+    TotpChallenge
+    MemberInvite
+    GpgKey
+    UserConnections
 }
 
 mk_list_based_api_response_types! {
     Organization
+    OrganizationMember
+    RepositoryMember
+    AuditLog
     Repository
     ApiKey
+    Webhook
+    WebhookDelivery
+    MemberInvite
+    GpgKey
+    Session
 }
@@ -13,9 +13,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod auditlog;
 pub mod avatars;
 pub mod db;
+pub mod gc;
+pub mod gpg;
+pub mod indexes;
+pub mod invites;
 pub mod jwt;
+pub mod latest;
+pub mod ldap;
+pub mod members;
+pub mod oidc;
+pub mod releases;
+pub mod totp;
+pub mod webhooks;
 
 use argon2::{
     PasswordHasher,
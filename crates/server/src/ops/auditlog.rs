@@ -0,0 +1,144 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording actions into the audit logs. Records are only stored if the audit logs
+//! server feature is enabled.
+
+use crate::{
+    Env,
+    middleware::{XRequestId, authn::Session},
+};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use charted_feature_audit_logs::{Action, AuditLog, diff};
+use charted_types::{DateTime, Repository, Ulid};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+/// The user or organization, and optionally the repository, that a record is about.
+#[derive(Debug, Clone, Copy)]
+pub struct Target {
+    pub owner: Ulid,
+    pub repository: Option<Ulid>,
+}
+
+/// Returns the [`Target`] of actions that happen in `repository`.
+pub fn repository(repository: &Repository) -> Target {
+    Target {
+        owner: repository.owner,
+        repository: Some(repository.id),
+    }
+}
+
+/// Returns the [`Target`] of actions on resources that `owner` owns directly, like API
+/// keys and sessions.
+pub fn owner(owner: Ulid) -> Target {
+    Target {
+        owner,
+        repository: None,
+    }
+}
+
+/// Axum extractor of who made the request that an action happened in.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    /// The authenticated user, if the route has its session middleware configured.
+    pub actor: Option<Ulid>,
+
+    /// IP address of the client.
+    pub ip: Option<IpAddr>,
+
+    /// The request's `x-request-id`.
+    pub request_id: Option<String>,
+}
+
+impl Context {
+    /// Sets the actor for routes that don't require authentication, like logging in.
+    pub fn with_actor(self, actor: Ulid) -> Self {
+        Self {
+            actor: Some(actor),
+            ..self
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Context {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(Context {
+            actor: parts.extensions.get::<Session>().map(|session| session.user.id),
+            ip: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+
+            request_id: parts.extensions.get::<XRequestId>().map(ToString::to_string),
+        })
+    }
+}
+
+/// Records that `action` happened on `target`, which changed a resource from `before`
+/// into `after`. `()` can be used as either side if the resource didn't exist.
+///
+/// Failures are logged and reported to Sentry but never surfaced to the caller, since
+/// the action has already happened.
+pub async fn record<B: Serialize, A: Serialize>(
+    env: &Env,
+    cx: &Context,
+    action: Action,
+    target: Target,
+    before: B,
+    after: A,
+) {
+    let Some(feature) = env.features.get::<charted_feature_audit_logs::Feature>() else {
+        return;
+    };
+
+    let id = match env.ulid.generate() {
+        Ok(id) => id,
+        Err(e) => {
+            error!(error = %e, %action, "failed to generate id for audit log record");
+            sentry::capture_error(&e);
+
+            return;
+        }
+    };
+
+    let before = serde_json::to_value(before).unwrap_or(Value::Null);
+    let after = serde_json::to_value(after).unwrap_or(Value::Null);
+    let record = AuditLog {
+        repository: target.repository,
+        request_id: cx.request_id.clone(),
+        timestamp: DateTime::now(),
+        changes: diff(&before, &after),
+        action,
+        actor: cx.actor,
+        owner: target.owner,
+        ip: cx.ip,
+        id: id.into(),
+    };
+
+    if let Err(e) = feature.sink().record(record).await {
+        error!(error = %e, owner = %target.owner, %action, "failed to record audit log");
+        sentry::capture_error(&*e);
+    }
+}
@@ -43,6 +43,28 @@ pub async fn get(
     }
 }
 
+#[instrument(name = "charted.server.ops.getRepositoryReleaseAsModel", skip_all, fields(versionOrId = %version_or_ulid, repository.id = %repo.id, repository.name = %repo.name))]
+pub async fn get_as_model(
+    db: &DbConn,
+    repo: &Repository,
+    version_or_ulid: VersionOrUlid,
+) -> Result<Option<release::Model>, api::Response> {
+    match version_or_ulid {
+        VersionOrUlid::Ulid(id) => RepositoryReleaseEntity::find_by_id(id)
+            .filter(release::Column::Repository.eq(repo.id))
+            .one(db)
+            .await
+            .into_system_failure(),
+
+        VersionOrUlid::Version(version) => RepositoryReleaseEntity::find()
+            .filter(release::Column::Tag.eq(version.to_string()))
+            .filter(release::Column::Repository.eq(repo.id))
+            .one(db)
+            .await
+            .into_system_failure(),
+    }
+}
+
 #[instrument(name = "charted.server.ops.getRepositoryReleaseWithAdditionalBounds", skip_all, fields(versionOrId = %version_or_id, repository.id = %repo.id, repository.name = %repo.name))]
 pub async fn get_with_additional_bounds(
     db: &DbConn,
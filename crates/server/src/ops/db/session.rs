@@ -13,26 +13,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(test)]
+mod tests;
+
 use super::user;
 use crate::{
     Env,
     ext::ResultExt,
-    ops::jwt::{self, Claims},
+    ops::{
+        jwt::{self, Claims},
+        totp,
+    },
+};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{StatusCode, header, request::Parts},
 };
-use axum::http::StatusCode;
 use charted_authz::InvalidPassword;
 use charted_core::api;
 use charted_database as db;
 use charted_database::entities::{SessionEntity, session};
 use charted_types::{
-    NameOrUlid, Session, User,
+    NameOrUlid, Session, Ulid, User,
     payloads::{Login, UserLoginPayload},
 };
-use chrono::{DateTime, Days, TimeZone, Utc};
+use chrono::{DateTime, Days, TimeDelta, TimeZone, Utc};
 use jsonwebtoken::{TokenData, errors::ErrorKind};
-use sea_orm::{ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
+use sea_orm::{ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, sea_query::Expr};
 use serde_json::json;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
 use validator::ValidateEmail;
 
 #[instrument(
@@ -42,7 +55,12 @@ use validator::ValidateEmail;
 )]
 pub async fn login(
     env: &Env,
-    UserLoginPayload { login, password }: &UserLoginPayload,
+    UserLoginPayload {
+        login,
+        password,
+        totp: code,
+    }: &UserLoginPayload,
+    client: Client,
 ) -> Result<Session, api::Response> {
     let (user, model) = (match login {
         Login::Username(name) => user::get_with_model(&env.db, NameOrUlid::Name(name.clone())).await,
@@ -88,39 +106,8 @@ pub async fn login(
         api::system_failure_from_report(e)
     })?;
 
-    let id = env.ulid.generate().into_system_failure()?;
-    let now = Utc::now();
-    let access_token = jwt::encode_jwt(env, Claims {
-        iss: String::from(jwt::ISS_VALUE),
-        aud: String::from(jwt::AUD_VALUE),
-        exp: to_seconds(now + Days::new(1)).try_into().into_system_failure()?,
-        uid: user.id,
-        sid: id.into(),
-    })
-    .into_system_failure()?;
-
-    let refresh_token = jwt::encode_jwt(env, Claims {
-        iss: String::from(jwt::ISS_VALUE),
-        aud: String::from(jwt::AUD_VALUE),
-        exp: to_seconds(now + Days::new(7)).try_into().into_system_failure()?,
-        uid: user.id,
-        sid: id.into(),
-    })
-    .into_system_failure()?;
-
-    let model = session::Model {
-        refresh_token,
-        access_token,
-        account: user.id,
-        id: id.into(),
-    };
-
-    SessionEntity::insert(model.clone().into_active_model())
-        .exec(&env.db)
-        .await
-        .into_system_failure()?;
-
-    Ok(model.into())
+    totp::challenge(env, user.id, code.as_deref()).await?;
+    create(env, user.id, client).await
 }
 
 #[instrument(
@@ -165,7 +152,12 @@ pub async fn logout(env: &Env, session: Session) -> Result<(), api::Response> {
     skip_all,
     fields(%session.id, %session.owner)
 )]
-pub async fn refresh_session(env: &Env, session: Session, user: User) -> Result<Session, api::Response> {
+pub async fn refresh_session(
+    env: &Env,
+    session: Session,
+    user: User,
+    client: Client,
+) -> Result<Session, api::Response> {
     match jwt::decode_jwt(env, session.refresh_token.as_ref().unwrap()) {
         Ok(_) => {}
         Err(err) => match err.kind() {
@@ -187,14 +179,19 @@ pub async fn refresh_session(env: &Env, session: Session, user: User) -> Result<
 
     // Perform a logout.
     logout(env, session.clone()).await?;
+    create(env, user.id, client).await
+}
 
+/// Mints a new session for a user that was already authenticated.
+#[instrument(name = "charted.server.ops.createSession", skip_all, fields(user.id = %user))]
+pub async fn create(env: &Env, user: Ulid, Client { user_agent, ip }: Client) -> Result<Session, api::Response> {
     let id = env.ulid.generate().into_system_failure()?;
     let now = Utc::now();
     let access_token = jwt::encode_jwt(env, Claims {
         iss: String::from(jwt::ISS_VALUE),
         aud: String::from(jwt::AUD_VALUE),
         exp: to_seconds(now + Days::new(1)).try_into().into_system_failure()?,
-        uid: user.id,
+        uid: user,
         sid: id.into(),
     })
     .into_system_failure()?;
@@ -203,15 +200,20 @@ pub async fn refresh_session(env: &Env, session: Session, user: User) -> Result<
         iss: String::from(jwt::ISS_VALUE),
         aud: String::from(jwt::AUD_VALUE),
         exp: to_seconds(now + Days::new(7)).try_into().into_system_failure()?,
-        uid: user.id,
+        uid: user,
         sid: id.into(),
     })
     .into_system_failure()?;
 
     let model = session::Model {
+        created_at: now,
+        updated_at: now,
+        last_used_at: None,
+        user_agent,
+        ip: ip.map(|ip| ip.to_string()),
         refresh_token,
         access_token,
-        account: user.id,
+        account: user,
         id: id.into(),
     };
 
@@ -223,6 +225,121 @@ pub async fn refresh_session(env: &Env, session: Session, user: User) -> Result<
     Ok(model.into())
 }
 
+/// Lists all of the sessions that `user` has, with the most recently created first.
+pub async fn list(env: &Env, user: Ulid) -> Result<Vec<Session>, api::Response> {
+    SessionEntity::find()
+        .filter(session::Column::Account.eq(user))
+        .order_by_desc(session::Column::CreatedAt)
+        .all(&env.db)
+        .await
+        .map(|sessions| {
+            sessions
+                .into_iter()
+                .map(|model| Session::from(model).sanitize())
+                .collect()
+        })
+        .into_system_failure()
+}
+
+/// Revokes a session that `user` owns, returning it.
+#[instrument(name = "charted.server.ops.revokeSession", skip_all, fields(user.id = %user, session.id = %id))]
+pub async fn revoke(env: &Env, user: Ulid, id: Ulid) -> Result<Session, api::Response> {
+    let Some(model) = SessionEntity::find_by_id(id)
+        .filter(session::Column::Account.eq(user))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "session with id was not found for user",
+                json!({"session":id, "user":user}),
+            ),
+        ));
+    };
+
+    SessionEntity::delete_by_id(id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    Ok(Session::from(model).sanitize())
+}
+
+/// Revokes every session that `user` has except `current`, returning how many
+/// sessions were revoked.
+#[instrument(name = "charted.server.ops.revokeOtherSessions", skip_all, fields(user.id = %user))]
+pub async fn revoke_others(env: &Env, user: Ulid, current: Option<Ulid>) -> Result<u64, api::Response> {
+    let mut query = SessionEntity::delete_many().filter(session::Column::Account.eq(user));
+    if let Some(current) = current {
+        query = query.filter(session::Column::Id.ne(current));
+    }
+
+    query
+        .exec(&env.db)
+        .await
+        .map(|res| res.rows_affected)
+        .inspect_err(|e| {
+            error!(error = %e, user.id = %user, "failed to revoke sessions");
+            sentry::capture_error(e);
+        })
+        .into_system_failure()
+}
+
+/// Records that `session` was used to authenticate a request. This is only written
+/// once a minute per session since every authenticated request would do a write
+/// otherwise.
+pub async fn touch(env: &Env, session: &session::Model) {
+    let now = Utc::now();
+    if session
+        .last_used_at
+        .is_some_and(|last| now - last < TimeDelta::minutes(1))
+    {
+        return;
+    }
+
+    if let Err(e) = SessionEntity::update_many()
+        .col_expr(session::Column::LastUsedAt, Expr::value(now))
+        .filter(session::Column::Id.eq(session.id))
+        .exec(&env.db)
+        .await
+    {
+        warn!(error = %e, session.id = %session.id, "failed to update when session was last used");
+    }
+}
+
+/// Client that a session is created for, which is recorded so that users can tell
+/// their sessions apart.
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    pub user_agent: Option<String>,
+    pub ip: Option<IpAddr>,
+}
+
+/// Longest `User-Agent` header that is kept.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+impl<S: Send + Sync> FromRequestParts<S> for Client {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(Client {
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect()),
+
+            ip: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+        })
+    }
+}
+
 fn to_seconds<Tz: TimeZone>(dt: DateTime<Tz>) -> i64 {
     dt.timestamp_millis()
         .checked_div(1000)
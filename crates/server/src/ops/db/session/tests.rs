@@ -0,0 +1,168 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::Client;
+use crate::{
+    Env,
+    extract::Json,
+    middleware::authn::Session,
+    ops::auditlog,
+    routing::v1::user::patch,
+    testutil::{self, create_user},
+};
+use axum::{Extension, extract::State, http::StatusCode};
+use charted_database::entities::{SessionEntity, session};
+use charted_types::{Ulid, User};
+use chrono::{TimeDelta, Utc};
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
+use serde_json::json;
+use std::sync::Arc;
+
+async fn create(env: &Env, user: &User) -> Ulid {
+    super::create(env, user.id, Client::default()).await.unwrap().id
+}
+
+async fn find(env: &Env, id: Ulid) -> Option<session::Model> {
+    SessionEntity::find_by_id(id).one(&env.db).await.unwrap()
+}
+
+#[tokio::test]
+async fn list() {
+    let env = testutil::create_environment(|_| {}).await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+
+    let first = create(&env, &noel).await;
+    let second = create(&env, &noel).await;
+    create(&env, &ice).await;
+
+    let sessions = super::list(&env, noel.id).await.unwrap();
+    assert_eq!(sessions.iter().map(|session| session.id).collect::<Vec<_>>(), [
+        second, first
+    ]);
+
+    // tokens are only handed out when a session is created
+    assert!(
+        sessions
+            .iter()
+            .all(|session| session.access_token.is_none() && session.refresh_token.is_none())
+    );
+}
+
+#[tokio::test]
+async fn revoke() {
+    let env = testutil::create_environment(|_| {}).await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+
+    let id = create(&env, &noel).await;
+
+    // sessions of other users can't be revoked
+    let Err(res) = super::revoke(&env, ice.id, id).await else {
+        panic!("expected revoking another user's session to fail");
+    };
+
+    assert_eq!(res.response.status(), StatusCode::NOT_FOUND);
+    assert!(find(&env, id).await.is_some());
+
+    let revoked = super::revoke(&env, noel.id, id).await.unwrap();
+    assert_eq!(revoked.id, id);
+    assert!(revoked.access_token.is_none());
+    assert!(find(&env, id).await.is_none());
+
+    let Err(res) = super::revoke(&env, noel.id, id).await else {
+        panic!("expected revoking a session twice to fail");
+    };
+
+    assert_eq!(res.response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn revoke_others() {
+    let env = testutil::create_environment(|_| {}).await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+
+    let current = create(&env, &noel).await;
+    let other = create(&env, &noel).await;
+    let unrelated = create(&env, &ice).await;
+
+    assert_eq!(super::revoke_others(&env, noel.id, Some(current)).await.unwrap(), 1);
+    assert!(find(&env, current).await.is_some());
+    assert!(find(&env, other).await.is_none());
+    assert!(find(&env, unrelated).await.is_some());
+
+    // without a current session, like with API keys, every session is revoked
+    assert_eq!(super::revoke_others(&env, noel.id, None).await.unwrap(), 1);
+    assert!(find(&env, current).await.is_none());
+    assert!(find(&env, unrelated).await.is_some());
+}
+
+#[tokio::test]
+async fn revoke_others_after_password_change() {
+    let env =
+        testutil::create_environment(|env| env.authz = Arc::new(charted_authz_local::Backend::default())).await;
+    let noel = create_user(&env, "noel").await;
+
+    let current = super::create(&env, noel.id, Client::default()).await.unwrap();
+    let other = create(&env, &noel).await;
+
+    let change = |payload: serde_json::Value| {
+        patch(
+            State(env.clone()),
+            Extension(Session {
+                session: Some(current.clone()),
+                user: noel.clone(),
+            }),
+            auditlog::Context::default(),
+            Json(serde_json::from_value(payload).unwrap()),
+        )
+    };
+
+    // other changes don't revoke any sessions
+    change(json!({"description": "cute polar bear"})).await.unwrap();
+    assert!(find(&env, other).await.is_some());
+
+    change(json!({"password": "noeliscutieuwu2"})).await.unwrap();
+    assert!(find(&env, current.id).await.is_some());
+    assert!(find(&env, other).await.is_none());
+}
+
+#[tokio::test]
+async fn touch() {
+    let env = testutil::create_environment(|_| {}).await;
+    let noel = create_user(&env, "noel").await;
+    let id = create(&env, &noel).await;
+
+    let model = find(&env, id).await.unwrap();
+    assert!(model.last_used_at.is_none());
+
+    super::touch(&env, &model).await;
+    let used = find(&env, id).await.unwrap().last_used_at.unwrap();
+
+    // used again within a minute, so it isn't written again
+    let mut model = find(&env, id).await.unwrap();
+    model.last_used_at = Some(Utc::now() - TimeDelta::seconds(30));
+    super::touch(&env, &model).await;
+    assert_eq!(find(&env, id).await.unwrap().last_used_at, Some(used));
+
+    let last = Utc::now() - TimeDelta::minutes(2);
+    let mut active = find(&env, id).await.unwrap().into_active_model();
+    active.last_used_at = ActiveValue::set(Some(last));
+    let model = active.update(&env.db).await.unwrap();
+
+    super::touch(&env, &model).await;
+    assert!(find(&env, id).await.unwrap().last_used_at.unwrap() > last);
+}
@@ -0,0 +1,59 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scheduled garbage collection of orphaned objects in the datastore and stale
+//! metadata in the database, which is only ran if `features.gc.enable` is set.
+
+use crate::Env;
+use charted_feature_gc::{Collector, Feature, Report};
+use chrono::Utc;
+
+/// Runs the garbage collector on its configured schedule forever.
+pub async fn job(env: Env) {
+    let Some(collector) = env.features.get::<Feature>().map(Feature::collector) else {
+        return;
+    };
+
+    loop {
+        let now = Utc::now();
+        let Some(next) = collector.next_run(now) else {
+            warn!("garbage collection schedule has no upcoming runs, stopping");
+            return;
+        };
+
+        info!(%next, "next garbage collection run is scheduled");
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+        if let Err(e) = run(&env, collector, collector.config().dry_run).await {
+            error!(error = %e, "failed to run garbage collection");
+            sentry::capture_error(&*e);
+        }
+    }
+}
+
+/// Runs the garbage collector once and regenerates the chart indexes and latest
+/// versions of every owner and repository that had a release collected.
+pub async fn run(env: &Env, collector: &Collector, dry_run: bool) -> eyre::Result<Report> {
+    let report = collector.run(dry_run).await?;
+    for repository in report.affected_repositories() {
+        crate::ops::latest::refresh(env, repository).await;
+    }
+
+    for owner in report.affected_owners() {
+        crate::ops::indexes::regenerate(env, owner).await;
+    }
+
+    Ok(report)
+}
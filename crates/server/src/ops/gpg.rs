@@ -0,0 +1,45 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Keyrings of OpenPGP public keys that users and organizations verify the provenance
+//! files of their repositories' releases with.
+
+use crate::Env;
+use charted_database::entities::{GpgKeyEntity, gpg_key};
+use charted_helm_charts::provenance::{self, SignedPublicKey};
+use charted_types::Ulid;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+/// Returns every public key in the keyring of `owner`.
+///
+/// Keys that can no longer be read are skipped, since they were validated when they
+/// were added to the keyring.
+#[instrument(name = "charted.server.ops.gpg.keyring", skip_all, fields(owner.id = %owner))]
+pub async fn keyring(env: &Env, owner: Ulid) -> eyre::Result<Vec<SignedPublicKey>> {
+    let keys = GpgKeyEntity::find()
+        .filter(gpg_key::Column::Owner.eq(owner))
+        .all(&env.db)
+        .await?;
+
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            provenance::read_public_key(&key.public_key)
+                .inspect_err(|e| {
+                    warn!(error = %e, gpg_key.id = %key.id, "failed to read public key from keyring; skipping");
+                })
+                .ok()
+        })
+        .collect())
+}
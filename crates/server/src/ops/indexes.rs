@@ -0,0 +1,265 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Regeneration of the `index.yaml` files that Helm consumes.
+//!
+//! An owner's `index.yaml` is rebuilt from scratch from the database and the chart
+//! tarballs in the datastore whenever something that is visible in it changes, which
+//! is a release being created, yanked, or deleted, or a repository being deleted or
+//! having its visibility changed.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{Env, ops};
+use charted_database::entities::{RepositoryEntity, RepositoryReleaseEntity, repository, repository::release};
+use charted_helm_charts::DataStoreExt;
+use charted_helm_types::{Chart, ChartIndex, ChartIndexSpec};
+use charted_types::{DateTime, Owner, Ulid, User};
+use chrono::Utc;
+use eyre::{Context, OptionExt};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use url::Url;
+
+/// Regenerates the `index.yaml` of the specified owner.
+///
+/// Failures are logged and reported to Sentry but never surfaced to the caller, since
+/// the mutation that triggered it has already happened and the next one will try again.
+pub async fn regenerate(env: &Env, owner: Ulid) {
+    if let Err(e) = build(env, owner).await {
+        error!(error = %e, owner.id = %owner, "failed to regenerate chart index");
+        sentry::capture_error(&*e);
+    }
+}
+
+/// Builds the [`ChartIndex`] of the specified owner and persists it in the datastore.
+#[instrument(name = "charted.server.ops.buildChartIndex", skip_all, fields(owner.id = %owner))]
+pub async fn build(env: &Env, owner: Ulid) -> eyre::Result<ChartIndex> {
+    let metadata = env.ds.metadata();
+    let generated = match metadata.get_chart_index(owner).await? {
+        Some(index) => index.generated_at(),
+        None => Utc::now().into(),
+    };
+
+    let repositories = RepositoryEntity::find()
+        .filter(repository::Column::Owner.eq(owner))
+        .filter(repository::Column::Private.eq(false))
+        .all(&env.db)
+        .await?;
+
+    let mut entries = HashMap::<String, Vec<ChartIndexSpec>>::new();
+    collect(env, owner, repositories, &mut entries).await?;
+    sort(&mut entries);
+
+    let index = ChartIndex::V1 { generated, entries };
+    metadata.put_chart_index(owner, &index).await?;
+
+    Ok(index)
+}
+
+/// Returns the `index.yaml` of `owner` as `user` sees it, which also has the entries
+/// of the private repositories that `user` can see. Returns [`None`] if `user` can't
+/// see any private repositories, in which case the persisted index is what they see.
+///
+/// The persisted index only ever contains public repositories, so the private entries
+/// are built on every request.
+pub async fn visible(env: &Env, owner: &Owner, user: &User) -> eyre::Result<Option<ChartIndex>> {
+    let mut repositories = Vec::new();
+    for model in RepositoryEntity::find()
+        .filter(repository::Column::Owner.eq(owner.id()))
+        .filter(repository::Column::Private.eq(true))
+        .all(&env.db)
+        .await?
+    {
+        if ops::members::can_view(env, Some(user), owner, &model.clone().into()).await? {
+            repositories.push(model);
+        }
+    }
+
+    if repositories.is_empty() {
+        return Ok(None);
+    }
+
+    let index = env.ds.metadata().get_chart_index(owner.id()).await?.unwrap_or_default();
+    let generated = index.generated_at();
+    let mut entries = index.entries().clone();
+
+    collect(env, owner.id(), repositories, &mut entries).await?;
+    sort(&mut entries);
+
+    Ok(Some(ChartIndex::V1 { generated, entries }))
+}
+
+/// Returns the `index.yaml` of a single repository, which is built from `releases`.
+///
+/// The index is generated at when the newest of `releases` was last updated, so that
+/// the same releases always build the same index that [`etag`] was computed for.
+pub async fn repository(env: &Env, owner: Ulid, releases: Vec<release::Model>) -> eyre::Result<ChartIndex> {
+    let generated = releases
+        .iter()
+        .map(|release| release.updated_at)
+        .max()
+        .map(DateTime::from)
+        .unwrap_or_default();
+
+    let mut entries = HashMap::new();
+    extend(env, owner, releases, &mut entries).await?;
+    sort(&mut entries);
+
+    Ok(ChartIndex::V1 { generated, entries })
+}
+
+/// Returns the releases of `repository` that are shown in its index, newest first.
+/// Pre-releases are only included if `prereleases` is true.
+pub async fn releases(env: &Env, repository: Ulid, prereleases: bool) -> eyre::Result<Vec<release::Model>> {
+    let releases = RepositoryReleaseEntity::find()
+        .filter(release::Column::Repository.eq(repository))
+        .filter(release::Column::Yanked.eq(false))
+        .order_by_desc(release::Column::CreatedAt)
+        .all(&env.db)
+        .await?;
+
+    Ok(releases
+        .into_iter()
+        .filter(|release| prereleases || release.tag.pre.is_empty())
+        .collect())
+}
+
+/// Computes the `ETag` of an index that is built from `releases` without building
+/// it, so that conditional requests don't have to touch the datastore.
+pub fn etag(env: &Env, releases: &[release::Model]) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(base_url) = env.config.base_url.as_ref() {
+        hasher.update(base_url.as_str());
+    }
+
+    for release in releases {
+        hasher.update(release.id.to_string());
+        hasher.update(release.tag.to_string());
+        hasher.update(release.digest.as_deref().unwrap_or_default());
+        hasher.update(release.updated_at.timestamp_micros().to_be_bytes());
+    }
+
+    format!("\"{}\"", hex::encode(hasher.finalize()))
+}
+
+/// Adds the index entries of every release in `repositories` to `entries`.
+async fn collect(
+    env: &Env,
+    owner: Ulid,
+    repositories: Vec<repository::Model>,
+    entries: &mut HashMap<String, Vec<ChartIndexSpec>>,
+) -> eyre::Result<()> {
+    for repo in repositories {
+        let releases = releases(env, repo.id, true).await?;
+        extend(env, owner, releases, entries).await?;
+    }
+
+    Ok(())
+}
+
+/// Adds the index entries of `releases` to `entries` from the chart metadata that
+/// was persisted when they were published.
+async fn extend(
+    env: &Env,
+    owner: Ulid,
+    releases: Vec<release::Model>,
+    entries: &mut HashMap<String, Vec<ChartIndexSpec>>,
+) -> eyre::Result<()> {
+    let base_url = env
+        .config
+        .base_url
+        .as_ref()
+        .ok_or_eyre("`base_url` was not configured")?;
+
+    for release in releases {
+        let (chart, digest) = match (release.chart, release.digest) {
+            (Some(chart), Some(digest)) => match serde_json::from_value::<Chart>(chart) {
+                Ok(chart) => (chart, digest),
+                Err(e) => {
+                    warn!(repository.id = %release.repository, version = %release.tag, error = %e, "persisted chart metadata is invalid; skipping");
+                    continue;
+                }
+            },
+
+            // releases that were published before the chart metadata was persisted
+            // have to be read from the tarball itself.
+            _ => {
+                let ns = env.ds.owner_repo(owner, release.repository);
+                let Some(file) = ns.get_chart(&release.tag, true).await? else {
+                    warn!(repository.id = %release.repository, version = %release.tag, "release has no chart tarball; skipping");
+                    continue;
+                };
+
+                match charted_helm_charts::read_chart_metadata(&file.data) {
+                    Ok(Some(chart)) => (chart, charted_helm_charts::digest(&file.data)),
+                    Ok(None) => {
+                        warn!(repository.id = %release.repository, version = %release.tag, "chart tarball has no `Chart.yaml`; skipping");
+                        continue;
+                    }
+
+                    Err(e) => {
+                        warn!(repository.id = %release.repository, version = %release.tag, error = %e, "failed to read `Chart.yaml` from chart tarball; skipping");
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let url = tarball_url(base_url, owner, &release)?;
+        entries.entry(chart.name.clone()).or_default().push(ChartIndexSpec {
+            digest: Some(digest),
+            created: Some(release.created_at.into()),
+            removed: false,
+            urls: vec![url.to_string()],
+            spec: chart,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the URL that the chart tarball of `release` is downloaded from.
+///
+/// The URL is relative to `base_url`, so that charted-server can be served from a
+/// sub-path like `https://example.com/charted`.
+pub fn tarball_url(base_url: &Url, owner: Ulid, release: &release::Model) -> eyre::Result<Url> {
+    // `Url::join` replaces the last segment of a path that doesn't end in a slash
+    let mut base_url = base_url.clone();
+    if !base_url.path().ends_with('/') {
+        base_url.set_path(&format!("{}/", base_url.path()));
+    }
+
+    let mut url = base_url
+        .join(&format!(
+            "v1/repositories/{owner}/{}/releases/{}/{}/tarball",
+            release.repository, release.id, release.tag
+        ))
+        .context("failed to build download url")?;
+
+    if !release.tag.pre.is_empty() {
+        url.set_query(Some("prereleases=true"));
+    }
+
+    Ok(url)
+}
+
+fn sort(entries: &mut HashMap<String, Vec<ChartIndexSpec>>) {
+    for specs in entries.values_mut() {
+        specs.sort_by(|a, b| b.spec.version.cmp(&a.spec.version));
+    }
+}
@@ -0,0 +1,136 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Env, testutil};
+use charted_database::entities::{RepositoryReleaseEntity, repository::release};
+use charted_types::Ulid;
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, EntityTrait, IntoActiveModel};
+use serde_json::json;
+
+async fn publish(env: &Env, repository: Ulid, version: &str) -> release::Model {
+    let now = Utc::now();
+    let model = release::Model {
+        update_text: None,
+        repository,
+        created_at: now,
+        updated_at: now,
+        yanked: false,
+        title: None,
+        chart: Some(json!({
+            "apiVersion": "v2",
+            "name": "hello-world",
+            "version": version,
+        })),
+        digest: Some(format!("digest-of-{version}")),
+        tag: version.parse().unwrap(),
+        id: env.ulid.generate().unwrap().into(),
+    };
+
+    RepositoryReleaseEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .unwrap();
+
+    model
+}
+
+#[tokio::test]
+async fn repository_index() {
+    let env = testutil::create_environment(|_| {}).await;
+    let owner = testutil::create_user(&env, "noel").await;
+    let repo = testutil::create_repository(&env, owner.id, "hello-world").await;
+
+    publish(&env, repo.id, "0.1.0").await;
+    publish(&env, repo.id, "0.2.0").await;
+    publish(&env, repo.id, "1.0.0-beta.1").await;
+
+    // the persisted chart metadata is used as-is, so nothing is read from the
+    // datastore since no tarballs were ever uploaded.
+    let releases = super::releases(&env, repo.id, false).await.unwrap();
+    let index = super::repository(&env, owner.id, releases).await.unwrap();
+    let specs = &index.entries()["hello-world"];
+    assert_eq!(
+        specs
+            .iter()
+            .map(|spec| spec.spec.version.to_string())
+            .collect::<Vec<_>>(),
+        &["0.2.0", "0.1.0"]
+    );
+
+    assert_eq!(specs[0].digest.as_deref(), Some("digest-of-0.2.0"));
+
+    let releases = super::releases(&env, repo.id, true).await.unwrap();
+    let index = super::repository(&env, owner.id, releases.clone()).await.unwrap();
+    assert_eq!(index.entries()["hello-world"].len(), 3);
+
+    // the same releases always build the same index, since its etag is strong
+    let again = super::repository(&env, owner.id, releases).await.unwrap();
+    assert_eq!(index.generated_at(), again.generated_at());
+}
+
+#[tokio::test]
+async fn etag() {
+    let env = testutil::create_environment(|_| {}).await;
+    let owner = testutil::create_user(&env, "noel").await;
+    let repo = testutil::create_repository(&env, owner.id, "hello-world").await;
+
+    let release = publish(&env, repo.id, "0.1.0").await;
+    let first = super::etag(&env, &super::releases(&env, repo.id, false).await.unwrap());
+    assert_eq!(
+        first,
+        super::etag(&env, &super::releases(&env, repo.id, false).await.unwrap())
+    );
+
+    // updating a release changes the etag
+    let mut active = release.into_active_model();
+    active.updated_at = Set(Utc::now() + Duration::seconds(1));
+    active.update(&env.db).await.unwrap();
+
+    let second = super::etag(&env, &super::releases(&env, repo.id, false).await.unwrap());
+    assert_ne!(first, second);
+
+    // so does publishing a new one
+    publish(&env, repo.id, "0.2.0").await;
+    assert_ne!(
+        second,
+        super::etag(&env, &super::releases(&env, repo.id, false).await.unwrap())
+    );
+
+    // pre-releases are only part of the etag when they're in the index
+    publish(&env, repo.id, "1.0.0-beta.1").await;
+    assert_ne!(
+        super::etag(&env, &super::releases(&env, repo.id, false).await.unwrap()),
+        super::etag(&env, &super::releases(&env, repo.id, true).await.unwrap())
+    );
+}
+
+#[tokio::test]
+async fn tarball_urls_keep_the_base_url_path() {
+    let env = testutil::create_environment(|_| {}).await;
+    let owner = testutil::create_user(&env, "noel").await;
+    let repo = testutil::create_repository(&env, owner.id, "hello-world").await;
+    let release = publish(&env, repo.id, "0.1.0").await;
+
+    let expected = format!(
+        "https://example.com/charted/v1/repositories/{}/{}/releases/{}/0.1.0/tarball",
+        owner.id, repo.id, release.id
+    );
+
+    for base_url in ["https://example.com/charted", "https://example.com/charted/"] {
+        let url = super::tarball_url(&base_url.parse().unwrap(), owner.id, &release).unwrap();
+        assert_eq!(url.as_str(), expected);
+    }
+}
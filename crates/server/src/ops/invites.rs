@@ -0,0 +1,401 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Invites for users to become repository or organization members.
+//!
+//! Invites are created with the permissions that the member will have, and are turned
+//! into a member once the invited user accepts them. The invited user is told about the
+//! invite through a [`Notifier`], which only logs the invite by default so that invites
+//! work without an email service.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    ops::{self, auditlog},
+};
+use axum::http::StatusCode;
+use charted_core::{BoxedFuture, api};
+use charted_database::entities::{
+    MemberInviteEntity, OrganizationMemberEntity, RepositoryMemberEntity, member_invite, organization, repository,
+};
+use charted_feature_audit_logs::Action;
+use charted_feature_webhooks::{Target, event::EventKind};
+use charted_types::{MemberInvite, OrganizationMember, RepositoryMember, Ulid, User};
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, Condition, DbErr, EntityTrait, IntoActiveModel, QueryFilter, SqlErr, TransactionTrait,
+};
+use serde_json::json;
+
+/// What a user was invited to.
+#[derive(Debug, Clone, Copy)]
+pub enum Invitation {
+    Repository(Ulid),
+    Organization(Ulid),
+}
+
+/// A invite that is sent to the invited user through a [`Notifier`].
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub invite: MemberInvite,
+
+    /// The user that created the invite.
+    pub inviter: User,
+
+    /// The user that was invited.
+    pub account: User,
+
+    /// Human-readable name of what `account` was invited to, like `noel/charted` for
+    /// repositories or `noelware` for organizations.
+    pub target: String,
+}
+
+/// Delivers invites to the users that were invited.
+pub trait Notifier: Send + Sync {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxedFuture<'a, eyre::Result<()>>;
+}
+
+/// [`Notifier`] that only logs invites. Invited users will still see their invites
+/// from the `GET /v1/users/@me/invites` endpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxedFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            info!(
+                invite.id = %notification.invite.id,
+                invite.target = %notification.target,
+                invite.expires_at = %notification.invite.expires_at,
+                inviter = %notification.inviter.username,
+                account = %notification.account.username,
+                "@{} was invited to {} by @{}",
+                notification.account.username,
+                notification.target,
+                notification.inviter.username
+            );
+
+            Ok(())
+        })
+    }
+}
+
+/// Creates an invite for `account` and notifies them about it. Fails with a `409 Conflict`
+/// response if `account` is already a member or has a pending invite.
+pub async fn create(
+    env: &Env,
+    cx: &auditlog::Context,
+    inviter: &User,
+    account: User,
+    invitation: Invitation,
+    target: String,
+    permissions: u64,
+) -> Result<MemberInvite, api::Response> {
+    let now = Utc::now();
+    let (repository, organization) = match invitation {
+        Invitation::Repository(id) => (Some(id), None),
+        Invitation::Organization(id) => (None, Some(id)),
+    };
+
+    let is_member = match invitation {
+        Invitation::Repository(id) => RepositoryMemberEntity::find()
+            .filter(repository::member::Column::Repository.eq(id))
+            .filter(repository::member::Column::Account.eq(account.id))
+            .one(&env.db)
+            .await
+            .into_system_failure()?
+            .is_some(),
+
+        Invitation::Organization(id) => OrganizationMemberEntity::find()
+            .filter(organization::member::Column::Organization.eq(id))
+            .filter(organization::member::Column::Account.eq(account.id))
+            .one(&env.db)
+            .await
+            .into_system_failure()?
+            .is_some(),
+    };
+
+    if is_member {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "user is already a member",
+                json!({"account":account.id}),
+            ),
+        ));
+    }
+
+    let scope = match invitation {
+        Invitation::Repository(id) => Condition::all()
+            .add(member_invite::Column::Repository.eq(id))
+            .add(member_invite::Column::Organization.is_null()),
+
+        Invitation::Organization(id) => Condition::all()
+            .add(member_invite::Column::Organization.eq(id))
+            .add(member_invite::Column::Repository.is_null()),
+    };
+
+    let existing = MemberInviteEntity::find()
+        .filter(scope)
+        .filter(member_invite::Column::Account.eq(account.id))
+        .all(&env.db)
+        .await
+        .into_system_failure()?;
+
+    for invite in existing {
+        if invite.expires_at > now {
+            return Err(api::err(
+                StatusCode::CONFLICT,
+                (
+                    api::ErrorCode::EntityAlreadyExists,
+                    "user already has a pending invite",
+                    json!({"account":account.id,"invite":invite.id}),
+                ),
+            ));
+        }
+
+        // expired invites can't be accepted anymore, so they're replaced
+        MemberInviteEntity::delete_by_id(invite.id)
+            .exec(&env.db)
+            .await
+            .into_system_failure()?;
+    }
+
+    let id = env.ulid.generate().into_system_failure()?;
+    let model = member_invite::Model {
+        permissions: permissions as i64,
+        created_at: now,
+        updated_at: now,
+        expires_at: now + chrono::Duration::from_std(*env.config.invites.expires_in).into_system_failure()?,
+        repository,
+        organization,
+        inviter: inviter.id,
+        account: account.id,
+        id: id.into(),
+    };
+
+    // a concurrent request could've invited the same user since we last checked.
+    if let Err(e) = MemberInviteEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+    {
+        if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+            return Err(api::err(
+                StatusCode::CONFLICT,
+                (
+                    api::ErrorCode::EntityAlreadyExists,
+                    "user already has a pending invite",
+                    json!({"account":account.id}),
+                ),
+            ));
+        }
+
+        return Err(api::system_failure(e));
+    }
+
+    record(env, cx, Action::InviteCreated, &model).await;
+
+    let notification = Notification {
+        invite: MemberInvite::from(model),
+        inviter: inviter.clone(),
+        account,
+        target,
+    };
+
+    if let Err(e) = env.notifier.notify(&notification).await {
+        error!(error = %e, invite.id = %notification.invite.id, "failed to notify user about their invite");
+        sentry::capture_error(&*e);
+    }
+
+    Ok(notification.invite)
+}
+
+/// Returns the invite of `account` by its ID, if it hasn't expired.
+pub async fn pending(env: &Env, account: Ulid, id: Ulid) -> Result<member_invite::Model, api::Response> {
+    MemberInviteEntity::find_by_id(id)
+        .filter(member_invite::Column::Account.eq(account))
+        .filter(member_invite::Column::ExpiresAt.gt(Utc::now()))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .ok_or_else(|| {
+            api::err(
+                StatusCode::NOT_FOUND,
+                (
+                    api::ErrorCode::EntityNotFound,
+                    "invite with id was not found",
+                    json!({"id":id}),
+                ),
+            )
+        })
+}
+
+/// Accepts `invite` by turning it into a member, and emits the `member.added` event.
+pub async fn accept(env: &Env, cx: &auditlog::Context, invite: member_invite::Model) -> Result<(), api::Response> {
+    let id = env.ulid.generate().into_system_failure()?;
+    let now = Utc::now();
+    let txn = env.db.begin().await.into_system_failure()?;
+
+    match (invite.repository, invite.organization) {
+        (Some(repository), _) => {
+            let model = repository::member::Model {
+                display_name: None,
+                permissions: invite.permissions,
+                created_at: now,
+                updated_at: now,
+                repository,
+                account: invite.account,
+                id: id.into(),
+            };
+
+            if let Err(e) = RepositoryMemberEntity::insert(model.clone().into_active_model())
+                .exec(&txn)
+                .await
+            {
+                txn.rollback().await.into_system_failure()?;
+                return Err(already_member(env, &invite, e).await);
+            }
+
+            MemberInviteEntity::delete_by_id(invite.id)
+                .exec(&txn)
+                .await
+                .into_system_failure()?;
+
+            txn.commit().await.into_system_failure()?;
+            record(env, cx, Action::InviteAccepted, &invite).await;
+
+            let Some(repository) = ops::db::repository::get(&env.db, repository.into()).await? else {
+                return Ok(());
+            };
+
+            let member = RepositoryMember::from(model);
+            ops::webhooks::emit(
+                env,
+                ops::webhooks::repository(&repository),
+                EventKind::MemberAdded,
+                &member,
+            )
+            .await;
+
+            auditlog::record(
+                env,
+                cx,
+                Action::MemberAdded,
+                auditlog::repository(&repository),
+                (),
+                &member,
+            )
+            .await;
+        }
+
+        (None, Some(organization)) => {
+            let model = organization::member::Model {
+                display_name: None,
+                permissions: invite.permissions,
+                created_at: now,
+                updated_at: now,
+                organization,
+                account: invite.account,
+                id: id.into(),
+            };
+
+            if let Err(e) = OrganizationMemberEntity::insert(model.clone().into_active_model())
+                .exec(&txn)
+                .await
+            {
+                txn.rollback().await.into_system_failure()?;
+                return Err(already_member(env, &invite, e).await);
+            }
+
+            MemberInviteEntity::delete_by_id(invite.id)
+                .exec(&txn)
+                .await
+                .into_system_failure()?;
+
+            txn.commit().await.into_system_failure()?;
+            record(env, cx, Action::InviteAccepted, &invite).await;
+
+            let member = OrganizationMember::from(model);
+            ops::webhooks::emit(
+                env,
+                Target {
+                    owner: organization,
+                    repository: None,
+                },
+                EventKind::MemberAdded,
+                &member,
+            )
+            .await;
+
+            auditlog::record(env, cx, Action::MemberAdded, auditlog::owner(organization), (), &member).await;
+        }
+
+        (None, None) => {
+            return Err(api::system_failure_from_report(eyre::eyre!(
+                "invite {} has neither a repository or organization",
+                invite.id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps a failed member insert of `invite` into a response. A concurrent request
+/// could've added the user as a member since the invite was sent, in which case the
+/// invite can never be accepted, so it's deleted.
+async fn already_member(env: &Env, invite: &member_invite::Model, e: DbErr) -> api::Response {
+    let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() else {
+        return api::system_failure(e);
+    };
+
+    if let Err(e) = MemberInviteEntity::delete_by_id(invite.id).exec(&env.db).await {
+        return api::system_failure(e);
+    }
+
+    api::err(
+        StatusCode::CONFLICT,
+        (
+            api::ErrorCode::EntityAlreadyExists,
+            "user is already a member",
+            json!({"account":invite.account}),
+        ),
+    )
+}
+
+/// Records `action` on `invite` into the audit logs of the repository or organization
+/// that the user was invited to. Only [`Action::InviteCreated`] records the invite as
+/// its new state, every other action records it as removed.
+pub async fn record(env: &Env, cx: &auditlog::Context, action: Action, invite: &member_invite::Model) {
+    let target = match (invite.repository, invite.organization) {
+        (Some(id), _) => match ops::db::repository::get(&env.db, id.into()).await {
+            Ok(Some(repository)) => auditlog::repository(&repository),
+            _ => return,
+        },
+
+        (None, Some(id)) => auditlog::owner(id),
+        (None, None) => return,
+    };
+
+    let invite = MemberInvite::from(invite.clone());
+    match action {
+        Action::InviteCreated => auditlog::record(env, cx, action, target, (), &invite).await,
+        _ => auditlog::record(env, cx, action, target, &invite, ()).await,
+    }
+}
@@ -0,0 +1,301 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{Invitation, Notification, Notifier};
+use crate::{
+    Env,
+    extract::Path,
+    middleware::authn::Session,
+    ops::auditlog,
+    routing::v1::user::invites::decline,
+    testutil::{self, create_organization, create_repository, create_user},
+};
+use axum::{Extension, extract::State, http::StatusCode};
+use charted_core::{BoxedFuture, api};
+use charted_database::entities::{MemberInviteEntity, RepositoryMemberEntity, member_invite, repository};
+use charted_types::{MemberInvite, User};
+use chrono::{TimeDelta, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+};
+use std::sync::{Arc, Mutex};
+
+/// [`Notifier`] that keeps every notification that it was given.
+#[derive(Default)]
+struct Recorder(Mutex<Vec<Notification>>);
+
+impl Notifier for Recorder {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxedFuture<'a, eyre::Result<()>> {
+        self.0.lock().unwrap().push(notification.clone());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+async fn create_env() -> (Env, Arc<Recorder>) {
+    let recorder = Arc::new(Recorder::default());
+    let notifier = recorder.clone();
+    let env = testutil::create_environment(move |env| env.notifier = notifier).await;
+
+    (env, recorder)
+}
+
+async fn invite(
+    env: &Env,
+    inviter: &User,
+    account: &User,
+    invitation: Invitation,
+) -> Result<MemberInvite, api::Response> {
+    super::create(
+        env,
+        &auditlog::Context::default(),
+        inviter,
+        account.clone(),
+        invitation,
+        String::from("noel/charted"),
+        0,
+    )
+    .await
+}
+
+fn status<T>(result: Result<T, api::Response>) -> StatusCode {
+    match result {
+        Ok(_) => panic!("expected request to fail"),
+        Err(response) => response.response.status(),
+    }
+}
+
+#[tokio::test]
+async fn create() {
+    let (env, recorder) = create_env().await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+    let repository = create_repository(&env, noel.id, "charted").await;
+    let org = create_organization(&env, &noel, "noelware").await;
+
+    let created = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    assert_eq!(created.repository, Some(repository.id));
+    assert_eq!(created.organization, None);
+    assert_eq!(created.account, ice.id);
+
+    {
+        let notifications = recorder.0.lock().unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].invite.id, created.id);
+        assert_eq!(notifications[0].account.id, ice.id);
+    }
+
+    // the same user can't be invited twice to the same repository...
+    assert_eq!(
+        status(invite(&env, &noel, &ice, Invitation::Repository(repository.id)).await),
+        StatusCode::CONFLICT
+    );
+
+    // ...but can be invited to an organization at the same time
+    invite(&env, &noel, &ice, Invitation::Organization(org.id))
+        .await
+        .unwrap();
+    assert_eq!(
+        status(invite(&env, &noel, &ice, Invitation::Organization(org.id)).await),
+        StatusCode::CONFLICT
+    );
+
+    assert_eq!(MemberInviteEntity::find().count(&env.db).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn accept() {
+    let (env, _) = create_env().await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+    let repository = create_repository(&env, noel.id, "charted").await;
+
+    let created = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    let pending = super::pending(&env, ice.id, created.id).await.unwrap();
+    super::accept(&env, &auditlog::Context::default(), pending)
+        .await
+        .unwrap();
+
+    let members = RepositoryMemberEntity::find()
+        .filter(repository::member::Column::Repository.eq(repository.id))
+        .all(&env.db)
+        .await
+        .unwrap();
+
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].account, ice.id);
+    assert!(
+        MemberInviteEntity::find_by_id(created.id)
+            .one(&env.db)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // members can't be invited again
+    assert_eq!(
+        status(invite(&env, &noel, &ice, Invitation::Repository(repository.id)).await),
+        StatusCode::CONFLICT
+    );
+}
+
+#[tokio::test]
+async fn accept_after_becoming_a_member() {
+    let (env, _) = create_env().await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+    let repository = create_repository(&env, noel.id, "charted").await;
+
+    let created = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    let pending = super::pending(&env, ice.id, created.id).await.unwrap();
+
+    // a concurrent request added the user as a member after they were invited
+    let now = Utc::now();
+    RepositoryMemberEntity::insert(
+        repository::member::Model {
+            display_name: None,
+            permissions: 0,
+            created_at: now,
+            updated_at: now,
+            repository: repository.id,
+            account: ice.id,
+            id: env.ulid.generate().unwrap().into(),
+        }
+        .into_active_model(),
+    )
+    .exec(&env.db)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        status(super::accept(&env, &auditlog::Context::default(), pending).await),
+        StatusCode::CONFLICT
+    );
+
+    // the invite can't ever be accepted, so it's gone
+    assert!(
+        MemberInviteEntity::find_by_id(created.id)
+            .one(&env.db)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn decline() {
+    let (env, _) = create_env().await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+    let repository = create_repository(&env, noel.id, "charted").await;
+
+    let created = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    // only the invited user can decline their invite
+    let session = |user: &User| {
+        Extension(Session {
+            session: None,
+            user: user.clone(),
+        })
+    };
+
+    assert_eq!(
+        status(
+            decline(
+                State(env.clone()),
+                session(&noel),
+                auditlog::Context::default(),
+                Path(created.id)
+            )
+            .await
+        ),
+        StatusCode::NOT_FOUND
+    );
+
+    decline(
+        State(env.clone()),
+        session(&ice),
+        auditlog::Context::default(),
+        Path(created.id),
+    )
+    .await
+    .unwrap();
+
+    assert!(
+        MemberInviteEntity::find_by_id(created.id)
+            .one(&env.db)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(RepositoryMemberEntity::find().count(&env.db).await.unwrap(), 0);
+
+    // declined invites can't be accepted anymore
+    assert_eq!(
+        status(super::pending(&env, ice.id, created.id).await),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn expired_invites() {
+    let (env, _) = create_env().await;
+    let noel = create_user(&env, "noel").await;
+    let ice = create_user(&env, "ice").await;
+    let repository = create_repository(&env, noel.id, "charted").await;
+
+    let created = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    let mut model = MemberInviteEntity::find_by_id(created.id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_active_model();
+
+    model.expires_at = ActiveValue::set(Utc::now() - TimeDelta::minutes(1));
+    model.update(&env.db).await.unwrap();
+
+    assert_eq!(
+        status(super::pending(&env, ice.id, created.id).await),
+        StatusCode::NOT_FOUND
+    );
+
+    // a expired invite is replaced by a new one
+    let replaced = invite(&env, &noel, &ice, Invitation::Repository(repository.id))
+        .await
+        .unwrap();
+
+    let invites = MemberInviteEntity::find()
+        .filter(member_invite::Column::Account.eq(ice.id))
+        .all(&env.db)
+        .await
+        .unwrap();
+
+    assert_eq!(invites.len(), 1);
+    assert_eq!(invites[0].id, replaced.id);
+    assert!(invites[0].expires_at > Utc::now());
+}
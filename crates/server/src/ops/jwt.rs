@@ -13,10 +13,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Signs and verifies the JWTs that sessions hand out.
+//!
+//! Tokens are signed with the `[jwt]` table's signing key and carry its `kid` header,
+//! while every configured key can verify them. Tokens without a `kid` header were
+//! signed with `jwt_secret_key` using HS512, which is how they were signed before
+//! asymmetric keys could be configured, and are rejected once `jwt.accept_legacy_tokens`
+//! is disabled.
+
+#[cfg(test)]
+mod tests;
+
 use crate::Env;
+use charted_config::{Config, jwt};
 use charted_types::Ulid;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode};
+use eyre::Context;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode,
+    errors::ErrorKind,
+    jwk::{Jwk, JwkSet, PublicKeyUse},
+};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
 
 /// The `iss` field value.
 ///
@@ -28,8 +46,8 @@ pub const ISS_VALUE: &str = "Noelware";
 /// <https://www.rfc-editor.org/rfc/rfc7519#section-4.1.3>
 pub const AUD_VALUE: &str = "charted-server";
 
-/// JWT algorithm to use.
-pub const ALGORITHM: Algorithm = Algorithm::HS512;
+/// JWT algorithm that tokens without a `kid` header are signed with.
+pub const LEGACY_ALGORITHM: Algorithm = Algorithm::HS512;
 
 /// JWT claim for the expiration.
 ///
@@ -64,36 +82,134 @@ pub struct Claims {
     pub uid: Ulid,
 }
 
+/// A key from the `[jwt.keys]` table.
+#[derive(Clone)]
+struct Key {
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    jwk: Jwk,
+}
+
+impl Key {
+    fn from_pem(kid: &str, algorithm: jwt::Algorithm, pem: &[u8]) -> eyre::Result<Key> {
+        let (algorithm, encoding) = match algorithm {
+            jwt::Algorithm::RS256 => (Algorithm::RS256, EncodingKey::from_rsa_pem(pem)?),
+            jwt::Algorithm::EdDSA => (Algorithm::EdDSA, EncodingKey::from_ed_pem(pem)?),
+        };
+
+        let mut jwk = Jwk::from_encoding_key(&encoding, algorithm)?;
+        jwk.common.key_id = Some(kid.to_owned());
+        jwk.common.public_key_use = Some(PublicKeyUse::Signature);
+
+        Ok(Key {
+            algorithm,
+            decoding: DecodingKey::from_jwk(&jwk)?,
+            encoding,
+            jwk,
+        })
+    }
+}
+
+/// Keys that tokens are signed and verified with.
+#[derive(Clone)]
+pub struct Keys {
+    /// `kid` of the key that new tokens are signed with, or `None` if they are
+    /// signed with the legacy secret.
+    signing: Option<String>,
+    keys: BTreeMap<String, Key>,
+    secret: Vec<u8>,
+
+    /// Whether if tokens without a `kid` header are verified with the legacy secret.
+    accept_legacy: bool,
+}
+
+impl Keys {
+    /// Loads the keys that were configured in `config`.
+    pub fn new(config: &Config) -> eyre::Result<Keys> {
+        let mut keys = BTreeMap::new();
+        for (kid, key) in &config.jwt.keys {
+            let pem = fs::read(&key.private_key)
+                .with_context(|| format!("failed to read private key of JWT key `{kid}`"))?;
+
+            keys.insert(
+                kid.clone(),
+                Key::from_pem(kid, key.algorithm, &pem)
+                    .with_context(|| format!("failed to load JWT key `{kid}`"))?,
+            );
+        }
+
+        Ok(Keys {
+            signing: config.jwt.signing_key.clone(),
+            secret: config.jwt_secret_key.as_bytes().to_vec(),
+            accept_legacy: config.jwt.accept_legacy_tokens,
+            keys,
+        })
+    }
+
+    /// Returns the public keys that tokens can be verified with.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.values().map(|key| key.jwk.clone()).collect(),
+        }
+    }
+
+    /// Signs `claims` with the signing key.
+    pub fn encode(&self, claims: &Claims) -> jsonwebtoken::errors::Result<String> {
+        let Some(kid) = self.signing.as_deref() else {
+            return jsonwebtoken::encode(
+                &Header::new(LEGACY_ALGORITHM),
+                claims,
+                &EncodingKey::from_secret(&self.secret),
+            );
+        };
+
+        let key = self.keys.get(kid).ok_or(ErrorKind::InvalidKeyFormat)?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(kid.to_owned());
+
+        jsonwebtoken::encode(&header, claims, &key.encoding)
+    }
+
+    /// Verifies `token` with the key that its `kid` header refers to.
+    pub fn decode(&self, token: &str) -> jsonwebtoken::errors::Result<TokenData<Claims>> {
+        let header = jsonwebtoken::decode_header(token)?;
+        match header.kid.as_deref() {
+            Some(kid) => {
+                let key = self.keys.get(kid).ok_or(ErrorKind::InvalidToken)?;
+                decode(token, &key.decoding, &create_jwt_validation(key.algorithm))
+            }
+
+            None if self.accept_legacy => decode(
+                token,
+                &DecodingKey::from_secret(&self.secret),
+                &create_jwt_validation(LEGACY_ALGORITHM),
+            ),
+
+            None => Err(ErrorKind::InvalidToken.into()),
+        }
+    }
+}
+
 /// Decodes a JWT token.
 ///
 /// This will add extra validation to ensure that the JWT token was created by the server.
 pub fn decode_jwt(env: &Env, token: &str) -> jsonwebtoken::errors::Result<TokenData<Claims>> {
-    let key = DecodingKey::from_secret(env.config.jwt_secret_key.as_ref());
-    let validation = create_jwt_validation();
-
-    decode(token, &key, &validation).inspect_err(|e| {
+    env.keys.decode(token).inspect_err(|e| {
         error!(error = %e, "failed to decode JWT token");
         sentry::capture_error(e);
     })
 }
 
 pub fn encode_jwt(env: &Env, claims: Claims) -> jsonwebtoken::errors::Result<String> {
-    jsonwebtoken::encode(
-        &Header {
-            alg: ALGORITHM,
-            ..Default::default()
-        },
-        &claims,
-        &EncodingKey::from_secret(env.config.jwt_secret_key.as_ref()),
-    )
-    .inspect_err(|e| {
+    env.keys.encode(&claims).inspect_err(|e| {
         sentry::capture_error(e);
     })
 }
 
 /// Creates a [`Validation`] to add more validation towards JWT deconstruction.
-fn create_jwt_validation() -> Validation {
-    let mut validation = Validation::new(ALGORITHM);
+fn create_jwt_validation(algorithm: Algorithm) -> Validation {
+    let mut validation = Validation::new(algorithm);
     validation.set_issuer(&[ISS_VALUE]);
     validation.set_audience(&[AUD_VALUE]);
     validation.set_required_spec_claims(&[EXP, ISS, AUD, UID, SID]);
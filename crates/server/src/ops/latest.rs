@@ -0,0 +1,155 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Caching of the latest versions of a repository.
+//!
+//! The latest stable and pre-release versions are kept in the repository's metadata
+//! and recomputed from its releases whenever a release is created, yanked, unyanked or
+//! deleted, so that downloading the `latest` version is a single lookup in the
+//! datastore instead of listing every chart tarball.
+
+use crate::Env;
+use charted_database::entities::{RepositoryEntity, RepositoryReleaseEntity, repository, repository::release};
+use charted_helm_charts::DataStoreExt;
+use charted_types::{QueryableVersion, Repository, Ulid, Version};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, sea_query::Expr};
+
+/// Recomputes the cached latest versions of the specified repository.
+///
+/// Failures are logged and reported to Sentry but never surfaced to the caller, since
+/// the mutation that triggered it has already happened. `charted admin repair-latest-versions`
+/// can be used to fix the cached versions afterwards.
+pub async fn refresh(env: &Env, repository: Ulid) {
+    if let Err(e) = update(env, repository).await {
+        error!(error = %e, repository.id = %repository, "failed to refresh latest versions");
+        sentry::capture_error(&*e);
+    }
+}
+
+/// Recomputes the cached latest versions of the specified repository from its
+/// releases that aren't yanked.
+#[instrument(name = "charted.server.ops.updateLatestVersions", skip_all, fields(repository.id = %repository))]
+pub async fn update(env: &Env, repository: Ulid) -> eyre::Result<(Option<Version>, Option<Version>)> {
+    let tags = RepositoryReleaseEntity::find()
+        .filter(release::Column::Repository.eq(repository))
+        .filter(release::Column::Yanked.eq(false))
+        .all(&env.db)
+        .await?
+        .into_iter()
+        .map(|release| release.tag);
+
+    let latest = latest(tags);
+    persist(env, repository, latest.clone()).await?;
+
+    Ok(latest)
+}
+
+/// Recomputes the cached latest versions of `repository` from the chart tarballs
+/// that are in the datastore rather than its releases, skipping yanked releases.
+///
+/// This lists every chart tarball of the repository, so this is only used by
+/// `charted admin repair-latest-versions`.
+#[instrument(name = "charted.server.ops.repairLatestVersions", skip_all, fields(repository.id = %repository.id))]
+pub async fn repair(
+    env: &Env,
+    repository: &repository::Model,
+) -> eyre::Result<(Option<Version>, Option<Version>)> {
+    let yanked = RepositoryReleaseEntity::find()
+        .filter(release::Column::Repository.eq(repository.id))
+        .filter(release::Column::Yanked.eq(true))
+        .all(&env.db)
+        .await?
+        .into_iter()
+        .map(|release| release.tag)
+        .collect::<Vec<_>>();
+
+    let ns = env.ds.owner_repo(repository.owner, repository.id);
+    let versions = ns
+        .sort_versions(true)
+        .await?
+        .into_iter()
+        .filter(|version| !yanked.contains(version));
+
+    let latest = latest(versions);
+    persist(env, repository.id, latest.clone()).await?;
+
+    Ok(latest)
+}
+
+/// Resolves `version` into the version of the chart that should be downloaded from
+/// `repository`, or [`None`] if there is no latest version to resolve.
+///
+/// If `prereleases` is true, the latest pre-release is used if it is newer than the
+/// latest stable version.
+pub fn resolve(repository: &Repository, version: QueryableVersion, prereleases: bool) -> Option<Version> {
+    match version {
+        QueryableVersion::Version(version) => Some(version),
+        QueryableVersion::Latest if prereleases => repository
+            .latest_version
+            .iter()
+            .chain(repository.latest_prerelease.iter())
+            .max()
+            .cloned(),
+
+        QueryableVersion::Latest => repository.latest_version.clone(),
+    }
+}
+
+/// Returns the latest stable and the latest pre-release version out of `versions`.
+fn latest(versions: impl IntoIterator<Item = Version>) -> (Option<Version>, Option<Version>) {
+    let (prereleases, stable): (Vec<_>, Vec<_>) =
+        versions.into_iter().partition(|version| !version.pre.is_empty());
+    (stable.into_iter().max(), prereleases.into_iter().max())
+}
+
+async fn persist(
+    env: &Env,
+    repository: Ulid,
+    (stable, prerelease): (Option<Version>, Option<Version>),
+) -> eyre::Result<()> {
+    RepositoryEntity::update_many()
+        .col_expr(repository::Column::LatestVersion, Expr::value(stable))
+        .col_expr(repository::Column::LatestPrerelease, Expr::value(prerelease))
+        .filter(repository::Column::Id.eq(repository))
+        .exec(&env.db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use charted_types::Version;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions.iter().map(|v| Version::parse(v).unwrap()).collect()
+    }
+
+    #[test]
+    fn latest() {
+        assert_eq!(super::latest(Vec::new()), (None, None));
+        assert_eq!(
+            super::latest(versions(&["0.1.0", "1.0.0-beta.1", "0.2.0", "0.3.0-rc.1"])),
+            (
+                Some(Version::parse("0.2.0").unwrap()),
+                Some(Version::parse("1.0.0-beta.1").unwrap())
+            )
+        );
+
+        assert_eq!(
+            super::latest(versions(&["1.0.0-alpha", "1.0.0-beta"])),
+            (None, Some(Version::parse("1.0.0-beta").unwrap()))
+        );
+    }
+}
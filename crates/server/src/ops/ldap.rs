@@ -0,0 +1,68 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Background synchronization of local users with the LDAP server, which is only
+//! ran if the LDAP authenticator is used and `sessions.ldap.schedule_new_users` or
+//! `sessions.ldap.schedule_user_updates` is enabled.
+
+use crate::Env;
+use charted_helm_charts::DataStoreExt;
+use std::time::Duration;
+
+/// How often users are synchronized with the LDAP server.
+const INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Returns `true` if [`sync_job`] should be spawned for this environment.
+pub fn enabled(env: &Env) -> bool {
+    env.authz
+        .downcast::<charted_authz_ldap::Backend>()
+        .is_some_and(|backend| backend.config().schedule_new_users || backend.config().schedule_user_updates)
+}
+
+/// Synchronizes local users with the LDAP server every 10 minutes.
+pub async fn sync_job(env: Env) {
+    let Some(backend) = env.authz.downcast::<charted_authz_ldap::Backend>() else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let report = match backend.sync(&env.db, &env.ulid).await {
+            Ok(report) => report,
+            Err(e) => {
+                error!(error = %e, "failed to synchronize users with LDAP server");
+                sentry::capture_error(&*e);
+
+                continue;
+            }
+        };
+
+        info!(
+            created = report.created.len(),
+            updated = report.updated,
+            "synchronized users with LDAP server"
+        );
+
+        let metadata = env.ds.metadata();
+        for id in report.created {
+            if let Err(e) = metadata.create_chart_index(id).await {
+                error!(error = %e, user.id = %id, "failed to create chart index for LDAP user");
+                sentry::capture_error(&*e);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolves what a user is allowed to do with repositories and organizations that
+//! they don't own.
+//!
+//! The owner of a repository, or the owner of the organization that owns it, is allowed
+//! to do everything. Everybody else needs to be a member of the repository or of the
+//! organization, and is only allowed to do what their [`MemberPermissions`] allow.
+//! A repository member in an organization has the permissions of both memberships.
+
+use crate::Env;
+use axum::http::StatusCode;
+use charted_core::{
+    api,
+    bitflags::{Bitflags, MemberPermission, MemberPermissions},
+};
+use charted_database::entities::{OrganizationMemberEntity, RepositoryMemberEntity, organization, repository};
+use charted_types::{Owner, Repository, Ulid, User};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter};
+use serde_json::json;
+
+/// Returns a bitfield of every [`MemberPermission`], which is what owners have.
+pub fn all() -> MemberPermissions {
+    MemberPermission::values().iter().copied().collect()
+}
+
+/// Returns the permissions that `user` has on the resources that `owner` owns, and in
+/// `repository` if specified. Returns [`None`] if `user` is neither the owner nor a
+/// member.
+pub async fn permissions(
+    env: &Env,
+    user: &User,
+    owner: &Owner,
+    repository: Option<Ulid>,
+) -> Result<Option<MemberPermissions>, DbErr> {
+    let org = match owner {
+        Owner::User(owner) if owner.id == user.id => return Ok(Some(all())),
+        Owner::Organization(org) if org.owner == user.id => return Ok(Some(all())),
+        Owner::Organization(org) => Some(org.id),
+        Owner::User(_) => None,
+    };
+
+    let mut permissions = None::<MemberPermissions>;
+    if let Some(org) = org &&
+        let Some(member) = OrganizationMemberEntity::find()
+            .filter(organization::member::Column::Organization.eq(org))
+            .filter(organization::member::Column::Account.eq(user.id))
+            .one(&env.db)
+            .await?
+    {
+        permissions = Some(MemberPermissions::new(member.permissions as u64));
+    }
+
+    if let Some(repository) = repository &&
+        let Some(member) = RepositoryMemberEntity::find()
+            .filter(repository::member::Column::Repository.eq(repository))
+            .filter(repository::member::Column::Account.eq(user.id))
+            .one(&env.db)
+            .await?
+    {
+        let org = permissions.map(|bitfield| bitfield.value()).unwrap_or_default();
+        permissions = Some(MemberPermissions::new(org | member.permissions as u64));
+    }
+
+    Ok(permissions)
+}
+
+/// Returns `true` if `user` is allowed to see `repository`. Public repositories are
+/// visible to everybody, while private repositories are only visible to their owner
+/// and to members of the repository or of the organization that owns it.
+pub async fn can_view(
+    env: &Env,
+    user: Option<&User>,
+    owner: &Owner,
+    repository: &Repository,
+) -> Result<bool, DbErr> {
+    if !repository.private {
+        return Ok(true);
+    }
+
+    let Some(user) = user else {
+        return Ok(false);
+    };
+
+    permissions(env, user, owner, Some(repository.id))
+        .await
+        .map(|permissions| permissions.is_some())
+}
+
+/// Checks that `user` has `permission` on the resources that `owner` owns, or in
+/// `repository` if specified, and fails with a `403 Forbidden` response otherwise.
+pub async fn require(
+    env: &Env,
+    user: &User,
+    owner: &Owner,
+    repository: Option<Ulid>,
+    permission: MemberPermission,
+) -> Result<MemberPermissions, api::Response> {
+    let permissions = permissions(env, user, owner, repository).await.map_err(|e| {
+        error!(error = %e, user.id = %user.id, owner.id = %owner.id(), "failed to query member permissions");
+        sentry::capture_error(&e);
+
+        api::system_failure(e)
+    })?;
+
+    match permissions {
+        Some(permissions) if permissions.contains(permission) => Ok(permissions),
+        _ => Err(api::err(
+            StatusCode::FORBIDDEN,
+            (
+                api::ErrorCode::AccessNotPermitted,
+                "you do not have permission to do this",
+                json!({"owner":owner.id(),"repository":repository,"permission":permission.to_string()}),
+            ),
+        )),
+    }
+}
+
+/// Checks that a user with `permissions` is allowed to grant the `granted`
+/// bitfield to another member. Members can't grant permissions that they don't have.
+pub fn check_grant(permissions: MemberPermissions, granted: u64) -> Result<MemberPermissions, api::Response> {
+    let all = all().value();
+    if granted & !all != 0 {
+        return Err(api::err(
+            StatusCode::NOT_ACCEPTABLE,
+            (
+                api::ErrorCode::InvalidInput,
+                "`permissions` contains unknown permissions",
+                json!({"permissions":granted,"max":all}),
+            ),
+        ));
+    }
+
+    if granted & !permissions.value() != 0 {
+        let missing = MemberPermissions::new(granted & !permissions.value());
+        return Err(api::err(
+            StatusCode::FORBIDDEN,
+            (
+                api::ErrorCode::AccessNotPermitted,
+                "you can't grant permissions that you don't have",
+                json!({"permissions":missing.flags().into_iter().map(|(name, _)| name).collect::<Vec<_>>()}),
+            ),
+        ));
+    }
+
+    Ok(MemberPermissions::new(granted))
+}
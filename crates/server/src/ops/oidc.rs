@@ -0,0 +1,628 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Authorization code flow with [PKCE](https://www.rfc-editor.org/rfc/rfc7636) for the
+//! OpenID Connect providers that are configured in `sessions.oidc`.
+//!
+//! Users link the provider's account (its `sub` claim) to one of their [`UserConnections`]
+//! and can then log in with it, which mints a session like a password login would.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    ops::{self, auditlog, db},
+};
+use axum::http::StatusCode;
+use base64::engine::{Engine, general_purpose::URL_SAFE_NO_PAD};
+use charted_config::sessions::oidc::{Connection, Provider};
+use charted_core::{api, rand_string};
+use charted_database::entities::{
+    OidcAuthorizationEntity, UserConnectionsEntity, oidc_authorization, user_connections,
+};
+use charted_feature_audit_logs::Action;
+use charted_types::{DateTime, Session, Ulid, UserConnections};
+use chrono::{TimeDelta, Utc};
+use eyre::{Context, bail, eyre};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use reqwest::header;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use url::Url;
+use utoipa::ToSchema;
+
+/// How long an authorization request can be completed for.
+pub const AUTHORIZATION_TTL: TimeDelta = TimeDelta::minutes(10);
+
+/// How long a user that has two-factor authentication enabled has to give a code after
+/// the provider redirected them back.
+pub const TOTP_CHALLENGE_TTL: TimeDelta = TimeDelta::minutes(5);
+
+/// Algorithms that ID tokens can be signed with. Symmetric algorithms are never
+/// accepted since the key would be the client secret.
+const ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Subset of a provider's metadata that charted-server uses.
+///
+/// <https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata>
+#[derive(Debug, Clone, Deserialize)]
+pub struct Metadata {
+    pub issuer: String,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub jwks_uri: Url,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+
+    #[serde(default)]
+    nonce: Option<String>,
+}
+
+/// Client for a single provider, with its discovered metadata.
+#[derive(Debug, Clone)]
+pub struct Client<'p> {
+    provider: &'p Provider,
+    metadata: Metadata,
+}
+
+impl<'p> Client<'p> {
+    /// Discovers the metadata of `provider` from its issuer.
+    pub async fn discover(http: &reqwest::Client, provider: &'p Provider) -> eyre::Result<Self> {
+        let issuer = provider.issuer.as_str().trim_end_matches('/');
+        let metadata = http
+            .get(format!("{issuer}/.well-known/openid-configuration"))
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to discover provider metadata")?
+            .json::<Metadata>()
+            .await?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            bail!(
+                "provider metadata was for issuer `{}`, expected `{issuer}`",
+                metadata.issuer
+            );
+        }
+
+        Ok(Client { provider, metadata })
+    }
+
+    /// Builds the URL that users are sent to so they can authorize charted-server.
+    pub fn authorization_url(&self, state: &str, nonce: &str, code_verifier: &str) -> Url {
+        let mut scopes = self.provider.scopes.clone();
+        if !scopes.iter().any(|scope| scope == "openid") {
+            scopes.insert(0, String::from("openid"));
+        }
+
+        let mut url = self.metadata.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.provider.client_id)
+            .append_pair("redirect_uri", self.provider.redirect_url.as_str())
+            .append_pair("scope", &scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &code_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        url
+    }
+
+    /// Exchanges an authorization code for an ID token and returns its `sub` claim once
+    /// the ID token was verified.
+    pub async fn exchange(
+        &self,
+        http: &reqwest::Client,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> eyre::Result<String> {
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+        form.append_pair("grant_type", "authorization_code")
+            .append_pair("code", code)
+            .append_pair("redirect_uri", self.provider.redirect_url.as_str())
+            .append_pair("client_id", &self.provider.client_id)
+            .append_pair("code_verifier", code_verifier);
+
+        if let Some(secret) = self.provider.client_secret.as_deref() {
+            form.append_pair("client_secret", secret);
+        }
+
+        let TokenResponse { id_token } = http
+            .post(self.metadata.token_endpoint.clone())
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::ACCEPT, "application/json")
+            .body(form.finish())
+            .send()
+            .await?
+            .error_for_status()
+            .context("provider rejected the token request")?
+            .json()
+            .await?;
+
+        self.verify(http, &id_token, nonce).await
+    }
+
+    async fn verify(&self, http: &reqwest::Client, id_token: &str, nonce: &str) -> eyre::Result<String> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+        if !ALGORITHMS.contains(&header.alg) {
+            bail!("ID token was signed with unsupported algorithm {:?}", header.alg);
+        }
+
+        let jwks = http
+            .get(self.metadata.jwks_uri.clone())
+            .send()
+            .await?
+            .error_for_status()
+            .context("failed to fetch provider's JWKS")?
+            .json::<JwkSet>()
+            .await?;
+
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(|| eyre!("provider has no key that the ID token could be verified with"))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.metadata.issuer]);
+        validation.set_audience(&[&self.provider.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims =
+            jsonwebtoken::decode::<IdTokenClaims>(id_token, &DecodingKey::from_jwk(jwk)?, &validation)?.claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            bail!("ID token's `nonce` claim didn't match the authorization request");
+        }
+
+        Ok(claims.sub)
+    }
+}
+
+/// Computes the `S256` PKCE code challenge of a code verifier.
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Outcome of a completed authorization request.
+pub enum Completion {
+    /// The user logged in and a new session was minted.
+    Session(Session),
+
+    /// The user linked the provider's account to their connections.
+    Linked(UserConnections),
+
+    /// The user has two-factor authentication enabled, so a session is only minted
+    /// once they complete the challenge with [`complete_totp`].
+    TotpRequired(TotpChallenge),
+}
+
+/// Login with an OpenID Connect provider that is waiting for a code from the user's
+/// authenticator app, or one of their recovery codes.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TotpChallenge {
+    /// opaque ticket that the code has to be sent back with.
+    pub ticket: String,
+
+    /// datetime of when the login can no longer be completed.
+    pub expires_at: DateTime,
+}
+
+/// Returns the provider that was configured as `name`.
+pub fn provider<'e>(env: &'e Env, name: &str) -> Result<&'e Provider, api::Response> {
+    env.config.sessions.oidc.get(name).ok_or_else(|| {
+        api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "openid connect provider was not found",
+                json!({"provider":name}),
+            ),
+        )
+    })
+}
+
+/// Starts an authorization request with the provider that was configured as `name`,
+/// returning the URL that the user should be sent to.
+///
+/// If `account` is set, then completing the request links the provider's account to
+/// that user instead of logging in.
+#[instrument(name = "charted.server.ops.oidc.authorize", skip_all, fields(provider = name))]
+pub async fn authorize(env: &Env, name: &str, account: Option<Ulid>) -> Result<Url, api::Response> {
+    let provider = provider(env, name)?;
+    let client = Client::discover(&env.http, provider)
+        .await
+        .map_err(api::system_failure_from_report)?;
+
+    let now = Utc::now();
+    if let Err(e) = OidcAuthorizationEntity::delete_many()
+        .filter(oidc_authorization::Column::ExpiresAt.lt(now))
+        .exec(&env.db)
+        .await
+    {
+        warn!(error = %e, "failed to delete expired authorization requests");
+    }
+
+    let model = oidc_authorization::Model {
+        created_at: now,
+        updated_at: now,
+        expires_at: now + AUTHORIZATION_TTL,
+        provider: name.to_owned(),
+        state: rand_string(32),
+        code_verifier: rand_string(64),
+        nonce: rand_string(32),
+        totp_pending: false,
+        account,
+        id: env.ulid.generate().into_system_failure()?.into(),
+    };
+
+    OidcAuthorizationEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    Ok(client.authorization_url(&model.state, &model.nonce, &model.code_verifier))
+}
+
+/// Completes an authorization request once the provider redirected the user back.
+///
+/// `initiator` is the user that is authenticated at the callback. Requests that link an
+/// account can only be completed by the user that started them, since the `state` alone
+/// would let anyone that was sent to the provider with it link their account to the
+/// user that started the request.
+#[instrument(name = "charted.server.ops.oidc.complete", skip_all, fields(provider = name))]
+pub async fn complete(
+    env: &Env,
+    cx: &auditlog::Context,
+    name: &str,
+    code: &str,
+    state: &str,
+    initiator: Option<Ulid>,
+    requester: db::session::Client,
+) -> Result<Completion, api::Response> {
+    let provider = provider(env, name)?;
+    let Some(request) = OidcAuthorizationEntity::find()
+        .filter(oidc_authorization::Column::State.eq(state))
+        .filter(oidc_authorization::Column::Provider.eq(name))
+        .filter(oidc_authorization::Column::TotpPending.eq(false))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (api::ErrorCode::EntityNotFound, "authorization request was not found"),
+        ));
+    };
+
+    if let Some(account) = request.account &&
+        initiator != Some(account)
+    {
+        return Err(api::err(
+            StatusCode::FORBIDDEN,
+            (
+                api::ErrorCode::AccessNotPermitted,
+                "authorization request can only be completed by the user that started it",
+            ),
+        ));
+    }
+
+    // requests can only be completed once.
+    OidcAuthorizationEntity::delete_by_id(request.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    if request.expires_at < Utc::now() {
+        return Err(api::err(
+            StatusCode::GONE,
+            (api::ErrorCode::SessionExpired, "authorization request had expired"),
+        ));
+    }
+
+    let client = Client::discover(&env.http, provider)
+        .await
+        .map_err(api::system_failure_from_report)?;
+
+    let subject = client
+        .exchange(&env.http, code, &request.code_verifier, &request.nonce)
+        .await
+        .map_err(|e| {
+            warn!(error = %e, "failed to complete authorization request");
+            api::err(
+                StatusCode::FORBIDDEN,
+                (
+                    api::ErrorCode::AccessNotPermitted,
+                    "provider didn't authorize the request",
+                    json!({"error":e.to_string()}),
+                ),
+            )
+        })?;
+
+    match request.account {
+        Some(account) => link(
+            env,
+            &cx.clone().with_actor(account),
+            account,
+            provider.connection,
+            &subject,
+        )
+        .await
+        .map(Completion::Linked),
+
+        None => {
+            let Some(connections) = UserConnectionsEntity::find()
+                .filter(column(provider.connection).eq(value(provider.connection, &subject)?))
+                .one(&env.db)
+                .await
+                .into_system_failure()?
+            else {
+                return Err(api::err(
+                    StatusCode::NOT_FOUND,
+                    (
+                        api::ErrorCode::EntityNotFound,
+                        "no user has linked their account to this provider's account",
+                        json!({"provider":name}),
+                    ),
+                ));
+            };
+
+            // the provider only stands in for the password, so users that have
+            // two-factor authentication enabled still need to give a code.
+            if ops::totp::enabled(env, connections.account).await? {
+                return challenge(env, name, connections.account)
+                    .await
+                    .map(Completion::TotpRequired);
+            }
+
+            db::session::create(env, connections.account, requester)
+                .await
+                .map(Completion::Session)
+        }
+    }
+}
+
+/// Keeps a login of `account` pending until they give a code from their authenticator
+/// app with [`complete_totp`].
+async fn challenge(env: &Env, name: &str, account: Ulid) -> Result<TotpChallenge, api::Response> {
+    let now = Utc::now();
+    let model = oidc_authorization::Model {
+        created_at: now,
+        updated_at: now,
+        expires_at: now + TOTP_CHALLENGE_TTL,
+        provider: name.to_owned(),
+        state: rand_string(32),
+        code_verifier: String::new(),
+        nonce: String::new(),
+        totp_pending: true,
+        account: Some(account),
+        id: env.ulid.generate().into_system_failure()?.into(),
+    };
+
+    OidcAuthorizationEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    Ok(TotpChallenge {
+        ticket: model.state,
+        expires_at: model.expires_at.into(),
+    })
+}
+
+/// Completes a login that [`complete`] left pending since the user has two-factor
+/// authentication enabled. The code is checked like a password login checks it.
+#[instrument(name = "charted.server.ops.oidc.completeTotp", skip_all, fields(provider = name))]
+pub async fn complete_totp(
+    env: &Env,
+    name: &str,
+    ticket: &str,
+    code: Option<&str>,
+    requester: db::session::Client,
+) -> Result<Session, api::Response> {
+    provider(env, name)?;
+
+    let Some(pending) = OidcAuthorizationEntity::find()
+        .filter(oidc_authorization::Column::State.eq(ticket))
+        .filter(oidc_authorization::Column::Provider.eq(name))
+        .filter(oidc_authorization::Column::TotpPending.eq(true))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (api::ErrorCode::EntityNotFound, "pending login was not found"),
+        ));
+    };
+
+    if pending.expires_at < Utc::now() {
+        OidcAuthorizationEntity::delete_by_id(pending.id)
+            .exec(&env.db)
+            .await
+            .into_system_failure()?;
+
+        return Err(api::err(
+            StatusCode::GONE,
+            (api::ErrorCode::SessionExpired, "pending login had expired"),
+        ));
+    }
+
+    let account = pending
+        .account
+        .ok_or_else(|| api::system_failure_from_report(eyre!("pending login has no account")))?;
+
+    // the ticket is kept when the code was wrong so that a typo doesn't require going
+    // through the provider again; it expires quickly either way.
+    ops::totp::challenge(env, account, code).await?;
+    OidcAuthorizationEntity::delete_by_id(pending.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    db::session::create(env, account, requester).await
+}
+
+/// Returns the connections of a user, creating them if the user never had any.
+pub async fn connections(env: &Env, account: Ulid) -> Result<user_connections::Model, api::Response> {
+    if let Some(model) = UserConnectionsEntity::find()
+        .filter(user_connections::Column::Account.eq(account))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+    {
+        return Ok(model);
+    }
+
+    let now = Utc::now();
+    let model = user_connections::Model {
+        noelware_account_id: None,
+        google_account_id: None,
+        github_account_id: None,
+        gitlab_account_id: None,
+        created_at: now,
+        updated_at: now,
+        account,
+        id: env.ulid.generate().into_system_failure()?.into(),
+    };
+
+    UserConnectionsEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    Ok(model)
+}
+
+/// Links a provider's account to a user's connections.
+pub async fn link(
+    env: &Env,
+    cx: &auditlog::Context,
+    account: Ulid,
+    connection: Connection,
+    subject: &str,
+) -> Result<UserConnections, api::Response> {
+    let value = value(connection, subject)?;
+    if let Some(existing) = UserConnectionsEntity::find()
+        .filter(column(connection).eq(value.clone()))
+        .one(&env.db)
+        .await
+        .into_system_failure()? &&
+        existing.account != account
+    {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "account is already linked to another user",
+                json!({"connection":connection.to_string()}),
+            ),
+        ));
+    }
+
+    update(env, cx, Action::ConnectionLinked, account, connection, value).await
+}
+
+/// Unlinks a provider's account from a user's connections.
+pub async fn unlink(
+    env: &Env,
+    cx: &auditlog::Context,
+    account: Ulid,
+    connection: Connection,
+) -> Result<UserConnections, api::Response> {
+    update(
+        env,
+        cx,
+        Action::ConnectionUnlinked,
+        account,
+        connection,
+        Value::String(None),
+    )
+    .await
+}
+
+async fn update(
+    env: &Env,
+    cx: &auditlog::Context,
+    action: Action,
+    account: Ulid,
+    connection: Connection,
+    value: Value,
+) -> Result<UserConnections, api::Response> {
+    let before = connections(env, account).await?;
+    let mut model = before.clone().into_active_model();
+    model.set(column(connection), value);
+    model.set(user_connections::Column::UpdatedAt, Utc::now().into());
+
+    let after = UserConnections::from(model.update(&env.db).await.into_system_failure()?);
+    auditlog::record(
+        env,
+        cx,
+        action,
+        auditlog::owner(account),
+        UserConnections::from(before),
+        &after,
+    )
+    .await;
+
+    Ok(after)
+}
+
+fn column(connection: Connection) -> user_connections::Column {
+    match connection {
+        Connection::Noelware => user_connections::Column::NoelwareAccountId,
+        Connection::Google => user_connections::Column::GoogleAccountId,
+        Connection::GitHub => user_connections::Column::GithubAccountId,
+        Connection::GitLab => user_connections::Column::GitlabAccountId,
+    }
+}
+
+fn value(connection: Connection, subject: &str) -> Result<Value, api::Response> {
+    match connection {
+        Connection::Noelware => Ulid::new(subject).map(Into::into).map_err(|_| {
+            api::err(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                (
+                    api::ErrorCode::UnableToDecodeUlid,
+                    "provider's `sub` claim was not a ULID",
+                ),
+            )
+        }),
+
+        _ => Ok(Value::String(Some(Box::new(subject.to_owned())))),
+    }
+}
@@ -0,0 +1,327 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Publishing new releases, either by uploading a chart tarball to the REST API or by
+//! pushing a Helm chart to the OCI registry.
+//!
+//! If the OCI registry is enabled, the registry and the repository's releases are kept
+//! in sync: releases that were uploaded to the REST API are tagged in the registry, and
+//! yanked or deleted releases are untagged from it.
+
+use crate::{Env, ext::ResultExt, ops, ops::auditlog};
+use axum::http::StatusCode;
+use charted_core::api;
+use charted_database::entities::{RepositoryReleaseEntity, repository::release};
+use charted_datastore::remi::Bytes;
+use charted_feature_audit_logs::Action;
+use charted_feature_oci::{Registry, reference::Reference};
+use charted_feature_webhooks::event::EventKind;
+use charted_helm_charts::{DataStoreExt, Limits, UploadedChart};
+use charted_types::{Repository, RepositoryRelease, Ulid, Version};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel, SqlErr};
+use serde_json::json;
+
+/// Where the chart tarball of a new release comes from.
+pub enum Tarball<'m> {
+    /// A multipart stream from `PUT /repositories/{owner}/{repo}/releases/{version}/tarball`.
+    Multipart(multer::Multipart<'m>),
+
+    /// The chart layer of a Helm chart that was pushed to the OCI registry.
+    Bytes(Bytes),
+}
+
+/// Publishes `version` of `repository` from `tarball`.
+///
+/// The release is created before its tarball is stored so that the unique index on
+/// `(repository, tag)` decides which of two concurrent uploads of the same version
+/// wins before either of them writes to `tarballs/{version}.tgz`.
+#[instrument(name = "charted.server.ops.publishRelease", skip_all, fields(repository.id = %repository.id, %version))]
+pub async fn publish(
+    env: &Env,
+    cx: &auditlog::Context,
+    repository: &Repository,
+    version: Version,
+    tarball: Tarball<'_>,
+) -> Result<RepositoryRelease, api::Response> {
+    let id: Ulid = env.ulid.generate().into_system_failure()?.into();
+
+    // charts that were pushed to the registry are already tagged in it
+    let uploaded = matches!(tarball, Tarball::Multipart(_));
+
+    let now = Utc::now();
+    let mut model = release::Model {
+        update_text: None,
+        repository: repository.id,
+        created_at: now,
+        updated_at: now,
+        yanked: false,
+        title: None,
+        chart: None,
+        digest: None,
+        tag: version.clone(),
+        id,
+    };
+
+    if let Err(e) = RepositoryReleaseEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+    {
+        if let Some(SqlErr::UniqueConstraintViolation(_)) = e.sql_err() {
+            return Err(api::err(
+                StatusCode::CONFLICT,
+                (
+                    api::ErrorCode::EntityAlreadyExists,
+                    "release with the given version already exists",
+                    json!({"version":version,"repository":repository.id}),
+                ),
+            ));
+        }
+
+        error!(error = %e, "failed to create repository release");
+        sentry::capture_error(&e);
+
+        return Err(api::system_failure(e));
+    }
+
+    let ns = env.ds.owner_repo(repository.owner, repository.id);
+    let limits = Limits::from(&env.config.charts);
+    let result = match tarball {
+        Tarball::Multipart(multipart) => ns.upload_chart(multipart, repository, version.clone(), limits).await,
+        Tarball::Bytes(data) => ns.upload_chart_bytes(data, repository, version.clone(), limits).await,
+    };
+
+    let UploadedChart { chart, digest } = match result {
+        Ok(uploaded) => uploaded,
+        Err(response) => {
+            // the tarball is only written once it was validated, so the release is
+            // the only thing left to clean up.
+            rollback(env, repository, &model, false).await;
+            return Err(response);
+        }
+    };
+
+    let chart = match serde_json::to_value(&chart) {
+        Ok(chart) => chart,
+        Err(e) => {
+            rollback(env, repository, &model, true).await;
+            return Err(api::system_failure(e));
+        }
+    };
+
+    model.chart = Some(chart.clone());
+    model.digest = Some(digest.clone());
+
+    let mut active = model.clone().into_active_model();
+    active.chart = ActiveValue::set(Some(chart));
+    active.digest = ActiveValue::set(Some(digest));
+
+    if let Err(e) = active.update(&env.db).await {
+        error!(error = %e, "failed to persist chart metadata of repository release");
+        sentry::capture_error(&e);
+
+        rollback(env, repository, &model, true).await;
+        return Err(api::system_failure(e));
+    }
+
+    ops::latest::refresh(env, repository.id).await;
+    if !repository.private {
+        ops::indexes::regenerate(env, repository.owner).await;
+    }
+
+    if uploaded {
+        tag(env, repository, &model).await;
+    }
+
+    let release = RepositoryRelease::from(model);
+    ops::webhooks::emit(
+        env,
+        ops::webhooks::repository(repository),
+        EventKind::ReleaseCreated,
+        &release,
+    )
+    .await;
+
+    auditlog::record(
+        env,
+        cx,
+        Action::ReleaseCreated,
+        auditlog::repository(repository),
+        (),
+        &release,
+    )
+    .await;
+
+    Ok(release)
+}
+
+/// Deletes a release that [`publish`] created when its tarball couldn't be stored or
+/// the release couldn't point to it.
+///
+/// The release is what reserves its version, so the tarball has to be deleted first:
+/// once the release is gone, another upload of the same version is free to write its
+/// own tarball.
+async fn rollback(env: &Env, repository: &Repository, model: &release::Model, uploaded: bool) {
+    if uploaded &&
+        let Err(e) = env
+            .ds
+            .owner_repo(repository.owner, repository.id)
+            .delete_chart(model.tag.clone())
+            .await
+    {
+        error!(error = %e, "failed to clean up chart tarball");
+    }
+
+    if let Err(e) = RepositoryReleaseEntity::delete_by_id(model.id).exec(&env.db).await {
+        error!(error = %e, "failed to roll back repository release");
+        sentry::capture_error(&e);
+    }
+}
+
+/// Deletes a release alongside its chart tarball, provenance file and tag in the OCI
+/// registry. The release's version can be published again afterwards.
+#[instrument(name = "charted.server.ops.deleteRelease", skip_all, fields(repository.id = %repository.id, version = %model.tag))]
+pub async fn delete(
+    env: &Env,
+    cx: &auditlog::Context,
+    repository: &Repository,
+    model: release::Model,
+) -> Result<(), api::Response> {
+    RepositoryReleaseEntity::delete_by_id(model.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    // the release is already gone at this point, so a tarball that couldn't be
+    // deleted is only logged and will be collected by the garbage collector.
+    let ns = env.ds.owner_repo(repository.owner, repository.id);
+    if let Err(e) = ns.delete_chart(model.tag.clone()).await {
+        error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to delete chart tarball");
+    }
+
+    if let Err(e) = ns.delete_chart_files(&model.tag).await {
+        error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to delete cached chart file listing");
+    }
+
+    match ns.get_chart_provenance(&model.tag, true).await {
+        Ok(Some(_)) => {
+            if let Err(e) = ns.delete_chart_provenance(model.tag.clone()).await {
+                error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to delete chart provenance");
+            }
+        }
+
+        Ok(None) => {}
+        Err(e) => {
+            error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to query chart provenance");
+        }
+    }
+
+    untag(env, repository, &model.tag).await;
+
+    if !model.yanked {
+        ops::latest::refresh(env, repository.id).await;
+        if !repository.private {
+            ops::indexes::regenerate(env, repository.owner).await;
+        }
+    }
+
+    let release = RepositoryRelease::from(model);
+    ops::webhooks::emit(
+        env,
+        ops::webhooks::repository(repository),
+        EventKind::ReleaseDeleted,
+        &release,
+    )
+    .await;
+
+    auditlog::record(
+        env,
+        cx,
+        Action::ReleaseDeleted,
+        auditlog::repository(repository),
+        &release,
+        (),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Returns the tag that `version` has in the OCI registry. Helm replaces `+` with `_`
+/// since tags can't contain a `+`.
+pub fn oci_tag(version: &Version) -> String {
+    version.to_string().replace('+', "_")
+}
+
+/// Tags a release's chart in the repository's OCI registry, so that releases which were
+/// uploaded to the REST API can be pulled from it. This is only logged if it fails since
+/// the release itself is what was published.
+pub async fn tag(env: &Env, repository: &Repository, model: &release::Model) {
+    if !env.features.has::<charted_feature_oci::Feature>() {
+        return;
+    }
+
+    let Some(ref chart) = model.chart else {
+        warn!(repository.id = %repository.id, version = %model.tag, "release has no chart metadata, not tagging it in the oci registry");
+        return;
+    };
+
+    let tarball = match env
+        .ds
+        .owner_repo(repository.owner, repository.id)
+        .get_chart(&model.tag, true)
+        .await
+    {
+        Ok(Some(file)) => file.data,
+        Ok(None) => {
+            warn!(repository.id = %repository.id, version = %model.tag, "release has no chart tarball, not tagging it in the oci registry");
+            return;
+        }
+
+        Err(e) => {
+            error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to read chart tarball");
+            return;
+        }
+    };
+
+    let config = match serde_json::to_vec(chart) {
+        Ok(config) => Bytes::from(config),
+        Err(e) => {
+            error!(error = %e, repository.id = %repository.id, version = %model.tag, "failed to serialize chart metadata");
+            return;
+        }
+    };
+
+    if let Err(e) = Registry::new(&env.ds, repository.owner, repository.id)
+        .put_chart(&oci_tag(&model.tag), config, tarball)
+        .await
+    {
+        error!(error = %e.message, repository.id = %repository.id, version = %model.tag, "failed to tag release in the oci registry");
+    }
+}
+
+/// Removes a release's tag from the repository's OCI registry, so that yanked and
+/// deleted releases can't be pulled from it anymore.
+pub async fn untag(env: &Env, repository: &Repository, version: &Version) {
+    if !env.features.has::<charted_feature_oci::Feature>() {
+        return;
+    }
+
+    if let Err(e) = Registry::new(&env.ds, repository.owner, repository.id)
+        .delete_manifest(&Reference::Tag(oci_tag(version)))
+        .await
+    {
+        error!(error = %e.message, repository.id = %repository.id, %version, "failed to untag release in the oci registry");
+    }
+}
@@ -0,0 +1,275 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Two-factor authentication with time-based one-time passwords. Codes are only
+//! required if the TOTP server feature is enabled.
+
+use crate::{Env, ext::ResultExt};
+use axum::http::StatusCode;
+use charted_core::api;
+use charted_database::entities::{UserTotpEntity, user_totp};
+use charted_feature_totp::{Feature, Totp, TotpEnrollment, TotpStatus, qr, recovery};
+use charted_types::{Ulid, User};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
+use serde_json::json;
+
+/// Returns the TOTP feature, or a `404 Not Found` response if it is disabled.
+pub fn feature(env: &Env) -> Result<&Feature, api::Response> {
+    env.features.get::<Feature>().ok_or_else(|| {
+        api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "two-factor authentication is not enabled on this instance",
+            ),
+        )
+    })
+}
+
+/// Returns the enrollment of `user`, which might not have been verified yet.
+pub async fn find(env: &Env, user: Ulid) -> Result<Option<user_totp::Model>, api::Response> {
+    UserTotpEntity::find()
+        .filter(user_totp::Column::Account.eq(user))
+        .one(&env.db)
+        .await
+        .into_system_failure()
+}
+
+/// Whether if `user` is required to give a code when logging in.
+pub async fn enabled(env: &Env, user: Ulid) -> Result<bool, api::Response> {
+    if !env.features.has::<Feature>() {
+        return Ok(false);
+    }
+
+    find(env, user)
+        .await
+        .map(|model| model.is_some_and(|model| model.enabled))
+}
+
+/// Second step of logging in: requires a code from `user`'s authenticator app, or one
+/// of their recovery codes, if they have two-factor authentication enabled.
+#[instrument(name = "charted.server.ops.totp.challenge", skip_all, fields(%user))]
+pub async fn challenge(env: &Env, user: Ulid, code: Option<&str>) -> Result<(), api::Response> {
+    let Some(feature) = env.features.get::<Feature>() else {
+        return Ok(());
+    };
+
+    let Some(model) = find(env, user).await?.filter(|model| model.enabled) else {
+        return Ok(());
+    };
+
+    let Some(code) = code else {
+        return Err(api::err(
+            StatusCode::UNAUTHORIZED,
+            (
+                api::ErrorCode::TwoFactorRequired,
+                "user has two-factor authentication enabled, a code is required",
+            ),
+        ));
+    };
+
+    match verify(env, feature, model, code, true).await? {
+        Some(_) => Ok(()),
+        None => Err(invalid_code()),
+    }
+}
+
+/// Verifies `code` against an enrollment and returns the updated enrollment if it was
+/// valid. A code from the authenticator app is only accepted once, and a recovery code
+/// is removed after it was used if `allow_recovery` is `true`.
+pub async fn verify(
+    env: &Env,
+    feature: &Feature,
+    model: user_totp::Model,
+    code: &str,
+    allow_recovery: bool,
+) -> Result<Option<user_totp::Model>, api::Response> {
+    let totp = feature.totp(&model.secret).into_system_failure()?;
+    let now = u64::try_from(Utc::now().timestamp()).into_system_failure()?;
+
+    if let Some(step) = totp.verify(code, now) {
+        let step = i64::try_from(step).into_system_failure()?;
+        if model.last_used_step.is_some_and(|last| step <= last) {
+            return Ok(None);
+        }
+
+        let mut active = model.into_active_model();
+        active.last_used_step = ActiveValue::set(Some(step));
+        active.updated_at = ActiveValue::set(Utc::now());
+        return active.update(&env.db).await.map(Some).into_system_failure();
+    }
+
+    if !allow_recovery {
+        return Ok(None);
+    }
+
+    let mut hashes = recovery_codes(&model);
+    let Some(position) = recovery::position(&hashes, code) else {
+        return Ok(None);
+    };
+
+    hashes.remove(position);
+
+    let mut active = model.into_active_model();
+    active.recovery_codes = ActiveValue::set(json!(hashes));
+    active.updated_at = ActiveValue::set(Utc::now());
+    active.update(&env.db).await.map(Some).into_system_failure()
+}
+
+/// Returns the [`TotpStatus`] of an enrollment.
+pub fn status(model: Option<&user_totp::Model>) -> TotpStatus {
+    TotpStatus {
+        enabled: model.is_some_and(|model| model.enabled),
+        recovery_codes: model.map(|model| recovery_codes(model).len()).unwrap_or_default(),
+    }
+}
+
+/// Starts enrolling `user` with a new secret and new recovery codes. An enrollment that
+/// wasn't verified yet is replaced.
+#[instrument(name = "charted.server.ops.totp.enroll", skip_all, fields(%user.id))]
+pub async fn enroll(env: &Env, feature: &Feature, user: &User) -> Result<TotpEnrollment, api::Response> {
+    let existing = find(env, user.id).await?;
+    if existing.as_ref().is_some_and(|model| model.enabled) {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "two-factor authentication is already enabled",
+            ),
+        ));
+    }
+
+    let totp = Totp::generate(feature.config().skew);
+    let uri = totp.uri(&feature.config().issuer, user.username.as_str()).to_string();
+    let qr_code = qr::svg(&uri).into_system_failure()?;
+    let recovery_codes = recovery::generate(feature.config().recovery_codes.into());
+    let hashes = recovery_codes
+        .iter()
+        .map(|code| recovery::hash(code))
+        .collect::<Vec<_>>();
+
+    if let Some(model) = existing {
+        UserTotpEntity::delete_by_id(model.id)
+            .exec(&env.db)
+            .await
+            .into_system_failure()?;
+    }
+
+    let now = Utc::now();
+    let model = user_totp::Model {
+        created_at: now,
+        updated_at: now,
+        recovery_codes: json!(hashes),
+        last_used_step: None,
+        secret: totp.to_base32(),
+        enabled: false,
+        account: user.id,
+        id: env.ulid.generate().into_system_failure()?.into(),
+    };
+
+    UserTotpEntity::insert(model.into_active_model())
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    Ok(TotpEnrollment {
+        secret: totp.to_base32(),
+        recovery_codes,
+        qr_code,
+        uri,
+    })
+}
+
+/// Enables two-factor authentication for `user` once they gave a valid code from the
+/// authenticator app that they enrolled with.
+#[instrument(name = "charted.server.ops.totp.confirm", skip_all, fields(%user))]
+pub async fn confirm(env: &Env, feature: &Feature, user: Ulid, code: &str) -> Result<TotpStatus, api::Response> {
+    let Some(model) = find(env, user).await? else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "user hasn't started enrolling into two-factor authentication",
+                json!({"user":user}),
+            ),
+        ));
+    };
+
+    if model.enabled {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "two-factor authentication is already enabled",
+            ),
+        ));
+    }
+
+    let Some(model) = verify(env, feature, model, code, false).await? else {
+        return Err(invalid_code());
+    };
+
+    let mut active = model.into_active_model();
+    active.enabled = ActiveValue::set(true);
+    active.updated_at = ActiveValue::set(Utc::now());
+
+    let model = active.update(&env.db).await.into_system_failure()?;
+    Ok(status(Some(&model)))
+}
+
+/// Disables two-factor authentication for `user`. A valid code or recovery code is
+/// required so that a stolen session can't remove it.
+#[instrument(name = "charted.server.ops.totp.disable", skip_all, fields(%user))]
+pub async fn disable(env: &Env, feature: &Feature, user: Ulid, code: &str) -> Result<(), api::Response> {
+    let Some(model) = find(env, user).await? else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "user doesn't have two-factor authentication enabled",
+                json!({"user":user}),
+            ),
+        ));
+    };
+
+    let id = model.id;
+
+    // enrollments that weren't verified don't protect anything yet, so they can be
+    // removed without a code.
+    if model.enabled && verify(env, feature, model, code, true).await?.is_none() {
+        return Err(invalid_code());
+    }
+
+    UserTotpEntity::delete_by_id(id)
+        .exec(&env.db)
+        .await
+        .map(|_| ())
+        .into_system_failure()
+}
+
+fn recovery_codes(model: &user_totp::Model) -> Vec<String> {
+    serde_json::from_value(model.recovery_codes.clone()).unwrap_or_default()
+}
+
+fn invalid_code() -> api::Response {
+    api::err(
+        StatusCode::FORBIDDEN,
+        (
+            api::ErrorCode::InvalidTwoFactorCode,
+            "invalid two-factor authentication code given",
+        ),
+    )
+}
@@ -0,0 +1,281 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Management of webhooks and emitting events to them. Events are only emitted if
+//! the webhooks server feature is enabled.
+
+use crate::{Env, ext::ResultExt, ops::auditlog, pagination::PaginationRequest};
+use axum::http::StatusCode;
+use charted_core::{api, clamp};
+use charted_database::entities::{WebhookDeliveryEntity, WebhookEntity, webhook};
+use charted_feature_audit_logs::Action;
+pub use charted_feature_webhooks::Target;
+use charted_feature_webhooks::{
+    CreateWebhookPayload, PatchWebhookPayload, Webhook, WebhookDelivery, destination,
+    event::{Event, EventKind},
+    signature::Secret,
+};
+use charted_types::{Repository, Ulid};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+    QueryOrder, Select,
+};
+use serde::Serialize;
+use serde_json::json;
+
+/// Returns the [`Target`] of events that happen in `repository`.
+pub fn repository(repository: &Repository) -> Target {
+    Target {
+        owner: repository.owner,
+        repository: Some(repository.id),
+    }
+}
+
+/// Emits a event to all webhooks of `target` that are subscribed to `kind`.
+///
+/// Failures are logged and reported to Sentry but never surfaced to the caller, since
+/// the action that the event is about has already happened.
+pub async fn emit<T: Serialize>(env: &Env, target: Target, kind: EventKind, data: T) {
+    let Some(feature) = env.features.get::<charted_feature_webhooks::Feature>() else {
+        return;
+    };
+
+    if let Err(e) = feature.dispatcher().dispatch(target, Event::new(kind, data)).await {
+        error!(error = %e, owner = %target.owner, event = %kind, "failed to emit webhook event");
+        sentry::capture_error(&*e);
+    }
+}
+
+/// Returns a query of the webhooks that belong to `target` itself. Owner webhooks
+/// aren't included in a repository's webhooks and vice versa.
+fn scoped(target: Target) -> Select<WebhookEntity> {
+    let query = WebhookEntity::find().filter(webhook::Column::Owner.eq(target.owner));
+    match target.repository {
+        Some(id) => query.filter(webhook::Column::Repository.eq(id)),
+        None => query.filter(webhook::Column::Repository.is_null()),
+    }
+}
+
+pub async fn list(env: &Env, target: Target) -> api::Result<Vec<Webhook>> {
+    let webhooks = scoped(target)
+        .order_by_asc(webhook::Column::Id)
+        .all(&env.db)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(|model| Webhook::from(model).sanitize())
+        .collect();
+
+    Ok(api::ok(StatusCode::OK, webhooks))
+}
+
+pub async fn get(env: &Env, target: Target, id: Ulid) -> Result<webhook::Model, api::Response> {
+    scoped(target)
+        .filter(webhook::Column::Id.eq(id))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .ok_or_else(|| {
+            api::err(
+                StatusCode::NOT_FOUND,
+                (
+                    api::ErrorCode::EntityNotFound,
+                    "webhook with id was not found",
+                    json!({"id":id}),
+                ),
+            )
+        })
+}
+
+pub async fn create(
+    env: &Env,
+    cx: &auditlog::Context,
+    target: Target,
+    CreateWebhookPayload { url, events, enabled }: CreateWebhookPayload,
+) -> api::Result<Webhook> {
+    validate_url(env, &url).await?;
+    if events.is_empty() {
+        return Err(no_events());
+    }
+
+    let id = env.ulid.generate().into_system_failure()?;
+    let now = Utc::now();
+    let model = webhook::Model {
+        created_at: now,
+        updated_at: now,
+        repository: target.repository,
+        events: serde_json::to_value(&events).into_system_failure()?,
+        secret: Secret::generate().to_string(),
+        enabled,
+        owner: target.owner,
+        url: url.to_string(),
+        id: id.into(),
+    };
+
+    WebhookEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .inspect_err(|e| {
+            error!(error = %e, owner = %target.owner, "failed to create webhook");
+            sentry::capture_error(e);
+        })
+        .into_system_failure()?;
+
+    let webhook = Webhook::from(model);
+    auditlog::record(
+        env,
+        cx,
+        Action::WebhookCreated,
+        audit_target(target),
+        (),
+        webhook.clone().sanitize(),
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, webhook))
+}
+
+pub async fn patch(
+    env: &Env,
+    cx: &auditlog::Context,
+    target: Target,
+    id: Ulid,
+    PatchWebhookPayload { url, events, enabled }: PatchWebhookPayload,
+) -> api::Result<()> {
+    let model = get(env, target, id).await?;
+    let mut active = model.clone().into_active_model();
+
+    if let Some(ref url) = url {
+        validate_url(env, url).await?;
+        active.url.set_if_not_equals(url.to_string());
+    }
+
+    if let Some(events) = events {
+        if events.is_empty() {
+            return Err(no_events());
+        }
+
+        active
+            .events
+            .set_if_not_equals(serde_json::to_value(&events).into_system_failure()?);
+    }
+
+    if let Some(enabled) = enabled {
+        active.enabled.set_if_not_equals(enabled);
+    }
+
+    active.updated_at = ActiveValue::set(Utc::now());
+    let updated = active.update(&env.db).await.into_system_failure()?;
+
+    auditlog::record(
+        env,
+        cx,
+        Action::WebhookUpdated,
+        audit_target(target),
+        Webhook::from(model).sanitize(),
+        Webhook::from(updated).sanitize(),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+pub async fn delete(env: &Env, cx: &auditlog::Context, target: Target, id: Ulid) -> api::Result<()> {
+    let model = get(env, target, id).await?;
+
+    WebhookEntity::delete_by_id(model.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    auditlog::record(
+        env,
+        cx,
+        Action::WebhookDeleted,
+        audit_target(target),
+        Webhook::from(model).sanitize(),
+        (),
+    )
+    .await;
+
+    Ok(api::from_default(StatusCode::ACCEPTED))
+}
+
+fn audit_target(Target { owner, repository }: Target) -> auditlog::Target {
+    auditlog::Target { owner, repository }
+}
+
+/// Lists the delivery attempts of a webhook, most recent first.
+pub async fn deliveries(
+    env: &Env,
+    target: Target,
+    id: Ulid,
+    PaginationRequest { per_page, page, .. }: PaginationRequest,
+) -> api::Result<Vec<WebhookDelivery>> {
+    let model = get(env, target, id).await?;
+    let per_page = clamp(per_page, 10, 100).unwrap_or(10);
+
+    let deliveries = WebhookDeliveryEntity::find()
+        .filter(webhook::delivery::Column::Webhook.eq(model.id))
+        .order_by_desc(webhook::delivery::Column::CreatedAt)
+        .order_by_desc(webhook::delivery::Column::Attempt)
+        .paginate(&env.db, per_page as u64)
+        .fetch_page(page.saturating_sub(1) as u64)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        // deliveries of events that no longer exist are skipped
+        .filter_map(|model| WebhookDelivery::try_from(model).ok())
+        .collect();
+
+    Ok(api::ok(StatusCode::OK, deliveries))
+}
+
+async fn validate_url(env: &Env, url: &url::Url) -> Result<(), api::Response> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(api::err(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            (
+                api::ErrorCode::ValidationFailed,
+                "`url` must be a HTTP or HTTPS endpoint",
+                json!({"url":url}),
+            ),
+        ));
+    }
+
+    destination::check(&env.config.features.webhooks, url)
+        .await
+        .map_err(|e| {
+            api::err(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                (
+                    api::ErrorCode::ValidationFailed,
+                    "`url` must be a publicly reachable endpoint",
+                    json!({"url":url,"reason":e.to_string()}),
+                ),
+            )
+        })
+}
+
+fn no_events() -> api::Response {
+    api::err(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        (
+            api::ErrorCode::ValidationFailed,
+            "`events` must contain at least one event type",
+        ),
+    )
+}
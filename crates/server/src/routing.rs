@@ -17,10 +17,10 @@
 
 use crate::Env;
 use axum::{
-    Extension, Router,
+    Extension, Json, Router,
     body::Body,
-    extract::DefaultBodyLimit,
-    http::{Method, Request, Response, StatusCode},
+    extract::{DefaultBodyLimit, State},
+    http::{Method, Request, Response, StatusCode, header},
     response::IntoResponse,
 };
 use charted_core::api;
@@ -30,6 +30,7 @@ use std::{any::Any, borrow::Cow};
 use tower::ServiceBuilder;
 use tower_http::compression::CompressionLayer;
 
+pub mod oci;
 pub mod v1;
 
 macro_rules! mk_router(
@@ -64,6 +65,13 @@ pub(crate) async fn prometheus_scrape(
     handle.render()
 }
 
+/// Publishes the keys that session tokens can be verified with, so that other
+/// services can verify tokens that were issued by charted-server.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub(crate) async fn jwks(State(env): State<Env>) -> impl IntoResponse {
+    ([(header::CACHE_CONTROL, "public, max-age=300")], Json(env.keys.jwks()))
+}
+
 fn panic_handler(message: Box<dyn Any + Send + 'static>) -> Response<Body> {
     let details = azalia::message_from_panic(message);
     error!(%details, "HTTP service has panicked");
@@ -109,7 +117,25 @@ async fn four_oh_five_method_not_allowed(req: Request<Body>) -> api::Response {
 const MAX_BODY_LIMIT: usize = 100 * 1024 * 1024;
 
 pub fn create_router(env: &Env) -> Router<Env> {
-    mk_router!(env, v1)
+    let mut router = mk_router!(env, v1).route("/.well-known/jwks.json", axum::routing::get(jwks));
+    if env.features.has::<charted_feature_oci::Feature>() {
+        // nesting only maps the `/` route onto `/v2`, but clients will
+        // check `/v2/` to see if the registry is avaliable.
+        router = router
+            .nest("/v2", oci::create_router(env))
+            .route("/v2/", axum::routing::get(oci::main));
+    }
+
+    // added before the other layers so that it runs after them, which
+    // lets rate limited requests still be logged with a request ID.
+    if env.ratelimits.is_some() {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            env.clone(),
+            crate::middleware::ratelimit,
+        ));
+    }
+
+    router
         .layer(
             ServiceBuilder::new()
                 .layer(sentry_tower::NewSentryLayer::new_from_top())
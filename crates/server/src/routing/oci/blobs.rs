@@ -0,0 +1,371 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Blobs and blob uploads.
+
+use super::{
+    Access, DOCKER_CONTENT_DIGEST, DOCKER_UPLOAD_UUID, current_session, location, params, resolve, respond,
+};
+use crate::{Env, middleware::authn::Session};
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::{Path, Query, State, rejection::PathRejection},
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use charted_feature_oci::{
+    Registry,
+    digest::Digest,
+    error::{Error, ErrorCode, Result},
+};
+use charted_types::{NameOrUlid, Ulid};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UploadQuery {
+    /// Digest of the blob, which completes the upload.
+    #[serde(default)]
+    digest: Option<String>,
+
+    /// Digest of a blob to mount from another repository.
+    #[serde(default)]
+    mount: Option<String>,
+
+    /// Repository (`{owner}/{repo}`) that the blob is mounted from.
+    #[serde(default)]
+    from: Option<String>,
+}
+
+/// `GET /v2/{owner}/{repo}/blobs/{digest}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn fetch(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, digest) = params(path)?;
+    let digest = parse_digest(&digest)?;
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Pull).await?;
+
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+    let Some(data) = registry.blob(&digest).await? else {
+        return Err(blob_unknown(&digest));
+    };
+
+    respond(
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_owned()),
+            (header::CONTENT_LENGTH, data.len().to_string()),
+            (DOCKER_CONTENT_DIGEST, digest.to_string()),
+        ],
+        data,
+    )
+}
+
+/// `DELETE /v2/{owner}/{repo}/blobs/{digest}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn delete(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, digest) = params(path)?;
+    let digest = parse_digest(&digest)?;
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Push).await?;
+
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+    if !registry.delete_blob(&digest).await? {
+        return Err(blob_unknown(&digest));
+    }
+
+    respond(StatusCode::ACCEPTED, [], ())
+}
+
+/// `POST /v2/{owner}/{repo}/blobs/uploads/`
+///
+/// * With `?digest=`, the blob is uploaded in a single request.
+/// * With `?mount=&from=`, the blob is mounted from another repository if it exists.
+/// * Otherwise, an upload session is started.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn start_upload(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid)>, PathRejection>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Result<Response> {
+    let (owner, repo) = params(path)?;
+    let session = current_session(&session);
+    let repository = resolve(&env, session, owner.clone(), repo.clone(), Access::Push).await?;
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+
+    if let Some(digest) = query.digest {
+        let digest = parse_digest(&digest)?;
+        registry.put_blob(&digest, body).await?;
+
+        return created(&owner, &repo, &digest);
+    }
+
+    if let Some(mount) = query.mount {
+        let digest = parse_digest(&mount)?;
+        if mount_blob(&env, session, &registry, &digest, query.from.as_deref()).await? {
+            return created(&owner, &repo, &digest);
+        }
+    }
+
+    let id = env.ulid.generate().map_err(Error::unknown)?;
+    registry.create_upload(&id.to_string()).await?;
+
+    respond(
+        StatusCode::ACCEPTED,
+        [
+            location(&owner, &repo, format!("blobs/uploads/{id}")),
+            (header::CONTENT_LENGTH, "0".to_owned()),
+            (DOCKER_UPLOAD_UUID, id.to_string()),
+        ],
+        (),
+    )
+}
+
+/// `GET /v2/{owner}/{repo}/blobs/uploads/{id}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn upload_status(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, id) = params(path)?;
+    let id = parse_upload_id(&id)?;
+    let repository = resolve(
+        &env,
+        current_session(&session),
+        owner.clone(),
+        repo.clone(),
+        Access::Push,
+    )
+    .await?;
+
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+    let Some(size) = registry.upload_size(&id).await? else {
+        return Err(upload_unknown(&id));
+    };
+
+    in_progress(StatusCode::NO_CONTENT, &owner, &repo, &id, size)
+}
+
+/// `PATCH /v2/{owner}/{repo}/blobs/uploads/{id}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn upload_chunk(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response> {
+    let (owner, repo, id) = params(path)?;
+    let id = parse_upload_id(&id)?;
+    let repository = resolve(
+        &env,
+        current_session(&session),
+        owner.clone(),
+        repo.clone(),
+        Access::Push,
+    )
+    .await?;
+
+    let offset = match headers.get(header::CONTENT_RANGE) {
+        Some(value) => {
+            let (start, end) = value
+                .to_str()
+                .ok()
+                .and_then(parse_content_range)
+                .ok_or_else(|| invalid_range(value.to_str().unwrap_or_default()))?;
+
+            if end < start || end - start + 1 != body.len() as u64 {
+                return Err(invalid_range(value.to_str().unwrap_or_default()));
+            }
+
+            Some(start)
+        }
+
+        None => None,
+    };
+
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+    let size = registry.append_upload(&id, offset, body).await?;
+
+    in_progress(StatusCode::ACCEPTED, &owner, &repo, &id, size)
+}
+
+/// `PUT /v2/{owner}/{repo}/blobs/uploads/{id}?digest=`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn complete_upload(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Result<Response> {
+    let (owner, repo, id) = params(path)?;
+    let id = parse_upload_id(&id)?;
+    let Some(digest) = query.digest else {
+        return Err(Error::new(
+            ErrorCode::DigestInvalid,
+            "`digest` query parameter is required to complete an upload",
+        ));
+    };
+
+    let digest = parse_digest(&digest)?;
+    let repository = resolve(
+        &env,
+        current_session(&session),
+        owner.clone(),
+        repo.clone(),
+        Access::Push,
+    )
+    .await?;
+
+    Registry::new(&env.ds, repository.owner, repository.id)
+        .complete_upload(&id, &digest, body)
+        .await?;
+
+    created(&owner, &repo, &digest)
+}
+
+/// `DELETE /v2/{owner}/{repo}/blobs/uploads/{id}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn cancel_upload(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, id) = params(path)?;
+    let id = parse_upload_id(&id)?;
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Push).await?;
+
+    if !Registry::new(&env.ds, repository.owner, repository.id)
+        .cancel_upload(&id)
+        .await?
+    {
+        return Err(upload_unknown(&id));
+    }
+
+    respond(StatusCode::NO_CONTENT, [], ())
+}
+
+/// Mounts a blob from the repository in `from` into `registry`. Returns `false` if the
+/// blob couldn't be mounted, in which case the client will upload it instead.
+async fn mount_blob(
+    env: &Env,
+    session: Option<&Session>,
+    registry: &Registry<'_>,
+    digest: &Digest,
+    from: Option<&str>,
+) -> Result<bool> {
+    if registry.has_blob(digest).await? {
+        return Ok(true);
+    }
+
+    let Some((owner, repo)) = from.and_then(|from| from.split_once('/')) else {
+        return Ok(false);
+    };
+
+    let (Ok(owner), Ok(repo)) = (
+        serde_json::from_value::<NameOrUlid>(json!(owner)),
+        serde_json::from_value::<NameOrUlid>(json!(repo)),
+    ) else {
+        return Ok(false);
+    };
+
+    // If the user can't pull from the other repository, then we act as if the blob
+    // doesn't exist.
+    let Ok(source) = resolve(env, session, owner, repo, Access::Pull).await else {
+        return Ok(false);
+    };
+
+    let Some(data) = Registry::new(&env.ds, source.owner, source.id).blob(digest).await? else {
+        return Ok(false);
+    };
+
+    registry.put_blob(digest, data).await?;
+    Ok(true)
+}
+
+fn created(owner: &NameOrUlid, repo: &NameOrUlid, digest: &Digest) -> Result<Response> {
+    respond(
+        StatusCode::CREATED,
+        [
+            location(owner, repo, format!("blobs/{digest}")),
+            (header::CONTENT_LENGTH, "0".to_owned()),
+            (DOCKER_CONTENT_DIGEST, digest.to_string()),
+        ],
+        (),
+    )
+}
+
+fn in_progress(
+    status: StatusCode,
+    owner: &NameOrUlid,
+    repo: &NameOrUlid,
+    id: &str,
+    size: u64,
+) -> Result<Response> {
+    respond(
+        status,
+        [
+            location(owner, repo, format!("blobs/uploads/{id}")),
+            (header::RANGE, format!("0-{}", size.saturating_sub(1))),
+            (header::CONTENT_LENGTH, "0".to_owned()),
+            (DOCKER_UPLOAD_UUID, id.to_owned()),
+        ],
+        (),
+    )
+}
+
+pub(super) fn parse_digest(digest: &str) -> Result<Digest> {
+    digest.parse().map_err(|e: charted_feature_oci::digest::ParseError| {
+        Error::new(ErrorCode::DigestInvalid, e.to_string()).with_detail(json!({"digest": digest}))
+    })
+}
+
+/// Upload IDs are always ULIDs, so anything else can't be a upload that exists.
+fn parse_upload_id(id: &str) -> Result<String> {
+    Ulid::new(id).map(|id| id.to_string()).map_err(|_| upload_unknown(id))
+}
+
+/// Parses a `Content-Range` header in the form of `{start}-{end}`.
+fn parse_content_range(value: &str) -> Option<(u64, u64)> {
+    let value = value.strip_prefix("bytes ").unwrap_or(value);
+    let (start, end) = value.split_once('-')?;
+
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+}
+
+fn invalid_range(value: &str) -> Error {
+    Error::new(ErrorCode::BlobUploadInvalid, "invalid `Content-Range` header")
+        .with_status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .with_detail(json!({"range": value}))
+}
+
+fn blob_unknown(digest: &Digest) -> Error {
+    Error::new(ErrorCode::BlobUnknown, "blob unknown to registry").with_detail(json!({"digest": digest}))
+}
+
+fn upload_unknown(id: &str) -> Error {
+    Error::new(ErrorCode::BlobUploadUnknown, "blob upload unknown to registry").with_detail(json!({"id": id}))
+}
@@ -0,0 +1,399 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Manifests, tags, and referrers.
+
+use super::{
+    Access, DOCKER_CONTENT_DIGEST, OCI_FILTERS_APPLIED, OCI_SUBJECT, blobs::parse_digest, current_session,
+    location, params, resolve, respond,
+};
+use crate::{
+    Env,
+    middleware::authn::Session,
+    ops::{self, auditlog, db, releases::Tarball},
+};
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query, State, rejection::PathRejection},
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use charted_core::api;
+use charted_feature_oci::{
+    Registry,
+    digest::Digest,
+    error::{Error, ErrorCode, Result},
+    manifest::{self, Descriptor, ImageIndex, Manifest},
+    reference::Reference,
+};
+use charted_types::{NameOrUlid, Version, VersionOrUlid};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TagsQuery {
+    /// Maximum amount of tags to return.
+    #[serde(default)]
+    n: Option<usize>,
+
+    /// Only return tags that are lexically after this tag.
+    #[serde(default)]
+    last: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferrersQuery {
+    /// Only return referrers with this artifact type.
+    #[serde(default)]
+    artifact_type: Option<String>,
+}
+
+/// `GET /v2/{owner}/{repo}/manifests/{reference}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn fetch(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, reference) = params(path)?;
+    let Ok(reference) = reference.parse::<Reference>() else {
+        return Err(manifest_unknown(&reference));
+    };
+
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Pull).await?;
+    let Some(manifest) = Registry::new(&env.ds, repository.owner, repository.id)
+        .manifest(&reference)
+        .await?
+    else {
+        return Err(manifest_unknown(&reference.to_string()));
+    };
+
+    respond(
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, manifest.media_type),
+            (header::CONTENT_LENGTH, manifest.data.len().to_string()),
+            (DOCKER_CONTENT_DIGEST, manifest.digest.to_string()),
+        ],
+        manifest.data,
+    )
+}
+
+/// `PUT /v2/{owner}/{repo}/manifests/{reference}`
+///
+/// Helm charts that are pushed with their version as the tag are published as a release
+/// of the repository, so that they are also avaliable in its `index.yaml`.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn put(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    cx: auditlog::Context,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response> {
+    let (owner, repo, reference) = params(path)?;
+    let reference = reference.parse::<Reference>().map_err(|e| {
+        Error::new(ErrorCode::ManifestInvalid, e.to_string()).with_detail(json!({
+            "reference": reference,
+        }))
+    })?;
+
+    let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) else {
+        return Err(Error::new(
+            ErrorCode::ManifestInvalid,
+            "`Content-Type` header is required to push a manifest",
+        )
+        .with_detail(json!({
+            "supported": manifest::SUPPORTED_MANIFESTS,
+        })));
+    };
+
+    let repository = resolve(
+        &env,
+        current_session(&session),
+        owner.clone(),
+        repo.clone(),
+        Access::Push,
+    )
+    .await?;
+    let registry = Registry::new(&env.ds, repository.owner, repository.id);
+    let release = match helm_chart(&reference, &body)? {
+        Some((version, layer)) => {
+            let exists =
+                db::repository::release::get(&env.db, &repository, VersionOrUlid::Version(version.clone()))
+                    .await
+                    .map_err(release_error)?
+                    .is_some();
+
+            // pushing the same manifest again is allowed, but the release can't be
+            // replaced with a different chart.
+            if exists && registry.resolve(&reference).await? != Some(Digest::sha256(&body)) {
+                return Err(
+                    Error::new(ErrorCode::Denied, "release with the given version already exists")
+                        .with_status(StatusCode::CONFLICT)
+                        .with_detail(json!({"version": version})),
+                );
+            }
+
+            (!exists).then_some((version, layer))
+        }
+
+        None => None,
+    };
+
+    let (digest, subject) = registry.put_manifest(&reference, content_type, body).await?;
+    if let Some((version, layer)) = release {
+        let published = match registry.blob(&layer.digest).await? {
+            Some(tarball) => ops::releases::publish(&env, &cx, &repository, version, Tarball::Bytes(tarball))
+                .await
+                .map(|_| ())
+                .map_err(release_error),
+
+            None => Err(Error::new(
+                ErrorCode::ManifestBlobUnknown,
+                "manifest references a manifest or blob unknown to registry",
+            )
+            .with_detail(json!({"digest": layer.digest}))),
+        };
+
+        // the manifest is kept since other tags can point to it, but the tag isn't
+        // since the chart wasn't published.
+        if let Err(e) = published {
+            if let Err(e) = registry.delete_manifest(&reference).await {
+                warn!(error = %e.message, %reference, "failed to remove tag of helm chart that couldn't be published");
+            }
+
+            return Err(e);
+        }
+    }
+
+    let mut response = respond(
+        StatusCode::CREATED,
+        [
+            location(&owner, &repo, format!("manifests/{digest}")),
+            (header::CONTENT_LENGTH, "0".to_owned()),
+            (DOCKER_CONTENT_DIGEST, digest.to_string()),
+        ],
+        (),
+    )?;
+
+    // Lets clients know that the referrers API is supported, so they don't fall back to
+    // the tag schema.
+    if let Some(subject) = subject {
+        let value = subject.to_string().parse().map_err(Error::unknown)?;
+        response.headers_mut().insert(OCI_SUBJECT, value);
+    }
+
+    Ok(response)
+}
+
+/// `DELETE /v2/{owner}/{repo}/manifests/{reference}`
+///
+/// Releases that were tagged by the deleted manifest are deleted as well, the same way
+/// as `DELETE /repositories/{owner}/{repo}/releases/{version}` does.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn delete(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    cx: auditlog::Context,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+) -> Result<Response> {
+    let (owner, repo, reference) = params(path)?;
+    let Ok(reference) = reference.parse::<Reference>() else {
+        return Err(manifest_unknown(&reference));
+    };
+
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Delete).await?;
+    let Some(tags) = Registry::new(&env.ds, repository.owner, repository.id)
+        .delete_manifest(&reference)
+        .await?
+    else {
+        return Err(manifest_unknown(&reference.to_string()));
+    };
+
+    for tag in tags {
+        let Ok(version) = tag.replace('_', "+").parse::<Version>() else {
+            continue;
+        };
+
+        if let Some(model) =
+            db::repository::release::get_as_model(&env.db, &repository, VersionOrUlid::Version(version))
+                .await
+                .map_err(release_error)?
+        {
+            ops::releases::delete(&env, &cx, &repository, model)
+                .await
+                .map_err(release_error)?;
+        }
+    }
+
+    respond(StatusCode::ACCEPTED, [], ())
+}
+
+/// `GET /v2/{owner}/{repo}/tags/list`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn tags(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid)>, PathRejection>,
+    Query(query): Query<TagsQuery>,
+) -> Result<Response> {
+    let (owner, repo) = params(path)?;
+    let repository = resolve(
+        &env,
+        current_session(&session),
+        owner.clone(),
+        repo.clone(),
+        Access::Pull,
+    )
+    .await?;
+
+    let mut tags = Registry::new(&env.ds, repository.owner, repository.id).tags().await?;
+    if let Some(ref last) = query.last {
+        tags.retain(|tag| tag > last);
+    }
+
+    let truncated = query.n.is_some_and(|n| tags.len() > n);
+    if let Some(n) = query.n {
+        tags.truncate(n);
+    }
+
+    let mut response = respond(
+        StatusCode::OK,
+        [],
+        Json(json!({
+            "name": format!("{owner}/{repo}"),
+            "tags": tags,
+        })),
+    )?;
+
+    if truncated && let (Some(n), Some(last)) = (query.n, tags.last()) {
+        let link = format!(r#"</v2/{owner}/{repo}/tags/list?n={n}&last={last}>; rel="next""#);
+        response
+            .headers_mut()
+            .insert(header::LINK, link.parse().map_err(Error::unknown)?);
+    }
+
+    Ok(response)
+}
+
+/// `GET /v2/{owner}/{repo}/referrers/{digest}`
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn referrers(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    path: std::result::Result<Path<(NameOrUlid, NameOrUlid, String)>, PathRejection>,
+    Query(query): Query<ReferrersQuery>,
+) -> Result<Response> {
+    let (owner, repo, digest) = params(path)?;
+    let digest = parse_digest(&digest)?;
+    let repository = resolve(&env, current_session(&session), owner, repo, Access::Pull).await?;
+
+    let manifests = Registry::new(&env.ds, repository.owner, repository.id)
+        .referrers(&digest, query.artifact_type.as_deref())
+        .await?;
+
+    let mut response = respond(
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, manifest::IMAGE_INDEX.to_owned())],
+        Json(ImageIndex::new(manifests)),
+    )?;
+
+    if query.artifact_type.is_some() {
+        response
+            .headers_mut()
+            .insert(OCI_FILTERS_APPLIED, "artifactType".parse().map_err(Error::unknown)?);
+    }
+
+    Ok(response)
+}
+
+fn manifest_unknown(reference: &str) -> Error {
+    Error::new(ErrorCode::ManifestUnknown, "manifest unknown to registry").with_detail(json!({
+        "reference": reference,
+    }))
+}
+
+/// Returns the version and chart layer of a Helm chart manifest that is pushed with a
+/// tag. Helm tags charts with their version, where `+` is replaced with `_` since
+/// tags can't contain a `+`.
+fn helm_chart(reference: &Reference, body: &[u8]) -> Result<Option<(Version, Descriptor)>> {
+    let Reference::Tag(tag) = reference else {
+        return Ok(None);
+    };
+
+    // invalid manifests are rejected by `Registry::put_manifest`
+    let Ok(manifest) = serde_json::from_slice::<Manifest>(body) else {
+        return Ok(None);
+    };
+
+    if manifest.config.as_ref().map(|config| config.media_type.as_str()) != Some(manifest::HELM_CONFIG) {
+        return Ok(None);
+    }
+
+    let Some(layer) = manifest
+        .layers
+        .into_iter()
+        .find(|layer| layer.media_type == manifest::HELM_CHART_CONTENT)
+    else {
+        return Err(Error::new(
+            ErrorCode::ManifestInvalid,
+            "helm chart manifest doesn't have a chart layer",
+        )
+        .with_detail(json!({
+            "mediaType": manifest::HELM_CHART_CONTENT,
+        })));
+    };
+
+    let version = Version::parse(&tag.replace('_', "+")).map_err(|e| {
+        Error::new(
+            ErrorCode::ManifestInvalid,
+            "helm charts must be tagged with their version",
+        )
+        .with_detail(json!({
+            "tag": tag,
+            "error": e.to_string(),
+        }))
+    })?;
+
+    Ok(Some((version, layer)))
+}
+
+/// Converts a REST API error from publishing a release into a registry error.
+fn release_error(response: api::Response) -> Error {
+    let status = response.response.status();
+    let Some(error) = response.errors.into_iter().next() else {
+        return Error::unknown(status);
+    };
+
+    if status.is_server_error() {
+        return Error::unknown(error.message);
+    }
+
+    let code = match status {
+        StatusCode::CONFLICT | StatusCode::FORBIDDEN => ErrorCode::Denied,
+        _ => ErrorCode::ManifestInvalid,
+    };
+
+    let details = error.details;
+    let error = Error::new(code, error.message).with_status(status);
+    match details {
+        Some(detail) => error.with_detail(detail),
+        None => error,
+    }
+}
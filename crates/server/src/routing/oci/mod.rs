@@ -0,0 +1,249 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Implementation of the [OCI Distribution Specification v1.1] for Helm charts, which is
+//! only avaliable if the OCI registry feature is enabled.
+//!
+//! OCI repository names are in the form of `{owner}/{repo}` and map onto the charted
+//! repositories that have the same owner and name. Anyone can pull from a public repository
+//! while pushing requires the user to own the repository or to be a member that can update
+//! its metadata.
+//!
+//! [OCI Distribution Specification v1.1]: https://github.com/opencontainers/distribution-spec/blob/v1.1.1/spec.md
+
+pub mod blobs;
+pub mod manifests;
+
+use crate::{
+    Env, OwnerExt,
+    middleware::authn::{Factory, Options, Session},
+    ops,
+};
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, rejection::PathRejection},
+    handler::Handler,
+    http::{HeaderName, HeaderValue, header},
+    response::{IntoResponse, Response},
+    routing,
+};
+use charted_core::bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission};
+use charted_database::entities::{RepositoryEntity, repository};
+use charted_feature_oci::error::{Error, ErrorCode, Result};
+use charted_types::{NameOrUlid, Owner, Repository};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+
+pub(crate) const DOCKER_CONTENT_DIGEST: HeaderName = HeaderName::from_static("docker-content-digest");
+pub(crate) const DOCKER_UPLOAD_UUID: HeaderName = HeaderName::from_static("docker-upload-uuid");
+pub(crate) const OCI_SUBJECT: HeaderName = HeaderName::from_static("oci-subject");
+pub(crate) const OCI_FILTERS_APPLIED: HeaderName = HeaderName::from_static("oci-filters-applied");
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    // Authentication is optional on all routes so that the registry can send a challenge
+    // to clients that haven't sent their credentials yet.
+    let pull = Options {
+        allow_unauthorized: true,
+        scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+        ..Default::default()
+    };
+
+    let push = Options {
+        allow_unauthorized: true,
+        scopes: ApiKeyScopes::new(ApiKeyScope::RepoReleaseCreate.into()),
+        ..Default::default()
+    };
+
+    let delete = Options {
+        allow_unauthorized: true,
+        scopes: ApiKeyScopes::new(ApiKeyScope::RepoReleaseDelete.into()),
+        ..Default::default()
+    };
+
+    Router::new()
+        .route("/", routing::get(main))
+        .route(
+            "/{owner}/{repo}/blobs/{digest}",
+            routing::get(blobs::fetch.layer(env.authn(pull.clone())))
+                .delete(blobs::delete.layer(env.authn(delete.clone()))),
+        )
+        .route(
+            "/{owner}/{repo}/blobs/uploads",
+            routing::post(blobs::start_upload.layer(env.authn(push.clone()))),
+        )
+        .route(
+            "/{owner}/{repo}/blobs/uploads/",
+            routing::post(blobs::start_upload.layer(env.authn(push.clone()))),
+        )
+        .route(
+            "/{owner}/{repo}/blobs/uploads/{id}",
+            routing::get(blobs::upload_status.layer(env.authn(push.clone())))
+                .patch(blobs::upload_chunk.layer(env.authn(push.clone())))
+                .put(blobs::complete_upload.layer(env.authn(push.clone())))
+                .delete(blobs::cancel_upload.layer(env.authn(push.clone()))),
+        )
+        .route(
+            "/{owner}/{repo}/manifests/{reference}",
+            routing::get(manifests::fetch.layer(env.authn(pull.clone())))
+                .put(manifests::put.layer(env.authn(push)))
+                .delete(manifests::delete.layer(env.authn(delete))),
+        )
+        .route(
+            "/{owner}/{repo}/tags/list",
+            routing::get(manifests::tags.layer(env.authn(pull.clone()))),
+        )
+        .route(
+            "/{owner}/{repo}/referrers/{digest}",
+            routing::get(manifests::referrers.layer(env.authn(pull))),
+        )
+}
+
+/// `GET /v2/`: lets clients check if the registry implements the distribution specification.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn main() -> impl IntoResponse {
+    (
+        [(
+            HeaderName::from_static("docker-distribution-api-version"),
+            HeaderValue::from_static("registry/2.0"),
+        )],
+        Json(json!({})),
+    )
+}
+
+/// What a user wants to do with a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Pull,
+    Push,
+    Delete,
+}
+
+/// Resolves the repository behind the `{owner}/{repo}` name and checks that the user is
+/// allowed to access it.
+///
+/// Anonymous users are challenged for credentials if the repository is not public, which
+/// also happens if the repository doesn't exist so that private repositories are
+/// indistinguishable from missing ones.
+pub(crate) async fn resolve(
+    env: &Env,
+    session: Option<&Session>,
+    owner: NameOrUlid,
+    repo: NameOrUlid,
+    access: Access,
+) -> Result<Repository> {
+    let name = format!("{owner}/{repo}");
+    let unknown = || match session {
+        Some(_) => {
+            Error::new(ErrorCode::NameUnknown, "repository name not known to registry").with_detail(json!({
+                "name": name,
+            }))
+        }
+
+        None => unauthorized(),
+    };
+
+    let Some(owner) = Owner::query_by_id_or_name(env, owner).await.map_err(Error::unknown)? else {
+        return Err(unknown());
+    };
+
+    let query = match repo {
+        NameOrUlid::Ulid(id) => RepositoryEntity::find_by_id(id),
+        NameOrUlid::Name(name) => RepositoryEntity::find().filter(repository::Column::Name.eq(name)),
+    };
+
+    let Some(repository) = query
+        .filter(repository::Column::Owner.eq(owner.id()))
+        .one(&env.db)
+        .await
+        .map_err(Error::unknown)?
+        .map(Into::<Repository>::into)
+    else {
+        return Err(unknown());
+    };
+
+    let permissions = match session {
+        Some(session) => ops::members::permissions(env, &session.user, &owner, Some(repository.id))
+            .await
+            .map_err(Error::unknown)?,
+
+        None => None,
+    };
+
+    let (permission, action) = match access {
+        Access::Pull => {
+            return match !repository.private || permissions.is_some() {
+                true => Ok(repository),
+                false => Err(unknown()),
+            };
+        }
+
+        Access::Push => (MemberPermission::MetadataUpdate, "push to"),
+        Access::Delete => (MemberPermission::MetadataDelete, "delete from"),
+    };
+
+    match permissions {
+        Some(permissions) if permissions.contains(permission) => Ok(repository),
+        _ if session.is_none() => Err(unauthorized()),
+        None if repository.private => Err(unknown()),
+        _ => Err(Error::new(
+            ErrorCode::Denied,
+            format!("you do not have permission to {action} this repository"),
+        )
+        .with_detail(json!({
+            "name": name,
+        }))),
+    }
+}
+
+/// Maps a [`PathRejection`] into a `NAME_INVALID` error.
+pub(crate) fn params<T>(path: std::result::Result<Path<T>, PathRejection>) -> Result<T> {
+    path.map(|Path(params)| params).map_err(|e| {
+        Error::new(ErrorCode::NameInvalid, "invalid repository name").with_detail(json!({
+            "error": e.body_text(),
+        }))
+    })
+}
+
+/// Returns the user's session, if the request was authenticated.
+pub(crate) fn current_session(session: &Option<Extension<Session>>) -> Option<&Session> {
+    session.as_ref().map(|Extension(session)| session)
+}
+
+fn unauthorized() -> Error {
+    Error::new(ErrorCode::Unauthorized, "authentication required")
+}
+
+/// Builds a response with the given status, headers and body.
+pub(crate) fn respond<const N: usize>(
+    status: axum::http::StatusCode,
+    headers: [(HeaderName, String); N],
+    body: impl IntoResponse,
+) -> Result<Response> {
+    let mut response = (status, body).into_response();
+    for (name, value) in headers {
+        let value = HeaderValue::from_str(&value).map_err(Error::unknown)?;
+        response.headers_mut().insert(name, value);
+    }
+
+    Ok(response)
+}
+
+pub(crate) fn location(
+    owner: &NameOrUlid,
+    repo: &NameOrUlid,
+    rest: impl std::fmt::Display,
+) -> (HeaderName, String) {
+    (header::LOCATION, format!("/v2/{owner}/{repo}/{rest}"))
+}
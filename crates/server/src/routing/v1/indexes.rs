@@ -13,8 +13,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Env, OwnerExt, ext::ResultExt, extract::Path, mk_api_response_types, mk_into_responses};
-use axum::{extract::State, http::StatusCode};
+use crate::{
+    Env, OwnerExt, ext::ResultExt, extract::Path, middleware::authn::Session, mk_api_response_types,
+    mk_into_responses, ops,
+};
+use axum::{Extension, extract::State, http::StatusCode};
 use charted_core::api::{self, Yaml};
 use charted_helm_charts::DataStoreExt;
 use charted_helm_types::ChartIndex;
@@ -31,6 +34,8 @@ mk_into_responses!(for ChartIndexResponse {
     "5XX" => [error(description("Internal Server Error"))];
 });
 
+/// Returns the `index.yaml` of a user or organization. Private repositories are only
+/// included for their owners and members.
 #[axum::debug_handler]
 #[utoipa::path(
     get,
@@ -42,6 +47,7 @@ mk_into_responses!(for ChartIndexResponse {
 )]
 pub async fn fetch(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path(id_or_name): Path<NameOrUlid>,
 ) -> Result<Yaml<ChartIndex>, api::Response> {
     let Some(owner) = Owner::query_by_id_or_name(&env, id_or_name)
@@ -57,6 +63,14 @@ pub async fn fetch(
         ));
     };
 
+    if let Some(Extension(Session { ref user, .. })) = session &&
+        let Some(index) = ops::indexes::visible(&env, &owner, user)
+            .await
+            .map_err(api::system_failure_from_report)?
+    {
+        return Ok(Yaml::new(StatusCode::OK, index));
+    }
+
     let metadata = env.ds.metadata();
     let index = metadata
         .get_chart_index(owner.id())
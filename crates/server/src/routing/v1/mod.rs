@@ -26,9 +26,16 @@ pub mod organization;
 pub mod repository;
 pub mod user;
 
-use crate::{Env, mk_api_response_types, mk_into_responses};
-use axum::{Router, routing};
-use charted_core::VERSION;
+use crate::{
+    Env,
+    middleware::authn::{Factory, Options},
+    mk_api_response_types, mk_into_responses,
+};
+use axum::{Router, handler::Handler, routing};
+use charted_core::{
+    VERSION,
+    bitflags::{ApiKeyScope, ApiKeyScopes},
+};
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -63,11 +70,24 @@ mk_into_responses!(for Entrypoint {
 pub fn create_router(env: &Env) -> Router<Env> {
     let mut router = Router::new()
         .nest("/users", user::create_router(env))
-        .route("/indexes/{idOrName}", routing::get(indexes::fetch))
+        .nest("/repositories", repository::create_router(env))
+        .route(
+            "/indexes/{idOrName}",
+            routing::get(indexes::fetch.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+                require_refresh_token: false,
+            }))),
+        )
         .route("/openapi.json", routing::get(openapi::openapi))
         .route("/healthz", routing::get(healthz::healthz))
         .route("/", routing::get(main::main));
 
+    // organizations don't exist on instances that only serve a single user.
+    if !env.config.single_user {
+        router = router.nest("/organizations", organization::create_router(env));
+    }
+
     if let Some(metrics) = env.config.metrics.as_prometheus() &&
         metrics.standalone.is_none()
     {
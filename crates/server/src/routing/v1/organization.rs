@@ -14,4 +14,569 @@
 // limitations under the License.
 
 pub mod icon;
+pub mod invites;
+pub mod members;
 pub mod repositories;
+pub mod webhooks;
+
+use crate::{
+    Env, OwnerExt, commit_patch,
+    ext::ResultExt,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListOrganizationResponse, OrganizationResponse},
+    ops::{self, auditlog, db},
+    pagination::PaginationRequest,
+    routing::v1::repository::can_modify,
+    util::{self, BuildLinkHeaderOpts},
+};
+use axum::{
+    Extension, Router,
+    extract::State,
+    handler::Handler,
+    http::{HeaderValue, StatusCode, header},
+    routing,
+};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission},
+    clamp,
+};
+use charted_database::entities::{
+    OrganizationEntity, OrganizationMemberEntity, RepositoryEntity, organization, repository,
+};
+use charted_feature_audit_logs::Action;
+use charted_helm_charts::DataStoreExt;
+use charted_types::{
+    NameOrUlid, Organization, Owner, Repository, User,
+    payloads::{CreateOrganizationPayload, PatchOrganizationPayload},
+};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, EntityTrait, IntoActiveModel, PaginatorTrait,
+    QueryFilter, QueryOrder, sea_query::Query,
+};
+use serde_json::json;
+use std::borrow::Cow;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    let maybe_authenticated = Options {
+        allow_unauthorized: true,
+        scopes: ApiKeyScopes::new(ApiKeyScope::OrgAccess.into()),
+        require_refresh_token: false,
+    };
+
+    let mut router = Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(maybe_authenticated.clone())))
+                .put(create.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgCreate)))),
+        )
+        .route(
+            "/{idOrName}",
+            routing::get(fetch.layer(env.authn(maybe_authenticated)))
+                .patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgUpdate))))
+                .delete(delete.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgDelete)))),
+        )
+        .route(
+            "/{idOrName}/icon",
+            routing::get(icon::get_org_icon).post(
+                icon::upload_org_icon.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgUpdate))),
+            ),
+        )
+        .route("/{idOrName}/icons/{hash}", routing::get(icon::get_org_icon_by_hash))
+        .nest("/{idOrName}/invites", invites::create_router(env))
+        .nest("/{idOrName}/members", members::create_router(env));
+
+    if env.features.has::<charted_feature_webhooks::Feature>() {
+        router = router.nest("/{idOrName}/webhooks", webhooks::create_router(env));
+    }
+
+    router
+}
+
+/// Returns `true` if `user` can see `org`. Private organizations are only visible
+/// to their owner and members.
+async fn can_view(env: &Env, org: &organization::Model, user: Option<&User>) -> Result<bool, api::Response> {
+    if !org.private {
+        return Ok(true);
+    }
+
+    let Some(user) = user else {
+        return Ok(false);
+    };
+
+    ops::members::permissions(env, user, &Owner::Organization(org.clone().into()), None)
+        .await
+        .map(|permissions| permissions.is_some())
+        .into_system_failure()
+}
+
+/// Resolves an organization that `user` can see, or a `404 Not Found` response
+/// otherwise.
+pub(crate) async fn resolve(
+    env: &Env,
+    id_or_name: NameOrUlid,
+    user: Option<&User>,
+) -> Result<organization::Model, api::Response> {
+    match db::organization::as_model(&env.db, id_or_name.clone()).await? {
+        Some(org) if can_view(env, &org, user).await? => Ok(org),
+        _ => Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "organization with id or name was not found",
+                json!({"idOrName":id_or_name}),
+            ),
+        )),
+    }
+}
+
+/// Fetches an organization that `user` has `permission` in, or that `user` owns if
+/// `permission` is [`None`].
+pub(crate) async fn fetch_modifiable(
+    env: &Env,
+    user: &User,
+    id_or_name: NameOrUlid,
+    permission: Option<MemberPermission>,
+) -> Result<organization::Model, api::Response> {
+    let org = resolve(env, id_or_name, Some(user)).await?;
+
+    let owner = Owner::Organization(org.clone().into());
+    match permission {
+        Some(permission) => {
+            ops::members::require(env, user, &owner, None, permission).await?;
+        }
+
+        None if !can_modify(&owner, user) => {
+            return Err(api::err(
+                StatusCode::FORBIDDEN,
+                (
+                    api::ErrorCode::AccessNotPermitted,
+                    "only the owner of this organization can do this",
+                    json!({"organization":org.id}),
+                ),
+            ));
+        }
+
+        None => {}
+    }
+
+    Ok(org)
+}
+
+struct ListOrganizationsR;
+mk_into_responses!(for ListOrganizationsR {
+    "200" => [ref(ListOrganizationResponse)];
+});
+
+/// Lists all the public organizations, and the private organizations that the
+/// authenticated user owns or is a member of.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/organizations",
+    operation_id = "listOrganizations",
+    tag = "Organizations",
+    params(PaginationRequest),
+    responses(ListOrganizationsR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Query(PaginationRequest {
+        per_page,
+        order_by,
+        page,
+    }): Query<PaginationRequest>,
+) -> api::Result<Vec<Organization>> {
+    let mut visible = Condition::any().add(organization::Column::Private.eq(false));
+    if let Some(Extension(Session { ref user, .. })) = session {
+        visible = visible.add(organization::Column::Owner.eq(user.id)).add(
+            organization::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization::member::Column::Organization)
+                    .from(OrganizationMemberEntity)
+                    .and_where(organization::member::Column::Account.eq(user.id))
+                    .to_owned(),
+            ),
+        );
+    }
+
+    let per_page = clamp(per_page, 10, 100).unwrap_or(10);
+    let paginator = OrganizationEntity::find()
+        .filter(visible)
+        .order_by(organization::Column::Id, order_by.into_sea_orm())
+        .paginate(&env.db, per_page as u64);
+
+    let pages = paginator.num_pages().await.into_system_failure()?;
+    let entries = paginator
+        .fetch_page(page.saturating_sub(1) as u64)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<Organization>::into)
+        .collect::<Vec<_>>();
+
+    let mut link_hdr = String::new();
+    util::build_link_header(&mut link_hdr, BuildLinkHeaderOpts {
+        entries: entries.len(),
+        current: page,
+        per_page,
+        max_pages: pages,
+        resource: env.config.base_url.unwrap().join("/organizations").unwrap(),
+    })
+    .into_system_failure()?;
+
+    let mut response = api::ok(StatusCode::OK, entries);
+    if !link_hdr.is_empty() {
+        response = response.with_header(header::LINK, HeaderValue::from_bytes(link_hdr.as_bytes()).unwrap());
+    }
+
+    Ok(response)
+}
+
+struct CreateOrganizationR;
+mk_into_responses!(for CreateOrganizationR {
+    "201" => [ref(OrganizationResponse)];
+    "403" => [error(description("instance only allows a single organization, which already exists"))];
+    "406" => [error(description("`displayName` was longer than 64 characters"))];
+    "406" => [error(description("a field failed validation"))];
+    "409" => [error(description("a user or organization with `name` already exists"))];
+});
+
+/// Creates an organization that is owned by the authenticated user.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+    path = "/v1/organizations",
+    operation_id = "createOrganization",
+    tag = "Organizations",
+    request_body(
+        content = ref("#/components/schemas/CreateOrganizationPayload"),
+        description = "Request body for creating a new organization",
+        content_type = "application/json"
+    ),
+    responses(CreateOrganizationR),
+    security(
+        ("ApiKey" = ["org:create"])
+    )
+)]
+pub async fn create(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Json(CreateOrganizationPayload {
+        display_name,
+        private,
+        name,
+    }): Json<CreateOrganizationPayload>,
+) -> api::Result<Organization> {
+    if env.config.single_org && OrganizationEntity::find().count(&env.db).await.into_system_failure()? > 0 {
+        return Err(api::err(
+            StatusCode::FORBIDDEN,
+            (
+                api::ErrorCode::AccessNotPermitted,
+                "this instance only allows a single organization",
+            ),
+        ));
+    }
+
+    if let Some(display_name) = display_name.as_deref() &&
+        display_name.len() > 64
+    {
+        return Err(api::err(
+            StatusCode::NOT_ACCEPTABLE,
+            (
+                api::ErrorCode::ValidationFailed,
+                "`displayName` was expected to be 64 characters or shorter",
+                json!({"expected":64,"received":display_name.len()}),
+            ),
+        ));
+    }
+
+    // users and organizations share the same namespace for names, since
+    // both are resolved by `/repositories/{owner}/{repo}`.
+    if Owner::query_by_name(&env, name.clone())
+        .await
+        .into_system_failure()?
+        .is_some()
+    {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "a user or organization with `name` already exists",
+                json!({"name":name.as_str()}),
+            ),
+        ));
+    }
+
+    let id = env.ulid.generate().into_system_failure()?;
+    let now = Utc::now();
+    let model = organization::Model {
+        verified_publisher: false,
+        prefers_gravatar: false,
+        gravatar_email: None,
+        display_name: display_name.filter(|name| !name.is_empty()),
+        created_at: now,
+        updated_at: now,
+        icon_hash: None,
+        private,
+        owner: user.id,
+        name,
+        id: id.into(),
+    };
+
+    OrganizationEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .inspect_err(|e| {
+            error!(error = %e, organization.name = %model.name, organization.owner = %user.id, "failed to create organization");
+            sentry::capture_error(e);
+        })
+        .map_err(api::system_failure)?;
+
+    let metadata = env.ds.metadata();
+    if let Err(e) = metadata.create_chart_index(model.id).await {
+        error!(error = %e, organization.id = %model.id, "failed to create chart index, retrying later...");
+        sentry::capture_error(&*e);
+    }
+
+    let org = Organization::from(model);
+    auditlog::record(
+        &env,
+        &cx,
+        Action::OrganizationCreated,
+        auditlog::owner(org.id),
+        (),
+        &org,
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, org))
+}
+
+struct FetchOrganizationR;
+mk_into_responses!(for FetchOrganizationR {
+    "200" => [ref(OrganizationResponse)];
+    "400" => [error(description("Invalid ULID or name specified"))];
+    "404" => [error(description("Organization not found"))];
+});
+
+/// Retrieve a single organization by its ID or name.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{idOrName}",
+    operation_id = "getOrganizationByIdOrName",
+    tag = "Organizations",
+    params(NameOrUlid),
+    responses(FetchOrganizationR)
+)]
+pub async fn fetch(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path(id_or_name): Path<NameOrUlid>,
+) -> api::Result<Organization> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    resolve(&env, id_or_name, user)
+        .await
+        .map(|org| api::ok(StatusCode::OK, org.into()))
+}
+
+struct PatchOrganizationR;
+mk_into_responses!(for PatchOrganizationR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("Patch was successful");
+    )];
+
+    "403" => [error(description("user is not allowed to modify this organization"))];
+    "404" => [error(description("Organization not found"))];
+    "409" => [error(description("a user or organization with `name` already exists"))];
+});
+
+/// Patch an organization's metadata.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+    path = "/v1/organizations/{idOrName}",
+    operation_id = "patchOrganization",
+    tag = "Organizations",
+    request_body(
+        content_type = "application/json",
+        description = "Payload object for patching organization metadata",
+        content = ref("#/components/schemas/PatchOrganizationPayload")
+    ),
+    params(NameOrUlid),
+    responses(PatchOrganizationR),
+    security(
+        ("ApiKey" = ["org:update"])
+    )
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id_or_name): Path<NameOrUlid>,
+    Json(PatchOrganizationPayload {
+        prefers_gravatar,
+        gravatar_email,
+        display_name,
+        private,
+        name,
+    }): Json<PatchOrganizationPayload>,
+) -> api::Result<()> {
+    let org = fetch_modifiable(&env, &user, id_or_name, Some(MemberPermission::MetadataUpdate)).await?;
+    let mut model = org.clone().into_active_model();
+    let mut errors = Vec::new();
+
+    commit_patch!(model of bool: old.prefers_gravatar => prefers_gravatar; [org]);
+    commit_patch!(model of string?: old.gravatar_email => gravatar_email);
+    commit_patch!(model of string?: old.display_name => display_name; validate that len < 64 [errors]);
+    commit_patch!(model of bool: old.private => private; [org]);
+
+    if let Some(name) = name &&
+        name != org.name
+    {
+        if Owner::query_by_name(&env, name.clone())
+            .await
+            .into_system_failure()?
+            .is_some()
+        {
+            errors.push(api::Error {
+                code: api::ErrorCode::EntityAlreadyExists,
+                message: Cow::Borrowed("a user or organization already exists with that name"),
+                details: Some(json!({
+                    "path": "name",
+                    "name": &name
+                })),
+            });
+        } else {
+            model.name = ActiveValue::set(name);
+        }
+    }
+
+    if !errors.is_empty() {
+        // name conflicts take precedence over fields that failed validation
+        let status = match errors
+            .iter()
+            .any(|error| matches!(error.code, api::ErrorCode::EntityAlreadyExists))
+        {
+            true => StatusCode::CONFLICT,
+            false => StatusCode::NOT_ACCEPTABLE,
+        };
+
+        return Err(api::Response {
+            errors,
+            ..api::empty(false, status)
+        });
+    }
+
+    model.updated_at = ActiveValue::set(Utc::now());
+    let updated = model
+        .update(&env.db)
+        .await
+        .inspect_err(|e| {
+            error!(error = %e, organization.id = %org.id, "failed to commit changes for patch");
+            sentry::capture_error(e);
+        })
+        .map_err(api::system_failure)?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::OrganizationUpdated,
+        auditlog::owner(org.id),
+        Organization::from(org),
+        Organization::from(updated),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+/// Deletes an organization, alongside every repository and chart that it owns.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/organizations/{idOrName}",
+    operation_id = "deleteOrganization",
+    tag = "Organizations",
+    params(NameOrUlid),
+    responses(
+        (
+            status = 204,
+            description = "Organization has been deleted",
+            body = EmptyApiResponse,
+            content_type = "application/json"
+        )
+    ),
+    security(
+        ("ApiKey" = ["org:delete"])
+    )
+)]
+pub async fn delete(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id_or_name): Path<NameOrUlid>,
+) -> api::Result<()> {
+    let org = fetch_modifiable(&env, &user, id_or_name, None).await?;
+    let repositories = RepositoryEntity::find()
+        .filter(repository::Column::Owner.eq(org.id))
+        .all(&env.db)
+        .await
+        .into_system_failure()?;
+
+    for model in repositories {
+        let repository = Repository::from(model);
+        if let Err(e) = env.ds.owner_repo(repository.owner, repository.id).delete_all().await {
+            error!(error = %e, repository.id = %repository.id, "failed to delete charts of repository");
+            sentry::capture_error(&*e);
+        }
+
+        RepositoryEntity::delete_by_id(repository.id)
+            .exec(&env.db)
+            .await
+            .into_system_failure()?;
+
+        auditlog::record(
+            &env,
+            &cx,
+            Action::RepositoryDeleted,
+            auditlog::repository(&repository),
+            &repository,
+            (),
+        )
+        .await;
+    }
+
+    if let Err(e) = env.ds.metadata().delete_chart_index(org.id).await {
+        error!(error = %e, organization.id = %org.id, "failed to delete chart index of organization");
+        sentry::capture_error(&*e);
+    }
+
+    OrganizationEntity::delete_by_id(org.id)
+        .exec(&env.db)
+        .await
+        .inspect_err(|e| {
+            error!(error = %e, organization.id = %org.id, "failed to delete organization");
+            sentry::capture_error(e);
+        })
+        .map_err(api::system_failure)?;
+
+    let org = Organization::from(org);
+    auditlog::record(
+        &env,
+        &cx,
+        Action::OrganizationDeleted,
+        auditlog::owner(org.id),
+        &org,
+        (),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
@@ -0,0 +1,329 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Members of a single organization. Organization members have their permissions
+//! applied to every repository that the organization owns.
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListOrganizationMemberResponse},
+    ops::{self, auditlog, db},
+    pagination::PaginationRequest,
+    util::{self, BuildLinkHeaderOpts},
+};
+use axum::{
+    Extension, Router,
+    extract::State,
+    handler::Handler,
+    http::{HeaderValue, StatusCode, header},
+    routing,
+};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission},
+    clamp,
+};
+use charted_database::entities::{
+    OrganizationMemberEntity,
+    organization::{self, member},
+};
+use charted_feature_audit_logs::Action;
+use charted_feature_webhooks::{Target, event::EventKind};
+use charted_types::{NameOrUlid, OrganizationMember, Owner, payloads::PatchMemberPayload};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+    QueryOrder,
+};
+use serde_json::json;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::OrgMemberList.into()),
+                require_refresh_token: false,
+            }))),
+        )
+        .route(
+            "/{member}",
+            routing::patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgMemberUpdate))))
+                .delete(kick.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgMemberKick)))),
+        )
+}
+
+/// Finds the member of `org` whose user account is `id_or_name`.
+async fn find(
+    env: &Env,
+    org: &organization::Model,
+    id_or_name: NameOrUlid,
+) -> Result<member::Model, api::Response> {
+    let not_found = || {
+        api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "organization member with id or name was not found",
+                json!({"member":&id_or_name}),
+            ),
+        )
+    };
+
+    let Some(user) = db::user::get_as_model(&env.db, id_or_name.clone()).await? else {
+        return Err(not_found());
+    };
+
+    OrganizationMemberEntity::find()
+        .filter(member::Column::Organization.eq(org.id))
+        .filter(member::Column::Account.eq(user.id))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .ok_or_else(not_found)
+}
+
+struct ListMembersR;
+mk_into_responses!(for ListMembersR {
+    "200" => [ref(ListOrganizationMemberResponse)];
+    "404" => [error(description("organization was not found"))];
+});
+
+/// Lists all the members of this organization.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/organizations/{idOrName}/members",
+    operation_id = "listOrganizationMembers",
+    tags = ["Organizations", "Organization/Members"],
+    params(NameOrUlid, PaginationRequest),
+    responses(ListMembersR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path(org): Path<NameOrUlid>,
+    Query(PaginationRequest {
+        per_page,
+        order_by,
+        page,
+    }): Query<PaginationRequest>,
+) -> api::Result<Vec<OrganizationMember>> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let org = super::resolve(&env, org, user).await?;
+
+    let per_page = clamp(per_page, 10, 100).unwrap_or(10);
+    let paginator = OrganizationMemberEntity::find()
+        .filter(member::Column::Organization.eq(org.id))
+        .order_by(member::Column::Id, order_by.into_sea_orm())
+        .paginate(&env.db, per_page as u64);
+
+    let pages = paginator.num_pages().await.into_system_failure()?;
+    let entries = paginator
+        .fetch_page(page.saturating_sub(1) as u64)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<OrganizationMember>::into)
+        .collect::<Vec<_>>();
+
+    let mut link_hdr = String::new();
+    util::build_link_header(&mut link_hdr, BuildLinkHeaderOpts {
+        entries: entries.len(),
+        current: page,
+        per_page,
+        max_pages: pages,
+        resource: env
+            .config
+            .base_url
+            .unwrap()
+            .join(&format!("/organizations/{}/members", org.id))
+            .unwrap(),
+    })
+    .into_system_failure()?;
+
+    let mut response = api::ok(StatusCode::OK, entries);
+    if !link_hdr.is_empty() {
+        response = response.with_header(header::LINK, HeaderValue::from_bytes(link_hdr.as_bytes()).unwrap());
+    }
+
+    Ok(response)
+}
+
+struct PatchMemberR;
+mk_into_responses!(for PatchMemberR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("patch was applied to the member");
+    )];
+
+    "403" => [error(description("user is not allowed to update members, or granted permissions that they don't have"))];
+    "404" => [error(description("organization or member was not found"))];
+});
+
+/// Updates a member's display name or permissions.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+    path = "/v1/organizations/{idOrName}/members/{member}",
+    operation_id = "patchOrganizationMember",
+    tags = ["Organizations", "Organization/Members"],
+    params(
+        ("idOrName" = NameOrUlid, Path, description = "ID or name of the organization"),
+        ("member" = NameOrUlid, Path, description = "ID or username of the member's user account")
+    ),
+    request_body(
+        content_type = "application/json",
+        description = "Payload object for patching a member",
+        content = ref("#/components/schemas/PatchMemberPayload")
+    ),
+    responses(PatchMemberR),
+    security(
+        ("ApiKey" = ["org:members:update"])
+    )
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((org, id_or_name)): Path<(NameOrUlid, NameOrUlid)>,
+    Json(PatchMemberPayload {
+        display_name,
+        permissions,
+    }): Json<PatchMemberPayload>,
+) -> api::Result<()> {
+    let org = super::resolve(&env, org, Some(&user)).await?;
+    let actor = ops::members::require(
+        &env,
+        &user,
+        &Owner::Organization(org.clone().into()),
+        None,
+        MemberPermission::MemberUpdate,
+    )
+    .await?;
+
+    let member = find(&env, &org, id_or_name).await?;
+    let mut model = member.clone().into_active_model();
+
+    if let Some(display_name) = display_name {
+        if display_name.len() > 64 {
+            return Err(api::err(
+                StatusCode::NOT_ACCEPTABLE,
+                (
+                    api::ErrorCode::ValidationFailed,
+                    "`displayName` was expected to be 64 characters or shorter",
+                    json!({"expected":64,"received":display_name.len()}),
+                ),
+            ));
+        }
+
+        model.display_name = ActiveValue::set(Some(display_name).filter(|name| !name.is_empty()));
+    }
+
+    if let Some(permissions) = permissions {
+        let permissions = ops::members::check_grant(actor, permissions)?;
+        model.permissions = ActiveValue::set(permissions.value() as i64);
+    }
+
+    model.updated_at = ActiveValue::set(Utc::now());
+    let updated = OrganizationMember::from(model.update(&env.db).await.into_system_failure()?);
+
+    ops::webhooks::emit(
+        &env,
+        Target {
+            owner: org.id,
+            repository: None,
+        },
+        EventKind::MemberUpdated,
+        &updated,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::MemberUpdated,
+        auditlog::owner(org.id),
+        OrganizationMember::from(member),
+        &updated,
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+/// Kicks a member from this organization. Members can always kick themselves to leave
+/// the organization.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/organizations/{idOrName}/members/{member}",
+    operation_id = "kickOrganizationMember",
+    tags = ["Organizations", "Organization/Members"],
+    params(
+        ("idOrName" = NameOrUlid, Path, description = "ID or name of the organization"),
+        ("member" = NameOrUlid, Path, description = "ID or username of the member's user account")
+    ),
+    responses(
+        (
+            status = 204,
+            description = "Member was kicked from the organization",
+            body = EmptyApiResponse,
+            content_type = "application/json"
+        )
+    ),
+    security(
+        ("ApiKey" = ["org:members:kick"])
+    )
+)]
+pub async fn kick(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((org, id_or_name)): Path<(NameOrUlid, NameOrUlid)>,
+) -> api::Result<()> {
+    let org = super::resolve(&env, org, Some(&user)).await?;
+    let member = find(&env, &org, id_or_name).await?;
+
+    if member.account != user.id {
+        let owner = Owner::Organization(org.clone().into());
+        ops::members::require(&env, &user, &owner, None, MemberPermission::MemberKick).await?;
+    }
+
+    OrganizationMemberEntity::delete_by_id(member.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    let member = OrganizationMember::from(member);
+    ops::webhooks::emit(
+        &env,
+        Target {
+            owner: org.id,
+            repository: None,
+        },
+        EventKind::MemberRemoved,
+        &member,
+    )
+    .await;
+
+    auditlog::record(&env, &cx, Action::MemberRemoved, auditlog::owner(org.id), &member, ()).await;
+
+    Ok(api::no_content())
+}
@@ -0,0 +1,268 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Webhooks that receive the events of every repository that an organization owns.
+//! Only the organization's owner and members with the `webhooks:*` permissions can
+//! manage its webhooks.
+
+use crate::{
+    Env,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListWebhookDeliveryResponse, ListWebhookResponse, WebhookResponse},
+    ops::{self, auditlog, db},
+    pagination::PaginationRequest,
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, MemberPermission},
+};
+use charted_feature_webhooks::{CreateWebhookPayload, PatchWebhookPayload, Target, Webhook, WebhookDelivery};
+use charted_types::{NameOrUlid, Owner, Ulid, User};
+use serde_json::json;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookList))))
+                .put(create.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookCreate)))),
+        )
+        .route(
+            "/{id}",
+            routing::get(fetch.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookList))))
+                .patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookUpdate))))
+                .delete(delete.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookDelete)))),
+        )
+        .route(
+            "/{id}/deliveries",
+            routing::get(
+                deliveries.layer(env.authn(Options::default().with_scope(ApiKeyScope::OrgWebhookEventList))),
+            ),
+        )
+}
+
+/// Resolves the webhook [`Target`] of the organization by `id_or_name`, if `user`
+/// has `permission` in it.
+async fn target(
+    env: &Env,
+    user: &User,
+    id_or_name: NameOrUlid,
+    permission: MemberPermission,
+) -> Result<Target, api::Response> {
+    let Some(org) = db::organization::get(&env.db, id_or_name.clone()).await? else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "organization with id or name was not found",
+                json!({"idOrName":id_or_name}),
+            ),
+        ));
+    };
+
+    let id = org.id;
+    ops::members::require(env, user, &Owner::Organization(org), None, permission).await?;
+
+    Ok(Target {
+        owner: id,
+        repository: None,
+    })
+}
+
+struct ListWebhooksR;
+mk_into_responses!(for ListWebhooksR {
+    "200" => [ref(ListWebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization was not found"))];
+});
+
+/// Lists all the webhooks of this organization.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/organizations/{idOrName}/webhooks",
+    operation_id = "listOrganizationWebhooks",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid),
+    responses(ListWebhooksR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path(id_or_name): Path<NameOrUlid>,
+) -> api::Result<Vec<Webhook>> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::list(&env, target).await
+}
+
+struct FetchWebhookR;
+mk_into_responses!(for FetchWebhookR {
+    "200" => [ref(WebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization or webhook was not found"))];
+});
+
+/// Retrieve a single webhook of this organization.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/organizations/{idOrName}/webhooks/{id}",
+    operation_id = "getOrganizationWebhook",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid, Ulid),
+    responses(FetchWebhookR)
+)]
+pub async fn fetch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((id_or_name, id)): Path<(NameOrUlid, Ulid)>,
+) -> api::Result<Webhook> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookUpdate).await?;
+    let model = ops::webhooks::get(&env, target, id).await?;
+
+    Ok(api::ok(StatusCode::OK, Webhook::from(model).sanitize()))
+}
+
+struct CreateWebhookR;
+mk_into_responses!(for CreateWebhookR {
+    "201" => [ref(WebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization was not found"))];
+    "422" => [error(description("`url` wasn't a HTTP endpoint or `events` was empty"))];
+});
+
+/// Creates a webhook that receives events from every repository this organization
+/// owns. The response is the only time that the webhook's secret is available.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+
+    path = "/v1/organizations/{idOrName}/webhooks",
+    operation_id = "createOrganizationWebhook",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid),
+    request_body(
+        content = ref("#/components/schemas/CreateWebhookPayload"),
+        description = "Request body for creating a new webhook",
+        content_type = "application/json"
+    ),
+    responses(CreateWebhookR)
+)]
+pub async fn create(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id_or_name): Path<NameOrUlid>,
+    Json(payload): Json<CreateWebhookPayload>,
+) -> api::Result<Webhook> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookCreate).await?;
+    ops::webhooks::create(&env, &cx, target, payload).await
+}
+
+struct PatchWebhookR;
+mk_into_responses!(for PatchWebhookR {
+    "204" => [ref(EmptyApiResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization or webhook was not found"))];
+    "422" => [error(description("`url` wasn't a HTTP endpoint or `events` was empty"))];
+});
+
+/// Patches a webhook's metadata.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+
+    path = "/v1/organizations/{idOrName}/webhooks/{id}",
+    operation_id = "patchOrganizationWebhook",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid, Ulid),
+    request_body(
+        content = ref("#/components/schemas/PatchWebhookPayload"),
+        description = "Request body for patching a webhook",
+        content_type = "application/json"
+    ),
+    responses(PatchWebhookR)
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((id_or_name, id)): Path<(NameOrUlid, Ulid)>,
+    Json(payload): Json<PatchWebhookPayload>,
+) -> api::Result<()> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::patch(&env, &cx, target, id, payload).await
+}
+
+struct DeleteWebhookR;
+mk_into_responses!(for DeleteWebhookR {
+    "202" => [ref(EmptyApiResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization or webhook was not found"))];
+});
+
+/// Deletes a webhook and all of its recorded deliveries.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+
+    path = "/v1/organizations/{idOrName}/webhooks/{id}",
+    operation_id = "deleteOrganizationWebhook",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid, Ulid),
+    responses(DeleteWebhookR)
+)]
+pub async fn delete(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((id_or_name, id)): Path<(NameOrUlid, Ulid)>,
+) -> api::Result<()> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookDelete).await?;
+    ops::webhooks::delete(&env, &cx, target, id).await
+}
+
+struct ListDeliveriesR;
+mk_into_responses!(for ListDeliveriesR {
+    "200" => [ref(ListWebhookDeliveryResponse)];
+    "403" => [error(description("user is not allowed to manage this organization's webhooks"))];
+    "404" => [error(description("organization or webhook was not found"))];
+});
+
+/// Lists the delivery attempts of a webhook, most recent first.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/organizations/{idOrName}/webhooks/{id}/deliveries",
+    operation_id = "listOrganizationWebhookDeliveries",
+    tags = ["Organizations", "Webhooks"],
+    params(NameOrUlid, Ulid, PaginationRequest),
+    responses(ListDeliveriesR)
+)]
+pub async fn deliveries(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((id_or_name, id)): Path<(NameOrUlid, Ulid)>,
+    Query(pagination): Query<PaginationRequest>,
+) -> api::Result<Vec<WebhookDelivery>> {
+    let target = target(&env, &user, id_or_name, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::deliveries(&env, target, id, pagination).await
+}
@@ -13,26 +13,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod audit_logs;
+pub mod invites;
+pub mod members;
 pub mod releases;
+pub mod webhooks;
 
 use crate::{
     Env, OwnerExt,
     ext::ResultExt,
-    extract::Path,
-    middleware::authn::{Factory, Options},
+    extract::{Path, Query},
+    middleware::authn::{Factory, Options, Session},
     mk_into_responses,
     openapi::RepositoryResponse,
-    ops::db,
+    ops::{self, db},
     routing::v1::Entrypoint,
 };
-use axum::{Router, extract::State, handler::Handler, http::StatusCode, routing};
+use axum::{
+    Extension, Router,
+    extract::State,
+    handler::Handler,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::IntoResponse,
+    routing,
+};
 use charted_core::{
-    api,
-    bitflags::{ApiKeyScope, ApiKeyScopes},
+    api::{self, Yaml},
+    bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission},
 };
 use charted_database::entities::repository;
-use charted_types::{NameOrUlid, Owner, Repository};
+use charted_helm_types::ChartIndex;
+use charted_types::{NameOrUlid, Owner, Repository, User};
 use sea_orm::{ColumnTrait, QueryFilter};
+use serde::Deserialize;
 use serde_json::json;
 use utoipa::{
     IntoParams,
@@ -40,7 +53,7 @@ use utoipa::{
 };
 
 pub fn create_router(env: &Env) -> Router<Env> {
-    Router::new()
+    let mut router = Router::new()
         .route("/", routing::get(main))
         .route(
             "/{owner}/{repo}",
@@ -50,7 +63,27 @@ pub fn create_router(env: &Env) -> Router<Env> {
                 require_refresh_token: false,
             }))),
         )
-        .nest("/releases/{owner}/{repo}", releases::create_router(env))
+        .route(
+            "/{owner}/{repo}/index.yaml",
+            routing::get(index.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+                require_refresh_token: false,
+            }))),
+        )
+        .nest("/{owner}/{repo}/invites", invites::create_router(env))
+        .nest("/{owner}/{repo}/members", members::create_router(env))
+        .nest("/{owner}/{repo}/releases", releases::create_router(env));
+
+    if env.features.has::<charted_feature_audit_logs::Feature>() {
+        router = router.nest("/{owner}/{repo}/audit-logs", audit_logs::create_router(env));
+    }
+
+    if env.features.has::<charted_feature_webhooks::Feature>() {
+        router = router.nest("/{owner}/{repo}/webhooks", webhooks::create_router(env));
+    }
+
+    router
 }
 
 /// Entrypoint handler to the Repositories API.
@@ -110,9 +143,91 @@ impl IntoParams for OwnerRepoP {
 )]
 pub async fn fetch(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
 ) -> api::Result<Repository> {
-    let Some(owner) = Owner::query_by_id_or_name(&env, owner.clone())
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = fetch_visible(&env, user, owner, repo).await?;
+
+    Ok(api::ok(StatusCode::OK, repository))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IndexQuery {
+    /// whether if pre-releases should be included in the index.
+    #[serde(default)]
+    pub prereleases: bool,
+}
+
+struct IndexR;
+mk_into_responses!(for IndexR {
+    "200" => [ref(
+        with "text/yaml" => ChartIndex;
+            description("the repository's `index.yaml`");
+    )];
+
+    "304" => [custom(
+        utoipa::openapi::ResponseBuilder::new()
+            .description("`index.yaml` didn't change since the `ETag` in `If-None-Match`")
+            .build()
+    )];
+    "404" => [error(description("repository was not found"))];
+});
+
+/// Returns a `index.yaml` that only contains the releases of this repository, so that
+/// it can be added with `helm repo add` on its own.
+///
+/// The response has an `ETag` header that can be sent back in `If-None-Match` to
+/// receive a `304 Not Modified` response if the index didn't change.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/repositories/{owner}/{repo}/index.yaml",
+    operation_id = "getRepositoryChartIndex",
+    tags = ["Repositories"],
+    params(OwnerRepoP, IndexQuery),
+    responses(IndexR)
+)]
+pub async fn index(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+    Query(IndexQuery { prereleases }): Query<IndexQuery>,
+    headers: HeaderMap,
+) -> Result<axum::response::Response, api::Response> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = fetch_visible(&env, user, owner, repo).await?;
+
+    let releases = ops::indexes::releases(&env, repository.id, prereleases)
+        .await
+        .map_err(api::system_failure_from_report)?;
+
+    let etag = ops::indexes::etag(&env, &releases);
+    let etag_hdr = HeaderValue::from_str(&etag).into_system_failure()?;
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').map(str::trim).any(|tag| tag == "*" || tag == etag))
+    {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag_hdr)]).into_response());
+    }
+
+    let index = ops::indexes::repository(&env, repository.owner, releases)
+        .await
+        .map_err(api::system_failure_from_report)?;
+
+    Ok(([(header::ETAG, etag_hdr)], Yaml::new(StatusCode::OK, index)).into_response())
+}
+
+/// Resolves a repository and its owner by their IDs or names. This doesn't check if
+/// anyone is allowed to see the repository.
+pub(crate) async fn resolve(
+    env: &Env,
+    owner: NameOrUlid,
+    repo: NameOrUlid,
+) -> Result<(Owner, Repository), api::Response> {
+    let Some(owner) = Owner::query_by_id_or_name(env, owner.clone())
         .await
         .into_system_failure()?
     else {
@@ -127,13 +242,11 @@ pub async fn fetch(
     };
 
     match db::repository::get_with_additional_bounds(&env.db, repo.clone(), |query| {
-        query
-            .filter(repository::Column::Owner.eq(owner.id()))
-            .filter(repository::Column::Private.eq(false))
+        query.filter(repository::Column::Owner.eq(owner.id()))
     })
     .await?
     {
-        Some(repo) => Ok(api::ok(StatusCode::OK, repo)),
+        Some(repository) => Ok((owner, repository)),
         None => Err(api::err(
             StatusCode::NOT_FOUND,
             (
@@ -144,3 +257,60 @@ pub async fn fetch(
         )),
     }
 }
+
+/// Resolves a repository that `user` is allowed to see, which includes private
+/// repositories for their owners and members. Anonymous users and users that can't
+/// see the repository get a `404 Not Found` response, so private repositories can't
+/// be discovered.
+pub(crate) async fn fetch_visible(
+    env: &Env,
+    user: Option<&User>,
+    owner: NameOrUlid,
+    repo: NameOrUlid,
+) -> Result<(Owner, Repository), api::Response> {
+    let (owner, repository) = resolve(env, owner, repo.clone()).await?;
+    if !ops::members::can_view(env, user, &owner, &repository)
+        .await
+        .into_system_failure()?
+    {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "repository with id or name was not found",
+                json!({"idOrName":repo}),
+            ),
+        ));
+    }
+
+    Ok((owner, repository))
+}
+
+/// Fetches a repository that `user` is allowed to modify, which includes private
+/// repositories. A user can modify a repository if they own it directly, if they own
+/// the organization that owns it, or if they are a member with `permission`.
+///
+/// Users that can't see the repository get a `404 Not Found` response like they would
+/// from [`fetch_visible`], and only users that can see it but lack `permission` get a
+/// `403 Forbidden` response.
+pub(crate) async fn fetch_modifiable(
+    env: &Env,
+    user: &User,
+    owner: NameOrUlid,
+    repo: NameOrUlid,
+    permission: MemberPermission,
+) -> Result<Repository, api::Response> {
+    let (owner, repository) = fetch_visible(env, Some(user), owner, repo).await?;
+    ops::members::require(env, user, &owner, Some(repository.id), permission).await?;
+
+    Ok(repository)
+}
+
+/// Returns `true` if `user` owns `owner`, or is `owner` itself. Members are never
+/// considered owners.
+pub(crate) fn can_modify(owner: &Owner, user: &User) -> bool {
+    match owner {
+        Owner::User(owner) => owner.id == user.id,
+        Owner::Organization(org) => org.owner == user.id,
+    }
+}
@@ -0,0 +1,129 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit logs of a single repository. Only users that can modify the repository's
+//! metadata can view its audit logs.
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    extract::{Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::ListAuditLogResponse,
+    pagination::PaginationRequest,
+    routing::v1::repository::OwnerRepoP,
+    util::{self, BuildLinkHeaderOpts},
+};
+use axum::{
+    Extension, Router,
+    extract::State,
+    handler::Handler,
+    http::{HeaderValue, StatusCode, header},
+    routing,
+};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, MemberPermission},
+    clamp,
+};
+use charted_feature_audit_logs::AuditLog;
+use charted_types::NameOrUlid;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new().route(
+        "/",
+        routing::get(list.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoAccess)))),
+    )
+}
+
+struct ListAuditLogsR;
+mk_into_responses!(for ListAuditLogsR {
+    "200" => [ref(ListAuditLogResponse)];
+    "403" => [error(description("user is not allowed to view this repository's audit logs"))];
+    "404" => [error(description("repository was not found"))];
+});
+
+/// Lists the audit logs of this repository, from newest to oldest.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/audit-logs",
+    operation_id = "listRepositoryAuditLogs",
+    tags = ["Repositories", "Audit Logs"],
+    params(OwnerRepoP, PaginationRequest),
+    responses(ListAuditLogsR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+    Query(PaginationRequest { per_page, page, .. }): Query<PaginationRequest>,
+) -> api::Result<Vec<AuditLog>> {
+    let repository = super::fetch_modifiable(
+        &env,
+        &user,
+        owner.clone(),
+        repo.clone(),
+        MemberPermission::MetadataUpdate,
+    )
+    .await?;
+
+    // the route is only registered if the feature is enabled
+    let feature = env.features.get::<charted_feature_audit_logs::Feature>().unwrap();
+
+    let per_page = clamp(per_page, 10, 100).unwrap_or(10);
+    let page = page.max(1);
+    let result = feature
+        .sink()
+        .query(charted_feature_audit_logs::Query {
+            owner: repository.owner,
+            repository: Some(repository.id),
+            per_page,
+            page,
+        })
+        .await
+        .map_err(|e| {
+            error!(error = %e, repository.id = %repository.id, "failed to query audit logs");
+            sentry::capture_error(&*e);
+
+            api::system_failure_from_report(e)
+        })?;
+
+    let resource = env
+        .config
+        .base_url
+        .unwrap()
+        .join(&format!("/v1/repositories/{owner}/{repo}/audit-logs"))
+        .unwrap();
+
+    let mut link_hdr = String::new();
+    util::build_link_header(&mut link_hdr, BuildLinkHeaderOpts {
+        entries: result.records.len(),
+        current: page,
+        per_page,
+        max_pages: result.pages as u64,
+        resource,
+    })
+    .into_system_failure()?;
+
+    let mut response = api::ok(StatusCode::OK, result.records);
+    if !link_hdr.is_empty() {
+        response = response.with_header(header::LINK, HeaderValue::from_bytes(link_hdr.as_bytes()).unwrap());
+    }
+
+    Ok(response)
+}
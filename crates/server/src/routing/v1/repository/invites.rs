@@ -0,0 +1,232 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pending invites for users to become members of a single repository. Invited users
+//! accept or decline their invites from `/v1/users/@me/invites`.
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    extract::{Json, Path},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListMemberInviteResponse, MemberInviteResponse},
+    ops::{
+        self, auditlog, db,
+        invites::{self, Invitation},
+    },
+    routing::v1::repository::{OwnerRepoP, can_modify},
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, MemberPermission},
+};
+use charted_database::entities::{MemberInviteEntity, member_invite};
+use charted_feature_audit_logs::Action;
+use charted_types::{MemberInvite, NameOrUlid, Ulid, payloads::CreateMemberPayload};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(
+                list.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoMemberInviteAccess))),
+            )
+            .put(create.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoMemberInviteAccess)))),
+        )
+        .route(
+            "/{id}",
+            routing::delete(
+                revoke.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoMemberInviteDelete))),
+            ),
+        )
+}
+
+struct ListInvitesR;
+mk_into_responses!(for ListInvitesR {
+    "200" => [ref(ListMemberInviteResponse)];
+    "403" => [error(description("user is not allowed to invite members"))];
+    "404" => [error(description("repository was not found"))];
+});
+
+/// Lists all the pending invites of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/repositories/{owner}/{repo}/invites",
+    operation_id = "listRepositoryInvites",
+    tags = ["Repositories", "Repository/Members"],
+    params(OwnerRepoP),
+    responses(ListInvitesR),
+    security(
+        ("ApiKey" = ["repo:members:invites:access"])
+    )
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+) -> api::Result<Vec<MemberInvite>> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MemberInvite).await?;
+
+    let invites = MemberInviteEntity::find()
+        .filter(member_invite::Column::Repository.eq(repository.id))
+        .filter(member_invite::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_asc(member_invite::Column::Id)
+        .all(&env.db)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<MemberInvite>::into)
+        .collect();
+
+    Ok(api::ok(StatusCode::OK, invites))
+}
+
+struct CreateInviteR;
+mk_into_responses!(for CreateInviteR {
+    "201" => [ref(MemberInviteResponse)];
+    "403" => [error(description("user is not allowed to invite members, or granted permissions that they don't have"))];
+    "404" => [error(description("repository or user was not found"))];
+    "409" => [error(description("user is already a member, or has a pending invite"))];
+});
+
+/// Invites a user to become a member of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+    path = "/v1/repositories/{owner}/{repo}/invites",
+    operation_id = "createRepositoryInvite",
+    tags = ["Repositories", "Repository/Members"],
+    params(OwnerRepoP),
+    request_body(
+        content_type = "application/json",
+        description = "Payload object for inviting a user",
+        content = ref("#/components/schemas/CreateMemberPayload")
+    ),
+    responses(CreateInviteR),
+    security(
+        ("ApiKey" = ["repo:members:invites:access"])
+    )
+)]
+pub async fn create(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+    cx: auditlog::Context,
+    Json(CreateMemberPayload { account, permissions }): Json<CreateMemberPayload>,
+) -> api::Result<MemberInvite> {
+    let (owner, repository) = super::resolve(&env, owner, repo).await?;
+    let actor =
+        ops::members::require(&env, &user, &owner, Some(repository.id), MemberPermission::MemberInvite).await?;
+
+    let permissions = ops::members::check_grant(actor, permissions)?;
+    let Some(account) = db::user::get(&env.db, account.clone()).await? else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "user with id or name was not found",
+                json!({"idOrName":account}),
+            ),
+        ));
+    };
+
+    if can_modify(&owner, &account) {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "user already owns this repository",
+                json!({"account":account.id}),
+            ),
+        ));
+    }
+
+    let target = format!("{}/{}", owner.name(), repository.name);
+    let invite = invites::create(
+        &env,
+        &cx,
+        &user,
+        account,
+        Invitation::Repository(repository.id),
+        target,
+        permissions.value(),
+    )
+    .await?;
+
+    Ok(api::ok(StatusCode::CREATED, invite))
+}
+
+/// Revokes a pending invite of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/repositories/{owner}/{repo}/invites/{id}",
+    operation_id = "revokeRepositoryInvite",
+    tags = ["Repositories", "Repository/Members"],
+    params(
+        OwnerRepoP,
+        ("id" = Ulid, Path, description = "ID of the invite")
+    ),
+    responses(
+        (
+            status = 204,
+            description = "Invite was revoked",
+            body = EmptyApiResponse,
+            content_type = "application/json"
+        )
+    ),
+    security(
+        ("ApiKey" = ["repo:members:invites:delete"])
+    )
+)]
+pub async fn revoke(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, id)): Path<(NameOrUlid, NameOrUlid, Ulid)>,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MemberInvite).await?;
+
+    let Some(invite) = MemberInviteEntity::find_by_id(id)
+        .filter(member_invite::Column::Repository.eq(repository.id))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "invite with id was not found",
+                json!({"id":id}),
+            ),
+        ));
+    };
+
+    MemberInviteEntity::delete_by_id(invite.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    invites::record(&env, &cx, Action::InviteRevoked, &invite).await;
+
+    Ok(api::no_content())
+}
@@ -0,0 +1,319 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Members of a single repository. Anyone that can see the repository can list its
+//! members, while updating or kicking members requires the `member:update` or
+//! `member:kick` permissions.
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListRepositoryMemberResponse},
+    ops::{self, auditlog, db},
+    pagination::PaginationRequest,
+    routing::v1::repository::OwnerRepoP,
+    util::{self, BuildLinkHeaderOpts},
+};
+use axum::{
+    Extension, Router,
+    extract::State,
+    handler::Handler,
+    http::{HeaderValue, StatusCode, header},
+    routing,
+};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission},
+    clamp,
+};
+use charted_database::entities::{RepositoryMemberEntity, repository::member};
+use charted_feature_audit_logs::Action;
+use charted_feature_webhooks::event::EventKind;
+use charted_types::{NameOrUlid, Repository, RepositoryMember, payloads::PatchMemberPayload};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+    QueryOrder,
+};
+use serde_json::json;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoMembersList.into()),
+                require_refresh_token: false,
+            }))),
+        )
+        .route(
+            "/{member}",
+            routing::patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoMemberUpdate))))
+                .delete(kick.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoMemberKick)))),
+        )
+}
+
+/// Finds the member of `repository` whose user account is `id_or_name`.
+async fn find(env: &Env, repository: &Repository, id_or_name: NameOrUlid) -> Result<member::Model, api::Response> {
+    let not_found = || {
+        api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "repository member with id or name was not found",
+                json!({"member":&id_or_name}),
+            ),
+        )
+    };
+
+    let Some(user) = db::user::get_as_model(&env.db, id_or_name.clone()).await? else {
+        return Err(not_found());
+    };
+
+    RepositoryMemberEntity::find()
+        .filter(member::Column::Repository.eq(repository.id))
+        .filter(member::Column::Account.eq(user.id))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .ok_or_else(not_found)
+}
+
+struct ListMembersR;
+mk_into_responses!(for ListMembersR {
+    "200" => [ref(ListRepositoryMemberResponse)];
+    "404" => [error(description("repository was not found"))];
+});
+
+/// Lists all the members of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/repositories/{owner}/{repo}/members",
+    operation_id = "listRepositoryMembers",
+    tags = ["Repositories", "Repository/Members"],
+    params(OwnerRepoP, PaginationRequest),
+    responses(ListMembersR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+    Query(PaginationRequest {
+        per_page,
+        order_by,
+        page,
+    }): Query<PaginationRequest>,
+) -> api::Result<Vec<RepositoryMember>> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
+
+    let per_page = clamp(per_page, 10, 100).unwrap_or(10);
+    let paginator = RepositoryMemberEntity::find()
+        .filter(member::Column::Repository.eq(repository.id))
+        .order_by(member::Column::Id, order_by.into_sea_orm())
+        .paginate(&env.db, per_page as u64);
+
+    let pages = paginator.num_pages().await.into_system_failure()?;
+    let entries = paginator
+        .fetch_page(page.saturating_sub(1) as u64)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<RepositoryMember>::into)
+        .collect::<Vec<_>>();
+
+    let mut link_hdr = String::new();
+    util::build_link_header(&mut link_hdr, BuildLinkHeaderOpts {
+        entries: entries.len(),
+        current: page,
+        per_page,
+        max_pages: pages,
+        resource: env
+            .config
+            .base_url
+            .unwrap()
+            .join(&format!("/repositories/{}/members", repository.id))
+            .unwrap(),
+    })
+    .into_system_failure()?;
+
+    let mut response = api::ok(StatusCode::OK, entries);
+    if !link_hdr.is_empty() {
+        response = response.with_header(header::LINK, HeaderValue::from_bytes(link_hdr.as_bytes()).unwrap());
+    }
+
+    Ok(response)
+}
+
+struct PatchMemberR;
+mk_into_responses!(for PatchMemberR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("patch was applied to the member");
+    )];
+
+    "403" => [error(description("user is not allowed to update members, or granted permissions that they don't have"))];
+    "404" => [error(description("repository or member was not found"))];
+});
+
+/// Updates a member's display name or permissions.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+    path = "/v1/repositories/{owner}/{repo}/members/{member}",
+    operation_id = "patchRepositoryMember",
+    tags = ["Repositories", "Repository/Members"],
+    params(
+        OwnerRepoP,
+        ("member" = NameOrUlid, Path, description = "ID or username of the member's user account")
+    ),
+    request_body(
+        content_type = "application/json",
+        description = "Payload object for patching a member",
+        content = ref("#/components/schemas/PatchMemberPayload")
+    ),
+    responses(PatchMemberR),
+    security(
+        ("ApiKey" = ["repo:members:update"])
+    )
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, id_or_name)): Path<(NameOrUlid, NameOrUlid, NameOrUlid)>,
+    Json(PatchMemberPayload {
+        display_name,
+        permissions,
+    }): Json<PatchMemberPayload>,
+) -> api::Result<()> {
+    let (owner, repository) = super::resolve(&env, owner, repo).await?;
+    let actor =
+        ops::members::require(&env, &user, &owner, Some(repository.id), MemberPermission::MemberUpdate).await?;
+
+    let member = find(&env, &repository, id_or_name).await?;
+    let mut model = member.clone().into_active_model();
+
+    if let Some(display_name) = display_name {
+        if display_name.len() > 64 {
+            return Err(api::err(
+                StatusCode::NOT_ACCEPTABLE,
+                (
+                    api::ErrorCode::ValidationFailed,
+                    "`displayName` was expected to be 64 characters or shorter",
+                    json!({"expected":64,"received":display_name.len()}),
+                ),
+            ));
+        }
+
+        model.display_name = ActiveValue::set(Some(display_name).filter(|name| !name.is_empty()));
+    }
+
+    if let Some(permissions) = permissions {
+        let permissions = ops::members::check_grant(actor, permissions)?;
+        model.permissions = ActiveValue::set(permissions.value() as i64);
+    }
+
+    model.updated_at = ActiveValue::set(Utc::now());
+    let updated = RepositoryMember::from(model.update(&env.db).await.into_system_failure()?);
+
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::MemberUpdated,
+        &updated,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::MemberUpdated,
+        auditlog::repository(&repository),
+        RepositoryMember::from(member),
+        &updated,
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+/// Kicks a member from this repository. Members can always kick themselves to leave
+/// the repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/repositories/{owner}/{repo}/members/{member}",
+    operation_id = "kickRepositoryMember",
+    tags = ["Repositories", "Repository/Members"],
+    params(
+        OwnerRepoP,
+        ("member" = NameOrUlid, Path, description = "ID or username of the member's user account")
+    ),
+    responses(
+        (
+            status = 204,
+            description = "Member was kicked from the repository",
+            body = EmptyApiResponse,
+            content_type = "application/json"
+        )
+    ),
+    security(
+        ("ApiKey" = ["repo:members:kick"])
+    )
+)]
+pub async fn kick(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, id_or_name)): Path<(NameOrUlid, NameOrUlid, NameOrUlid)>,
+) -> api::Result<()> {
+    let (owner, repository) = super::resolve(&env, owner, repo).await?;
+    let member = find(&env, &repository, id_or_name).await?;
+
+    if member.account != user.id {
+        ops::members::require(&env, &user, &owner, Some(repository.id), MemberPermission::MemberKick).await?;
+    }
+
+    RepositoryMemberEntity::delete_by_id(member.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    let member = RepositoryMember::from(member);
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::MemberRemoved,
+        &member,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::MemberRemoved,
+        auditlog::repository(&repository),
+        &member,
+        (),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
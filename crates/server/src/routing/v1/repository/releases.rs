@@ -14,16 +14,19 @@
 // limitations under the License.
 
 use crate::{
-    Env,
-    extract::{Path, Query},
-    middleware::authn::{Factory, Options},
-    ops::db,
+    Env, commit_patch,
+    ext::ResultExt,
+    extract::{Json, Multipart, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_api_response_types, mk_into_responses, mk_list_based_api_response_types,
+    openapi::{EmptyApiResponse, RepositoryReleaseResponse},
+    ops::{self, auditlog, db, releases::Tarball},
     pagination::PaginationRequest,
     routing::v1::repository::OwnerRepoP,
     util::{self, BuildLinkHeaderOpts},
 };
 use axum::{
-    Router,
+    Extension, Router,
     extract::State,
     handler::Handler,
     http::{
@@ -35,14 +38,24 @@ use axum::{
 };
 use charted_core::{
     api,
-    bitflags::{ApiKeyScope, ApiKeyScopes},
+    bitflags::{ApiKeyScope, ApiKeyScopes, MemberPermission},
     clamp,
 };
 use charted_database::entities::{RepositoryReleaseEntity, repository::release};
 use charted_datastore::fs;
+use charted_feature_audit_logs::Action;
+use charted_feature_webhooks::event::EventKind;
 use charted_helm_charts::DataStoreExt;
-use charted_types::{NameOrUlid, QueryableVersion, RepositoryRelease, Ulid, VersionOrUlid};
-use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
+use charted_helm_types::{Chart, ChartFile};
+use charted_types::{
+    NameOrUlid, QueryableVersion, Repository, RepositoryRelease, Ulid, User, Version, VersionOrUlid,
+    payloads::PatchRepositoryReleasePayload,
+};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+    QueryOrder,
+};
 use serde::Deserialize;
 use serde_json::json;
 use std::cmp;
@@ -79,18 +92,79 @@ pub fn create_router(env: &Env) -> Router<Env> {
             }))),
         );
 
-    let version_or_ulid = Router::new().route(
-        "/",
-        routing::get(get_single_release.layer(env.authn(Options {
-            allow_unauthorized: true,
-            scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+    let version_or_ulid = Router::new()
+        .route(
+            "/",
+            routing::get(get_single_release.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
 
-            ..Default::default()
-        }))),
-    );
+                ..Default::default()
+            })))
+            .patch(patch_release.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseUpdate))))
+            .delete(
+                delete_release.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseDelete))),
+            ),
+        )
+        .route(
+            "/yank",
+            routing::post(
+                yank_release.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseUpdate))),
+            ),
+        )
+        .route(
+            "/unyank",
+            routing::post(
+                unyank_release.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseUpdate))),
+            ),
+        )
+        .route(
+            "/chart",
+            routing::get(get_single_release_chart.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+
+                ..Default::default()
+            }))),
+        )
+        .route(
+            "/files",
+            routing::get(list_release_files.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
 
+                ..Default::default()
+            }))),
+        )
+        .route(
+            "/files/{*path}",
+            routing::get(get_release_file.layer(env.authn(Options {
+                allow_unauthorized: true,
+                scopes: ApiKeyScopes::new(ApiKeyScope::RepoAccess.into()),
+
+                ..Default::default()
+            }))),
+        )
+        .route(
+            "/tarball",
+            routing::put(
+                upload_release_tarball
+                    .layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseCreate))),
+            ),
+        )
+        .route(
+            "/provenance",
+            routing::put(
+                upload_release_provenance
+                    .layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoReleaseCreate))),
+            ),
+        );
+
+    // the router requires that path parameters in the same position share the
+    // same name, so `{id}` is registered as `{versionOrId}` here. extractors are
+    // positional, so the handlers don't care.
     router
-        .nest("/{id}/{version}", id)
+        .nest("/{versionOrId}/{version}", id)
         .nest("/{versionOrId}", version_or_ulid)
 }
 
@@ -106,6 +180,7 @@ pub fn create_router(env: &Env) -> Router<Env> {
 )]
 pub async fn fetch_releases(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
     Query(PaginationRequest {
         per_page,
@@ -114,10 +189,8 @@ pub async fn fetch_releases(
     }): Query<PaginationRequest>,
 ) -> api::Result<Vec<RepositoryRelease>> {
     let per_page = clamp(per_page, 10, 100).unwrap_or(10);
-    let repository = super::fetch(State(env.clone()), Path((owner.clone(), repo.clone())))
-        .await?
-        .data
-        .unwrap();
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner.clone(), repo.clone()).await?;
 
     let paginator = RepositoryReleaseEntity::find()
         .filter(release::Column::Repository.eq(repository.id))
@@ -170,12 +243,11 @@ pub async fn fetch_releases(
 )]
 pub async fn get_single_release(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
 ) -> api::Result<RepositoryRelease> {
-    let repository = super::fetch(State(env.clone()), Path((owner.clone(), repo.clone())))
-        .await?
-        .data
-        .unwrap();
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
 
     match db::repository::release::get(&env.db, &repository, version.clone()).await? {
         Some(release) => Ok(api::ok(StatusCode::OK, release)),
@@ -190,10 +262,174 @@ pub async fn get_single_release(
     }
 }
 
+mk_api_response_types!(Chart);
+
+struct GetReleaseChartR;
+mk_into_responses!(for GetReleaseChartR {
+    "200" => [ref(ChartResponse)];
+    "404" => [error(description("repository, release, or the release's chart metadata was not found"))];
+});
+
+/// Retrieve the parsed `Chart.yaml` of a repository release.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}/chart",
+    operation_id = "getRepositoryReleaseChart",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    responses(GetReleaseChartR)
+)]
+pub async fn get_single_release_chart(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+) -> api::Result<Chart> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
+
+    let model = db::repository::release::get_as_model(&env.db, &repository, version.clone())
+        .await?
+        .ok_or_else(|| {
+            api::err(
+                StatusCode::NOT_FOUND,
+                (
+                    api::ErrorCode::EntityNotFound,
+                    "repository release with version or ulid was not found",
+                    json!({"versionOrUlid":version}),
+                ),
+            )
+        })?;
+
+    // releases that were published before `Chart.yaml` was persisted
+    // won't have it avaliable.
+    let Some(chart) = model.chart else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "chart metadata for release was not found",
+                json!({"versionOrUlid":version}),
+            ),
+        ));
+    };
+
+    serde_json::from_value(chart)
+        .map(|chart| api::ok(StatusCode::OK, chart))
+        .into_system_failure()
+}
+
+mk_list_based_api_response_types!(ChartFile);
+
+struct ListReleaseFilesR;
+mk_into_responses!(for ListReleaseFilesR {
+    "200" => [ref(ListChartFileResponse)];
+    "404" => [error(description("repository, release, or the release's chart tarball was not found"))];
+});
+
+/// Lists every file in the chart tarball of a repository release.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}/files",
+    operation_id = "listRepositoryReleaseFiles",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    responses(ListReleaseFilesR)
+)]
+pub async fn list_release_files(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+) -> api::Result<Vec<ChartFile>> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
+    let model = find(&env, &repository, version).await?;
+
+    let Some(files) = env
+        .ds
+        .owner_repo(repository.owner, repository.id)
+        .get_chart_files(&model.tag)
+        .await
+        .map_err(api::system_failure_from_report)?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "chart tarball for release was not found",
+                json!({"version":model.tag}),
+            ),
+        ));
+    };
+
+    Ok(api::ok(StatusCode::OK, files))
+}
+
+/// Returns a single file from the chart tarball of a repository release.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}/files/{path}",
+    operation_id = "getRepositoryReleaseFile",
+    tags = ["Repositories", "Repository/Releases"],
+    params(
+        OwnerRepoP,
+        VersionOrUlid,
+        ("path" = String, Path, description = "Path of the file, relative to the chart's root directory")
+    )
+)]
+pub async fn get_release_file(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    Path((owner, repo, version, path)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid, String)>,
+) -> Result<impl IntoResponse, api::Response> {
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
+    let model = find(&env, &repository, version).await?;
+
+    let Some(contents) = env
+        .ds
+        .owner_repo(repository.owner, repository.id)
+        .get_chart_file(&model.tag, &path)
+        .await
+        .map_err(api::system_failure_from_report)?
+    else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "file was not found in the release's chart tarball",
+                json!({"version":model.tag,"path":path}),
+            ),
+        ));
+    };
+
+    // files are served as plain text or as an opaque blob, never as what they claim
+    // to be, since anyone that can publish a release controls their contents.
+    let ct = match std::str::from_utf8(&contents) {
+        Ok(_) => HeaderValue::from_static("text/plain; charset=utf-8"),
+        Err(_) => HeaderValue::from_static("application/octet-stream"),
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, ct),
+        (header::CONTENT_LENGTH, HeaderValue::from(contents.len())),
+        (header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff")),
+        (header::CONTENT_SECURITY_POLICY, HeaderValue::from_static("sandbox")),
+    ];
+
+    Ok((headers, contents))
+}
+
 #[derive(Deserialize, IntoParams)]
 #[into_params(parameter_in = Query)]
 pub struct QueryParams {
     /// whether if querying for pre-releases is useful.
+    #[serde(default)]
     pub prereleases: bool,
 }
 
@@ -210,13 +446,12 @@ pub struct QueryParams {
 )]
 pub async fn get_single_release_provenance(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path((owner, repo, id, version)): Path<(NameOrUlid, NameOrUlid, Ulid, QueryableVersion)>,
     Query(QueryParams { prereleases }): Query<QueryParams>,
 ) -> Result<impl IntoResponse, api::Response> {
-    let repository = super::fetch(State(env.clone()), Path((owner.clone(), repo.clone())))
-        .await?
-        .data
-        .unwrap();
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
 
     match db::repository::release::get(&env.db, &repository, VersionOrUlid::Ulid(id)).await? {
         Some(_) => {}
@@ -232,9 +467,19 @@ pub async fn get_single_release_provenance(
         }
     };
 
+    let Some(version) = ops::latest::resolve(&repository, version, prereleases) else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "repository has no releases to resolve the latest version from",
+            ),
+        ));
+    };
+
     let ns = env.ds.owner_repo(repository.owner, repository.id);
     let Some(chart_prov) = ns
-        .get_chart_provenance(version, prereleases)
+        .get_chart_provenance(&version, prereleases)
         .await
         .map_err(api::system_failure_from_report)?
     else {
@@ -266,13 +511,12 @@ pub async fn get_single_release_provenance(
 )]
 pub async fn get_single_release_tarball(
     State(env): State<Env>,
+    session: Option<Extension<Session>>,
     Path((owner, repo, id, version)): Path<(NameOrUlid, NameOrUlid, Ulid, QueryableVersion)>,
     Query(QueryParams { prereleases }): Query<QueryParams>,
 ) -> Result<impl IntoResponse, api::Response> {
-    let repository = super::fetch(State(env.clone()), Path((owner.clone(), repo.clone())))
-        .await?
-        .data
-        .unwrap();
+    let user = session.as_ref().map(|Extension(session)| &session.user);
+    let (_, repository) = super::fetch_visible(&env, user, owner, repo).await?;
 
     match db::repository::release::get(&env.db, &repository, VersionOrUlid::Ulid(id)).await? {
         Some(_) => {}
@@ -288,9 +532,19 @@ pub async fn get_single_release_tarball(
         }
     };
 
+    let Some(version) = ops::latest::resolve(&repository, version, prereleases) else {
+        return Err(api::err(
+            StatusCode::NOT_FOUND,
+            (
+                api::ErrorCode::EntityNotFound,
+                "repository has no releases to resolve the latest version from",
+            ),
+        ));
+    };
+
     let ns = env.ds.owner_repo(repository.owner, repository.id);
     let Some(chart_prov) = ns
-        .get_chart(version, prereleases)
+        .get_chart(&version, prereleases)
         .await
         .map_err(api::system_failure_from_report)?
     else {
@@ -309,3 +563,367 @@ pub async fn get_single_release_tarball(
     let headers = [(header::CONTENT_TYPE, ct), (header::CONTENT_LENGTH, HeaderValue::from(chart_prov.size))];
     Ok((headers, chart_prov.data))
 }
+
+struct UploadReleaseTarballR;
+mk_into_responses!(for UploadReleaseTarballR {
+    "201" => [ref(RepositoryReleaseResponse)];
+    "403" => [error(description("user is not allowed to publish releases to this repository"))];
+    "404" => [error(description("repository was not found"))];
+    "409" => [error(description("release with the given version already exists"))];
+    "412" => [error(description("multipart stream was missing a field or had an invalid content type"))];
+    "422" => [error(description("tarball was not a valid Helm chart or its `Chart.yaml` doesn't match the repository or release"))];
+});
+
+/// Publishes a new release by uploading its chart tarball.
+///
+/// The release is created with the given version as its tag and the tarball is
+/// validated and stored in the datastore in the same request.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{version}/tarball",
+    operation_id = "uploadRepositoryReleaseTarball",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, Version),
+    request_body(
+        description = "Multipart form of a single field being the chart tarball",
+        content = [u8],
+        content_type = "multipart/form-data"
+    ),
+    responses(UploadReleaseTarballR)
+)]
+pub async fn upload_release_tarball(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, Version)>,
+    multipart: Multipart,
+) -> api::Result<RepositoryRelease> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MetadataUpdate).await?;
+    let release = ops::releases::publish(&env, &cx, &repository, version, Tarball::Multipart(multipart.0)).await?;
+
+    Ok(api::ok(StatusCode::CREATED, release))
+}
+
+struct UploadReleaseProvenanceR;
+mk_into_responses!(for UploadReleaseProvenanceR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("provenance file was uploaded");
+    )];
+
+    "403" => [error(description("user is not allowed to publish releases to this repository"))];
+    "404" => [error(description("repository or release was not found"))];
+    "412" => [error(description("multipart stream was missing a field or had an invalid content type"))];
+    "422" => [error(description("provenance file was not valid, its checksum doesn't match the chart tarball, or it wasn't signed by one of the owner's GPG keys"))];
+});
+
+/// Uploads the [provenance](https://helm.sh/docs/topics/provenance/) file of an
+/// existing release.
+///
+/// The provenance file's checksum has to match the release's chart tarball and, if
+/// the repository owner has any GPG keys, it has to be signed by one of them.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{version}/provenance",
+    operation_id = "uploadRepositoryReleaseProvenance",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, Version),
+    request_body(
+        description = "Multipart form of a single field being the chart's provenance file",
+        content = [u8],
+        content_type = "multipart/form-data"
+    ),
+    responses(UploadReleaseProvenanceR)
+)]
+pub async fn upload_release_provenance(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, Version)>,
+    multipart: Multipart,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MetadataUpdate).await?;
+    let release = find(&env, &repository, VersionOrUlid::Version(version.clone())).await?;
+    let ns = env.ds.owner_repo(repository.owner, repository.id);
+
+    // releases that were uploaded before digests were recorded have to be
+    // hashed from their tarball instead.
+    let digest = match release.digest {
+        Some(digest) => digest,
+        None => {
+            let Some(file) = ns
+                .get_chart(&version, true)
+                .await
+                .map_err(api::system_failure_from_report)?
+            else {
+                return Err(api::err(
+                    StatusCode::NOT_FOUND,
+                    (
+                        api::ErrorCode::EntityNotFound,
+                        "release has no chart tarball to verify the provenance file against",
+                        json!({"version":version}),
+                    ),
+                ));
+            };
+
+            charted_helm_charts::digest(&file.data)
+        }
+    };
+
+    let keyring = ops::gpg::keyring(&env, repository.owner)
+        .await
+        .map_err(api::system_failure_from_report)?;
+
+    ns.upload_chart_provenance(multipart.0, &repository, version, &digest, &keyring)
+        .await
+}
+
+struct PatchReleaseR;
+mk_into_responses!(for PatchReleaseR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("patch was applied to the release");
+    )];
+
+    "403" => [error(description("user is not allowed to update releases in this repository"))];
+    "404" => [error(description("repository or release was not found"))];
+});
+
+/// Updates the title or changelog of a release.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}",
+    operation_id = "patchRepositoryRelease",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    request_body(
+        content_type = "application/json",
+        description = "Payload object for patching a release",
+        content = ref("#/components/schemas/PatchRepositoryReleasePayload")
+    ),
+    responses(PatchReleaseR),
+    security(
+        ("ApiKey" = ["repo:releases:update"])
+    )
+)]
+pub async fn patch_release(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+    Json(PatchRepositoryReleasePayload { update_text, title }): Json<PatchRepositoryReleasePayload>,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MetadataUpdate).await?;
+    let model = find(&env, &repository, version).await?;
+
+    let mut active = model.clone().into_active_model();
+    commit_patch!(active of string?: old.title => title);
+    commit_patch!(active of string?: old.update_text => update_text);
+
+    active.updated_at = ActiveValue::set(Utc::now());
+    let updated = active.update(&env.db).await.into_system_failure()?;
+
+    let release = RepositoryRelease::from(updated);
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::ReleaseUpdated,
+        &release,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::ReleaseUpdated,
+        auditlog::repository(&repository),
+        RepositoryRelease::from(model),
+        &release,
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+struct YankReleaseR;
+mk_into_responses!(for YankReleaseR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("release was yanked, or was already yanked");
+    )];
+
+    "403" => [error(description("user is not allowed to update releases in this repository"))];
+    "404" => [error(description("repository or release was not found"))];
+});
+
+/// Yanks a release.
+///
+/// Yanked releases are removed from the repository's `index.yaml` and are skipped
+/// when downloading the `latest` version, but can still be downloaded by their
+/// exact version so that existing installations keep working.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    post,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}/yank",
+    operation_id = "yankRepositoryRelease",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    responses(YankReleaseR),
+    security(
+        ("ApiKey" = ["repo:releases:update"])
+    )
+)]
+pub async fn yank_release(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+) -> api::Result<()> {
+    set_yanked(&env, &cx, &user, (owner, repo, version), true).await
+}
+
+struct UnyankReleaseR;
+mk_into_responses!(for UnyankReleaseR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("release was unyanked, or wasn't yanked");
+    )];
+
+    "403" => [error(description("user is not allowed to update releases in this repository"))];
+    "404" => [error(description("repository or release was not found"))];
+});
+
+/// Reverts a yanked release, which adds it back into the repository's `index.yaml`.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    post,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}/unyank",
+    operation_id = "unyankRepositoryRelease",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    responses(UnyankReleaseR),
+    security(
+        ("ApiKey" = ["repo:releases:update"])
+    )
+)]
+pub async fn unyank_release(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+) -> api::Result<()> {
+    set_yanked(&env, &cx, &user, (owner, repo, version), false).await
+}
+
+struct DeleteReleaseR;
+mk_into_responses!(for DeleteReleaseR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("release was deleted");
+    )];
+
+    "403" => [error(description("user is not allowed to delete releases in this repository"))];
+    "404" => [error(description("repository or release was not found"))];
+});
+
+/// Deletes a release alongside its chart tarball and provenance file. The
+/// release's version can be published again afterwards.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+
+    path = "/v1/repositories/{owner}/{repo}/releases/{versionOrId}",
+    operation_id = "deleteRepositoryRelease",
+    tags = ["Repositories", "Repository/Releases"],
+    params(OwnerRepoP, VersionOrUlid),
+    responses(DeleteReleaseR),
+    security(
+        ("ApiKey" = ["repo:releases:delete"])
+    )
+)]
+pub async fn delete_release(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, version)): Path<(NameOrUlid, NameOrUlid, VersionOrUlid)>,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::MetadataDelete).await?;
+    let model = find(&env, &repository, version).await?;
+
+    ops::releases::delete(&env, &cx, &repository, model).await?;
+    Ok(api::no_content())
+}
+
+async fn set_yanked(
+    env: &Env,
+    cx: &auditlog::Context,
+    user: &User,
+    (owner, repo, version): (NameOrUlid, NameOrUlid, VersionOrUlid),
+    yanked: bool,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(env, user, owner, repo, MemberPermission::MetadataUpdate).await?;
+    let model = find(env, &repository, version).await?;
+    if model.yanked == yanked {
+        return Ok(api::no_content());
+    }
+
+    let mut active = model.clone().into_active_model();
+    active.yanked = ActiveValue::set(yanked);
+    active.updated_at = ActiveValue::set(Utc::now());
+
+    let updated = active.update(&env.db).await.into_system_failure()?;
+
+    // yanked releases are excluded from `index.yaml`, the latest versions and the
+    // OCI registry
+    ops::latest::refresh(env, repository.id).await;
+    if !repository.private {
+        ops::indexes::regenerate(env, repository.owner).await;
+    }
+
+    match yanked {
+        true => ops::releases::untag(env, &repository, &updated.tag).await,
+        false => ops::releases::tag(env, &repository, &updated).await,
+    }
+
+    let release = RepositoryRelease::from(updated);
+    ops::webhooks::emit(
+        env,
+        ops::webhooks::repository(&repository),
+        EventKind::ReleaseUpdated,
+        &release,
+    )
+    .await;
+
+    auditlog::record(
+        env,
+        cx,
+        Action::ReleaseUpdated,
+        auditlog::repository(&repository),
+        RepositoryRelease::from(model),
+        &release,
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+async fn find(
+    env: &Env,
+    repository: &Repository,
+    version: VersionOrUlid,
+) -> Result<release::Model, api::Response> {
+    db::repository::release::get_as_model(&env.db, repository, version.clone())
+        .await?
+        .ok_or_else(|| {
+            api::err(
+                StatusCode::NOT_FOUND,
+                (
+                    api::ErrorCode::EntityNotFound,
+                    "repository release with version or ulid was not found",
+                    json!({"versionOrUlid":version}),
+                ),
+            )
+        })
+}
@@ -0,0 +1,239 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Webhooks that receive the events of a single repository. Only the repository's
+//! owner and members with the `webhooks:*` permissions can manage its webhooks.
+
+use crate::{
+    Env,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListWebhookDeliveryResponse, ListWebhookResponse, WebhookResponse},
+    ops::{self, auditlog},
+    pagination::PaginationRequest,
+    routing::v1::repository::OwnerRepoP,
+};
+use axum::{Extension, Router, extract::State, handler::Handler, routing};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, MemberPermission},
+};
+use charted_feature_webhooks::{CreateWebhookPayload, PatchWebhookPayload, Webhook, WebhookDelivery};
+use charted_types::{NameOrUlid, Ulid};
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookList))))
+                .put(create.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookCreate)))),
+        )
+        .route(
+            "/{id}",
+            routing::get(fetch.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookList))))
+                .patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookUpdate))))
+                .delete(delete.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookDelete)))),
+        )
+        .route(
+            "/{id}/deliveries",
+            routing::get(
+                deliveries.layer(env.authn(Options::default().with_scope(ApiKeyScope::RepoWebhookEventAccess))),
+            ),
+        )
+}
+
+struct ListWebhooksR;
+mk_into_responses!(for ListWebhooksR {
+    "200" => [ref(ListWebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository was not found"))];
+});
+
+/// Lists all the webhooks of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks",
+    operation_id = "listRepositoryWebhooks",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP),
+    responses(ListWebhooksR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+) -> api::Result<Vec<Webhook>> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::list(&env, ops::webhooks::repository(&repository)).await
+}
+
+struct FetchWebhookR;
+mk_into_responses!(for FetchWebhookR {
+    "200" => [ref(WebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository or webhook was not found"))];
+});
+
+/// Retrieve a single webhook of this repository.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks/{id}",
+    operation_id = "getRepositoryWebhook",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP, Ulid),
+    responses(FetchWebhookR)
+)]
+pub async fn fetch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo, id)): Path<(NameOrUlid, NameOrUlid, Ulid)>,
+) -> api::Result<Webhook> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookUpdate).await?;
+    let model = ops::webhooks::get(&env, ops::webhooks::repository(&repository), id).await?;
+
+    Ok(api::ok(axum::http::StatusCode::OK, Webhook::from(model).sanitize()))
+}
+
+struct CreateWebhookR;
+mk_into_responses!(for CreateWebhookR {
+    "201" => [ref(WebhookResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository was not found"))];
+    "422" => [error(description("`url` wasn't a HTTP endpoint or `events` was empty"))];
+});
+
+/// Creates a webhook for this repository. The response is the only time that the
+/// webhook's secret is available.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks",
+    operation_id = "createRepositoryWebhook",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP),
+    request_body(
+        content = ref("#/components/schemas/CreateWebhookPayload"),
+        description = "Request body for creating a new webhook",
+        content_type = "application/json"
+    ),
+    responses(CreateWebhookR)
+)]
+pub async fn create(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo)): Path<(NameOrUlid, NameOrUlid)>,
+    Json(payload): Json<CreateWebhookPayload>,
+) -> api::Result<Webhook> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookCreate).await?;
+    ops::webhooks::create(&env, &cx, ops::webhooks::repository(&repository), payload).await
+}
+
+struct PatchWebhookR;
+mk_into_responses!(for PatchWebhookR {
+    "204" => [ref(EmptyApiResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository or webhook was not found"))];
+    "422" => [error(description("`url` wasn't a HTTP endpoint or `events` was empty"))];
+});
+
+/// Patches a webhook's metadata.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks/{id}",
+    operation_id = "patchRepositoryWebhook",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP, Ulid),
+    request_body(
+        content = ref("#/components/schemas/PatchWebhookPayload"),
+        description = "Request body for patching a webhook",
+        content_type = "application/json"
+    ),
+    responses(PatchWebhookR)
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, id)): Path<(NameOrUlid, NameOrUlid, Ulid)>,
+    Json(payload): Json<PatchWebhookPayload>,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::patch(&env, &cx, ops::webhooks::repository(&repository), id, payload).await
+}
+
+struct DeleteWebhookR;
+mk_into_responses!(for DeleteWebhookR {
+    "202" => [ref(EmptyApiResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository or webhook was not found"))];
+});
+
+/// Deletes a webhook and all of its recorded deliveries.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks/{id}",
+    operation_id = "deleteRepositoryWebhook",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP, Ulid),
+    responses(DeleteWebhookR)
+)]
+pub async fn delete(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path((owner, repo, id)): Path<(NameOrUlid, NameOrUlid, Ulid)>,
+) -> api::Result<()> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookDelete).await?;
+    ops::webhooks::delete(&env, &cx, ops::webhooks::repository(&repository), id).await
+}
+
+struct ListDeliveriesR;
+mk_into_responses!(for ListDeliveriesR {
+    "200" => [ref(ListWebhookDeliveryResponse)];
+    "403" => [error(description("user is not allowed to manage this repository's webhooks"))];
+    "404" => [error(description("repository or webhook was not found"))];
+});
+
+/// Lists the delivery attempts of a webhook, most recent first.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+
+    path = "/v1/repositories/{owner}/{repo}/webhooks/{id}/deliveries",
+    operation_id = "listRepositoryWebhookDeliveries",
+    tags = ["Repositories", "Webhooks"],
+    params(OwnerRepoP, Ulid, PaginationRequest),
+    responses(ListDeliveriesR)
+)]
+pub async fn deliveries(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path((owner, repo, id)): Path<(NameOrUlid, NameOrUlid, Ulid)>,
+    Query(pagination): Query<PaginationRequest>,
+) -> api::Result<Vec<WebhookDelivery>> {
+    let repository = super::fetch_modifiable(&env, &user, owner, repo, MemberPermission::WebhookUpdate).await?;
+    ops::webhooks::deliveries(&env, ops::webhooks::repository(&repository), id, pagination).await
+}
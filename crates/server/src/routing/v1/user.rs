@@ -15,25 +15,30 @@
 
 pub mod apikeys;
 pub mod avatars;
+pub mod connections;
+pub mod gpg_keys;
+pub mod invites;
 pub mod repositories;
 pub mod sessions;
+pub mod totp;
 
 use crate::{
-    Env, commit_patch,
+    Env, OwnerExt, commit_patch,
     ext::ResultExt,
     extract::{Json, Path},
     middleware::authn::{Factory, Options, Session},
     mk_into_responses,
     openapi::{EmptyApiResponse, UserResponse},
-    ops::{self, db},
+    ops::{self, auditlog, db},
     routing::v1::Entrypoint,
 };
 use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
 use charted_core::{api, bitflags::ApiKeyScope};
 use charted_database::entities::{UserEntity, user};
+use charted_feature_audit_logs::Action;
 use charted_helm_charts::DataStoreExt;
 use charted_types::{
-    NameOrUlid, User,
+    NameOrUlid, Owner, User,
     payloads::{CreateUserPayload, PatchUserPayload},
 };
 use chrono::Utc;
@@ -54,7 +59,7 @@ pub fn create_router(env: &Env) -> Router<Env> {
         .route("/avatars/{hash}", routing::get(avatars::get_user_avatar_by_hash))
         .route("/repositories", routing::get(repositories::list_user_repositories));
 
-    let at_me = {
+    let mut at_me = {
         let base = match env.config.single_user {
             false => Router::new().route(
                 "/",
@@ -70,11 +75,32 @@ pub fn create_router(env: &Env) -> Router<Env> {
         };
 
         base.nest("/apikeys", apikeys::create_router(env))
+            .nest("/connections", connections::create_router(env))
+            .nest("/gpg-keys", gpg_keys::create_router(env))
+            .nest("/invites", invites::create_router(env))
             .route(
                 "/session",
                 routing::get(sessions::fetch.layer(env.authn(Options::default())))
                     .delete(sessions::logout.layer(env.authn(Options::default()))),
             )
+            .route(
+                "/sessions",
+                routing::get(
+                    sessions::list_sessions
+                        .layer(env.authn(Options::default().with_scope(ApiKeyScope::UserSessionsList))),
+                )
+                .delete(
+                    sessions::revoke_other_sessions
+                        .layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate))),
+                ),
+            )
+            .route(
+                "/sessions/{id}",
+                routing::delete(
+                    sessions::revoke_session
+                        .layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate))),
+                ),
+            )
             .route(
                 "/session/refresh",
                 routing::post(sessions::refresh_session.layer(env.authn(Options {
@@ -125,10 +151,30 @@ pub fn create_router(env: &Env) -> Router<Env> {
             )
     };
 
+    if env.features.has::<charted_feature_totp::Feature>() {
+        at_me = at_me.nest("/2fa", totp::create_router(env));
+    }
+
     router
         .nest("/@me", at_me)
         .nest("/{idOrName}", id_or_name)
         .route("/login", routing::post(sessions::login))
+        .route("/login/oidc/{provider}", routing::get(sessions::oidc_login))
+        .route(
+            "/login/oidc/{provider}/callback",
+            routing::get(
+                sessions::oidc_callback.layer(
+                    env.authn(
+                        Options {
+                            allow_unauthorized: true,
+                            ..Default::default()
+                        }
+                        .with_scope(ApiKeyScope::UserConnections),
+                    ),
+                ),
+            ),
+        )
+        .route("/login/oidc/{provider}/totp", routing::post(sessions::oidc_totp))
 }
 
 /// Entrypoint to the Users API.
@@ -149,7 +195,7 @@ mk_into_responses!(for CreateUserR {
     "201" => [ref(UserResponse)];
     "403" => [error(description("Instance doesn't allow registrations"))];
     "406" => [error(description("Session backend requires `password` or `email` wasn't a valid email address"))];
-    "409" => [error(description("Either the `username` or `email` was taken by another user"))];
+    "409" => [error(description("Either the `username` was taken by another user or organization, or the `email` by another user"))];
 });
 
 /// Creates a new user.
@@ -205,15 +251,17 @@ pub async fn create(
         ));
     }
 
-    if db::user::get_as_model(&env.db, NameOrUlid::Name(username.clone()))
-        .await?
+    // users and organizations share a namespace, since repositories are owned by either
+    if Owner::query_by_name(&env, username.clone())
+        .await
+        .into_system_failure()?
         .is_some()
     {
         return Err(api::err(
             StatusCode::CONFLICT,
             (
                 api::ErrorCode::EntityAlreadyExists,
-                "a user with `username` already exists",
+                "a user or organization with `username` already exists",
                 json!({"username":username.as_str()}),
             ),
         ));
@@ -362,7 +410,8 @@ mk_into_responses!(for PatchUserR {
 )]
 pub async fn patch(
     State(env): State<Env>,
-    Extension(Session { user, .. }): Extension<Session>,
+    Extension(Session { user, session }): Extension<Session>,
+    cx: auditlog::Context,
     Json(PatchUserPayload {
         prefers_gravatar,
         gravatar_email,
@@ -412,13 +461,14 @@ pub async fn patch(
     }
 
     if let Some(username) = username {
-        if db::user::get_as_model(&env.db, NameOrUlid::Name(username.clone()))
-            .await?
+        if Owner::query_by_name(&env, username.clone())
+            .await
+            .into_system_failure()?
             .is_some()
         {
             errors.push(api::Error {
                 code: api::ErrorCode::EntityAlreadyExists,
-                message: Cow::Borrowed("an existing user already exists with that name"),
+                message: Cow::Borrowed("a user or organization already exists with that name"),
                 details: Some(json!({
                     "path": "username",
                     "username": &username
@@ -464,15 +514,33 @@ pub async fn patch(
         return Err(api::empty(false, StatusCode::CONFLICT));
     }
 
-    model
+    let password_changed = model.password.is_set();
+    let updated = model
         .update(&env.db)
         .await
         .inspect_err(|e| {
             error!(error = %e, %user.username, "failed to commit changes for patch");
             sentry::capture_error(e);
         })
-        .map_err(api::system_failure)
-        .map(|_| api::no_content())
+        .map_err(api::system_failure)?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::UserUpdated,
+        auditlog::owner(user.id),
+        &user,
+        User::from(updated),
+    )
+    .await;
+
+    // sessions that were created with the old password shouldn't outlive it.
+    if password_changed {
+        let revoked = db::session::revoke_others(&env, user.id, session.map(|session| session.id)).await?;
+        info!(%user.id, revoked, "password was changed, revoked other sessions");
+    }
+
+    Ok(api::no_content())
 }
 
 /// Delete yourself.
@@ -495,6 +563,7 @@ pub async fn patch(
 pub async fn delete(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
 ) -> api::Result<()> {
     UserEntity::delete_by_id(user.id)
         .exec(&env.db)
@@ -503,6 +572,8 @@ pub async fn delete(
             error!(error = %e, %user.id, %user.username, "failed to delete user");
             sentry::capture_error(e);
         })
-        .map_err(api::system_failure)
-        .map(|_| api::no_content())
+        .map_err(api::system_failure)?;
+
+    auditlog::record(&env, &cx, Action::UserDeleted, auditlog::owner(user.id), &user, ()).await;
+    Ok(api::no_content())
 }
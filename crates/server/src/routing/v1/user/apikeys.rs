@@ -20,7 +20,7 @@ use crate::{
     middleware::authn::{Factory, Options, Session},
     mk_into_responses,
     openapi::{ApiKeyResponse, EmptyApiResponse, ListApiKeyResponse},
-    ops::db,
+    ops::{auditlog, db},
     pagination::PaginationRequest,
     util::{self, BuildLinkHeaderOpts},
 };
@@ -33,6 +33,7 @@ use axum::{
 };
 use charted_core::{api, bitflags::ApiKeyScope, clamp, rand_string};
 use charted_database::entities::{ApiKeyEntity, apikey};
+use charted_feature_audit_logs::Action;
 use charted_types::{
     ApiKey, NameOrUlid,
     payloads::{CreateApiKeyPayload, PatchApiKeyPayload},
@@ -176,6 +177,7 @@ mk_into_responses!(for CreateApiKeyR {
 pub async fn create(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Json(CreateApiKeyPayload {
         display_name,
         description,
@@ -207,6 +209,8 @@ pub async fn create(
         display_name,
         description,
         expires_in: expires_in.map(Into::into),
+        deactivated_at: None,
+        expiry_notified_at: None,
         created_at: now,
         updated_at: now,
         scopes,
@@ -226,7 +230,18 @@ pub async fn create(
         })
         .map_err(api::system_failure)?;
 
-    Ok(api::ok(StatusCode::CREATED, model.into()))
+    let apikey = ApiKey::from(model);
+    auditlog::record(
+        &env,
+        &cx,
+        Action::ApiKeyCreated,
+        auditlog::owner(user.id),
+        (),
+        apikey.clone().sanitize(),
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, apikey))
 }
 
 struct PatchApiKeyR;
@@ -253,6 +268,7 @@ mk_into_responses!(for PatchApiKeyR {
 pub async fn patch(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Path(id_or_name): Path<NameOrUlid>,
     Json(PatchApiKeyPayload {
         display_name,
@@ -261,7 +277,7 @@ pub async fn patch(
         name,
     }): Json<PatchApiKeyPayload>,
 ) -> api::Result<()> {
-    let mut model = db::apikey::get_as_model(&env.db, id_or_name.clone())
+    let existing = db::apikey::get_as_model(&env.db, id_or_name.clone())
         .await?
         .ok_or_else(|| {
             api::err(
@@ -272,8 +288,9 @@ pub async fn patch(
                     json!({"idOrName":id_or_name}),
                 ),
             )
-        })?
-        .into_active_model();
+        })?;
+
+    let mut model = existing.clone().into_active_model();
 
     let mut errors = Vec::new();
     commit_patch!(model of string?: old.display_name => display_name);
@@ -303,11 +320,18 @@ pub async fn patch(
         return Err(api::empty(false, StatusCode::CONFLICT));
     }
 
-    model
-        .update(&env.db)
-        .await
-        .map(|_| api::no_content())
-        .into_system_failure()
+    let updated = model.update(&env.db).await.into_system_failure()?;
+    auditlog::record(
+        &env,
+        &cx,
+        Action::ApiKeyUpdated,
+        auditlog::owner(user.id),
+        ApiKey::from(existing).sanitize(),
+        ApiKey::from(updated).sanitize(),
+    )
+    .await;
+
+    Ok(api::no_content())
 }
 
 struct DeleteApiKeyR;
@@ -329,9 +353,10 @@ mk_into_responses!(for DeleteApiKeyR {
 pub async fn delete(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Path(id_or_name): Path<NameOrUlid>,
 ) -> api::Result<()> {
-    let ApiKey { id, .. } = db::apikey::get_with_additional_bounds(&env.db, id_or_name.clone(), |query| {
+    let apikey = db::apikey::get_with_additional_bounds(&env.db, id_or_name.clone(), |query| {
         query.filter(apikey::Column::Owner.eq(user.id))
     })
     .await?
@@ -346,9 +371,20 @@ pub async fn delete(
         )
     })?;
 
-    ApiKeyEntity::delete_by_id(id)
+    ApiKeyEntity::delete_by_id(apikey.id)
         .exec(&env.db)
         .await
-        .map(|_| api::from_default(StatusCode::ACCEPTED))
-        .map_err(api::system_failure)
+        .map_err(api::system_failure)?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::ApiKeyDeleted,
+        auditlog::owner(user.id),
+        apikey.sanitize(),
+        (),
+    )
+    .await;
+
+    Ok(api::from_default(StatusCode::ACCEPTED))
 }
@@ -19,6 +19,7 @@ use crate::{
     extract::{Multipart, Path},
     middleware::authn::Session,
     ops::{
+        auditlog,
         avatars::{DataStoreExt, GetAvatarR, Params, UpdateAvatarR},
         db,
     },
@@ -26,7 +27,8 @@ use crate::{
 use axum::{Extension, extract::State, http::StatusCode, response::IntoResponse};
 use charted_core::api;
 use charted_database::entities::user;
-use charted_types::NameOrUlid;
+use charted_feature_audit_logs::Action;
+use charted_types::{NameOrUlid, User};
 use sea_orm::{ActiveModelTrait, IntoActiveModel};
 use serde_json::json;
 use url::Url;
@@ -137,6 +139,7 @@ pub async fn get_self_user_avatar_by_hash(
 pub async fn upload_user_avatar(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     data: Multipart,
 ) -> api::Result<Url> {
     let mut model = db::user::get_as_model(&env.db, NameOrUlid::Ulid(user.id))
@@ -148,7 +151,17 @@ pub async fn upload_user_avatar(
     let hash = ns.upload(data).await?;
 
     model.set(user::Column::AvatarHash, Some(hash.clone()).into());
-    model.update(&env.db).await.into_system_failure()?;
+    let updated = model.update(&env.db).await.into_system_failure()?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::UserUpdated,
+        auditlog::owner(user.id),
+        &user,
+        User::from(updated),
+    )
+    .await;
 
     let resource = env
         .config
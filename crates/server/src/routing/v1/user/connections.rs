@@ -0,0 +1,122 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Linking and unlinking accounts from the OpenID Connect providers that are
+//! configured in `sessions.oidc` to the authenticated user.
+
+use crate::{
+    Env,
+    extract::Path,
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{UrlResponse, UserConnectionsResponse},
+    ops::{auditlog, oidc},
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{api, bitflags::ApiKeyScope};
+use charted_types::UserConnections;
+use url::Url;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(fetch.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserConnections)))),
+        )
+        .route(
+            "/{provider}",
+            routing::post(link.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserConnections))))
+                .delete(unlink.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserConnections)))),
+        )
+}
+
+struct FetchR;
+mk_into_responses!(for FetchR {
+    "200" => [ref(UserConnectionsResponse)];
+});
+
+/// Retrieve the connections of the authenticated user.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/users/@me/connections",
+    operation_id = "getUserConnections",
+    tags = ["Users", "Users/Connections"],
+    responses(FetchR)
+)]
+pub async fn fetch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+) -> api::Result<UserConnections> {
+    oidc::connections(&env, user.id)
+        .await
+        .map(|model| api::ok(StatusCode::OK, model.into()))
+}
+
+struct LinkR;
+mk_into_responses!(for LinkR {
+    "200" => [ref(UrlResponse)];
+    "404" => [error(description("openid connect provider was not found"))];
+});
+
+/// Starts linking an account from an OpenID Connect provider.
+///
+/// The user should be sent to the returned URL, and the provider will redirect them
+/// back to the provider's callback endpoint to finish linking the account.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    post,
+    path = "/v1/users/@me/connections/{provider}",
+    operation_id = "linkUserConnection",
+    tags = ["Users", "Users/Connections"],
+    params(("provider" = String, Path, description = "Name of the OpenID Connect provider")),
+    responses(LinkR)
+)]
+pub async fn link(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path(provider): Path<String>,
+) -> api::Result<Url> {
+    oidc::authorize(&env, &provider, Some(user.id))
+        .await
+        .map(|url| api::ok(StatusCode::OK, url))
+}
+
+struct UnlinkR;
+mk_into_responses!(for UnlinkR {
+    "200" => [ref(UserConnectionsResponse)];
+    "404" => [error(description("openid connect provider was not found"))];
+});
+
+/// Unlinks the account from an OpenID Connect provider.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/users/@me/connections/{provider}",
+    operation_id = "unlinkUserConnection",
+    tags = ["Users", "Users/Connections"],
+    params(("provider" = String, Path, description = "Name of the OpenID Connect provider")),
+    responses(UnlinkR)
+)]
+pub async fn unlink(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(provider): Path<String>,
+) -> api::Result<UserConnections> {
+    let provider = oidc::provider(&env, &provider)?;
+    oidc::unlink(&env, &cx, user.id, provider.connection)
+        .await
+        .map(|connections| api::ok(StatusCode::OK, connections))
+}
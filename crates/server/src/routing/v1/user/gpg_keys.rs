@@ -0,0 +1,387 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Management of the OpenPGP public keys in the keyring of the authenticated user, or
+//! of an organization that they can modify with the `organization` query parameter.
+//!
+//! When the keyring of a repository's owner isn't empty, provenance files that are
+//! uploaded to that repository have to be signed by one of its keys.
+
+use crate::{
+    Env, commit_patch,
+    ext::ResultExt,
+    extract::{Json, Path, Query},
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, GpgKeyResponse, ListGpgKeyResponse},
+    ops::auditlog,
+    routing::v1::organization,
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{
+    api,
+    bitflags::{ApiKeyScope, MemberPermission},
+};
+use charted_database::entities::{GpgKeyEntity, gpg_key};
+use charted_feature_audit_logs::Action;
+use charted_helm_charts::provenance;
+use charted_types::{
+    GpgKey, NameOrUlid, Ulid, User,
+    payloads::{CreateGpgKeyPayload, PatchGpgKeyPayload},
+};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserAccess))))
+                .put(create.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+        .route(
+            "/{id}",
+            routing::get(fetch.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserAccess))))
+                .patch(patch.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate))))
+                .delete(delete.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct KeyringQuery {
+    /// ID or name of an organization to manage the keyring of instead. The
+    /// `metadata:update` member permission is required to modify it.
+    #[serde(default)]
+    pub organization: Option<NameOrUlid>,
+}
+
+/// Returns the ID of the user or organization whose keyring is being managed.
+async fn owner(
+    env: &Env,
+    user: &User,
+    KeyringQuery { organization }: KeyringQuery,
+    modify: bool,
+) -> Result<Ulid, api::Response> {
+    match organization {
+        None => Ok(user.id),
+        Some(id_or_name) if modify => {
+            organization::fetch_modifiable(env, user, id_or_name, Some(MemberPermission::MetadataUpdate))
+                .await
+                .map(|org| org.id)
+        }
+
+        Some(id_or_name) => organization::resolve(env, id_or_name, Some(user))
+            .await
+            .map(|org| org.id),
+    }
+}
+
+async fn find(env: &Env, owner: Ulid, id: Ulid) -> Result<gpg_key::Model, api::Response> {
+    GpgKeyEntity::find_by_id(id)
+        .filter(gpg_key::Column::Owner.eq(owner))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .ok_or_else(|| {
+            api::err(
+                StatusCode::NOT_FOUND,
+                (
+                    api::ErrorCode::EntityNotFound,
+                    "gpg key with id was not found",
+                    json!({"id":id}),
+                ),
+            )
+        })
+}
+
+fn validate_display_name(display_name: Option<&str>) -> Result<(), api::Response> {
+    match display_name {
+        Some(name) if name.len() > 32 => Err(api::err(
+            StatusCode::NOT_ACCEPTABLE,
+            (
+                api::ErrorCode::ValidationFailed,
+                "`displayName` was expected to be 32 characters or shorter",
+                json!({"expected":32,"received":name.len()}),
+            ),
+        )),
+
+        _ => Ok(()),
+    }
+}
+
+struct ListGpgKeysR;
+mk_into_responses!(for ListGpgKeysR {
+    "200" => [ref(ListGpgKeyResponse)];
+    "404" => [error(description("organization was not found"))];
+});
+
+/// Lists all the public keys in the keyring.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/users/@me/gpg-keys",
+    operation_id = "listGpgKeys",
+    tag = "Users",
+    params(KeyringQuery),
+    responses(ListGpgKeysR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Query(query): Query<KeyringQuery>,
+) -> api::Result<Vec<GpgKey>> {
+    let owner = owner(&env, &user, query, false).await?;
+    let keys = GpgKeyEntity::find()
+        .filter(gpg_key::Column::Owner.eq(owner))
+        .order_by_asc(gpg_key::Column::Id)
+        .all(&env.db)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<GpgKey>::into)
+        .collect();
+
+    Ok(api::ok(StatusCode::OK, keys))
+}
+
+struct SingleGpgKeyR;
+mk_into_responses!(for SingleGpgKeyR {
+    "200" => [ref(GpgKeyResponse)];
+    "404" => [error(description("organization or gpg key was not found"))];
+});
+
+/// Retrieve a single public key from the keyring.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/users/@me/gpg-keys/{id}",
+    operation_id = "getGpgKey",
+    tag = "Users",
+    params(
+        ("id" = Ulid, Path, description = "ID of the gpg key"),
+        KeyringQuery
+    ),
+    responses(SingleGpgKeyR)
+)]
+pub async fn fetch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path(id): Path<Ulid>,
+    Query(query): Query<KeyringQuery>,
+) -> api::Result<GpgKey> {
+    let owner = owner(&env, &user, query, false).await?;
+    find(&env, owner, id)
+        .await
+        .map(|key| api::ok(StatusCode::OK, GpgKey::from(key)))
+}
+
+struct CreateGpgKeyR;
+mk_into_responses!(for CreateGpgKeyR {
+    "201" => [ref(GpgKeyResponse)];
+    "403" => [error(description("user is not allowed to modify the organization's keyring"))];
+    "404" => [error(description("organization was not found"))];
+    "406" => [error(description("`displayName` was too long"))];
+    "409" => [error(description("public key is already in the keyring"))];
+    "422" => [error(description("public key was not a valid ASCII-armored OpenPGP public key"))];
+});
+
+/// Adds a public key into the keyring.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+    path = "/v1/users/@me/gpg-keys",
+    operation_id = "createGpgKey",
+    tag = "Users",
+    params(KeyringQuery),
+    request_body(
+        content = ref("#/components/schemas/CreateGpgKeyPayload"),
+        description = "Request body for adding a public key",
+        content_type = "application/json"
+    ),
+    responses(CreateGpgKeyR)
+)]
+pub async fn create(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Query(query): Query<KeyringQuery>,
+    Json(CreateGpgKeyPayload {
+        display_name,
+        public_key,
+    }): Json<CreateGpgKeyPayload>,
+) -> api::Result<GpgKey> {
+    let owner = owner(&env, &user, query, true).await?;
+    validate_display_name(display_name.as_deref())?;
+
+    let key = provenance::read_public_key(&public_key).map_err(|e| {
+        api::err(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            (
+                api::ErrorCode::InvalidInput,
+                "public key was not a valid ASCII-armored OpenPGP public key",
+                json!({"error":e.to_string()}),
+            ),
+        )
+    })?;
+
+    let fingerprint = provenance::fingerprint(&key);
+    if GpgKeyEntity::find()
+        .filter(gpg_key::Column::Owner.eq(owner))
+        .filter(gpg_key::Column::Fingerprint.eq(&fingerprint))
+        .one(&env.db)
+        .await
+        .into_system_failure()?
+        .is_some()
+    {
+        return Err(api::err(
+            StatusCode::CONFLICT,
+            (
+                api::ErrorCode::EntityAlreadyExists,
+                "public key is already in the keyring",
+                json!({"fingerprint":fingerprint}),
+            ),
+        ));
+    }
+
+    let id = env.ulid.generate().into_system_failure()?;
+    let now = Utc::now();
+    let model = gpg_key::Model {
+        display_name: display_name.filter(|name| !name.is_empty()),
+        created_at: now,
+        updated_at: now,
+        fingerprint,
+        public_key,
+        owner,
+        id: id.into(),
+    };
+
+    GpgKeyEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .inspect_err(|e| {
+            error!(error = %e, owner.id = %owner, "failed to add gpg key");
+            sentry::capture_error(e);
+        })
+        .map_err(api::system_failure)?;
+
+    let key = GpgKey::from(model);
+    auditlog::record(&env, &cx, Action::GpgKeyCreated, auditlog::owner(owner), (), &key).await;
+
+    Ok(api::ok(StatusCode::CREATED, key))
+}
+
+struct PatchGpgKeyR;
+mk_into_responses!(for PatchGpgKeyR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("patch was applied to the gpg key");
+    )];
+
+    "403" => [error(description("user is not allowed to modify the organization's keyring"))];
+    "404" => [error(description("organization or gpg key was not found"))];
+    "406" => [error(description("`displayName` was too long"))];
+});
+
+/// Updates a public key's display name.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    patch,
+    path = "/v1/users/@me/gpg-keys/{id}",
+    operation_id = "patchGpgKey",
+    tag = "Users",
+    params(
+        ("id" = Ulid, Path, description = "ID of the gpg key"),
+        KeyringQuery
+    ),
+    request_body(
+        content = ref("#/components/schemas/PatchGpgKeyPayload"),
+        description = "Request body for patching a public key",
+        content_type = "application/json"
+    ),
+    responses(PatchGpgKeyR)
+)]
+pub async fn patch(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id): Path<Ulid>,
+    Query(query): Query<KeyringQuery>,
+    Json(PatchGpgKeyPayload { display_name }): Json<PatchGpgKeyPayload>,
+) -> api::Result<()> {
+    let owner = owner(&env, &user, query, true).await?;
+    validate_display_name(display_name.as_deref())?;
+
+    let existing = find(&env, owner, id).await?;
+    let mut model = existing.clone().into_active_model();
+    commit_patch!(model of string?: old.display_name => display_name);
+
+    model.updated_at = ActiveValue::set(Utc::now());
+    let updated = model.update(&env.db).await.into_system_failure()?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::GpgKeyUpdated,
+        auditlog::owner(owner),
+        GpgKey::from(existing),
+        GpgKey::from(updated),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+/// Removes a public key from the keyring.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/users/@me/gpg-keys/{id}",
+    operation_id = "deleteGpgKey",
+    tag = "Users",
+    params(
+        ("id" = Ulid, Path, description = "ID of the gpg key"),
+        KeyringQuery
+    ),
+    responses(
+        (
+            status = 204,
+            description = "Public key was removed from the keyring",
+            body = EmptyApiResponse,
+            content_type = "application/json"
+        )
+    )
+)]
+pub async fn delete(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id): Path<Ulid>,
+    Query(query): Query<KeyringQuery>,
+) -> api::Result<()> {
+    let owner = owner(&env, &user, query, true).await?;
+    let key = find(&env, owner, id).await?;
+
+    GpgKeyEntity::delete_by_id(key.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    let key = GpgKey::from(key);
+    auditlog::record(&env, &cx, Action::GpgKeyDeleted, auditlog::owner(owner), &key, ()).await;
+
+    Ok(api::no_content())
+}
@@ -0,0 +1,155 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Endpoints for the authenticated user to accept or decline the invites that they
+//! received to become a repository or organization member.
+
+use crate::{
+    Env,
+    ext::ResultExt,
+    extract::Path,
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, ListMemberInviteResponse},
+    ops::{auditlog, invites},
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{api, bitflags::ApiKeyScope};
+use charted_database::entities::{MemberInviteEntity, member_invite};
+use charted_feature_audit_logs::Action;
+use charted_types::{MemberInvite, Ulid};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(list.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserAccess)))),
+        )
+        .route(
+            "/{id}",
+            routing::delete(decline.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+        .route(
+            "/{id}/accept",
+            routing::post(accept.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+}
+
+struct ListInvitesR;
+mk_into_responses!(for ListInvitesR {
+    "200" => [ref(ListMemberInviteResponse)];
+});
+
+/// Lists all the pending invites that you received.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/users/@me/invites",
+    operation_id = "listSelfUserInvites",
+    tags = ["Users"],
+    responses(ListInvitesR)
+)]
+pub async fn list(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+) -> api::Result<Vec<MemberInvite>> {
+    let invites = MemberInviteEntity::find()
+        .filter(member_invite::Column::Account.eq(user.id))
+        .filter(member_invite::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_asc(member_invite::Column::Id)
+        .all(&env.db)
+        .await
+        .into_system_failure()?
+        .into_iter()
+        .map(Into::<MemberInvite>::into)
+        .collect();
+
+    Ok(api::ok(StatusCode::OK, invites))
+}
+
+struct AcceptInviteR;
+mk_into_responses!(for AcceptInviteR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("invite was accepted and you are now a member");
+    )];
+
+    "404" => [error(description("invite was not found or has expired"))];
+    "409" => [error(description("you are already a member"))];
+});
+
+/// Accepts an invite, which makes you a member of the repository or organization that
+/// you were invited to.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    post,
+    path = "/v1/users/@me/invites/{id}/accept",
+    operation_id = "acceptSelfUserInvite",
+    tags = ["Users"],
+    params(
+        ("id" = Ulid, Path, description = "ID of the invite")
+    ),
+    responses(AcceptInviteR)
+)]
+pub async fn accept(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id): Path<Ulid>,
+) -> api::Result<()> {
+    let invite = invites::pending(&env, user.id, id).await?;
+    invites::accept(&env, &cx, invite).await?;
+
+    Ok(api::no_content())
+}
+
+struct DeclineInviteR;
+mk_into_responses!(for DeclineInviteR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("invite was declined");
+    )];
+
+    "404" => [error(description("invite was not found or has expired"))];
+});
+
+/// Declines an invite.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/users/@me/invites/{id}",
+    operation_id = "declineSelfUserInvite",
+    tags = ["Users"],
+    params(
+        ("id" = Ulid, Path, description = "ID of the invite")
+    ),
+    responses(DeclineInviteR)
+)]
+pub async fn decline(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Path(id): Path<Ulid>,
+) -> api::Result<()> {
+    let invite = invites::pending(&env, user.id, id).await?;
+    MemberInviteEntity::delete_by_id(invite.id)
+        .exec(&env.db)
+        .await
+        .into_system_failure()?;
+
+    invites::record(&env, &cx, Action::InviteDeclined, &invite).await;
+
+    Ok(api::no_content())
+}
@@ -22,7 +22,7 @@ use crate::{
     middleware::authn::Session,
     mk_into_responses,
     openapi::{EmptyApiResponse, ListRepositoryResponse, RepositoryResponse},
-    ops::db,
+    ops::{self, auditlog, db},
     pagination::PaginationRequest,
     util::{self, BuildLinkHeaderOpts},
 };
@@ -40,6 +40,8 @@ use charted_datastore::{
     fs,
     remi::{StorageService, UploadRequest},
 };
+use charted_feature_audit_logs::Action;
+use charted_feature_webhooks::event::EventKind;
 use charted_types::{
     NameOrUlid, Repository,
     payloads::{CreateRepositoryPayload, PatchRepositoryPayload},
@@ -205,6 +207,7 @@ mk_into_responses!(for CreateRepositoryR {
 pub async fn create_user_repository(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Json(CreateRepositoryPayload {
         description,
         private,
@@ -258,6 +261,8 @@ pub async fn create_user_repository(
         private,
         creator: None,
         owner: user.id,
+        latest_version: None,
+        latest_prerelease: None,
         type_: ty,
         name: name.clone(),
         id: id.into(),
@@ -297,7 +302,26 @@ pub async fn create_user_repository(
         }
     }
 
-    Ok(api::ok(StatusCode::CREATED, model.into()))
+    let repository = Repository::from(model);
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::RepositoryCreated,
+        &repository,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::RepositoryCreated,
+        auditlog::repository(&repository),
+        (),
+        &repository,
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, repository))
 }
 
 struct PatchRepoR;
@@ -330,6 +354,7 @@ mk_into_responses!(for PatchRepoR {
 pub async fn patch_user_repository(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Path(id_or_name): Path<NameOrUlid>,
     Json(PatchRepositoryPayload {
         description,
@@ -401,7 +426,13 @@ pub async fn patch_user_repository(
     }
 
     active.updated_at = ActiveValue::set(Utc::now());
-    active.update(&env.db).await.into_system_failure()?;
+    let updated = active.update(&env.db).await.into_system_failure()?;
+
+    // private repositories are excluded from `index.yaml`, so it has to
+    // be rebuilt whenever the visibility changes.
+    if private.is_some_and(|private| private != model.private) {
+        ops::indexes::regenerate(&env, model.owner).await;
+    }
 
     if let Some(readme) = readme.as_deref() {
         let content_type = fs::default_resolver(readme.as_bytes());
@@ -421,6 +452,25 @@ pub async fn patch_user_repository(
         }
     }
 
+    let repository = Repository::from(updated);
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::RepositoryUpdated,
+        &repository,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::RepositoryUpdated,
+        auditlog::repository(&repository),
+        Repository::from(model),
+        &repository,
+    )
+    .await;
+
     Ok(api::from_default(StatusCode::ACCEPTED))
 }
 
@@ -445,6 +495,7 @@ pub async fn patch_user_repository(
 pub async fn delete(
     State(env): State<Env>,
     Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
     Path(id_or_name): Path<NameOrUlid>,
 ) -> api::Result<()> {
     let repository = db::repository::get_with_additional_bounds(&env.db, id_or_name.clone(), |query| {
@@ -468,5 +519,29 @@ pub async fn delete(
         .await
         .into_system_failure()?;
 
+    if !repository.private {
+        ops::indexes::regenerate(&env, repository.owner).await;
+    }
+
+    // the repository's own webhooks were deleted alongside it, so only the
+    // owner's webhooks will receive this event.
+    ops::webhooks::emit(
+        &env,
+        ops::webhooks::repository(&repository),
+        EventKind::RepositoryDeleted,
+        &repository,
+    )
+    .await;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::RepositoryDeleted,
+        auditlog::repository(&repository),
+        &repository,
+        (),
+    )
+    .await;
+
     Ok(api::from_default(StatusCode::ACCEPTED))
 }
@@ -15,20 +15,37 @@
 
 use crate::{
     Env,
-    extract::Json,
+    extract::{Json, Path, Query},
     middleware::authn::Session,
     mk_into_responses,
-    openapi::{EmptyApiResponse, SessionResponse},
-    ops::db,
+    openapi::{
+        EmptyApiResponse, ListSessionResponse, SessionResponse, TotpChallengeResponse, UrlResponse,
+        UserConnectionsResponse,
+    },
+    ops::{
+        auditlog, db,
+        oidc::{self, Completion},
+    },
+};
+use axum::{
+    Extension,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
-use axum::{Extension, extract::State, http::StatusCode};
 use charted_core::api;
-use charted_types::payloads::UserLoginPayload;
+use charted_feature_audit_logs::Action;
+use charted_types::{Ulid, payloads::UserLoginPayload};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+use utoipa::{IntoParams, ToSchema};
 
 struct LoginR;
 mk_into_responses!(for LoginR {
     "201" => [ref(SessionResponse)];
-    "403" => [error(description("invalid password"))];
+    "401" => [error(description("user has two-factor authentication enabled and no code was given"))];
+    "403" => [error(description("invalid password or two-factor authentication code"))];
     "404" => [error(description("user was not found by username or email address"))];
     "406" => [error(description("email was not properly formatted"))];
 });
@@ -49,11 +66,22 @@ mk_into_responses!(for LoginR {
 #[cfg_attr(debug_assertions, axum::debug_handler)]
 pub async fn login(
     State(env): State<Env>,
+    cx: auditlog::Context,
+    client: db::session::Client,
     Json(payload): Json<UserLoginPayload>,
 ) -> api::Result<charted_types::Session> {
-    db::session::login(&env, &payload)
-        .await
-        .map(|session| api::ok(StatusCode::CREATED, session))
+    let session = db::session::login(&env, &payload, client).await?;
+    auditlog::record(
+        &env,
+        &cx.with_actor(session.owner),
+        Action::SessionCreated,
+        auditlog::owner(session.owner),
+        (),
+        session.clone().sanitize(),
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, session))
 }
 
 struct FetchSessionR;
@@ -140,6 +168,7 @@ mk_into_responses!(for RefreshSessionR {
 #[cfg_attr(debug_assertions, axum::debug_handler)]
 pub async fn refresh_session(
     State(env): State<Env>,
+    client: db::session::Client,
     Extension(Session { session, user }): Extension<Session>,
 ) -> api::Result<charted_types::Session> {
     let Some(session) = session else {
@@ -152,7 +181,300 @@ pub async fn refresh_session(
         ));
     };
 
-    db::session::refresh_session(&env, session, user)
+    db::session::refresh_session(&env, session, user, client)
         .await
         .map(|session| api::ok(StatusCode::CREATED, session))
 }
+
+struct ListSessionsR;
+mk_into_responses!(for ListSessionsR {
+    "200" => [ref(ListSessionResponse)];
+});
+
+/// Lists all of the sessions that the current user has, with the most recently
+/// created first.
+#[utoipa::path(
+    get,
+
+    path = "/v1/users/@me/sessions",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "listUserSessions",
+    responses(ListSessionsR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn list_sessions(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+) -> api::Result<Vec<charted_types::Session>> {
+    db::session::list(&env, user.id)
+        .await
+        .map(|sessions| api::ok(StatusCode::OK, sessions))
+}
+
+struct RevokeSessionR;
+mk_into_responses!(for RevokeSessionR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("session was successfully revoked");
+    )];
+
+    "404" => [error(description("session was not found"))];
+});
+
+/// Revokes one of the current user's sessions.
+#[utoipa::path(
+    delete,
+
+    path = "/v1/users/@me/sessions/{id}",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "revokeUserSession",
+    params(("id" = Ulid, Path, description = "ID of the session to revoke")),
+    responses(RevokeSessionR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn revoke_session(
+    State(env): State<Env>,
+    cx: auditlog::Context,
+    Extension(Session { user, .. }): Extension<Session>,
+    Path(id): Path<Ulid>,
+) -> api::Result<()> {
+    let session = db::session::revoke(&env, user.id, id).await?;
+    auditlog::record(&env, &cx, Action::SessionRevoked, auditlog::owner(user.id), session, ()).await;
+
+    Ok(api::no_content())
+}
+
+struct RevokeOtherSessionsR;
+mk_into_responses!(for RevokeOtherSessionsR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("sessions were successfully revoked");
+    )];
+});
+
+/// Revokes every session of the current user except the one that was used to send
+/// this request.
+///
+/// If an API key or basic authentication was used instead, then every session is
+/// revoked.
+#[utoipa::path(
+    delete,
+
+    path = "/v1/users/@me/sessions",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "revokeOtherUserSessions",
+    responses(RevokeOtherSessionsR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn revoke_other_sessions(
+    State(env): State<Env>,
+    cx: auditlog::Context,
+    Extension(Session { user, session }): Extension<Session>,
+) -> api::Result<()> {
+    let current = session.map(|session| session.id);
+    let revoked = db::session::revoke_others(&env, user.id, current).await?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::SessionRevoked,
+        auditlog::owner(user.id),
+        json!({"sessions":revoked}),
+        (),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
+
+struct OidcLoginR;
+mk_into_responses!(for OidcLoginR {
+    "200" => [ref(UrlResponse)];
+    "404" => [error(description("openid connect provider was not found"))];
+});
+
+/// Starts logging in with an OpenID Connect provider.
+///
+/// The user should be sent to the returned URL, and the provider will redirect them
+/// back to the provider's callback endpoint to create the session. Only users that
+/// linked their account to the provider can log in with it.
+#[utoipa::path(
+    get,
+
+    path = "/v1/users/login/oidc/{provider}",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "loginWithOidc",
+    params(("provider" = String, Path, description = "Name of the OpenID Connect provider")),
+    responses(OidcLoginR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn oidc_login(State(env): State<Env>, Path(provider): Path<String>) -> api::Result<Url> {
+    oidc::authorize(&env, &provider, None)
+        .await
+        .map(|url| api::ok(StatusCode::OK, url))
+}
+
+/// Query parameters that a provider redirects the user back with.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OidcCallbackParams {
+    /// Authorization code to exchange for an ID token.
+    #[serde(default)]
+    pub code: Option<String>,
+
+    /// The `state` parameter of the authorization request.
+    pub state: String,
+
+    /// Error code if the user or provider denied the authorization request.
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// Human-readable description of `error`.
+    #[serde(default)]
+    pub error_description: Option<String>,
+}
+
+struct OidcCallbackR;
+mk_into_responses!(for OidcCallbackR {
+    "200" => [ref(UserConnectionsResponse)];
+    "201" => [ref(SessionResponse)];
+    "202" => [ref(TotpChallengeResponse)];
+    "400" => [error(description("provider didn't redirect back with an authorization code"))];
+    "403" => [error(description("provider didn't authorize the request, or the user that is linking their account isn't the one that started the request"))];
+    "404" => [error(description("provider, authorization request, or a user that linked the provider's account was not found"))];
+    "409" => [error(description("provider's account is already linked to another user"))];
+    "410" => [error(description("authorization request had expired"))];
+});
+
+/// Completes an authorization request with an OpenID Connect provider.
+///
+/// This creates a new session if the user was logging in, or returns the user's
+/// connections if the user was linking their account. Linking an account has to be
+/// completed with the same user's credentials that it was started with.
+///
+/// Users that have two-factor authentication enabled get a `202 Accepted` response
+/// with a ticket instead of a session, which is exchanged for a session along with
+/// a code from their authenticator app at `/v1/users/login/oidc/{provider}/totp`.
+#[utoipa::path(
+    get,
+
+    path = "/v1/users/login/oidc/{provider}/callback",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "oidcCallback",
+    params(
+        ("provider" = String, Path, description = "Name of the OpenID Connect provider"),
+        OidcCallbackParams
+    ),
+    responses(OidcCallbackR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn oidc_callback(
+    State(env): State<Env>,
+    session: Option<Extension<Session>>,
+    cx: auditlog::Context,
+    client: db::session::Client,
+    Path(provider): Path<String>,
+    Query(OidcCallbackParams {
+        code,
+        state,
+        error,
+        error_description,
+    }): Query<OidcCallbackParams>,
+) -> Result<Response, api::Response> {
+    if let Some(error) = error {
+        return Err(api::err(
+            StatusCode::FORBIDDEN,
+            (
+                api::ErrorCode::AccessNotPermitted,
+                "provider didn't authorize the request",
+                json!({"error":error,"description":error_description}),
+            ),
+        ));
+    }
+
+    let Some(code) = code else {
+        return Err(api::err(
+            StatusCode::BAD_REQUEST,
+            (
+                api::ErrorCode::BadRequest,
+                "provider didn't redirect back with an authorization code",
+            ),
+        ));
+    };
+
+    let initiator = session.map(|Extension(Session { user, .. })| user.id);
+    match oidc::complete(&env, &cx, &provider, &code, &state, initiator, client).await? {
+        Completion::Session(session) => {
+            auditlog::record(
+                &env,
+                &cx.with_actor(session.owner),
+                Action::SessionCreated,
+                auditlog::owner(session.owner),
+                (),
+                session.clone().sanitize(),
+            )
+            .await;
+
+            Ok(api::ok(StatusCode::CREATED, session).into_response())
+        }
+
+        Completion::Linked(connections) => Ok(api::ok(StatusCode::OK, connections).into_response()),
+        Completion::TotpRequired(challenge) => Ok(api::ok(StatusCode::ACCEPTED, challenge).into_response()),
+    }
+}
+
+/// Request body for completing a login with an OpenID Connect provider that requires
+/// a code from the user's authenticator app.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OidcTotpPayload {
+    /// ticket that the provider's callback endpoint returned.
+    pub ticket: String,
+
+    /// code from the user's authenticator app, or a recovery code.
+    #[serde(default)]
+    pub totp: Option<String>,
+}
+
+struct OidcTotpR;
+mk_into_responses!(for OidcTotpR {
+    "201" => [ref(SessionResponse)];
+    "401" => [error(description("no two-factor authentication code was given"))];
+    "403" => [error(description("invalid two-factor authentication code"))];
+    "404" => [error(description("provider or pending login was not found"))];
+    "410" => [error(description("pending login had expired"))];
+});
+
+/// Completes a login with an OpenID Connect provider for a user that has two-factor
+/// authentication enabled.
+#[utoipa::path(
+    post,
+
+    path = "/v1/users/login/oidc/{provider}/totp",
+    tags = ["Users", "Users/Sessions"],
+    operation_id = "oidcTotp",
+    params(("provider" = String, Path, description = "Name of the OpenID Connect provider")),
+    request_body(
+        content = ref("#/components/schemas/OidcTotpPayload"),
+        content_type = "application/json"
+    ),
+    responses(OidcTotpR)
+)]
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+pub async fn oidc_totp(
+    State(env): State<Env>,
+    cx: auditlog::Context,
+    client: db::session::Client,
+    Path(provider): Path<String>,
+    Json(OidcTotpPayload { ticket, totp }): Json<OidcTotpPayload>,
+) -> api::Result<charted_types::Session> {
+    let session = oidc::complete_totp(&env, &provider, &ticket, totp.as_deref(), client).await?;
+    auditlog::record(
+        &env,
+        &cx.with_actor(session.owner),
+        Action::SessionCreated,
+        auditlog::owner(session.owner),
+        (),
+        session.clone().sanitize(),
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::CREATED, session))
+}
@@ -0,0 +1,186 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Endpoints that enroll users into two-factor authentication, only available if the
+//! TOTP server feature is enabled.
+
+use crate::{
+    Env,
+    extract::Json,
+    middleware::authn::{Factory, Options, Session},
+    mk_into_responses,
+    openapi::{EmptyApiResponse, TotpEnrollmentResponse, TotpStatusResponse},
+    ops::{auditlog, totp},
+};
+use axum::{Extension, Router, extract::State, handler::Handler, http::StatusCode, routing};
+use charted_core::{api, bitflags::ApiKeyScope};
+use charted_feature_audit_logs::Action;
+use charted_feature_totp::{TotpCodePayload, TotpEnrollment, TotpStatus};
+use serde_json::json;
+
+pub fn create_router(env: &Env) -> Router<Env> {
+    Router::new()
+        .route(
+            "/",
+            routing::get(status.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserAccess))))
+                .put(enroll.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate))))
+                .delete(disable.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+        .route(
+            "/verify",
+            routing::post(verify.layer(env.authn(Options::default().with_scope(ApiKeyScope::UserUpdate)))),
+        )
+}
+
+struct StatusR;
+mk_into_responses!(for StatusR {
+    "200" => [ref(TotpStatusResponse)];
+});
+
+/// Returns whether if you have two-factor authentication enabled.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    get,
+    path = "/v1/users/@me/2fa",
+    operation_id = "getTwoFactorStatus",
+    tags = ["Users", "Users/Two-Factor Authentication"],
+    responses(StatusR)
+)]
+pub async fn status(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+) -> api::Result<TotpStatus> {
+    let model = totp::find(&env, user.id).await?;
+    Ok(api::ok(StatusCode::OK, totp::status(model.as_ref())))
+}
+
+struct EnrollR;
+mk_into_responses!(for EnrollR {
+    "201" => [ref(TotpEnrollmentResponse)];
+    "409" => [error(description("two-factor authentication is already enabled"))];
+});
+
+/// Starts enrolling into two-factor authentication.
+///
+/// Returns the secret for an authenticator app and a set of recovery codes. Two-factor
+/// authentication is only enabled once a code from the authenticator app was verified
+/// with `POST /v1/users/@me/2fa/verify`.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    put,
+    path = "/v1/users/@me/2fa",
+    operation_id = "enrollTwoFactor",
+    tags = ["Users", "Users/Two-Factor Authentication"],
+    responses(EnrollR)
+)]
+pub async fn enroll(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+) -> api::Result<TotpEnrollment> {
+    let feature = totp::feature(&env)?;
+    totp::enroll(&env, feature, &user)
+        .await
+        .map(|enrollment| api::ok(StatusCode::CREATED, enrollment))
+}
+
+struct VerifyR;
+mk_into_responses!(for VerifyR {
+    "200" => [ref(TotpStatusResponse)];
+    "403" => [error(description("invalid code given"))];
+    "404" => [error(description("user hasn't started enrolling"))];
+    "409" => [error(description("two-factor authentication is already enabled"))];
+});
+
+/// Enables two-factor authentication with a code from the authenticator app that was
+/// enrolled with.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    post,
+    path = "/v1/users/@me/2fa/verify",
+    operation_id = "verifyTwoFactor",
+    tags = ["Users", "Users/Two-Factor Authentication"],
+    request_body(
+        content = ref("#/components/schemas/TotpCodePayload"),
+        content_type = "application/json"
+    ),
+    responses(VerifyR)
+)]
+pub async fn verify(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Json(TotpCodePayload { code }): Json<TotpCodePayload>,
+) -> api::Result<TotpStatus> {
+    let feature = totp::feature(&env)?;
+    let status = totp::confirm(&env, feature, user.id, &code).await?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::TotpEnabled,
+        auditlog::owner(user.id),
+        json!({"enabled":false}),
+        json!({"enabled":true}),
+    )
+    .await;
+
+    Ok(api::ok(StatusCode::OK, status))
+}
+
+struct DisableR;
+mk_into_responses!(for DisableR {
+    "204" => [ref(with "application/json" => EmptyApiResponse;
+        description("two-factor authentication was disabled");
+    )];
+
+    "403" => [error(description("invalid code given"))];
+    "404" => [error(description("user doesn't have two-factor authentication enabled"))];
+});
+
+/// Disables two-factor authentication. Requires a code from the authenticator app or
+/// a recovery code.
+#[cfg_attr(debug_assertions, axum::debug_handler)]
+#[utoipa::path(
+    delete,
+    path = "/v1/users/@me/2fa",
+    operation_id = "disableTwoFactor",
+    tags = ["Users", "Users/Two-Factor Authentication"],
+    request_body(
+        content = ref("#/components/schemas/TotpCodePayload"),
+        content_type = "application/json"
+    ),
+    responses(DisableR)
+)]
+pub async fn disable(
+    State(env): State<Env>,
+    Extension(Session { user, .. }): Extension<Session>,
+    cx: auditlog::Context,
+    Json(TotpCodePayload { code }): Json<TotpCodePayload>,
+) -> api::Result<()> {
+    let feature = totp::feature(&env)?;
+    totp::disable(&env, feature, user.id, &code).await?;
+
+    auditlog::record(
+        &env,
+        &cx,
+        Action::TotpDisabled,
+        auditlog::owner(user.id),
+        json!({"enabled":true}),
+        json!({"enabled":false}),
+    )
+    .await;
+
+    Ok(api::no_content())
+}
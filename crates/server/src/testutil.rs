@@ -0,0 +1,188 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Utilities for tests that need a server environment backed by an in-memory
+//! SQLite database.
+
+use crate::Env;
+use charted_config::{
+    Config, database, metrics,
+    sessions::{self, Backend},
+};
+use charted_database::entities::{
+    ApiKeyEntity, OrganizationEntity, RepositoryEntity, UserEntity, apikey, organization, repository, user,
+};
+use charted_types::{Organization, Repository, Ulid, User};
+use chrono::{DateTime, Utc};
+use sea_orm::{EntityTrait, IntoActiveModel};
+use sentry::protocol::Url;
+
+// so that sessions are "consistent" enough between tests
+const JWT_SECRET_KEY: &str =
+    "ahashthatshouldbeavalidhashfromopensslbutidontwanttodothatandnooneshouldusethisvaluetobeginwithuwu";
+
+pub async fn create_environment(ov: impl FnOnce(&mut Env)) -> Env {
+    let mut env = Env::new(Config {
+        jwt_secret_key: JWT_SECRET_KEY.to_owned(),
+        registrations: true,
+        single_user: false,
+        single_org: false,
+        sentry_dsn: None,
+        base_url: Some(Url::parse("http://localhost:3651").unwrap()),
+        logging: Default::default(),
+        storage: Default::default(),
+        charts: Default::default(),
+        features: Default::default(),
+        invites: Default::default(),
+        jwt: Default::default(),
+        tracing: None,
+        metrics: metrics::Config::Disabled,
+        server: Default::default(),
+        workers: Default::default(),
+
+        sessions: sessions::Config {
+            enable_basic_auth: false,
+            backend: Backend::Static(azalia::btreemap! {
+                // echo "noeliscutieuwu" | cargo cli admin authz hash-password --stdin
+                "noel" => "$argon2id$v=19$m=19456,t=2,p=1$gIcVA4mVHgr8ZWkmDrtJlw$sb5ypFAvphFCGrJXy9fRI1Gb/2vGIH1FTzDax458+xY"
+            }),
+            oidc: Default::default(),
+        },
+
+        database: database::Config::SQLite(database::sqlite::Config {
+            common: database::common::Config {
+                run_migrations: true,
+                ..Default::default()
+            },
+            path: String::from(":memory:").into(),
+        }),
+    }).await.expect("failed to create server environment");
+
+    ov(&mut env);
+    env
+}
+
+fn generate(env: &Env) -> Ulid {
+    env.ulid.generate().expect("failed to generate id").into()
+}
+
+/// Inserts a user named `username` into the database.
+pub async fn create_user(env: &Env, username: &str) -> User {
+    let now = Utc::now();
+    let model = user::Model {
+        verified_publisher: false,
+        prefers_gravatar: false,
+        gravatar_email: None,
+        description: None,
+        avatar_hash: None,
+        created_at: now,
+        updated_at: now,
+        username: username.parse().unwrap(),
+        password: None,
+        email: format!("{username}@noelware.org"),
+        admin: false,
+        name: None,
+        id: generate(env),
+    };
+
+    UserEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .expect("failed to create user");
+
+    model.into()
+}
+
+/// Inserts a public organization named `name` that is owned by `owner`.
+pub async fn create_organization(env: &Env, owner: &User, name: &str) -> Organization {
+    let now = Utc::now();
+    let model = organization::Model {
+        verified_publisher: false,
+        prefers_gravatar: false,
+        gravatar_email: None,
+        display_name: None,
+        created_at: now,
+        updated_at: now,
+        icon_hash: None,
+        private: false,
+        owner: owner.id,
+        name: name.parse().unwrap(),
+        id: generate(env),
+    };
+
+    OrganizationEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .expect("failed to create organization");
+
+    model.into()
+}
+
+/// Inserts a public repository named `name` that is owned by `owner`.
+pub async fn create_repository(env: &Env, owner: Ulid, name: &str) -> Repository {
+    let now = Utc::now();
+    let model = repository::Model {
+        description: None,
+        deprecated: false,
+        created_at: now,
+        updated_at: now,
+        icon_hash: None,
+        private: false,
+        creator: None,
+        owner,
+        name: name.parse().unwrap(),
+        latest_version: None,
+        latest_prerelease: None,
+        type_: Default::default(),
+        id: generate(env),
+    };
+
+    RepositoryEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .expect("failed to create repository");
+
+    model.into()
+}
+
+/// Inserts an API key named `name` that is owned by `owner` and has no scopes.
+pub async fn create_apikey(
+    env: &Env,
+    owner: Ulid,
+    name: &str,
+    expires_in: Option<DateTime<Utc>>,
+) -> apikey::Model {
+    let now = Utc::now();
+    let model = apikey::Model {
+        display_name: None,
+        description: None,
+        expires_in,
+        deactivated_at: None,
+        expiry_notified_at: None,
+        created_at: now,
+        updated_at: now,
+        scopes: 0,
+        owner,
+        token: charted_core::rand_string(32),
+        name: name.parse().unwrap(),
+        id: generate(env),
+    };
+
+    ApiKeyEntity::insert(model.clone().into_active_model())
+        .exec(&env.db)
+        .await
+        .expect("failed to create api key");
+
+    model
+}
@@ -163,6 +163,18 @@ pub struct Repository {
     #[cfg_attr(feature = "openapi", schema(read_only))]
     pub owner: Ulid,
 
+    /// the latest version of this repository's chart that isn't a pre-release
+    /// or yanked, or `null` if no releases were published.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub latest_version: Option<Version>,
+
+    /// the latest pre-release version of this repository's chart that isn't yanked,
+    /// or `null` if no pre-releases were published.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub latest_prerelease: Option<Version>,
+
     /// representation of this repository.
     ///
     /// Repositories can also be considered as a library chart and can be
@@ -311,6 +323,13 @@ pub struct ApiKey {
     #[cfg_attr(feature = "openapi", schema(read_only))]
     pub expires_in: Option<DateTime>,
 
+    /// datetime of when this api key was deactivated after it expired. Deactivated
+    /// api keys are kept so that their owner can see them, but they can no longer
+    /// be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub deactivated_at: Option<DateTime>,
+
     /// the list of permissions that this api key has as a [bitfield] data structure.
     ///
     /// [bitfield]: https://charts.noelware.org/docs/server/latest/api/reference#bitfield-data-structure
@@ -334,6 +353,12 @@ pub struct ApiKey {
 }
 
 impl ApiKey {
+    /// Returns whether if this api key can no longer be used, either because it had
+    /// expired or was deactivated.
+    pub fn is_expired(&self, now: DateTime) -> bool {
+        self.deactivated_at.is_some() || self.expires_in.is_some_and(|expires_in| expires_in <= now)
+    }
+
     /// Returns a new [`Bitfield`][charted_core::bitflags::Bitfield] of the
     /// avaliable scopes.
     pub fn bitfield(&self) -> ApiKeyScopes {
@@ -371,10 +396,26 @@ pub struct Session {
     #[cfg_attr(feature = "openapi", schema(read_only))]
     pub access_token: Option<String>,
 
+    /// `User-Agent` header of the client that created this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// IP address of the client that created this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    /// date-time of when this session was created.
+    pub created_at: DateTime,
+
+    /// date-time of when this session last authenticated a request, which is
+    /// only tracked to the minute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime>,
+
     /// reference to the [`User`] that owns this session
     pub owner: Ulid,
 
-    /// the api key's unique identifier.
+    /// the session's unique identifier.
     pub id: Ulid,
 }
 
@@ -440,3 +481,78 @@ macro_rules! mk_member_struct {
 
 mk_member_struct!(Repository);
 mk_member_struct!(Organization);
+
+/// Resource for a pending invite for a [`User`] to become a repository or organization
+/// member. Invites are turned into members once they are accepted by the user that
+/// was invited.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MemberInvite {
+    /// the permissions that the member will have once the invite is accepted, as a
+    /// [bitfield] data structure.
+    ///
+    /// [bitfield]: https://charts.noelware.org/docs/server/latest/api/reference#bitfield-data-structure
+    #[serde(default)]
+    pub permissions: u64,
+
+    /// datetime of when this invite was created.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub created_at: DateTime,
+
+    /// datetime of when this invite can no longer be accepted.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub expires_at: DateTime,
+
+    /// reference to the [`Repository`] that the user was invited to, if this is a
+    /// repository invite.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Ulid>,
+
+    /// reference to the [`Organization`] that the user was invited to, if this is an
+    /// organization invite.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<Ulid>,
+
+    /// reference to the [`User`] that sent this invite.
+    pub inviter: Ulid,
+
+    /// reference to the [`User`] that was invited.
+    pub account: Ulid,
+
+    /// the invite's unique identifier.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub id: Ulid,
+}
+
+/// An OpenPGP public key in the keyring of a [`User`] or [`Organization`], which
+/// provenance files of their repositories' releases are verified against.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GpgKey {
+    /// a name to distinguish this key from other keys in the keyring.
+    #[serde(default)]
+    pub display_name: Option<String>,
+
+    /// datetime of when this key was added.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub created_at: DateTime,
+
+    /// datetime of when this key was last modified.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub updated_at: DateTime,
+
+    /// the key's fingerprint as an uppercase hex string.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub fingerprint: String,
+
+    /// the ASCII-armored public key.
+    pub public_key: String,
+
+    /// the [`User`] or [`Organization`] that this key belongs to.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub owner: Ulid,
+
+    /// the key's unique identifier.
+    #[cfg_attr(feature = "openapi", schema(read_only))]
+    pub id: Ulid,
+}
@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use super::Ulid;
-use crate::{Organization, User};
+use crate::{Organization, User, name::Name};
 
 /// Representation of a repository owner.
 #[derive(Debug, Clone)]
@@ -34,4 +34,12 @@ impl Owner {
             Self::Organization(ref org) => org.id,
         }
     }
+
+    /// Returns the [`Name`] of this owner.
+    pub const fn name(&self) -> &Name {
+        match *self {
+            Self::User(ref user) => &user.username,
+            Self::Organization(ref org) => &org.name,
+        }
+    }
 }
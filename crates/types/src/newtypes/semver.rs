@@ -165,10 +165,16 @@ cfg_jsonschema! {
 const _: () = {
     use sea_orm::{
         ColIdx, DbErr, QueryResult, TryGetError, TryGetable,
-        sea_query::{ArrayType, ColumnType, Value, ValueType, ValueTypeErr},
+        sea_query::{ArrayType, ColumnType, Nullable, Value, ValueType, ValueTypeErr},
     };
     use std::any::type_name;
 
+    impl Nullable for Version {
+        fn null() -> Value {
+            Value::String(None)
+        }
+    }
+
     impl From<Version> for Value {
         fn from(v: Version) -> Self {
             Value::String(Some(Box::new(v.0.to_string())))
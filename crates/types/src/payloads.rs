@@ -16,7 +16,7 @@
 //! Types that can effictively create or patch a object's metadata. Used by
 //! the API server for the `PUT` and `PATCH` REST endpoints.
 
-use crate::{ChartType, DateTime, Version, name::Name};
+use crate::{ChartType, DateTime, NameOrUlid, Version, name::Name};
 use charted_core::bitflags::ApiKeyScope;
 use serde::Deserialize;
 
@@ -224,6 +224,11 @@ pub struct UserLoginPayload {
 
     /// password to login as.
     pub password: String,
+
+    /// code from the user's authenticator app, or a recovery code. This is only
+    /// required if the user has two-factor authentication enabled.
+    #[serde(default)]
+    pub totp: Option<String>,
 }
 
 mk_payload_structs! {
@@ -354,23 +359,139 @@ mk_payload_structs! {
 mk_payload_structs! {
     Organization;
 
+    /// Request body for creating an organization.
     #[derive(Debug, Clone, Deserialize)]
     #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
-    create {}
+    create {
+        /// the organization's display name.
+        #[serde(default)]
+        #[cfg_attr(feature = "openapi", schema(maximum = 64))]
+        pub display_name: Option<String>,
 
+        /// whether if this organization is private and only its owner
+        /// can view it and its repositories.
+        #[serde(default)]
+        pub private: bool,
+
+        /// name of this organization, which can't be taken by another
+        /// user or organization.
+        pub name: Name,
+    }
+
+    /// Request body for modifying an organization.
     #[derive(Debug, Clone, Deserialize)]
     #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
-    patch {}
+    patch {
+        /// Toggle to use when preferring the Gravatar icon
+        /// over the ones used by the API server locally.
+        ///
+        /// - `null` or empty: field will not be updated
+        #[serde(default)]
+        pub prefers_gravatar: Option<bool>,
+
+        /// Changes the Gravatar email address associated
+        /// for this organization.
+        ///
+        /// - `null` or empty: field will not be updated
+        /// - an empty string: field is set to nothing
+        /// - string that is different: field will update
+        /// - string that is the same: field will not update
+        #[serde(default)]
+        pub gravatar_email: Option<String>,
+
+        /// Changes the organization's display name.
+        ///
+        /// - `null` or empty: field will not be updated
+        /// - an empty string: field is set to nothing
+        /// - string that is different: field will update
+        /// - string that is the same: field will not update
+        #[serde(default)]
+        #[cfg_attr(feature = "openapi", schema(maximum = 64))]
+        pub display_name: Option<String>,
+
+        /// whether if this organization is private.
+        #[serde(default)]
+        pub private: Option<bool>,
+
+        /// changes the organization's name.
+        ///
+        /// if any user or organization has the name already taken, a
+        /// 409 Conflict HTTP response is sent.
+        #[serde(default)]
+        pub name: Option<Name>,
+    }
 }
 
 mk_payload_structs! {
     Member;
 
+    /// Request body for inviting a user to become a repository or organization member.
     #[derive(Debug, Clone, Deserialize)]
     #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
-    create {}
+    create {
+        /// ID or username of the user to invite.
+        pub account: NameOrUlid,
 
+        /// the permissions that the member will have once the invite is accepted, as a
+        /// [bitfield] data structure. permissions that the user who made the request
+        /// doesn't have can't be granted.
+        ///
+        /// [bitfield]: https://charts.noelware.org/docs/server/latest/api/reference#bitfield-data-structure
+        #[serde(default)]
+        pub permissions: u64,
+    }
+
+    /// Request body for modifying a repository or organization member.
     #[derive(Debug, Clone, Deserialize)]
     #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
-    patch {}
+    patch {
+        /// changes the member's display name.
+        ///
+        /// - `null` or empty: field will not be updated
+        /// - an empty string: field is set to nothing
+        /// - string that is different: field will update
+        /// - string that is the same: field will not update
+        #[serde(default)]
+        #[cfg_attr(feature = "openapi", schema(maximum = 64))]
+        pub display_name: Option<String>,
+
+        /// replaces the member's permissions as a [bitfield] data structure. permissions
+        /// that the user who made the request doesn't have can't be granted.
+        ///
+        /// [bitfield]: https://charts.noelware.org/docs/server/latest/api/reference#bitfield-data-structure
+        #[serde(default)]
+        pub permissions: Option<u64>,
+    }
+}
+
+mk_payload_structs! {
+    GpgKey;
+
+    /// Request body for adding an OpenPGP public key into a keyring.
+    #[derive(Debug, Clone, Deserialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    create {
+        /// a name to distinguish this key from other keys in the keyring.
+        #[serde(default)]
+        #[cfg_attr(feature = "openapi", schema(maximum = 32))]
+        pub display_name: Option<String>,
+
+        /// the ASCII-armored public key, as exported by `gpg --armor --export`.
+        pub public_key: String,
+    }
+
+    /// Request body for modifying an OpenPGP public key in a keyring.
+    #[derive(Debug, Clone, Deserialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    patch {
+        /// changes the key's display name.
+        ///
+        /// - `null` or empty: field will not be updated
+        /// - an empty string: field is set to nothing
+        /// - string that is different: field will update
+        /// - string that is the same: field will not update
+        #[serde(default)]
+        #[cfg_attr(feature = "openapi", schema(maximum = 32))]
+        pub display_name: Option<String>,
+    }
 }
@@ -0,0 +1,241 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! # 🐻‍❄️📦 `charted_apikey_worker`
+//! The **API Key Expiration** worker takes care of API keys once they expire: they
+//! are either deactivated or deleted based off the `workers.apikeys.action`
+//! configuration key, and their owners can be told beforehand with a [`Notifier`].
+//!
+//! The worker is ran alongside the API server by `charted server` unless
+//! `workers.apikeys.standalone` is set, in which case it is only ran with
+//! `charted worker apikey`.
+//!
+//! It is safe to run the worker on multiple replicas at once: an API key is claimed
+//! by setting its `expiry_notified_at` column before its owner is told, and only the
+//! replica that claimed it tells them. If telling them fails, the claim is released so
+//! that the next run tries again. A replica that crashes in between won't release its
+//! claim, so owners are told at most once.
+
+#[cfg(test)]
+mod tests;
+
+use charted_config::workers::apikeys::{Action, Config};
+use charted_core::BoxedFuture;
+use charted_database::entities::{ApiKeyEntity, UserEntity, apikey};
+use charted_types::{ApiKey, User};
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, sea_query::Expr};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info, instrument, warn};
+
+/// An API key that is about to expire.
+#[derive(Debug, Clone)]
+pub struct Expiring {
+    /// The API key itself, without its token.
+    pub apikey: ApiKey,
+
+    /// The user that owns the API key.
+    pub owner: User,
+}
+
+/// Tells owners that their API keys are about to expire.
+pub trait Notifier: Send + Sync {
+    /// Tells the owner of `expiring` that it is about to expire. If this fails, then
+    /// the owner is told again on the next run.
+    fn notify<'a>(&'a self, expiring: &'a Expiring) -> BoxedFuture<'a, eyre::Result<()>>;
+}
+
+/// [`Notifier`] that only logs API keys that are about to expire.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify<'a>(&'a self, expiring: &'a Expiring) -> BoxedFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            info!(
+                apikey.id = %expiring.apikey.id,
+                apikey.name = %expiring.apikey.name,
+                owner = %expiring.owner.username,
+                "api key is about to expire"
+            );
+
+            Ok(())
+        })
+    }
+}
+
+/// What happened in a single run of the worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Report {
+    /// How many API keys were deactivated or deleted.
+    pub expired: u64,
+
+    /// How many owners were told that their API keys are about to expire.
+    pub notified: u64,
+}
+
+/// The **API Key Expiration** worker.
+#[derive(Clone)]
+pub struct Worker {
+    notifier: Arc<dyn Notifier>,
+    config: Config,
+    db: DatabaseConnection,
+}
+
+impl Worker {
+    /// Creates a new [`Worker`] that uses the [`LogNotifier`].
+    pub fn new(config: Config, db: DatabaseConnection) -> Self {
+        Worker {
+            notifier: Arc::new(LogNotifier),
+            config,
+            db,
+        }
+    }
+
+    /// Replaces the [`Notifier`] that owners are told with.
+    pub fn with_notifier<N: Notifier + 'static>(self, notifier: N) -> Self {
+        Worker {
+            notifier: Arc::new(notifier),
+            ..self
+        }
+    }
+
+    /// Runs the worker every `workers.apikeys.interval` forever.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval((*self.config.interval).max(Duration::from_secs(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        info!(
+            interval = %self.config.interval,
+            action = %self.config.action,
+            "starting api key expiration worker"
+        );
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.run_once(Utc::now()).await {
+                error!(error = %e, "failed to take care of expired api keys");
+            }
+        }
+    }
+
+    /// Takes care of every API key that expired by `now` and tells the owners of
+    /// API keys that expire within `workers.apikeys.notify_before` of `now`.
+    #[instrument(name = "charted.workers.apikeys.run", skip(self))]
+    pub async fn run_once(&self, now: DateTime<Utc>) -> eyre::Result<Report> {
+        let started = Instant::now();
+        let notified = match self.config.notify_before {
+            Some(before) => self.notify(now, *before).await?,
+            None => 0,
+        };
+
+        let expired = self.expire(now).await?;
+
+        charted_metrics::counter!("charted.workers.apikeys.expired", "action" => self.config.action.to_string())
+            .increment(expired);
+
+        charted_metrics::counter!("charted.workers.apikeys.notified").increment(notified);
+        charted_metrics::histogram!("charted.workers.apikeys.latency").record(started.elapsed());
+
+        info!(expired, notified, "took care of expired api keys");
+        Ok(Report { expired, notified })
+    }
+
+    async fn expire(&self, now: DateTime<Utc>) -> eyre::Result<u64> {
+        let expired = apikey::Column::ExpiresIn.lte(now);
+        let affected = match self.config.action {
+            Action::Delete => {
+                ApiKeyEntity::delete_many()
+                    .filter(expired)
+                    .exec(&self.db)
+                    .await?
+                    .rows_affected
+            }
+            Action::Deactivate => {
+                ApiKeyEntity::update_many()
+                    .col_expr(apikey::Column::DeactivatedAt, Expr::value(now))
+                    .col_expr(apikey::Column::UpdatedAt, Expr::value(now))
+                    .filter(expired)
+                    .filter(apikey::Column::DeactivatedAt.is_null())
+                    .exec(&self.db)
+                    .await?
+                    .rows_affected
+            }
+        };
+
+        Ok(affected)
+    }
+
+    async fn notify(&self, now: DateTime<Utc>, before: Duration) -> eyre::Result<u64> {
+        let horizon = now + TimeDelta::from_std(before)?;
+        let keys = ApiKeyEntity::find()
+            .filter(apikey::Column::ExpiresIn.gt(now))
+            .filter(apikey::Column::ExpiresIn.lte(horizon))
+            .filter(apikey::Column::ExpiryNotifiedAt.is_null())
+            .filter(apikey::Column::DeactivatedAt.is_null())
+            .find_also_related(UserEntity)
+            .all(&self.db)
+            .await?;
+
+        let mut notified = 0;
+        for (key, owner) in keys {
+            let Some(owner) = owner else {
+                continue;
+            };
+
+            let id = key.id;
+            let expiring = Expiring {
+                apikey: ApiKey::from(key).sanitize(),
+                owner: owner.into(),
+            };
+
+            // another replica may have found the same API key, so only the one that
+            // claims it tells the owner.
+            let claimed = ApiKeyEntity::update_many()
+                .col_expr(apikey::Column::ExpiryNotifiedAt, Expr::value(now))
+                .filter(apikey::Column::Id.eq(id))
+                .filter(apikey::Column::ExpiryNotifiedAt.is_null())
+                .exec(&self.db)
+                .await?
+                .rows_affected;
+
+            if claimed == 0 {
+                continue;
+            }
+
+            if let Err(e) = self.notifier.notify(&expiring).await {
+                warn!(error = %e, apikey.id = %id, "failed to tell owner that their api key is about to expire");
+
+                ApiKeyEntity::update_many()
+                    .col_expr(
+                        apikey::Column::ExpiryNotifiedAt,
+                        Expr::value(Option::<DateTime<Utc>>::None),
+                    )
+                    .filter(apikey::Column::Id.eq(id))
+                    .exec(&self.db)
+                    .await?;
+
+                continue;
+            }
+
+            notified += 1;
+        }
+
+        Ok(notified)
+    }
+}
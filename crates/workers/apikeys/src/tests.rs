@@ -0,0 +1,238 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Expiring, Notifier, Report, Worker};
+use charted_config::{
+    database,
+    workers::apikeys::{Action, Config},
+};
+use charted_core::BoxedFuture;
+use charted_database::entities::{ApiKeyEntity, UserEntity, apikey, user};
+use charted_types::Ulid;
+use chrono::{DateTime, TimeDelta, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait, IntoActiveModel};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+const OWNER: &str = "01J5SG1RPKN0PT9N1GHQP7XB30";
+
+/// [`Notifier`] that keeps the API keys that it was told about, after failing
+/// `failures` times.
+#[derive(Clone, Default)]
+struct Recorder {
+    failures: Arc<AtomicUsize>,
+    notified: Arc<Mutex<Vec<Ulid>>>,
+}
+
+impl Recorder {
+    fn notified(&self) -> Vec<Ulid> {
+        self.notified.lock().unwrap().clone()
+    }
+}
+
+impl Notifier for Recorder {
+    fn notify<'a>(&'a self, expiring: &'a Expiring) -> BoxedFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| failures.checked_sub(1))
+                .is_ok()
+            {
+                eyre::bail!("mail server is down");
+            }
+
+            self.notified.lock().unwrap().push(expiring.apikey.id);
+            Ok(())
+        })
+    }
+}
+
+async fn create_db() -> DatabaseConnection {
+    let db = charted_database::create_pool(&database::Config::SQLite(database::sqlite::Config {
+        common: database::common::Config {
+            run_migrations: true,
+            max_connections: 1,
+            ..Default::default()
+        },
+        path: String::from(":memory:").into(),
+    }))
+    .await
+    .unwrap();
+
+    let now = Utc::now();
+    UserEntity::insert(
+        user::Model {
+            verified_publisher: false,
+            prefers_gravatar: false,
+            gravatar_email: None,
+            description: None,
+            avatar_hash: None,
+            created_at: now,
+            updated_at: now,
+            username: "noel".parse().unwrap(),
+            password: None,
+            email: String::from("noel@noelware.org"),
+            admin: false,
+            name: None,
+            id: Ulid::new(OWNER).unwrap(),
+        }
+        .into_active_model(),
+    )
+    .exec(&db)
+    .await
+    .unwrap();
+
+    db
+}
+
+async fn create_apikey(db: &DatabaseConnection, id: &str, expires_in: DateTime<Utc>) -> Ulid {
+    let now = Utc::now();
+    let id = Ulid::new(id).unwrap();
+
+    ApiKeyEntity::insert(
+        apikey::Model {
+            display_name: None,
+            description: None,
+            expires_in: Some(expires_in),
+            deactivated_at: None,
+            expiry_notified_at: None,
+            created_at: now,
+            updated_at: now,
+            scopes: 0,
+            owner: Ulid::new(OWNER).unwrap(),
+            token: id.to_string(),
+            name: format!("key-{}", id.to_string().to_lowercase()).parse().unwrap(),
+            id,
+        }
+        .into_active_model(),
+    )
+    .exec(db)
+    .await
+    .unwrap();
+
+    id
+}
+
+async fn find(db: &DatabaseConnection, id: Ulid) -> Option<apikey::Model> {
+    ApiKeyEntity::find_by_id(id).one(db).await.unwrap()
+}
+
+fn worker(db: &DatabaseConnection, action: Action, recorder: &Recorder) -> Worker {
+    Worker::new(
+        Config {
+            action,
+            notify_before: Some(Duration::from_secs(60 * 60 * 24).into()),
+            ..Default::default()
+        },
+        db.clone(),
+    )
+    .with_notifier(recorder.clone())
+}
+
+#[tokio::test]
+async fn deactivates_expired_apikeys() {
+    let db = create_db().await;
+    let now = Utc::now();
+    let expired = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB31", now - TimeDelta::hours(1)).await;
+    let active = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB32", now + TimeDelta::days(7)).await;
+
+    let worker = worker(&db, Action::Deactivate, &Recorder::default());
+    assert_eq!(worker.run_once(now).await.unwrap(), Report {
+        expired: 1,
+        notified: 0
+    });
+
+    // deactivated API keys are kept so that their owners can still see them
+    assert!(find(&db, expired).await.unwrap().deactivated_at.is_some());
+    assert!(find(&db, active).await.unwrap().deactivated_at.is_none());
+
+    // and aren't deactivated again on the next run
+    assert_eq!(worker.run_once(now + TimeDelta::minutes(1)).await.unwrap().expired, 0);
+    assert!(find(&db, expired).await.unwrap().deactivated_at.is_some());
+}
+
+#[tokio::test]
+async fn deletes_expired_apikeys() {
+    let db = create_db().await;
+    let now = Utc::now();
+    let expired = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB31", now - TimeDelta::hours(1)).await;
+    let active = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB32", now + TimeDelta::days(7)).await;
+
+    let report = worker(&db, Action::Delete, &Recorder::default())
+        .run_once(now)
+        .await
+        .unwrap();
+
+    assert_eq!(report.expired, 1);
+    assert!(find(&db, expired).await.is_none());
+    assert!(find(&db, active).await.is_some());
+}
+
+#[tokio::test]
+async fn notifies_owners_once() {
+    let db = create_db().await;
+    let now = Utc::now();
+    let expiring = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB31", now + TimeDelta::hours(1)).await;
+    create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB32", now + TimeDelta::days(7)).await;
+
+    let recorder = Recorder::default();
+    let worker = worker(&db, Action::Deactivate, &recorder);
+
+    assert_eq!(worker.run_once(now).await.unwrap().notified, 1);
+    assert!(find(&db, expiring).await.unwrap().expiry_notified_at.is_some());
+
+    assert_eq!(worker.run_once(now + TimeDelta::minutes(1)).await.unwrap().notified, 0);
+    assert_eq!(recorder.notified(), [expiring]);
+}
+
+#[tokio::test]
+async fn retries_failed_notifications() {
+    let db = create_db().await;
+    let now = Utc::now();
+    let expiring = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB31", now + TimeDelta::hours(1)).await;
+
+    let recorder = Recorder::default();
+    recorder.failures.store(1, Ordering::SeqCst);
+
+    let worker = worker(&db, Action::Deactivate, &recorder);
+    assert_eq!(worker.run_once(now).await.unwrap().notified, 0);
+    assert!(find(&db, expiring).await.unwrap().expiry_notified_at.is_none());
+    assert!(recorder.notified().is_empty());
+
+    let later = now + TimeDelta::minutes(1);
+    assert_eq!(worker.run_once(later).await.unwrap().notified, 1);
+    assert!(find(&db, expiring).await.unwrap().expiry_notified_at.is_some());
+    assert_eq!(recorder.notified(), [expiring]);
+}
+
+#[tokio::test]
+async fn replicas_notify_once() {
+    let db = create_db().await;
+    let now = Utc::now();
+    let expiring = create_apikey(&db, "01J5SG1RPKN0PT9N1GHQP7XB31", now + TimeDelta::hours(1)).await;
+
+    let recorder = Recorder::default();
+    let first = worker(&db, Action::Deactivate, &recorder);
+    let second = worker(&db, Action::Deactivate, &recorder);
+
+    let (a, b) = tokio::join!(first.run_once(now), second.run_once(now));
+    assert_eq!(a.unwrap().notified + b.unwrap().notified, 1);
+    assert_eq!(recorder.notified(), [expiring]);
+}
@@ -0,0 +1,61 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! # 🐻‍❄️📦 `charted-feature-audit-logs`
+//! This crate implements the **audit logs** server feature, which records who changed
+//! what in repositories and organizations.
+//!
+//! Every [`AuditLog`] keeps the actor, the IP address and `x-request-id` of the request
+//! that caused it, and the fields that were changed. Records are stored in a [`Sink`],
+//! which can be the server's database, a ClickHouse table, or a JSON lines file.
+
+mod record;
+pub mod sink;
+
+use charted_feature::Metadata;
+pub use record::*;
+pub use sink::Sink;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Feature {
+    sink: Arc<dyn Sink>,
+}
+
+impl Feature {
+    pub fn new(sink: Arc<dyn Sink>) -> Feature {
+        Feature { sink }
+    }
+
+    /// Returns the [`Sink`] that records are stored in.
+    pub fn sink(&self) -> &dyn Sink {
+        &*self.sink
+    }
+}
+
+impl charted_feature::Feature for Feature {
+    fn metadata(&self) -> Metadata {
+        const METADATA: Metadata = Metadata {
+            name: "Audit Logs",
+            config_key: "auditlog",
+            description: env!("CARGO_PKG_DESCRIPTION"),
+            authors: &["Noelware, LLC. <team@noelware.org>"],
+            since: "0.1.0",
+            deprecated: None,
+        };
+
+        METADATA
+    }
+}
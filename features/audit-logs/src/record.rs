@@ -0,0 +1,376 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records that are stored by the audit logs feature.
+
+use charted_types::{DateTime, Ulid};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    net::IpAddr,
+    str::FromStr,
+};
+use utoipa::ToSchema;
+
+/// Fields whose values are never stored in an [`AuditLog`]. The change itself is still
+/// recorded, but both sides are replaced with [`REDACTED`].
+pub const SENSITIVE_FIELDS: &[&str] = &[
+    "access_token",
+    "accessToken",
+    "password",
+    "refresh_token",
+    "refreshToken",
+    "secret",
+    "token",
+];
+
+/// Placeholder of a [sensitive field](SENSITIVE_FIELDS)'s value.
+pub const REDACTED: &str = "[redacted]";
+
+macro_rules! mk_actions {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident => $ty:literal;
+    )*) => {
+        /// Action that an [`AuditLog`] is about.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+        #[schema(as = AuditLogAction)]
+        pub enum Action {
+            $(
+                $(#[$meta])*
+                #[serde(rename = $ty)]
+                $name,
+            )*
+        }
+
+        impl Action {
+            /// All the available actions.
+            pub const ALL: &[Action] = &[$(Action::$name,)*];
+
+            /// Returns the name of this action, as it is stored.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(Action::$name => $ty,)*
+                }
+            }
+        }
+
+        impl FromStr for Action {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($ty => Ok(Action::$name),)*
+                    _ => Err(format!("unknown action: {s}")),
+                }
+            }
+        }
+    };
+}
+
+mk_actions! {
+    /// A repository was created.
+    RepositoryCreated => "repository.created";
+
+    /// A repository's metadata was updated.
+    RepositoryUpdated => "repository.updated";
+
+    /// A repository was deleted.
+    RepositoryDeleted => "repository.deleted";
+
+    /// A new release was published.
+    ReleaseCreated => "release.created";
+
+    /// A release's metadata was updated, or it was yanked or unyanked.
+    ReleaseUpdated => "release.updated";
+
+    /// A release was deleted.
+    ReleaseDeleted => "release.deleted";
+
+    /// An organization was created.
+    OrganizationCreated => "organization.created";
+
+    /// An organization's metadata was updated.
+    OrganizationUpdated => "organization.updated";
+
+    /// An organization was deleted.
+    OrganizationDeleted => "organization.deleted";
+
+    /// A API key was created.
+    ApiKeyCreated => "apikey.created";
+
+    /// A API key's metadata was updated.
+    ApiKeyUpdated => "apikey.updated";
+
+    /// A API key was deleted.
+    ApiKeyDeleted => "apikey.deleted";
+
+    /// A OpenPGP public key was added into a keyring.
+    GpgKeyCreated => "gpgkey.created";
+
+    /// A OpenPGP public key's metadata was updated.
+    GpgKeyUpdated => "gpgkey.updated";
+
+    /// A OpenPGP public key was removed from a keyring.
+    GpgKeyDeleted => "gpgkey.deleted";
+
+    /// A user logged in and created a new session.
+    SessionCreated => "session.created";
+
+    /// A session was revoked by its user, or because the user changed their password.
+    SessionRevoked => "session.revoked";
+
+    /// A user's metadata or avatar was updated.
+    UserUpdated => "user.updated";
+
+    /// A user deleted themselves.
+    UserDeleted => "user.deleted";
+
+    /// A user became a member of a repository or organization.
+    MemberAdded => "member.added";
+
+    /// A member's display name or permissions were updated.
+    MemberUpdated => "member.updated";
+
+    /// A member was kicked, or left a repository or organization.
+    MemberRemoved => "member.removed";
+
+    /// A user was invited to become a member.
+    InviteCreated => "invite.created";
+
+    /// A invite was accepted by the invited user.
+    InviteAccepted => "invite.accepted";
+
+    /// A invite was declined by the invited user.
+    InviteDeclined => "invite.declined";
+
+    /// A pending invite was revoked.
+    InviteRevoked => "invite.revoked";
+
+    /// A webhook was created.
+    WebhookCreated => "webhook.created";
+
+    /// A webhook's URL, events, or status was updated.
+    WebhookUpdated => "webhook.updated";
+
+    /// A webhook was deleted.
+    WebhookDeleted => "webhook.deleted";
+
+    /// A user enabled two-factor authentication.
+    TotpEnabled => "totp.enabled";
+
+    /// A user disabled two-factor authentication.
+    TotpDisabled => "totp.disabled";
+
+    /// A user linked an account from a OpenID Connect provider.
+    ConnectionLinked => "connection.linked";
+
+    /// A user unlinked an account from a OpenID Connect provider.
+    ConnectionUnlinked => "connection.unlinked";
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single field that was changed by an [`Action`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(as = AuditLogChange)]
+pub struct Change {
+    /// Value of this field before the action happened, `null` if it didn't exist.
+    pub before: Value,
+
+    /// Value of this field after the action happened, `null` if it no longer exists.
+    pub after: Value,
+}
+
+/// Returns the top-level fields that differ between the `before` and `after` JSON
+/// objects. Values that aren't objects, like `null`, are treated as an object without
+/// any fields.
+pub fn diff(before: &Value, after: &Value) -> BTreeMap<String, Change> {
+    let before = before.as_object();
+    let after = after.as_object();
+
+    let keys = before
+        .into_iter()
+        .chain(after)
+        .flat_map(|object| object.keys())
+        .collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = before.and_then(|object| object.get(key)).unwrap_or(&Value::Null);
+            let new = after.and_then(|object| object.get(key)).unwrap_or(&Value::Null);
+            if old == new {
+                return None;
+            }
+
+            let change = if SENSITIVE_FIELDS.contains(&key.as_str()) {
+                Change {
+                    before: redact(old),
+                    after: redact(new),
+                }
+            } else {
+                Change {
+                    before: old.clone(),
+                    after: new.clone(),
+                }
+            };
+
+            Some((key.clone(), change))
+        })
+        .collect()
+}
+
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        _ => Value::String(REDACTED.to_owned()),
+    }
+}
+
+/// A single record of who changed what, and when.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditLog {
+    /// The repository that this record is about, if any.
+    #[serde(default)]
+    pub repository: Option<Ulid>,
+
+    /// The `x-request-id` of the request that caused this action.
+    #[serde(default)]
+    pub request_id: Option<String>,
+
+    /// When this action happened.
+    pub timestamp: DateTime,
+
+    /// Fields that were changed by this action, keyed by their name.
+    pub changes: BTreeMap<String, Change>,
+
+    /// The action that happened.
+    pub action: Action,
+
+    /// The user that did this action, if it was done by a user.
+    #[serde(default)]
+    pub actor: Option<Ulid>,
+
+    /// The user or organization that owns the resource that this record is about.
+    pub owner: Ulid,
+
+    /// IP address that the request came from.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub ip: Option<IpAddr>,
+
+    /// the record's unique identifier.
+    pub id: Ulid,
+}
+
+/// Query of the [`AuditLog`]s that a [`Sink`][crate::Sink] returns.
+#[derive(Debug, Clone, Copy)]
+pub struct Query {
+    /// Only returns the records of resources that are owned by this user or organization.
+    pub owner: Ulid,
+
+    /// If not `None`, only returns the records of this repository.
+    pub repository: Option<Ulid>,
+
+    /// Amount of records per page.
+    pub per_page: usize,
+
+    /// The page to return, starting from `1`.
+    pub page: usize,
+}
+
+impl Query {
+    /// Returns whether if `record` matches this query.
+    pub fn matches(&self, record: &AuditLog) -> bool {
+        record.owner == self.owner && self.repository.is_none_or(|id| record.repository == Some(id))
+    }
+
+    /// Amount of records that are skipped before this query's page.
+    pub fn offset(&self) -> usize {
+        self.page.saturating_sub(1) * self.per_page
+    }
+}
+
+/// A single page of records, from newest to oldest.
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    /// The records of this page.
+    pub records: Vec<AuditLog>,
+
+    /// Amount of pages that are available for the query.
+    pub pages: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn roundtrip() {
+        for action in Action::ALL {
+            assert_eq!(action.as_str().parse::<Action>(), Ok(*action));
+            assert_eq!(
+                serde_json::to_value(action).unwrap(),
+                Value::String(action.as_str().to_owned())
+            );
+        }
+
+        assert!("repository.archived".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn diff_changed_fields() {
+        let changes = diff(
+            &json!({"name": "hello", "private": false, "description": null}),
+            &json!({"name": "world", "private": false, "description": "hi"}),
+        );
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes["name"], Change {
+            before: json!("hello"),
+            after: json!("world"),
+        });
+
+        assert_eq!(changes["description"], Change {
+            before: Value::Null,
+            after: json!("hi"),
+        });
+    }
+
+    #[test]
+    fn diff_created_and_deleted() {
+        let created = diff(&Value::Null, &json!({"name": "hello"}));
+        assert_eq!(created["name"].before, Value::Null);
+        assert_eq!(created["name"].after, json!("hello"));
+
+        let deleted = diff(&json!({"name": "hello"}), &Value::Null);
+        assert_eq!(deleted["name"].before, json!("hello"));
+        assert_eq!(deleted["name"].after, Value::Null);
+    }
+
+    #[test]
+    fn diff_redacts_sensitive_fields() {
+        let changes = diff(&Value::Null, &json!({"name": "key", "token": "ck_abcdef"}));
+        assert_eq!(changes["token"].after, json!(REDACTED));
+        assert_eq!(changes["name"].after, json!("key"));
+    }
+}
@@ -0,0 +1,55 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sinks are where [`AuditLog`]s are stored and queried from.
+
+pub mod clickhouse;
+pub mod database;
+pub mod jsonl;
+
+use crate::{AuditLog, Page, Query};
+use charted_config::features::auditlog;
+use charted_core::BoxedFuture;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+
+/// Storage of [`AuditLog`]s.
+pub trait Sink: Send + Sync {
+    /// Prepares this sink before any records are stored, like creating the table that
+    /// records are stored in.
+    fn init(&self) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Stores a single record.
+    fn record(&self, record: AuditLog) -> BoxedFuture<'_, eyre::Result<()>>;
+
+    /// Returns the records that match `query`, from newest to oldest.
+    fn query(&self, query: Query) -> BoxedFuture<'_, eyre::Result<Page>>;
+}
+
+/// Creates the [`Sink`] that is configured by `config`.
+pub fn from_config(config: &auditlog::Sink, db: DatabaseConnection, http: reqwest::Client) -> Arc<dyn Sink> {
+    match config {
+        auditlog::Sink::Database => Arc::new(database::Database::new(db)),
+        auditlog::Sink::ClickHouse(config) => Arc::new(clickhouse::ClickHouse::new(config.clone(), http)),
+        auditlog::Sink::Jsonl(config) => Arc::new(jsonl::Jsonl::new(config.path.clone())),
+    }
+}
+
+/// Returns the amount of pages that `total` records are split into.
+pub(crate) fn pages(total: usize, per_page: usize) -> usize {
+    total.div_ceil(per_page.max(1))
+}
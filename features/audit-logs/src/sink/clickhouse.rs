@@ -0,0 +1,209 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Action, AuditLog, Page, Query, sink::Sink};
+use charted_config::features::auditlog;
+use charted_core::BoxedFuture;
+use charted_types::Ulid;
+use chrono::{DateTime, Utc};
+use eyre::{Context, bail};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// [`Sink`] that stores records in a [ClickHouse](https://clickhouse.com) table via its
+/// [HTTP interface](https://clickhouse.com/docs/interfaces/http).
+#[derive(Debug, Clone)]
+pub struct ClickHouse {
+    config: auditlog::ClickHouse,
+    http: reqwest::Client,
+}
+
+impl ClickHouse {
+    pub fn new(config: auditlog::ClickHouse, http: reqwest::Client) -> ClickHouse {
+        ClickHouse { config, http }
+    }
+
+    /// Returns the configured table name as a quoted identifier.
+    fn table(&self) -> String {
+        format!("`{}`", self.config.table.replace('`', "\\`"))
+    }
+
+    /// Sends `body` as a SQL statement, with `params` as the [query parameters] that the
+    /// statement references.
+    ///
+    /// [query parameters]: https://clickhouse.com/docs/interfaces/cli#cli-queries-with-parameters
+    async fn execute(&self, body: String, params: &[(&str, String)]) -> eyre::Result<String> {
+        let mut url = self.config.url.clone();
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs
+                .append_pair("database", &self.config.database)
+                .append_pair("date_time_input_format", "best_effort")
+                .append_pair("date_time_output_format", "iso");
+
+            for (name, value) in params {
+                pairs.append_pair(&format!("param_{name}"), value);
+            }
+        }
+
+        let mut request = self.http.post(url).body(body);
+        if let Some(ref username) = self.config.username {
+            request = request.header("x-clickhouse-user", username);
+        }
+
+        if let Some(ref password) = self.config.password {
+            request = request.header("x-clickhouse-key", password);
+        }
+
+        let response = request.send().await.context("failed to send request to ClickHouse")?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            bail!("ClickHouse responded with {status}: {}", body.trim());
+        }
+
+        Ok(body)
+    }
+
+    /// Returns the `WHERE` clause and its parameters that `query` selects.
+    fn condition(query: &Query) -> (&'static str, Vec<(&'static str, String)>) {
+        let mut params = vec![("owner", query.owner.to_string())];
+        match query.repository {
+            Some(repository) => {
+                params.push(("repository", repository.to_string()));
+                ("owner = {owner:String} AND repository = {repository:String}", params)
+            }
+
+            None => ("owner = {owner:String}", params),
+        }
+    }
+}
+
+/// Representation of an [`AuditLog`] as a ClickHouse row.
+#[derive(Serialize, Deserialize)]
+struct Row {
+    repository: Option<Ulid>,
+    request_id: Option<String>,
+    timestamp: DateTime<Utc>,
+    changes: String,
+    action: String,
+    actor: Option<Ulid>,
+    owner: Ulid,
+    ip: Option<IpAddr>,
+    id: Ulid,
+}
+
+impl Row {
+    fn into_record(self) -> Option<AuditLog> {
+        Some(AuditLog {
+            repository: self.repository,
+            request_id: self.request_id,
+            timestamp: self.timestamp.into(),
+            changes: serde_json::from_str(&self.changes).ok()?,
+            action: self.action.parse::<Action>().ok()?,
+            actor: self.actor,
+            owner: self.owner,
+            ip: self.ip,
+            id: self.id,
+        })
+    }
+}
+
+impl Sink for ClickHouse {
+    fn init(&self) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            let statement = format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    repository Nullable(String),
+                    request_id Nullable(String),
+                    timestamp DateTime64(3, 'UTC'),
+                    changes String,
+                    action LowCardinality(String),
+                    actor Nullable(String),
+                    owner String,
+                    ip Nullable(String),
+                    id String
+                ) ENGINE = MergeTree ORDER BY (owner, timestamp)",
+                self.table()
+            );
+
+            self.execute(statement, &[]).await.map(|_| ())
+        })
+    }
+
+    fn record(&self, record: AuditLog) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            let row = Row {
+                repository: record.repository,
+                request_id: record.request_id,
+                timestamp: record.timestamp.into(),
+                changes: serde_json::to_string(&record.changes)?,
+                action: record.action.as_str().to_owned(),
+                actor: record.actor,
+                owner: record.owner,
+                ip: record.ip,
+                id: record.id,
+            };
+
+            let statement = format!(
+                "INSERT INTO {} FORMAT JSONEachRow\n{}",
+                self.table(),
+                serde_json::to_string(&row)?
+            );
+
+            self.execute(statement, &[]).await.map(|_| ())
+        })
+    }
+
+    fn query(&self, query: Query) -> BoxedFuture<'_, eyre::Result<Page>> {
+        Box::pin(async move {
+            let (condition, mut params) = Self::condition(&query);
+            let total = self
+                .execute(
+                    format!("SELECT count() FROM {} WHERE {condition}", self.table()),
+                    &params,
+                )
+                .await?
+                .trim()
+                .parse::<usize>()
+                .context("failed to parse amount of records")?;
+
+            params.push(("limit", query.per_page.to_string()));
+            params.push(("offset", query.offset().to_string()));
+
+            let rows = self
+                .execute(
+                    format!(
+                        "SELECT * FROM {} WHERE {condition} ORDER BY timestamp DESC, id DESC \
+                         LIMIT {{limit:UInt64}} OFFSET {{offset:UInt64}} FORMAT JSONEachRow",
+                        self.table()
+                    ),
+                    &params,
+                )
+                .await?;
+
+            let records = rows
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Row>(line).ok())
+                .filter_map(Row::into_record)
+                .collect();
+
+            Ok(Page {
+                pages: super::pages(total, query.per_page),
+                records,
+            })
+        })
+    }
+}
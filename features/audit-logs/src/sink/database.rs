@@ -0,0 +1,203 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Action, AuditLog, Page, Query, sink::Sink};
+use charted_core::BoxedFuture;
+use charted_database::entities::{AuditLogEntity, audit_log};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder,
+};
+
+/// [`Sink`] that stores records in the `audit_logs` table of the server's database.
+#[derive(Debug, Clone)]
+pub struct Database {
+    db: DatabaseConnection,
+}
+
+impl Database {
+    pub fn new(db: DatabaseConnection) -> Database {
+        Database { db }
+    }
+}
+
+impl Sink for Database {
+    fn record(&self, record: AuditLog) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            let model = audit_log::Model {
+                created_at: record.timestamp.into(),
+                updated_at: record.timestamp.into(),
+                request_id: record.request_id,
+                repository: record.repository,
+                changes: serde_json::to_value(&record.changes)?,
+                action: record.action.as_str().to_owned(),
+                actor: record.actor,
+                owner: record.owner,
+                ip: record.ip.map(|ip| ip.to_string()),
+                id: record.id,
+            };
+
+            AuditLogEntity::insert(model.into_active_model())
+                .exec(&self.db)
+                .await
+                .map(|_| ())
+                .map_err(Into::into)
+        })
+    }
+
+    fn query(&self, query: Query) -> BoxedFuture<'_, eyre::Result<Page>> {
+        Box::pin(async move {
+            let mut select = AuditLogEntity::find().filter(audit_log::Column::Owner.eq(query.owner));
+            if let Some(repository) = query.repository {
+                select = select.filter(audit_log::Column::Repository.eq(repository));
+            }
+
+            let paginator = select
+                .order_by_desc(audit_log::Column::CreatedAt)
+                .order_by_desc(audit_log::Column::Id)
+                .paginate(&self.db, query.per_page.max(1) as u64);
+
+            let pages = paginator.num_pages().await?;
+            let records = paginator
+                .fetch_page(query.page.saturating_sub(1) as u64)
+                .await?
+                .into_iter()
+                // records of actions that no longer exist are skipped
+                .filter_map(|model| {
+                    Some(AuditLog {
+                        repository: model.repository,
+                        request_id: model.request_id,
+                        timestamp: model.created_at.into(),
+                        changes: serde_json::from_value(model.changes).ok()?,
+                        action: model.action.parse::<Action>().ok()?,
+                        actor: model.actor,
+                        owner: model.owner,
+                        ip: model.ip.and_then(|ip| ip.parse().ok()),
+                        id: model.id,
+                    })
+                })
+                .collect();
+
+            Ok(Page {
+                records,
+                pages: pages as usize,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use charted_config::database;
+    use charted_types::{DateTime, Ulid};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    const OWNER: &str = "01JQBR2V6V6MJ3D1JZ2GBKG9YW";
+    const REPOSITORY: &str = "01JQBR3C9WZ2C3XJ5QGCZV4T4Q";
+
+    async fn sink() -> Database {
+        let db = charted_database::create_pool(&database::Config::SQLite(database::sqlite::Config {
+            common: Default::default(),
+            path: String::from(":memory:").into(),
+        }))
+        .await
+        .expect("failed to create database pool");
+
+        Database::new(db)
+    }
+
+    fn record(id: &str, repository: Option<&str>, action: Action) -> AuditLog {
+        AuditLog {
+            repository: repository.map(|id| Ulid::new(id).unwrap()),
+            request_id: Some(String::from("abcdefghijkl")),
+            timestamp: DateTime::now(),
+            changes: BTreeMap::from([(String::from("name"), Change {
+                before: json!(null),
+                after: json!("hello-world"),
+            })]),
+            action,
+            actor: Some(Ulid::new(OWNER).unwrap()),
+            owner: Ulid::new(OWNER).unwrap(),
+            ip: Some("127.0.0.1".parse().unwrap()),
+            id: Ulid::new(id).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_query() {
+        let sink = sink().await;
+        sink.record(record(
+            "01JQBR4A0000000000000000A1",
+            Some(REPOSITORY),
+            Action::RepositoryCreated,
+        ))
+        .await
+        .unwrap();
+
+        sink.record(record("01JQBR4A0000000000000000A2", None, Action::ApiKeyCreated))
+            .await
+            .unwrap();
+
+        let query = Query {
+            owner: Ulid::new(OWNER).unwrap(),
+            repository: Some(Ulid::new(REPOSITORY).unwrap()),
+            per_page: 10,
+            page: 1,
+        };
+
+        let page = sink.query(query).await.unwrap();
+        assert_eq!(page.pages, 1);
+        assert_eq!(page.records.len(), 1);
+
+        let record = &page.records[0];
+        assert_eq!(record.action, Action::RepositoryCreated);
+        assert_eq!(record.request_id.as_deref(), Some("abcdefghijkl"));
+        assert_eq!(record.ip, Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(record.changes["name"].after, json!("hello-world"));
+
+        // without a repository, all of the owner's records are returned
+        let page = sink
+            .query(Query {
+                repository: None,
+                ..query
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.records.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn pagination() {
+        let sink = sink().await;
+        for id in ["01JQBR4A0000000000000000B1", "01JQBR4A0000000000000000B2", "01JQBR4A0000000000000000B3"] {
+            sink.record(record(id, Some(REPOSITORY), Action::RepositoryUpdated))
+                .await
+                .unwrap();
+        }
+
+        let query = Query {
+            owner: Ulid::new(OWNER).unwrap(),
+            repository: Some(Ulid::new(REPOSITORY).unwrap()),
+            per_page: 2,
+            page: 2,
+        };
+
+        let page = sink.query(query).await.unwrap();
+        assert_eq!(page.pages, 2);
+        assert_eq!(page.records.len(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{AuditLog, Page, Query, sink::Sink};
+use charted_core::BoxedFuture;
+use std::{io::ErrorKind, path::PathBuf};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+
+/// [`Sink`] that appends records as JSON lines to a file.
+///
+/// Querying reads the whole file, so this is only meant for development and testing.
+#[derive(Debug)]
+pub struct Jsonl {
+    path: PathBuf,
+
+    // serializes appends so that lines of concurrent records don't interleave
+    lock: Mutex<()>,
+}
+
+impl Jsonl {
+    pub fn new(path: impl Into<PathBuf>) -> Jsonl {
+        Jsonl {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl Sink for Jsonl {
+    fn init(&self) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            if let Some(parent) = self.path.parent() &&
+                !parent.as_os_str().is_empty()
+            {
+                fs::create_dir_all(parent).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn record(&self, record: AuditLog) -> BoxedFuture<'_, eyre::Result<()>> {
+        Box::pin(async move {
+            let mut line = serde_json::to_vec(&record)?;
+            line.push(b'\n');
+
+            let _guard = self.lock.lock().await;
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+
+            file.write_all(&line).await?;
+            file.flush().await.map_err(Into::into)
+        })
+    }
+
+    fn query(&self, query: Query) -> BoxedFuture<'_, eyre::Result<Page>> {
+        Box::pin(async move {
+            let contents = {
+                let _guard = self.lock.lock().await;
+                match fs::read_to_string(&self.path).await {
+                    Ok(contents) => contents,
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Page::default()),
+                    Err(e) => return Err(e.into()),
+                }
+            };
+
+            // records are appended, so the newest ones are at the end of the file
+            let matching = contents
+                .lines()
+                .rev()
+                .filter_map(|line| serde_json::from_str::<AuditLog>(line).ok())
+                .filter(|record| query.matches(record))
+                .collect::<Vec<_>>();
+
+            Ok(Page {
+                pages: super::pages(matching.len(), query.per_page),
+                records: matching.into_iter().skip(query.offset()).take(query.per_page).collect(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+    use charted_types::{DateTime, Ulid};
+    use std::collections::BTreeMap;
+
+    const OWNER: &str = "01JQBR2V6V6MJ3D1JZ2GBKG9YW";
+
+    fn record(id: &str, action: Action) -> AuditLog {
+        AuditLog {
+            repository: None,
+            request_id: None,
+            timestamp: DateTime::now(),
+            changes: BTreeMap::new(),
+            action,
+            actor: None,
+            owner: Ulid::new(OWNER).unwrap(),
+            ip: None,
+            id: Ulid::new(id).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn newest_first() {
+        let path = std::env::temp_dir().join(format!("charted-audit-logs-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let sink = Jsonl::new(&path);
+        sink.init().await.unwrap();
+
+        sink.record(record("01JQBR4A0000000000000000C1", Action::ApiKeyCreated))
+            .await
+            .unwrap();
+
+        sink.record(record("01JQBR4A0000000000000000C2", Action::ApiKeyUpdated))
+            .await
+            .unwrap();
+
+        sink.record(record("01JQBR4A0000000000000000C3", Action::ApiKeyDeleted))
+            .await
+            .unwrap();
+
+        let query = Query {
+            owner: Ulid::new(OWNER).unwrap(),
+            repository: None,
+            per_page: 2,
+            page: 1,
+        };
+
+        let page = sink.query(query).await.unwrap();
+        assert_eq!(page.pages, 2);
+        assert_eq!(page.records.iter().map(|record| record.action).collect::<Vec<_>>(), [
+            Action::ApiKeyDeleted,
+            Action::ApiKeyUpdated
+        ]);
+
+        let page = sink.query(Query { page: 2, ..query }).await.unwrap();
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.records[0].action, Action::ApiKeyCreated);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
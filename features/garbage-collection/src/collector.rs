@@ -0,0 +1,341 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    Garbage, Item, Report,
+    rules::{self, Context},
+};
+use charted_config::features::gc::Config;
+use charted_database::entities::{RepositoryEntity, RepositoryReleaseEntity, repository::release};
+use charted_datastore::{DataStore, remi::StorageService as _};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use eyre::Context as _;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use std::{str::FromStr, time::SystemTime};
+use tracing::{error, info, instrument};
+
+/// Runs the configured rules against the database and datastore.
+#[derive(Clone)]
+pub struct Collector {
+    schedule: Schedule,
+    config: Config,
+    db: DatabaseConnection,
+    ds: DataStore,
+}
+
+impl std::fmt::Debug for Collector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Collector")
+            .field("schedule", &self.config.schedule)
+            .field("rules", &self.config.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Collector {
+    /// Creates a new [`Collector`]. Fails if `schedule` is not a valid cron expression.
+    pub fn new(config: Config, db: DatabaseConnection, ds: DataStore) -> eyre::Result<Collector> {
+        let schedule = Schedule::from_str(&config.schedule).with_context(|| {
+            format!(
+                "invalid cron expression for `features.gc.schedule`: {}",
+                config.schedule
+            )
+        })?;
+
+        Ok(Collector {
+            schedule,
+            config,
+            db,
+            ds,
+        })
+    }
+
+    /// Returns the `[features.gc]` configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns when the collector should run next, after `after`.
+    pub fn next_run(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule.after(&after).next()
+    }
+
+    /// Runs every rule in order and collects what they found, unless `dry_run` is
+    /// `true`.
+    ///
+    /// A rule that fails is logged and skipped, and so is garbage that fails to be
+    /// deleted, which is then reported as not collected.
+    #[instrument(name = "charted.gc.run", skip(self))]
+    pub async fn run(&self, dry_run: bool) -> eyre::Result<Report> {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+        let cx = Context {
+            db: &self.db,
+            ds: &self.ds,
+            cutoff: now.saturating_sub(*self.config.grace_period).as_millis(),
+        };
+
+        let mut report = Report {
+            dry_run,
+            items: Vec::new(),
+        };
+
+        for rule in &self.config.rules {
+            let name = rules::name(rule);
+            let garbage = match rules::find(&cx, rule).await {
+                Ok(garbage) => garbage,
+                Err(e) => {
+                    error!(error = %e, rule = name, "failed to run garbage collection rule");
+                    continue;
+                }
+            };
+
+            for garbage in garbage {
+                let collected = !dry_run &&
+                    match self.collect(&garbage).await {
+                        Ok(()) => true,
+                        Err(e) => {
+                            error!(error = %e, rule = name, %garbage, "failed to collect garbage");
+                            false
+                        }
+                    };
+
+                report.items.push(Item {
+                    rule: name,
+                    collected,
+                    garbage,
+                });
+            }
+        }
+
+        info!(
+            dry_run,
+            found = report.items.len(),
+            collected = report.collected(),
+            "garbage collection finished"
+        );
+
+        Ok(report)
+    }
+
+    async fn collect(&self, garbage: &Garbage) -> eyre::Result<()> {
+        match garbage {
+            Garbage::Object { namespace, path } => {
+                self.ds.namespace(namespace.as_str()).delete(path).await?;
+            }
+
+            Garbage::Repository { id, .. } => {
+                RepositoryReleaseEntity::delete_many()
+                    .filter(release::Column::Repository.eq(*id))
+                    .exec(&self.db)
+                    .await?;
+
+                RepositoryEntity::delete_by_id(*id).exec(&self.db).await?;
+            }
+
+            Garbage::Release {
+                owner,
+                repository,
+                version,
+                id,
+            } => {
+                RepositoryReleaseEntity::delete_by_id(*id).exec(&self.db).await?;
+
+                let ns = self.ds.namespace(format!("repositories/{owner}/{repository}"));
+                for path in [format!("tarballs/{version}.tgz"), format!("tarballs/{version}.prov.tgz")] {
+                    if ns.exists(&path).await? {
+                        ns.delete(&path).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charted_config::{database, features::gc::Rule, storage};
+    use charted_database::entities::{UserEntity, repository, user};
+    use charted_datastore::{fs, remi::UploadRequest};
+    use charted_types::{ChartType, Ulid, Version};
+    use chrono::Duration;
+    use sea_orm::{IntoActiveModel, PaginatorTrait};
+    use tempfile::TempDir;
+
+    const USER: &str = "01JQBR2V6V6MJ3D1JZ2GBKG9YW";
+    const REPOSITORY: &str = "01JQBR3C9WZ2C3XJ5QGCZV4T4Q";
+    const ORPHAN: &str = "01JQBR4F3D1Y8T0Z8ZQK3A6N7M";
+
+    async fn collector(rules: Vec<Rule>) -> (Collector, TempDir) {
+        let db = charted_database::create_pool(&database::Config::SQLite(database::sqlite::Config {
+            common: Default::default(),
+            path: String::from(":memory:").into(),
+        }))
+        .await
+        .expect("failed to create database pool");
+
+        let tmpdir = TempDir::new().unwrap();
+        let ds = DataStore::new(&storage::Config::Filesystem(fs::StorageConfig::new(
+            tmpdir.path().to_path_buf(),
+        )))
+        .await
+        .expect("failed to create datastore");
+
+        let config = Config {
+            enable: true,
+            grace_period: charted_core::serde::Duration::from_secs(0),
+            rules,
+            ..Default::default()
+        };
+
+        let now = Utc::now();
+        UserEntity::insert(
+            user::Model {
+                verified_publisher: false,
+                prefers_gravatar: false,
+                gravatar_email: None,
+                description: None,
+                avatar_hash: None,
+                created_at: now,
+                updated_at: now,
+                username: "noel".parse().unwrap(),
+                password: None,
+                email: String::from("noel@noelware.org"),
+                admin: false,
+                name: None,
+                id: Ulid::new(USER).unwrap(),
+            }
+            .into_active_model(),
+        )
+        .exec(&db)
+        .await
+        .unwrap();
+
+        for (owner, id) in [(USER, REPOSITORY), ("01JQBR5X1MZ5QF6W2H8C7T9B0E", ORPHAN)] {
+            RepositoryEntity::insert(
+                repository::Model {
+                    description: None,
+                    deprecated: false,
+                    created_at: now,
+                    updated_at: now,
+                    icon_hash: None,
+                    private: false,
+                    creator: None,
+                    owner: Ulid::new(owner).unwrap(),
+                    latest_version: None,
+                    latest_prerelease: None,
+                    name: "hello-world".parse().unwrap(),
+                    type_: ChartType::Application,
+                    id: Ulid::new(id).unwrap(),
+                }
+                .into_active_model(),
+            )
+            .exec(&db)
+            .await
+            .unwrap();
+        }
+
+        let releases = [
+            ("01JQBR6A0000000000000000A1", "1.0.0"),
+            ("01JQBR6A0000000000000000A2", "1.1.0-beta.1"),
+            ("01JQBR6A0000000000000000A3", "1.1.0-beta.2"),
+            ("01JQBR6A0000000000000000A4", "1.1.0-beta.3"),
+        ];
+
+        for (i, (id, tag)) in releases.into_iter().enumerate() {
+            let created_at = now + Duration::minutes(i as i64);
+            RepositoryReleaseEntity::insert(
+                release::Model {
+                    update_text: None,
+                    repository: Ulid::new(REPOSITORY).unwrap(),
+                    created_at,
+                    updated_at: created_at,
+                    yanked: false,
+                    title: None,
+                    chart: None,
+                    digest: None,
+                    tag: Version::parse(tag).unwrap(),
+                    id: Ulid::new(id).unwrap(),
+                }
+                .into_active_model(),
+            )
+            .exec(&db)
+            .await
+            .unwrap();
+        }
+
+        (Collector::new(config, db, ds).unwrap(), tmpdir)
+    }
+
+    #[tokio::test]
+    async fn dry_run_keeps_everything() {
+        let (collector, _tmpdir) =
+            collector(vec![Rule::OrphanedRepositories, Rule::Prereleases { keep: 1 }]).await;
+        let report = collector.run(true).await.unwrap();
+
+        let garbage = report.items.iter().map(|item| &item.garbage).collect::<Vec<_>>();
+        assert_eq!(garbage.len(), 3);
+        assert!(matches!(garbage[0], Garbage::Repository { id, .. } if id.to_string() == ORPHAN));
+        assert!(matches!(garbage[1], Garbage::Release { version, .. } if version.to_string() == "1.1.0-beta.2"));
+        assert!(matches!(garbage[2], Garbage::Release { version, .. } if version.to_string() == "1.1.0-beta.1"));
+
+        assert_eq!(report.collected(), 0);
+        assert_eq!(RepositoryEntity::find().count(&collector.db).await.unwrap(), 2);
+        assert_eq!(RepositoryReleaseEntity::find().count(&collector.db).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn collects_orphans_and_prereleases() {
+        let (collector, _tmpdir) = collector(vec![
+            Rule::OrphanedRepositories,
+            Rule::Prereleases { keep: 1 },
+            Rule::OrphanedTarballs,
+        ])
+        .await;
+
+        let ns = collector.ds.namespace(format!("repositories/{USER}/{REPOSITORY}"));
+        for version in ["1.0.0", "1.1.0-beta.1", "0.1.0"] {
+            ns.upload(
+                format!("tarballs/{version}.tgz"),
+                UploadRequest::default().with_data(b"tarball".to_vec()),
+            )
+            .await
+            .unwrap();
+        }
+
+        let report = collector.run(false).await.unwrap();
+        assert_eq!(report.collected(), report.items.len());
+        assert_eq!(report.affected_owners(), [Ulid::new(USER).unwrap()]);
+        assert_eq!(report.affected_repositories(), [Ulid::new(REPOSITORY).unwrap()]);
+
+        // the tarball of `1.1.0-beta.1` was deleted alongside its release, so only the
+        // tarball of `0.1.0` is left to be found as an orphan.
+        assert!(report.items.iter().any(|item| item.garbage ==
+            Garbage::Object {
+                namespace: String::from("repositories"),
+                path: format!("{USER}/{REPOSITORY}/tarballs/0.1.0.tgz"),
+            }));
+
+        assert!(ns.exists("tarballs/1.0.0.tgz").await.unwrap());
+        assert!(!ns.exists("tarballs/1.1.0-beta.1.tgz").await.unwrap());
+        assert!(!ns.exists("tarballs/0.1.0.tgz").await.unwrap());
+
+        assert_eq!(RepositoryEntity::find().count(&collector.db).await.unwrap(), 1);
+        assert_eq!(RepositoryReleaseEntity::find().count(&collector.db).await.unwrap(), 2);
+    }
+}
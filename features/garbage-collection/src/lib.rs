@@ -13,10 +13,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! # 🐻‍❄️📦 `charted-feature-gc`
+//! This crate implements the **garbage collection** server feature, which periodically
+//! deletes data that nothing refers to anymore, like the tarballs of releases that
+//! no longer exist or avatars that were replaced.
+//!
+//! What is collected is decided by rules in the `[features.gc]` table, which the
+//! [`Collector`] runs in order on a cron schedule. Every run produces a [`Report`] of
+//! what was found, and a dry run only produces the report.
+
+mod collector;
+mod report;
+mod rules;
+
 use charted_feature::Metadata;
+pub use collector::*;
+pub use report::*;
 
 #[derive(Debug, Clone)]
-pub struct Feature;
+pub struct Feature {
+    collector: Collector,
+}
+
+impl Feature {
+    pub fn new(collector: Collector) -> Feature {
+        Feature { collector }
+    }
+
+    /// Returns the [`Collector`] that runs on the configured schedule.
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+}
+
 impl charted_feature::Feature for Feature {
     fn metadata(&self) -> Metadata {
         const METADATA: Metadata = Metadata {
@@ -0,0 +1,115 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use charted_types::{Ulid, Version};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Something that a rule found and is collected by the garbage collector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Garbage {
+    /// A object in the datastore, `path` is relative to `namespace`.
+    Object { namespace: String, path: String },
+
+    /// A repository and all of its releases.
+    Repository { owner: Ulid, id: Ulid },
+
+    /// A single release of a repository and its chart tarball and provenance file.
+    Release {
+        owner: Ulid,
+        repository: Ulid,
+        version: Version,
+        id: Ulid,
+    },
+}
+
+impl Display for Garbage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Garbage::Object { namespace, path } => write!(f, "object {namespace}/{path}"),
+            Garbage::Repository { owner, id } => write!(f, "repository {id} (owner {owner})"),
+            Garbage::Release {
+                repository, version, ..
+            } => write!(f, "release {version} of repository {repository}"),
+        }
+    }
+}
+
+/// A piece of [`Garbage`] and the rule that found it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Item {
+    /// Name of the rule that found this garbage.
+    pub rule: &'static str,
+
+    /// Whether if the garbage was deleted. This is always `false` in a dry run.
+    pub collected: bool,
+
+    #[serde(flatten)]
+    pub garbage: Garbage,
+}
+
+/// Report of a single run of the garbage collector.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// Whether if this was a dry run, in which case nothing was deleted.
+    pub dry_run: bool,
+
+    /// Everything that the rules found.
+    pub items: Vec<Item>,
+}
+
+impl Report {
+    /// Returns the amount of items that were deleted.
+    pub fn collected(&self) -> usize {
+        self.items.iter().filter(|item| item.collected).count()
+    }
+
+    /// Returns the owners that had at least one release deleted, whose chart
+    /// indexes are now out of date.
+    pub fn affected_owners(&self) -> Vec<Ulid> {
+        let mut owners = self
+            .items
+            .iter()
+            .filter(|item| item.collected)
+            .filter_map(|item| match item.garbage {
+                Garbage::Release { owner, .. } => Some(owner),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        owners.sort();
+        owners.dedup();
+        owners
+    }
+
+    /// Returns the repositories that had at least one release deleted, whose cached
+    /// latest versions are now out of date.
+    pub fn affected_repositories(&self) -> Vec<Ulid> {
+        let mut repositories = self
+            .items
+            .iter()
+            .filter(|item| item.collected)
+            .filter_map(|item| match item.garbage {
+                Garbage::Release { repository, .. } => Some(repository),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        repositories.sort();
+        repositories.dedup();
+        repositories
+    }
+}
@@ -0,0 +1,300 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rules find garbage without deleting anything, so that the same code path is used
+//! for dry runs.
+
+use crate::Garbage;
+use charted_config::features::gc::Rule;
+use charted_database::entities::{
+    OrganizationEntity, RepositoryEntity, RepositoryReleaseEntity, UserEntity, repository::release,
+};
+use charted_datastore::{
+    DataStore, Namespace,
+    remi::{Blob, File, StorageService as _},
+};
+use charted_types::Ulid;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::{HashMap, HashSet};
+
+pub(crate) struct Context<'a> {
+    pub db: &'a DatabaseConnection,
+    pub ds: &'a DataStore,
+
+    /// Objects that were modified after this time, in milliseconds since the Unix
+    /// epoch, are never considered as garbage.
+    pub cutoff: u128,
+}
+
+impl Context<'_> {
+    fn is_recent(&self, file: &File) -> bool {
+        file.last_modified_at
+            .or(file.created_at)
+            .is_some_and(|modified| modified > self.cutoff)
+    }
+}
+
+/// Returns the name of a rule as it is written in the configuration file.
+pub(crate) const fn name(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::OrphanedRepositories => "orphaned_repositories",
+        Rule::OrphanedTarballs => "orphaned_tarballs",
+        Rule::UnreferencedAvatars => "unreferenced_avatars",
+        Rule::Prereleases { .. } => "prereleases",
+    }
+}
+
+pub(crate) async fn find(cx: &Context<'_>, rule: &Rule) -> eyre::Result<Vec<Garbage>> {
+    match rule {
+        Rule::OrphanedRepositories => orphaned_repositories(cx).await,
+        Rule::OrphanedTarballs => orphaned_tarballs(cx).await,
+        Rule::UnreferencedAvatars => unreferenced_avatars(cx).await,
+        Rule::Prereleases { keep } => prereleases(cx, *keep).await,
+    }
+}
+
+async fn orphaned_repositories(cx: &Context<'_>) -> eyre::Result<Vec<Garbage>> {
+    let mut owners = HashMap::<Ulid, bool>::new();
+    let mut garbage = Vec::new();
+
+    for repository in RepositoryEntity::find().all(cx.db).await? {
+        let exists = match owners.get(&repository.owner) {
+            Some(exists) => *exists,
+            None => {
+                let exists = UserEntity::find_by_id(repository.owner).one(cx.db).await?.is_some() ||
+                    OrganizationEntity::find_by_id(repository.owner)
+                        .one(cx.db)
+                        .await?
+                        .is_some();
+
+                owners.insert(repository.owner, exists);
+                exists
+            }
+        };
+
+        if !exists {
+            garbage.push(Garbage::Repository {
+                owner: repository.owner,
+                id: repository.id,
+            });
+        }
+    }
+
+    Ok(garbage)
+}
+
+/// Walks `repositories/{owner}/{repo}/tarballs` and finds the tarballs and provenance
+/// files of versions that don't have a release.
+async fn orphaned_tarballs(cx: &Context<'_>) -> eyre::Result<Vec<Garbage>> {
+    let ns = cx.ds.namespace("repositories");
+    let mut garbage = Vec::new();
+
+    for owner in directories(&ns, None).await? {
+        for repository in directories(&ns, Some(owner.as_str())).await? {
+            // `repositories/{id}/avatars` holds the icons of a repository
+            let Ok(id) = Ulid::new(&repository) else {
+                continue;
+            };
+
+            let releases = RepositoryReleaseEntity::find()
+                .filter(release::Column::Repository.eq(id))
+                .all(cx.db)
+                .await?
+                .into_iter()
+                .map(|release| release.tag.to_string())
+                .collect::<HashSet<_>>();
+
+            let path = format!("{owner}/{repository}/tarballs");
+            for file in files(&ns, &path).await? {
+                if cx.is_recent(&file) {
+                    continue;
+                }
+
+                let Some(version) = file
+                    .name
+                    .strip_suffix(".prov.tgz")
+                    .or_else(|| file.name.strip_suffix(".tgz"))
+                else {
+                    continue;
+                };
+
+                if !releases.contains(version) {
+                    garbage.push(Garbage::Object {
+                        namespace: String::from("repositories"),
+                        path: format!("{path}/{}", file.name),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(garbage)
+}
+
+/// Finds user avatars under `avatars/users/{id}`, organization icons under
+/// `avatars/orgs/{id}` and repository icons under `repositories/{id}/avatars` that
+/// aren't the current avatar or icon.
+async fn unreferenced_avatars(cx: &Context<'_>) -> eyre::Result<Vec<Garbage>> {
+    let mut garbage = Vec::new();
+
+    let avatars = cx.ds.namespace("avatars");
+    for id in directories(&avatars, Some("users")).await? {
+        let current = match Ulid::new(&id) {
+            Ok(id) => UserEntity::find_by_id(id)
+                .one(cx.db)
+                .await?
+                .and_then(|user| user.avatar_hash),
+
+            Err(_) => continue,
+        };
+
+        unreferenced(cx, "avatars", &format!("users/{id}"), current.as_deref(), &mut garbage).await?;
+    }
+
+    for id in directories(&avatars, Some("orgs")).await? {
+        let current = match Ulid::new(&id) {
+            Ok(id) => OrganizationEntity::find_by_id(id)
+                .one(cx.db)
+                .await?
+                .and_then(|org| org.icon_hash),
+
+            Err(_) => continue,
+        };
+
+        unreferenced(cx, "avatars", &format!("orgs/{id}"), current.as_deref(), &mut garbage).await?;
+    }
+
+    let repositories = cx.ds.namespace("repositories");
+    for id in directories(&repositories, None).await? {
+        // only repositories have a `avatars` directory, owners have a directory
+        // for every repository instead.
+        if !directories(&repositories, Some(id.as_str()))
+            .await?
+            .iter()
+            .any(|name| name == "avatars")
+        {
+            continue;
+        }
+
+        let current = match Ulid::new(&id) {
+            Ok(id) => RepositoryEntity::find_by_id(id)
+                .one(cx.db)
+                .await?
+                .and_then(|repository| repository.icon_hash),
+
+            Err(_) => continue,
+        };
+
+        unreferenced(
+            cx,
+            "repositories",
+            &format!("{id}/avatars"),
+            current.as_deref(),
+            &mut garbage,
+        )
+        .await?;
+    }
+
+    Ok(garbage)
+}
+
+async fn unreferenced(
+    cx: &Context<'_>,
+    namespace: &str,
+    path: &str,
+    current: Option<&str>,
+    garbage: &mut Vec<Garbage>,
+) -> eyre::Result<()> {
+    for file in files(&cx.ds.namespace(namespace), path).await? {
+        if current == Some(file.name.as_str()) || cx.is_recent(&file) {
+            continue;
+        }
+
+        garbage.push(Garbage::Object {
+            namespace: namespace.to_owned(),
+            path: format!("{path}/{}", file.name),
+        });
+    }
+
+    Ok(())
+}
+
+/// Finds every prerelease of a repository except for the last `keep` that were
+/// published.
+async fn prereleases(cx: &Context<'_>, keep: usize) -> eyre::Result<Vec<Garbage>> {
+    let owners = RepositoryEntity::find()
+        .all(cx.db)
+        .await?
+        .into_iter()
+        .map(|repository| (repository.id, repository.owner))
+        .collect::<HashMap<_, _>>();
+
+    let mut seen = HashMap::<Ulid, usize>::new();
+    let mut garbage = Vec::new();
+
+    for release in RepositoryReleaseEntity::find()
+        .order_by_desc(release::Column::CreatedAt)
+        .all(cx.db)
+        .await?
+    {
+        if release.tag.pre.is_empty() {
+            continue;
+        }
+
+        let count = seen.entry(release.repository).or_default();
+        *count += 1;
+
+        if *count <= keep {
+            continue;
+        }
+
+        let Some(owner) = owners.get(&release.repository) else {
+            continue;
+        };
+
+        garbage.push(Garbage::Release {
+            owner: *owner,
+            repository: release.repository,
+            version: release.tag,
+            id: release.id,
+        });
+    }
+
+    Ok(garbage)
+}
+
+async fn directories(ns: &Namespace<'_>, path: Option<&str>) -> eyre::Result<Vec<String>> {
+    Ok(ns
+        .blobs(path, None)
+        .await?
+        .into_iter()
+        .filter_map(|blob| match blob {
+            Blob::Directory(dir) => Some(dir.name),
+            Blob::File(_) => None,
+        })
+        .collect())
+}
+
+async fn files(ns: &Namespace<'_>, path: &str) -> eyre::Result<Vec<File>> {
+    Ok(ns
+        .blobs(Some(path), None)
+        .await?
+        .into_iter()
+        .filter_map(|blob| match blob {
+            Blob::File(file) => Some(file),
+            Blob::Directory(_) => None,
+        })
+        .collect())
+}
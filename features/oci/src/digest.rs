@@ -0,0 +1,195 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Content-addressable digests, as described in the [OCI Image Specification].
+//!
+//! [OCI Image Specification]: https://github.com/opencontainers/image-spec/blob/v1.1.1/descriptor.md#digests
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use sha2::{Digest as _, Sha256, Sha512};
+use std::{fmt::Display, str::FromStr};
+
+/// Algorithms that the registry can verify content with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Algorithm {
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    /// Returns the name of this algorithm as it appears in a digest.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+        }
+    }
+
+    /// Length of the hex-encoded part of a digest that uses this algorithm.
+    const fn encoded_len(&self) -> usize {
+        match self {
+            Algorithm::Sha256 => 64,
+            Algorithm::Sha512 => 128,
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A digest in the form of `algorithm:encoded`, i.e, `sha256:6c3c624b58dbbcd3c0dd82b4c53f04194d1247c6eebdaab7c610cf7d66709b3b`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest {
+    algorithm: Algorithm,
+    encoded: String,
+}
+
+impl Digest {
+    /// Computes the `sha256` digest of `data`.
+    pub fn sha256(data: &[u8]) -> Digest {
+        Digest {
+            algorithm: Algorithm::Sha256,
+            encoded: hex::encode(Sha256::digest(data)),
+        }
+    }
+
+    /// Computes the digest of `data` with the given [`Algorithm`].
+    pub fn compute(algorithm: Algorithm, data: &[u8]) -> Digest {
+        let encoded = match algorithm {
+            Algorithm::Sha256 => hex::encode(Sha256::digest(data)),
+            Algorithm::Sha512 => hex::encode(Sha512::digest(data)),
+        };
+
+        Digest { algorithm, encoded }
+    }
+
+    /// Returns `true` if `data` hashes to this digest.
+    pub fn verify(&self, data: &[u8]) -> bool {
+        Digest::compute(self.algorithm, data) == *self
+    }
+
+    /// Returns the [`Algorithm`] of this digest.
+    pub const fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Returns the hex-encoded portion of this digest.
+    pub fn encoded(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.encoded)
+    }
+}
+
+/// Error type when parsing a [`Digest`] has failed.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum ParseError {
+    #[display("digest must be in the form of `algorithm:encoded`")]
+    MissingSeparator,
+
+    #[display("unsupported digest algorithm `{}`", _0)]
+    UnsupportedAlgorithm(String),
+
+    #[display("encoded portion of a `{}` digest must be {} lowercase hex characters", _0, _0.encoded_len())]
+    InvalidEncoding(Algorithm),
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for Digest {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((algorithm, encoded)) = s.split_once(':') else {
+            return Err(ParseError::MissingSeparator);
+        };
+
+        let algorithm = match algorithm {
+            "sha256" => Algorithm::Sha256,
+            "sha512" => Algorithm::Sha512,
+            other => return Err(ParseError::UnsupportedAlgorithm(other.to_owned())),
+        };
+
+        if encoded.len() != algorithm.encoded_len() ||
+            !encoded.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        {
+            return Err(ParseError::InvalidEncoding(algorithm));
+        }
+
+        Ok(Digest {
+            algorithm,
+            encoded: encoded.to_owned(),
+        })
+    }
+}
+
+impl Serialize for Digest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Digest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let digest: Digest = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            .parse()
+            .unwrap();
+
+        assert_eq!(digest.algorithm(), Algorithm::Sha256);
+        assert!(digest.verify(b"hello"));
+        assert_eq!(digest, Digest::sha256(b"hello"));
+        assert_eq!(
+            digest.to_string(),
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!("sha256".parse::<Digest>(), Err(ParseError::MissingSeparator));
+        assert_eq!(
+            "md5:5d41402abc4b2a76b9719d911017c592".parse::<Digest>(),
+            Err(ParseError::UnsupportedAlgorithm("md5".into()))
+        );
+
+        assert_eq!(
+            "sha256:abc".parse::<Digest>(),
+            Err(ParseError::InvalidEncoding(Algorithm::Sha256))
+        );
+
+        assert_eq!(
+            "sha256:2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824".parse::<Digest>(),
+            Err(ParseError::InvalidEncoding(Algorithm::Sha256))
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Error responses that follow the [OCI Distribution Specification].
+//!
+//! [OCI Distribution Specification]: https://github.com/opencontainers/distribution-spec/blob/v1.1.1/spec.md#error-codes
+
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header::WWW_AUTHENTICATE},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{borrow::Cow, fmt::Display};
+use tracing::error;
+
+/// Error codes that are defined by the distribution specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// blob unknown to registry
+    BlobUnknown,
+
+    /// blob upload invalid
+    BlobUploadInvalid,
+
+    /// blob upload unknown to registry
+    BlobUploadUnknown,
+
+    /// provided digest did not match uploaded content
+    DigestInvalid,
+
+    /// manifest references a manifest or blob unknown to registry
+    ManifestBlobUnknown,
+
+    /// manifest invalid
+    ManifestInvalid,
+
+    /// manifest unknown to registry
+    ManifestUnknown,
+
+    /// invalid repository name
+    NameInvalid,
+
+    /// repository name not known to registry
+    NameUnknown,
+
+    /// provided length did not match content length
+    SizeInvalid,
+
+    /// authentication required
+    Unauthorized,
+
+    /// requested access to the resource is denied
+    Denied,
+
+    /// the operation is unsupported
+    Unsupported,
+
+    /// not part of the specification, but used by most registries when the
+    /// registry itself has failed.
+    Unknown,
+}
+
+impl ErrorCode {
+    /// Returns the [`StatusCode`] that a response should be sent with.
+    pub const fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::BlobUnknown |
+            ErrorCode::BlobUploadUnknown |
+            ErrorCode::ManifestUnknown |
+            ErrorCode::NameUnknown => StatusCode::NOT_FOUND,
+
+            ErrorCode::BlobUploadInvalid |
+            ErrorCode::DigestInvalid |
+            ErrorCode::ManifestBlobUnknown |
+            ErrorCode::ManifestInvalid |
+            ErrorCode::NameInvalid |
+            ErrorCode::SizeInvalid => StatusCode::BAD_REQUEST,
+
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Denied => StatusCode::FORBIDDEN,
+            ErrorCode::Unsupported => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// A single error that is sent in the `errors` array of a response.
+#[derive(Debug, Clone, Serialize)]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: Cow<'static, str>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<Value>,
+
+    /// Overrides the [status code][ErrorCode::status] of [`Error::code`].
+    #[serde(skip)]
+    status: Option<StatusCode>,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl Into<Cow<'static, str>>) -> Error {
+        Error {
+            code,
+            message: message.into(),
+            detail: None,
+            status: None,
+        }
+    }
+
+    /// Attaches detail to this error.
+    pub fn with_detail(mut self, detail: Value) -> Error {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// Overrides the status code that this error is sent with.
+    pub fn with_status(mut self, status: StatusCode) -> Error {
+        self.status = Some(status);
+        self
+    }
+
+    /// Reports `error` and returns a [`ErrorCode::Unknown`] error.
+    pub fn unknown<E: Display>(error: E) -> Error {
+        error!(%error, "oci registry failed to process request");
+        sentry::capture_message(&error.to_string(), sentry::Level::Error);
+
+        Error::new(ErrorCode::Unknown, "registry was unable to process this request")
+    }
+
+    /// Returns the [`StatusCode`] that this error will be sent as.
+    pub fn status(&self) -> StatusCode {
+        self.status.unwrap_or_else(|| self.code.status())
+    }
+}
+
+impl From<eyre::Report> for Error {
+    fn from(report: eyre::Report) -> Self {
+        Error::unknown(report)
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let mut response = (status, Json(json!({ "errors": [self] }))).into_response();
+
+        // Clients like Helm and ORAS will only send credentials after they are
+        // challenged to do so.
+        if status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(
+                WWW_AUTHENTICATE,
+                HeaderValue::from_static(r#"Basic realm="charted-server""#),
+            );
+        }
+
+        response
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
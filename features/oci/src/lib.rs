@@ -0,0 +1,50 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! # 🐻‍❄️📦 `charted-feature-oci`
+//! This crate implements the storage side of the **OCI registry** server feature, which
+//! allows Helm charts to be pushed and pulled with `helm push oci://...` and `helm pull oci://...`
+//! as described in the [OCI Distribution Specification v1.1].
+//!
+//! OCI repositories are mapped one-to-one onto charted repositories, so the name
+//! `noel/hello-world` refers to the `hello-world` repository that is owned by `noel`.
+//!
+//! [OCI Distribution Specification v1.1]: https://github.com/opencontainers/distribution-spec/blob/v1.1.1/spec.md
+
+pub mod digest;
+pub mod error;
+pub mod manifest;
+pub mod reference;
+mod registry;
+
+use charted_feature::Metadata;
+pub use registry::*;
+
+#[derive(Debug, Clone)]
+pub struct Feature;
+impl charted_feature::Feature for Feature {
+    fn metadata(&self) -> Metadata {
+        const METADATA: Metadata = Metadata {
+            name: "OCI Registry",
+            config_key: "oci",
+            description: env!("CARGO_PKG_DESCRIPTION"),
+            authors: &["Noelware, LLC. <team@noelware.org>"],
+            since: "0.1.0",
+            deprecated: None,
+        };
+
+        METADATA
+    }
+}
@@ -0,0 +1,139 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types from the [OCI Image Specification] that the registry needs to understand.
+//!
+//! [OCI Image Specification]: https://github.com/opencontainers/image-spec/blob/v1.1.1/spec.md
+
+use crate::digest::Digest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Media type of an OCI image manifest.
+pub const IMAGE_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+
+/// Media type of an OCI image index.
+pub const IMAGE_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+
+/// Media type of a Helm chart's configuration blob.
+pub const HELM_CONFIG: &str = "application/vnd.cncf.helm.config.v1+json";
+
+/// Media type of a Helm chart's tarball layer.
+pub const HELM_CHART_CONTENT: &str = "application/vnd.cncf.helm.chart.content.v1.tar+gzip";
+
+/// Media type of a Helm chart's provenance layer.
+pub const HELM_CHART_PROVENANCE: &str = "application/vnd.cncf.helm.chart.provenance.v1.prov";
+
+/// Manifest media types that can be pushed to the registry.
+pub const SUPPORTED_MANIFESTS: &[&str] = &[IMAGE_MANIFEST, IMAGE_INDEX];
+
+/// Describes content that is addressed by its [`Digest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    pub media_type: String,
+    pub digest: Digest,
+    pub size: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<BTreeMap<String, String>>,
+}
+
+/// A manifest that was pushed to the registry. Both image manifests and image indexes
+/// are represented by this type as the registry only cares about the content that they
+/// reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub schema_version: u32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+
+    /// Configuration blob of an image manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Descriptor>,
+
+    /// Layers of an image manifest.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Descriptor>,
+
+    /// Manifests that an image index points to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manifests: Vec<Descriptor>,
+
+    /// The manifest that this manifest refers to, used by the referrers API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<Descriptor>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<BTreeMap<String, String>>,
+}
+
+impl Manifest {
+    /// Returns the artifact type of this manifest as described in the
+    /// [referrers API](https://github.com/opencontainers/distribution-spec/blob/v1.1.1/spec.md#listing-referrers):
+    /// the `artifactType` field, or the config's media type otherwise.
+    pub fn effective_artifact_type(&self) -> Option<&str> {
+        self.artifact_type
+            .as_deref()
+            .or_else(|| self.config.as_ref().map(|config| config.media_type.as_str()))
+    }
+
+    /// Returns a [`Descriptor`] that points to this manifest.
+    pub fn descriptor(&self, media_type: &str, digest: Digest, size: u64) -> Descriptor {
+        Descriptor {
+            media_type: media_type.to_owned(),
+            digest,
+            size,
+            artifact_type: self.effective_artifact_type().map(ToOwned::to_owned),
+            annotations: self.annotations.clone(),
+        }
+    }
+
+    /// Returns the digests of all blobs and manifests that this manifest references. The
+    /// `subject` is not included as it is allowed to be pushed after its referrers.
+    pub fn references(&self) -> impl Iterator<Item = &Descriptor> {
+        self.config
+            .iter()
+            .chain(self.layers.iter())
+            .chain(self.manifests.iter())
+    }
+}
+
+/// An image index that is returned by the referrers API.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageIndex {
+    pub schema_version: u32,
+    pub media_type: &'static str,
+    pub manifests: Vec<Descriptor>,
+}
+
+impl ImageIndex {
+    pub fn new(manifests: Vec<Descriptor>) -> ImageIndex {
+        ImageIndex {
+            schema_version: 2,
+            media_type: IMAGE_INDEX,
+            manifests,
+        }
+    }
+}
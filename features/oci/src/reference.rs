@@ -0,0 +1,100 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::digest::{Digest, ParseError};
+use std::{fmt::Display, str::FromStr};
+
+/// Maximum length of a tag, as defined by the distribution specification.
+const MAX_TAG_LENGTH: usize = 128;
+
+/// A reference to a manifest: either a tag or a digest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Reference {
+    Tag(String),
+    Digest(Digest),
+}
+
+/// Error type when parsing a [`Reference`] has failed.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum ReferenceError {
+    #[display("tag must match `[a-zA-Z0-9_][a-zA-Z0-9._-]{{0,127}}`")]
+    InvalidTag,
+
+    #[display("{}", _0)]
+    InvalidDigest(ParseError),
+}
+
+impl std::error::Error for ReferenceError {}
+
+impl FromStr for Reference {
+    type Err = ReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(':') {
+            return s.parse().map(Reference::Digest).map_err(ReferenceError::InvalidDigest);
+        }
+
+        let mut bytes = s.bytes();
+        let valid = s.len() <= MAX_TAG_LENGTH &&
+            bytes.next().is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_') &&
+            bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'-'));
+
+        if !valid {
+            return Err(ReferenceError::InvalidTag);
+        }
+
+        Ok(Reference::Tag(s.to_owned()))
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reference::Tag(tag) => f.write_str(tag),
+            Reference::Digest(digest) => Display::fmt(digest, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("0.1.0".parse(), Ok(Reference::Tag("0.1.0".into())));
+        assert_eq!(
+            "1.0.0-beta.1_build.5".parse(),
+            Ok(Reference::Tag("1.0.0-beta.1_build.5".into()))
+        );
+        assert_eq!("latest".parse(), Ok(Reference::Tag("latest".into())));
+        assert!(matches!(
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".parse(),
+            Ok(Reference::Digest(_))
+        ));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!("".parse::<Reference>(), Err(ReferenceError::InvalidTag));
+        assert_eq!(".hidden".parse::<Reference>(), Err(ReferenceError::InvalidTag));
+        assert_eq!("1.0.0+build".parse::<Reference>(), Err(ReferenceError::InvalidTag));
+        assert_eq!("a".repeat(129).parse::<Reference>(), Err(ReferenceError::InvalidTag));
+        assert!(matches!(
+            "sha256:nope".parse::<Reference>(),
+            Err(ReferenceError::InvalidDigest(_))
+        ));
+    }
+}
@@ -0,0 +1,621 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    digest::Digest,
+    error::{Error, ErrorCode, Result},
+    manifest::{self, Descriptor, Manifest},
+    reference::Reference,
+};
+use axum::http::StatusCode;
+use charted_core::ResultExt;
+use charted_datastore::{
+    DataStore, Namespace,
+    remi::{Blob, Bytes, StorageService, UploadRequest},
+};
+use charted_types::Ulid;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{instrument, warn};
+
+/// Maximum size of a manifest that can be pushed. The distribution specification
+/// recommends that registries accept manifests of at least 4MiB.
+pub const MAX_MANIFEST_SIZE: usize = 4 * 1024 * 1024;
+
+/// Chunks that were uploaded in a blob upload session, in order.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UploadState {
+    /// Size of each chunk.
+    chunks: Vec<u64>,
+}
+
+impl UploadState {
+    fn size(&self) -> u64 {
+        self.chunks.iter().sum()
+    }
+}
+
+/// A manifest that was retrieved from the registry.
+#[derive(Debug, Clone)]
+pub struct StoredManifest {
+    pub digest: Digest,
+    pub media_type: String,
+    pub data: Bytes,
+}
+
+/// Storage for a single OCI repository, which maps to a charted repository. All
+/// content lives in the `repositories/{owner}/{repo}/oci` namespace:
+///
+/// * `blobs/{algorithm}/{encoded}` — content of blobs
+/// * `manifests/{algorithm}/{encoded}` — content of manifests
+/// * `tags/{tag}` — the digest of the manifest that a tag points to
+/// * `uploads/{id}/state.json` — sizes of the chunks of a blob upload that is in progress
+/// * `uploads/{id}/chunks/{index}` — content of a chunk of a blob upload
+/// * `referrers/{algorithm}/{encoded}/{algorithm}-{encoded}` — descriptors of manifests
+///   that have a `subject`, keyed by the subject's digest
+#[derive(Clone, derive_more::Display, derive_more::Deref)]
+#[display("{}", self.namespace)]
+pub struct Registry<'storage> {
+    ds: &'storage DataStore,
+    owner: Ulid,
+    repo: Ulid,
+
+    #[deref]
+    namespace: Namespace<'storage>,
+}
+
+impl<'storage> Registry<'storage> {
+    pub fn new(ds: &'storage DataStore, owner: Ulid, repo: Ulid) -> Self {
+        Registry {
+            owner,
+            repo,
+            namespace: ds.namespace(format!("repositories/{owner}/{repo}/oci")),
+            ds,
+        }
+    }
+
+    //// blobs \\\\
+
+    /// Returns the content of a blob, if it exists.
+    pub async fn blob(&self, digest: &Digest) -> Result<Option<Bytes>> {
+        self.namespace
+            .open(blob_path(digest))
+            .await
+            .into_report()
+            .map_err(Error::from)
+    }
+
+    /// Returns `true` if the blob exists in this repository.
+    pub async fn has_blob(&self, digest: &Digest) -> Result<bool> {
+        self.namespace
+            .exists(blob_path(digest))
+            .await
+            .into_report()
+            .map_err(Error::from)
+    }
+
+    /// Stores `data` as a blob, verifying that it matches `digest`.
+    #[instrument(name = "charted.oci.blobs.put", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, %digest))]
+    pub async fn put_blob(&self, digest: &Digest, data: Bytes) -> Result<()> {
+        if !digest.verify(&data) {
+            return Err(Error::new(
+                ErrorCode::DigestInvalid,
+                "provided digest did not match uploaded content",
+            )
+            .with_detail(json!({
+                "expected": digest,
+                "received": Digest::compute(digest.algorithm(), &data),
+            })));
+        }
+
+        let request = UploadRequest::default()
+            .with_data(data)
+            .with_content_type(Some("application/octet-stream"));
+
+        self.namespace
+            .upload(blob_path(digest), request)
+            .await
+            .into_report()
+            .map_err(Error::from)
+    }
+
+    /// Deletes a blob. Returns `false` if it didn't exist.
+    #[instrument(name = "charted.oci.blobs.delete", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, %digest))]
+    pub async fn delete_blob(&self, digest: &Digest) -> Result<bool> {
+        if !self.has_blob(digest).await? {
+            return Ok(false);
+        }
+
+        self.namespace.delete(blob_path(digest)).await.into_report()?;
+        Ok(true)
+    }
+
+    //// uploads \\\\
+
+    /// Starts a new blob upload session.
+    pub async fn create_upload(&self, id: &str) -> Result<()> {
+        self.write_upload_state(id, &UploadState::default()).await
+    }
+
+    /// Returns how many bytes were uploaded in an upload session, or [`None`] if the
+    /// session doesn't exist.
+    pub async fn upload_size(&self, id: &str) -> Result<Option<u64>> {
+        Ok(self.read_upload_state(id).await?.map(|state| state.size()))
+    }
+
+    /// Appends `chunk` to an upload session and returns the new size of the upload.
+    ///
+    /// Each chunk is stored on its own, so appending a chunk never reads or rewrites
+    /// the chunks that were uploaded before it.
+    ///
+    /// If `offset` is specified (from the `Content-Range` header), then it must be the
+    /// current size of the upload as chunks must be uploaded in order.
+    #[instrument(name = "charted.oci.uploads.append", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, upload.id = id))]
+    pub async fn append_upload(&self, id: &str, offset: Option<u64>, chunk: Bytes) -> Result<u64> {
+        let Some(mut state) = self.read_upload_state(id).await? else {
+            return Err(upload_unknown(id));
+        };
+
+        if let Some(offset) = offset &&
+            offset != state.size()
+        {
+            return Err(
+                Error::new(ErrorCode::BlobUploadInvalid, "chunk was uploaded out of order")
+                    .with_status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .with_detail(json!({
+                        "expected": state.size(),
+                        "received": offset,
+                    })),
+            );
+        }
+
+        if chunk.is_empty() {
+            return Ok(state.size());
+        }
+
+        let request = UploadRequest::default()
+            .with_data(chunk.clone())
+            .with_content_type(Some("application/octet-stream"));
+
+        self.namespace
+            .upload(upload_chunk_path(id, state.chunks.len()), request)
+            .await
+            .into_report()?;
+
+        state.chunks.push(chunk.len() as u64);
+        self.write_upload_state(id, &state).await?;
+
+        Ok(state.size())
+    }
+
+    /// Finishes an upload session by appending the last `chunk` and moving the content
+    /// into `blobs/` if it matches `digest`.
+    ///
+    /// This is the only time that the chunks of an upload are read back, which is once.
+    #[instrument(name = "charted.oci.uploads.complete", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, upload.id = id, %digest))]
+    pub async fn complete_upload(&self, id: &str, digest: &Digest, chunk: Bytes) -> Result<()> {
+        let Some(state) = self.read_upload_state(id).await? else {
+            return Err(upload_unknown(id));
+        };
+
+        let mut buf = Vec::with_capacity((state.size() as usize).saturating_add(chunk.len()));
+        for (index, len) in state.chunks.iter().enumerate() {
+            let Some(data) = self.namespace.open(upload_chunk_path(id, index)).await.into_report()? else {
+                return Err(Error::unknown(format!("chunk #{index} of upload {id} is missing")));
+            };
+
+            if data.len() as u64 != *len {
+                return Err(Error::unknown(format!(
+                    "chunk #{index} of upload {id} has {} bytes, expected {len}",
+                    data.len()
+                )));
+            }
+
+            buf.extend_from_slice(&data);
+        }
+
+        buf.extend_from_slice(&chunk);
+
+        self.put_blob(digest, Bytes::from(buf)).await?;
+        self.delete_upload(id, &state).await
+    }
+
+    /// Cancels an upload session. Returns `false` if it didn't exist.
+    pub async fn cancel_upload(&self, id: &str) -> Result<bool> {
+        let Some(state) = self.read_upload_state(id).await? else {
+            return Ok(false);
+        };
+
+        self.delete_upload(id, &state).await?;
+        Ok(true)
+    }
+
+    async fn read_upload_state(&self, id: &str) -> Result<Option<UploadState>> {
+        let Some(data) = self.namespace.open(upload_state_path(id)).await.into_report()? else {
+            return Ok(None);
+        };
+
+        serde_json::from_slice(&data).map(Some).map_err(Error::unknown)
+    }
+
+    async fn write_upload_state(&self, id: &str, state: &UploadState) -> Result<()> {
+        let request = UploadRequest::default()
+            .with_data(serde_json::to_vec(state).map_err(Error::unknown)?)
+            .with_content_type(Some("application/json"));
+
+        self.namespace
+            .upload(upload_state_path(id), request)
+            .await
+            .into_report()
+            .map_err(Error::from)
+    }
+
+    async fn delete_upload(&self, id: &str, state: &UploadState) -> Result<()> {
+        for index in 0..state.chunks.len() {
+            self.namespace
+                .delete(upload_chunk_path(id, index))
+                .await
+                .into_report()?;
+        }
+
+        self.namespace
+            .delete(upload_state_path(id))
+            .await
+            .into_report()
+            .map_err(Error::from)
+    }
+
+    //// manifests \\\\
+
+    /// Resolves a [`Reference`] into the digest of the manifest that it points to.
+    pub async fn resolve(&self, reference: &Reference) -> Result<Option<Digest>> {
+        match reference {
+            Reference::Digest(digest) => Ok(Some(digest.clone())),
+            Reference::Tag(tag) => {
+                let Some(data) = self.namespace.open(tag_path(tag)).await.into_report()? else {
+                    return Ok(None);
+                };
+
+                match std::str::from_utf8(&data).map(str::parse::<Digest>) {
+                    Ok(Ok(digest)) => Ok(Some(digest)),
+                    _ => {
+                        warn!(owner.id = %self.owner, repository.id = %self.repo, %tag, "tag doesn't point to a valid digest; possibly tampered with or malformed");
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a manifest by its tag or digest.
+    pub async fn manifest(&self, reference: &Reference) -> Result<Option<StoredManifest>> {
+        let Some(digest) = self.resolve(reference).await? else {
+            return Ok(None);
+        };
+
+        let Some(data) = self.namespace.open(manifest_path(&digest)).await.into_report()? else {
+            return Ok(None);
+        };
+
+        // manifests are validated before they are stored, so the media type will always
+        // be either from the manifest itself or an image manifest.
+        let media_type = serde_json::from_slice::<Manifest>(&data)
+            .ok()
+            .and_then(|manifest| manifest.media_type)
+            .unwrap_or_else(|| manifest::IMAGE_MANIFEST.to_owned());
+
+        Ok(Some(StoredManifest {
+            digest,
+            media_type,
+            data,
+        }))
+    }
+
+    /// Validates and stores a manifest. If `reference` is a tag, then the tag will point
+    /// to the new manifest.
+    ///
+    /// Returns the manifest's digest and the digest of its `subject`, if it has one.
+    #[instrument(name = "charted.oci.manifests.put", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, %reference))]
+    pub async fn put_manifest(
+        &self,
+        reference: &Reference,
+        content_type: &str,
+        data: Bytes,
+    ) -> Result<(Digest, Option<Digest>)> {
+        if data.len() > MAX_MANIFEST_SIZE {
+            return Err(Error::new(ErrorCode::ManifestInvalid, "manifest is too large")
+                .with_status(StatusCode::PAYLOAD_TOO_LARGE)
+                .with_detail(json!({"max": MAX_MANIFEST_SIZE})));
+        }
+
+        let content_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !manifest::SUPPORTED_MANIFESTS.contains(&content_type) {
+            return Err(
+                Error::new(ErrorCode::ManifestInvalid, "unsupported manifest media type").with_detail(json!({
+                    "mediaType": content_type,
+                    "supported": manifest::SUPPORTED_MANIFESTS,
+                })),
+            );
+        }
+
+        let manifest: Manifest = serde_json::from_slice(&data).map_err(|e| {
+            Error::new(ErrorCode::ManifestInvalid, "manifest is not valid JSON").with_detail(json!({
+                "error": e.to_string(),
+            }))
+        })?;
+
+        if manifest.schema_version != 2 {
+            return Err(
+                Error::new(ErrorCode::ManifestInvalid, "`schemaVersion` must be 2").with_detail(json!({
+                    "received": manifest.schema_version,
+                })),
+            );
+        }
+
+        if let Some(media_type) = manifest.media_type.as_deref() &&
+            media_type != content_type
+        {
+            return Err(Error::new(
+                ErrorCode::ManifestInvalid,
+                "`mediaType` of manifest doesn't match the `Content-Type` header",
+            )
+            .with_detail(json!({
+                "mediaType": media_type,
+                "contentType": content_type,
+            })));
+        }
+
+        for descriptor in manifest.references() {
+            let exists = match content_type {
+                manifest::IMAGE_INDEX => self.namespace.exists(manifest_path(&descriptor.digest)).await,
+                _ => self.namespace.exists(blob_path(&descriptor.digest)).await,
+            }
+            .into_report()?;
+
+            if !exists {
+                return Err(Error::new(
+                    ErrorCode::ManifestBlobUnknown,
+                    "manifest references a manifest or blob unknown to registry",
+                )
+                .with_detail(json!({"digest": descriptor.digest})));
+            }
+        }
+
+        let digest = match reference {
+            Reference::Digest(digest) if !digest.verify(&data) => {
+                return Err(Error::new(
+                    ErrorCode::DigestInvalid,
+                    "provided digest did not match the manifest's content",
+                )
+                .with_detail(json!({
+                    "expected": digest,
+                    "received": Digest::compute(digest.algorithm(), &data),
+                })));
+            }
+
+            Reference::Digest(digest) => digest.clone(),
+            Reference::Tag(_) => Digest::sha256(&data),
+        };
+
+        let descriptor = manifest.descriptor(content_type, digest.clone(), data.len() as u64);
+        self.namespace
+            .upload(
+                manifest_path(&digest),
+                UploadRequest::default()
+                    .with_data(data)
+                    .with_content_type(Some(content_type)),
+            )
+            .await
+            .into_report()?;
+
+        if let Reference::Tag(tag) = reference {
+            self.namespace
+                .upload(
+                    tag_path(tag),
+                    UploadRequest::default()
+                        .with_data(digest.to_string())
+                        .with_content_type(Some("text/plain; charset=utf-8")),
+                )
+                .await
+                .into_report()?;
+        }
+
+        let subject = manifest.subject.map(|subject| subject.digest);
+        if let Some(ref subject) = subject {
+            self.namespace
+                .upload(
+                    referrer_path(subject, &digest),
+                    UploadRequest::default()
+                        .with_data(serde_json::to_vec(&descriptor).map_err(Error::unknown)?)
+                        .with_content_type(Some("application/json")),
+                )
+                .await
+                .into_report()?;
+        }
+
+        Ok((digest, subject))
+    }
+
+    /// Pushes a Helm chart as a manifest with its `config` and `chart` tarball as blobs,
+    /// and points `tag` to it. This is how releases that weren't pushed to the registry
+    /// can still be pulled from it.
+    #[instrument(name = "charted.oci.charts.put", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, %tag))]
+    pub async fn put_chart(&self, tag: &str, config: Bytes, chart: Bytes) -> Result<Digest> {
+        let descriptor = |media_type: &str, data: &Bytes| Descriptor {
+            media_type: media_type.to_owned(),
+            digest: Digest::sha256(data),
+            size: data.len() as u64,
+            artifact_type: None,
+            annotations: None,
+        };
+
+        let manifest = Manifest {
+            schema_version: 2,
+            media_type: Some(manifest::IMAGE_MANIFEST.to_owned()),
+            artifact_type: None,
+            config: Some(descriptor(manifest::HELM_CONFIG, &config)),
+            layers: vec![descriptor(manifest::HELM_CHART_CONTENT, &chart)],
+            manifests: Vec::new(),
+            subject: None,
+            annotations: None,
+        };
+
+        self.put_blob(&Digest::sha256(&config), config).await?;
+        self.put_blob(&Digest::sha256(&chart), chart).await?;
+
+        let data = serde_json::to_vec(&manifest).map_err(Error::unknown)?;
+        self.put_manifest(
+            &Reference::Tag(tag.to_owned()),
+            manifest::IMAGE_MANIFEST,
+            Bytes::from(data),
+        )
+        .await
+        .map(|(digest, _)| digest)
+    }
+
+    /// Deletes a manifest. If `reference` is a tag, only the tag is removed; otherwise, the
+    /// manifest and every tag that points to it is removed. Returns the tags that were
+    /// removed, or [`None`] if the manifest or tag didn't exist.
+    #[instrument(name = "charted.oci.manifests.delete", skip_all, fields(owner.id = %self.owner, repository.id = %self.repo, %reference))]
+    pub async fn delete_manifest(&self, reference: &Reference) -> Result<Option<Vec<String>>> {
+        let digest = match reference {
+            Reference::Tag(tag) => {
+                if !self.namespace.exists(tag_path(tag)).await.into_report()? {
+                    return Ok(None);
+                }
+
+                self.namespace.delete(tag_path(tag)).await.into_report()?;
+                return Ok(Some(vec![tag.clone()]));
+            }
+
+            Reference::Digest(digest) => digest,
+        };
+
+        let Some(manifest) = self.manifest(reference).await? else {
+            return Ok(None);
+        };
+
+        let mut removed = Vec::new();
+        for tag in self.tags().await? {
+            if self.resolve(&Reference::Tag(tag.clone())).await?.as_ref() == Some(digest) {
+                self.namespace.delete(tag_path(&tag)).await.into_report()?;
+                removed.push(tag);
+            }
+        }
+
+        if let Some(subject) = serde_json::from_slice::<Manifest>(&manifest.data)
+            .ok()
+            .and_then(|manifest| manifest.subject)
+        {
+            self.namespace
+                .delete(referrer_path(&subject.digest, digest))
+                .await
+                .into_report()?;
+        }
+
+        self.namespace.delete(manifest_path(digest)).await.into_report()?;
+        Ok(Some(removed))
+    }
+
+    /// Returns all tags in this repository, in lexical order.
+    pub async fn tags(&self) -> Result<Vec<String>> {
+        let mut tags = self.list("tags").await?;
+        tags.sort();
+
+        Ok(tags)
+    }
+
+    /// Returns descriptors of all manifests that have `digest` as their subject. If
+    /// `artifact_type` is specified, only manifests with that artifact type are returned.
+    pub async fn referrers(&self, digest: &Digest, artifact_type: Option<&str>) -> Result<Vec<Descriptor>> {
+        let prefix = format!("referrers/{}/{}", digest.algorithm(), digest.encoded());
+
+        let mut descriptors = Vec::new();
+        for name in self.list(&prefix).await? {
+            let Some(data) = self.namespace.open(format!("{prefix}/{name}")).await.into_report()? else {
+                continue;
+            };
+
+            let descriptor = match serde_json::from_slice::<Descriptor>(&data) {
+                Ok(descriptor) => descriptor,
+                Err(e) => {
+                    warn!(owner.id = %self.owner, repository.id = %self.repo, referrer = %name, error = %e, "referrer is malformed; possibly tampered with");
+                    continue;
+                }
+            };
+
+            if artifact_type.is_none_or(|ty| descriptor.artifact_type.as_deref() == Some(ty)) {
+                descriptors.push(descriptor);
+            }
+        }
+
+        descriptors.sort_by(|a, b| a.digest.cmp(&b.digest));
+        Ok(descriptors)
+    }
+
+    /// Lists the names of all files in a directory of this namespace.
+    async fn list(&self, dir: &str) -> Result<Vec<String>> {
+        // Only use '#exists(Path) -> bool' on the filesystem datastore as listing a
+        // directory that doesn't exist will fail.
+        if self.ds.is_filesystem() && !self.namespace.exists(dir).await.into_report()? {
+            return Ok(vec![]);
+        }
+
+        let blobs = self.namespace.blobs(Some(dir), None).await.into_report()?;
+        Ok(blobs
+            .into_iter()
+            .filter_map(|blob| match blob {
+                // in the S3 driver, file names are returned as their full path, so only
+                // the last segment is used.
+                Blob::File(file) => file.name.rsplit('/').next().map(ToOwned::to_owned),
+                Blob::Directory(_) => None,
+            })
+            .collect())
+    }
+}
+
+fn blob_path(digest: &Digest) -> String {
+    format!("blobs/{}/{}", digest.algorithm(), digest.encoded())
+}
+
+fn manifest_path(digest: &Digest) -> String {
+    format!("manifests/{}/{}", digest.algorithm(), digest.encoded())
+}
+
+fn tag_path(tag: &str) -> String {
+    format!("tags/{tag}")
+}
+
+fn upload_state_path(id: &str) -> String {
+    format!("uploads/{id}/state.json")
+}
+
+fn upload_chunk_path(id: &str, index: usize) -> String {
+    format!("uploads/{id}/chunks/{index}")
+}
+
+fn referrer_path(subject: &Digest, digest: &Digest) -> String {
+    format!(
+        "referrers/{}/{}/{}-{}",
+        subject.algorithm(),
+        subject.encoded(),
+        digest.algorithm(),
+        digest.encoded()
+    )
+}
+
+fn upload_unknown(id: &str) -> Error {
+    Error::new(ErrorCode::BlobUploadUnknown, "blob upload unknown to registry").with_detail(json!({"id": id}))
+}
@@ -0,0 +1,54 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! API resources of the TOTP feature.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Returned when a user starts enrolling into two-factor authentication.
+///
+/// The secret and recovery codes are only ever returned here, the user should store
+/// the recovery codes somewhere safe before verifying the enrollment.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TotpEnrollment {
+    /// `otpauth://` URI that authenticator apps can enroll with.
+    pub uri: String,
+
+    /// The base32-encoded secret, for authenticator apps that can't scan QR codes.
+    pub secret: String,
+
+    /// SVG document of a QR code that contains `uri`.
+    pub qr_code: String,
+
+    /// Single-use codes that can be used instead of a code from the authenticator app.
+    pub recovery_codes: Vec<String>,
+}
+
+/// Whether if a user has two-factor authentication enabled.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TotpStatus {
+    /// whether if a code is required when logging in.
+    pub enabled: bool,
+
+    /// amount of recovery codes that haven't been used yet.
+    pub recovery_codes: usize,
+}
+
+/// Payload object that contains a code from an authenticator app, or a recovery code.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TotpCodePayload {
+    pub code: String,
+}
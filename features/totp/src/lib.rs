@@ -12,3 +12,58 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+//! # 🐻‍❄️📦 `charted-feature-totp`
+//! This crate implements the **TOTP** server feature, which allows local users to require
+//! a time-based one-time password from an authenticator app when they log in.
+//!
+//! Enrollment happens in two steps: the server generates a secret and a set of recovery
+//! codes, and only enables two-factor authentication once the user verified that their
+//! authenticator app generates valid codes.
+
+pub mod qr;
+pub mod recovery;
+
+mod enrollment;
+mod totp;
+
+use charted_config::features::totp::Config;
+use charted_feature::Metadata;
+pub use enrollment::*;
+pub use totp::*;
+
+#[derive(Debug, Clone)]
+pub struct Feature {
+    config: Config,
+}
+
+impl Feature {
+    pub fn new(config: Config) -> Feature {
+        Feature { config }
+    }
+
+    /// Returns the `[features.totp]` configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Creates a [`Totp`] from a stored base32 secret with the configured skew.
+    pub fn totp(&self, secret: &str) -> Result<Totp, data_encoding::DecodeError> {
+        Totp::from_base32(secret, self.config.skew)
+    }
+}
+
+impl charted_feature::Feature for Feature {
+    fn metadata(&self) -> Metadata {
+        const METADATA: Metadata = Metadata {
+            name: "TOTP",
+            config_key: "totp",
+            description: env!("CARGO_PKG_DESCRIPTION"),
+            authors: &["Noelware, LLC. <team@noelware.org>"],
+            since: "0.1.0",
+            deprecated: None,
+        };
+
+        METADATA
+    }
+}
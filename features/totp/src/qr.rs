@@ -0,0 +1,40 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Renders QR codes for the `otpauth://` URIs so that they can be scanned by an
+//! authenticator app.
+
+pub use qrcode::types::QrError;
+use qrcode::{EcLevel, QrCode, render::svg};
+
+/// Renders `data` as a QR code in a SVG document.
+pub fn svg(data: &str) -> Result<String, QrError> {
+    QrCode::with_error_correction_level(data, EcLevel::M).map(|code| {
+        code.render::<svg::Color<'_>>()
+            .min_dimensions(200, 200)
+            .quiet_zone(true)
+            .build()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn renders_svg() {
+        let svg =
+            super::svg("otpauth://totp/charted-server:noel?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+        assert!(svg.contains("<svg"));
+    }
+}
@@ -0,0 +1,90 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recovery codes let a user log in once without their authenticator app. Only their
+//! SHA-256 hashes are stored, and a code is removed once it was used.
+
+use data_encoding::HEXLOWER;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// Characters that recovery codes are made of. Ambiguous ones like `0`/`o` and `1`/`l`
+/// are left out so that codes can be read back from paper.
+const ALPHABET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+
+/// Generates `count` recovery codes in the form of `xxxxx-xxxxx`.
+pub fn generate(count: usize) -> Vec<String> {
+    let mut rng = rand::rng();
+    (0..count)
+        .map(|_| {
+            let mut code = String::with_capacity(11);
+            for i in 0..10 {
+                if i == 5 {
+                    code.push('-');
+                }
+
+                code.push(ALPHABET[rng.random_range(0..ALPHABET.len())] as char);
+            }
+
+            code
+        })
+        .collect()
+}
+
+/// Hashes a recovery code. Casing, whitespace and dashes are ignored.
+pub fn hash(code: &str) -> String {
+    let normalized = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+
+    HEXLOWER.encode(&Sha256::digest(normalized.as_bytes()))
+}
+
+/// Returns the position of `code` in a list of hashes that were created by [`hash`].
+pub fn position(hashes: &[String], code: &str) -> Option<usize> {
+    let hashed = hash(code);
+    hashes
+        .iter()
+        .position(|candidate| crate::totp::constant_time_eq(candidate.as_bytes(), hashed.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_codes() {
+        let codes = generate(10);
+        assert_eq!(codes.len(), 10);
+
+        for code in &codes {
+            assert_eq!(code.len(), 11);
+            assert_eq!(code.as_bytes()[5], b'-');
+            assert!(code.bytes().filter(|b| *b != b'-').all(|b| ALPHABET.contains(&b)));
+        }
+    }
+
+    #[test]
+    fn position_ignores_formatting() {
+        let codes = generate(3);
+        let hashes = codes.iter().map(|code| hash(code)).collect::<Vec<_>>();
+
+        assert_eq!(position(&hashes, &codes[1]), Some(1));
+        assert_eq!(position(&hashes, &codes[2].to_uppercase().replace('-', " ")), Some(2));
+        assert_eq!(position(&hashes, "aaaaa-aaaaa"), None);
+    }
+}
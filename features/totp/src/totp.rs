@@ -0,0 +1,191 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Time-based one-time passwords as described in [RFC 6238], using the parameters that
+//! every authenticator app supports: HMAC-SHA1, 6 digits and a 30 second time step.
+//!
+//! [RFC 6238]: https://datatracker.ietf.org/doc/html/rfc6238
+
+use data_encoding::{BASE32_NOPAD, DecodeError};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+use url::Url;
+
+/// Length of a time step in seconds.
+pub const STEP: u64 = 30;
+
+/// Amount of digits in a code.
+pub const DIGITS: u32 = 6;
+
+/// A shared secret that generates and verifies codes.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Totp {
+    secret: Vec<u8>,
+    skew: u8,
+}
+
+impl Totp {
+    /// Creates a [`Totp`] from a raw secret. Codes from `skew` time steps before and after
+    /// the current one are accepted by [`Totp::verify`].
+    pub fn new(secret: impl Into<Vec<u8>>, skew: u8) -> Totp {
+        Totp {
+            secret: secret.into(),
+            skew,
+        }
+    }
+
+    /// Generates a new 160-bit secret, the size that RFC 4226 recommends.
+    pub fn generate(skew: u8) -> Totp {
+        let mut secret = vec![0u8; 20];
+        rand::rng().fill_bytes(&mut secret);
+
+        Totp::new(secret, skew)
+    }
+
+    /// Parses a base32-encoded secret, the encoding that authenticator apps use.
+    pub fn from_base32(secret: &str, skew: u8) -> Result<Totp, DecodeError> {
+        BASE32_NOPAD
+            .decode(secret.trim_end_matches('=').as_bytes())
+            .map(|secret| Totp::new(secret, skew))
+    }
+
+    /// Returns the secret encoded as base32 without padding.
+    pub fn to_base32(&self) -> String {
+        BASE32_NOPAD.encode(&self.secret)
+    }
+
+    /// Returns the time step that `timestamp`, in seconds since the Unix epoch, is in.
+    pub const fn step(timestamp: u64) -> u64 {
+        timestamp / STEP
+    }
+
+    /// Generates the code for a time step.
+    pub fn code(&self, step: u64) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(&step.to_be_bytes());
+
+        let hash = mac.finalize().into_bytes();
+        let offset = (hash[hash.len() - 1] & 0xf) as usize;
+        let binary =
+            u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+
+        format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize)
+    }
+
+    /// Verifies a code at `timestamp` (in seconds since the Unix epoch) and returns the
+    /// time step that it was generated for.
+    ///
+    /// Callers should remember the returned step and refuse codes for that step or any
+    /// earlier one afterwards, so that an intercepted code can't be used again.
+    pub fn verify(&self, code: &str, timestamp: u64) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let current = Totp::step(timestamp);
+        let skew = u64::from(self.skew);
+
+        (current.saturating_sub(skew)..=current.saturating_add(skew))
+            .find(|&step| constant_time_eq(self.code(step).as_bytes(), code.as_bytes()))
+    }
+
+    /// Returns the `otpauth://` URI that authenticator apps enroll with, as described
+    /// in the [Key Uri Format].
+    ///
+    /// [Key Uri Format]: https://github.com/google/google-authenticator/wiki/Key-Uri-Format
+    pub fn uri(&self, issuer: &str, account: &str) -> Url {
+        let mut uri = Url::parse("otpauth://totp").expect("valid url");
+        uri.path_segments_mut()
+            .expect("url to have a host")
+            .push(&format!("{issuer}:{account}"));
+
+        uri.query_pairs_mut()
+            .append_pair("secret", &self.to_base32())
+            .append_pair("issuer", issuer)
+            .append_pair("algorithm", "SHA1")
+            .append_pair("digits", &DIGITS.to_string())
+            .append_pair("period", &STEP.to_string());
+
+        uri
+    }
+}
+
+// secrets shouldn't end up in logs
+impl std::fmt::Debug for Totp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Totp")
+            .field("secret", &"<redacted>")
+            .field("skew", &self.skew)
+            .finish()
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vectors from RFC 6238 Appendix B (SHA-1), truncated to 6 digits
+    const SECRET: &[u8] = b"12345678901234567890";
+    const VECTORS: &[(u64, &str)] = &[
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130"),
+    ];
+
+    #[test]
+    fn rfc6238_vectors() {
+        let totp = Totp::new(SECRET, 0);
+        for (timestamp, code) in VECTORS {
+            assert_eq!(totp.code(Totp::step(*timestamp)), *code, "timestamp {timestamp}");
+            assert_eq!(totp.verify(code, *timestamp), Some(Totp::step(*timestamp)));
+        }
+    }
+
+    #[test]
+    fn verify_with_skew() {
+        let totp = Totp::new(SECRET, 1);
+        let previous = totp.code(Totp::step(1111111109) - 1);
+
+        assert_eq!(totp.verify(&previous, 1111111109), Some(Totp::step(1111111109) - 1));
+        assert_eq!(Totp::new(SECRET, 0).verify(&previous, 1111111109), None);
+        assert_eq!(totp.verify("12345", 1111111109), None);
+        assert_eq!(totp.verify("abcdef", 1111111109), None);
+    }
+
+    #[test]
+    fn base32_roundtrip() {
+        let totp = Totp::generate(1);
+        assert_eq!(Totp::from_base32(&totp.to_base32(), 1).unwrap(), totp);
+        assert_eq!(Totp::new(SECRET, 0).to_base32(), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    }
+
+    #[test]
+    fn uri() {
+        let totp = Totp::new(SECRET, 0);
+        assert_eq!(
+            totp.uri("charted-server", "noel").as_str(),
+            "otpauth://totp/charted-server:noel?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=charted-server&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Checks that webhook URLs don't point to addresses that are only reachable from
+//! inside of charted-server's network, like loopback or private addresses.
+
+use charted_config::features::webhooks::Config;
+use std::{
+    fmt::{self, Display},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use url::{Host, Url};
+
+/// Reason why a webhook URL was refused by [`check`].
+#[derive(Debug)]
+pub enum Error {
+    /// The URL doesn't have a host.
+    NoHost,
+
+    /// The host couldn't be resolved.
+    Resolve(io::Error),
+
+    /// The host resolved to a loopback, private, or link-local address.
+    Forbidden(IpAddr),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoHost => f.write_str("url doesn't have a host"),
+            Error::Resolve(e) => write!(f, "failed to resolve host: {e}"),
+            Error::Forbidden(ip) => write!(f, "host resolves to non-public address {ip}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Resolve(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves the host of `url` and refuses it if any of its addresses isn't publicly
+/// routable, unless the host is in the `allowed_hosts` of the webhooks configuration.
+pub async fn check(config: &Config, url: &Url) -> Result<(), Error> {
+    let host = url.host().ok_or(Error::NoHost)?;
+    let name = match host {
+        Host::Domain(domain) => domain.to_owned(),
+        Host::Ipv4(ip) => ip.to_string(),
+        Host::Ipv6(ip) => ip.to_string(),
+    };
+
+    if config
+        .allowed_hosts
+        .iter()
+        .any(|allowed| allowed.trim_matches(['[', ']']).eq_ignore_ascii_case(&name))
+    {
+        return Ok(());
+    }
+
+    let addrs: Vec<IpAddr> = match host {
+        Host::Ipv4(ip) => vec![IpAddr::V4(ip)],
+        Host::Ipv6(ip) => vec![IpAddr::V6(ip)],
+        Host::Domain(domain) => {
+            let port = url.port_or_known_default().unwrap_or(80);
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(Error::Resolve)?
+                .map(|addr| addr.ip())
+                .collect()
+        }
+    };
+
+    if addrs.is_empty() {
+        return Err(Error::Resolve(io::Error::new(
+            io::ErrorKind::NotFound,
+            "host didn't resolve to any address",
+        )));
+    }
+
+    match addrs.into_iter().find(|ip| !is_public(ip)) {
+        Some(ip) => Err(Error::Forbidden(ip)),
+        None => Ok(()),
+    }
+}
+
+/// Returns `true` if `ip` is a publicly routable unicast address.
+pub fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(&ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 0.0.0.0/8: "this network"
+        || a == 0
+        // 100.64.0.0/10: carrier-grade NAT
+        || (a == 100 && (b & 0xc0) == 64)
+        // 198.18.0.0/15: benchmarking
+        || (a == 198 && (b & 0xfe) == 18)
+        // 240.0.0.0/4: reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // 2001:db8::/32: documentation
+        || (ip.segments()[0] == 0x2001 && ip.segments()[1] == 0xdb8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(allowed_hosts: &[&str]) -> Config {
+        Config {
+            allowed_hosts: allowed_hosts.iter().map(|host| host.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn public_addresses() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public(&ip.parse().unwrap()), "{ip} should be public");
+        }
+
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(&ip.parse().unwrap()), "{ip} shouldn't be public");
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_non_public_hosts() {
+        for url in [
+            "http://127.0.0.1:3651/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/",
+            "http://localhost/",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(
+                matches!(check(&config(&[]), &url).await, Err(Error::Forbidden(_))),
+                "{url} should be refused"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn allowed_hosts() {
+        let config = config(&["127.0.0.1", "[::1]", "LOCALHOST"]);
+        for url in ["http://127.0.0.1:3651/", "http://[::1]/", "http://localhost/"] {
+            check(&config, &Url::parse(url).unwrap()).await.unwrap();
+        }
+
+        assert!(matches!(
+            check(&config, &Url::parse("http://10.0.0.1/").unwrap()).await,
+            Err(Error::Forbidden(_))
+        ));
+    }
+}
@@ -0,0 +1,467 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    destination,
+    event::{Event, EventKind},
+    signature::Secret,
+};
+use charted_config::features::webhooks::Config;
+use charted_core::ulid::Generator;
+use charted_database::entities::{WebhookDeliveryEntity, WebhookEntity, webhook};
+use charted_types::Ulid;
+use chrono::Utc;
+use reqwest::{StatusCode, header};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter};
+use std::{sync::Arc, time::Duration};
+use tracing::{error, instrument, warn};
+
+/// Header that holds the unique identifier of a message. It is the same across retries.
+pub const WEBHOOK_ID: &str = "webhook-id";
+
+/// Header that holds when a attempt was made, as a Unix timestamp in seconds.
+pub const WEBHOOK_TIMESTAMP: &str = "webhook-timestamp";
+
+/// Header that holds the signature of the payload.
+pub const WEBHOOK_SIGNATURE: &str = "webhook-signature";
+
+/// What a [`Event`] happened on, which determines the webhooks that receive it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    /// The user or organization that owns the resource.
+    pub owner: Ulid,
+
+    /// The repository that the event happened in, if any. Events without a repository,
+    /// like organization membership changes, are only delivered to owner webhooks.
+    pub repository: Option<Ulid>,
+}
+
+/// Delivers events to the webhooks that are subscribed to them.
+#[derive(Debug, Clone)]
+pub struct Dispatcher {
+    config: Config,
+    http: reqwest::Client,
+    ulid: Generator,
+    db: DatabaseConnection,
+}
+
+impl Dispatcher {
+    pub fn new(config: Config, db: DatabaseConnection, http: reqwest::Client) -> Dispatcher {
+        Dispatcher {
+            config,
+            http,
+            ulid: Generator::new(),
+            db,
+        }
+    }
+
+    /// Delivers `event` to every enabled webhook of `target` that is subscribed to it.
+    ///
+    /// Deliveries happen in the background, so this only fails if the webhooks couldn't
+    /// be queried.
+    #[instrument(name = "charted.webhooks.dispatch", skip_all, fields(%event.kind, owner = %target.owner))]
+    pub async fn dispatch(&self, target: Target, event: Event) -> eyre::Result<()> {
+        let webhooks = self.subscribers(target, event.kind).await?;
+        if webhooks.is_empty() {
+            return Ok(());
+        }
+
+        let payload: Arc<[u8]> = serde_json::to_vec(&event)?.into();
+        for webhook in webhooks {
+            let dispatcher = self.clone();
+            let payload = payload.clone();
+
+            tokio::spawn(async move { dispatcher.deliver(webhook, event.kind, payload).await });
+        }
+
+        Ok(())
+    }
+
+    /// Returns all enabled webhooks of `target` that are subscribed to `kind`.
+    pub async fn subscribers(&self, target: Target, kind: EventKind) -> eyre::Result<Vec<webhook::Model>> {
+        let mut repository = Condition::any().add(webhook::Column::Repository.is_null());
+        if let Some(id) = target.repository {
+            repository = repository.add(webhook::Column::Repository.eq(id));
+        }
+
+        let webhooks = WebhookEntity::find()
+            .filter(webhook::Column::Owner.eq(target.owner))
+            .filter(webhook::Column::Enabled.eq(true))
+            .filter(repository)
+            .all(&self.db)
+            .await?;
+
+        Ok(webhooks
+            .into_iter()
+            .filter(|webhook| {
+                serde_json::from_value::<Vec<EventKind>>(webhook.events.clone())
+                    .is_ok_and(|events| events.contains(&kind))
+            })
+            .collect())
+    }
+
+    /// Delivers a payload to a webhook, retrying with exponential backoff until the
+    /// endpoint accepts it or `max_attempts` is reached. Every attempt is recorded.
+    #[instrument(name = "charted.webhooks.deliver", skip_all, fields(webhook.id = %webhook.id, %kind))]
+    pub async fn deliver(&self, webhook: webhook::Model, kind: EventKind, payload: Arc<[u8]>) {
+        let secret = match Secret::parse(&webhook.secret) {
+            Ok(secret) => secret,
+            Err(e) => {
+                error!(error = %e, "webhook has a malformed secret, not delivering event");
+                return;
+            }
+        };
+
+        let message: Ulid = match self.ulid.generate() {
+            Ok(id) => id.into(),
+            Err(e) => {
+                error!(error = %e, "failed to generate message id");
+                return;
+            }
+        };
+
+        for attempt in 1..=self.config.max_attempts {
+            let (status, error) = match self.send(&webhook.url, &secret, message, &payload).await {
+                Ok(status) if status.is_success() => (Some(status), None),
+                Ok(status) => (Some(status), Some(format!("endpoint responded with status {status}"))),
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            let success = error.is_none();
+            if let Err(e) = self.record(&webhook, kind, message, attempt, status, error).await {
+                error!(error = %e, "failed to record webhook delivery");
+                sentry::capture_error(&*e);
+            }
+
+            if success {
+                return;
+            }
+
+            if attempt < self.config.max_attempts {
+                tokio::time::sleep(backoff(&self.config, attempt)).await;
+            }
+        }
+
+        warn!(%message, attempts = self.config.max_attempts, "giving up on delivering event to webhook");
+    }
+
+    async fn send(&self, url: &str, secret: &Secret, message: Ulid, payload: &[u8]) -> eyre::Result<StatusCode> {
+        // the host is resolved again as it might resolve to a different address than
+        // when the webhook was created.
+        let url = url::Url::parse(url)?;
+        destination::check(&self.config, &url).await?;
+
+        let message = message.to_string();
+        let timestamp = Utc::now().timestamp();
+
+        self.http
+            .post(url)
+            .timeout(*self.config.timeout)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_ID, &message)
+            .header(WEBHOOK_TIMESTAMP, timestamp)
+            .header(WEBHOOK_SIGNATURE, secret.sign(&message, timestamp, payload))
+            .body(payload.to_vec())
+            .send()
+            .await
+            .map(|response| response.status())
+            .map_err(Into::into)
+    }
+
+    async fn record(
+        &self,
+        webhook: &webhook::Model,
+        kind: EventKind,
+        message: Ulid,
+        attempt: u32,
+        status: Option<StatusCode>,
+        error: Option<String>,
+    ) -> eyre::Result<()> {
+        let now = Utc::now();
+        let model = webhook::delivery::Model {
+            created_at: now,
+            updated_at: now,
+            status_code: status.map(|status| status.as_u16().into()),
+            success: error.is_none(),
+            error,
+            attempt: attempt.try_into()?,
+            event: kind.to_string(),
+            message,
+            webhook: webhook.id,
+            id: self.ulid.generate()?.into(),
+        };
+
+        WebhookDeliveryEntity::insert(model.into_active_model())
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns how long to wait after the `attempt`th attempt failed: the configured
+/// `backoff` doubled for every attempt after the first, up to `max_backoff`.
+pub fn backoff(config: &Config, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    config.backoff.saturating_mul(factor).min(*config.max_backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Bytes, extract::State, http::HeaderMap, routing};
+    use charted_config::database;
+    use charted_database::entities::{RepositoryEntity, repository};
+    use sea_orm::{QueryOrder, prelude::Json};
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    /// Requests that the test endpoint received.
+    type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+    fn config() -> Config {
+        Config {
+            enable: true,
+            max_attempts: 3,
+            backoff: Duration::from_millis(10).into(),
+            max_backoff: Duration::from_millis(20).into(),
+            timeout: Duration::from_secs(5).into(),
+            allowed_hosts: vec![String::from("127.0.0.1")],
+        }
+    }
+
+    /// Spawns a HTTP server that fails the first request and accepts all other ones.
+    async fn endpoint() -> (String, Received) {
+        async fn handler(State(received): State<Received>, headers: HeaderMap, body: Bytes) -> StatusCode {
+            let mut received = received.lock().unwrap();
+            received.push((headers, body));
+
+            match received.len() {
+                1 => StatusCode::INTERNAL_SERVER_ERROR,
+                _ => StatusCode::NO_CONTENT,
+            }
+        }
+
+        let received = Received::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new()
+            .route("/", routing::post(handler))
+            .with_state(received.clone());
+
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (format!("http://{addr}/"), received)
+    }
+
+    async fn create_db() -> DatabaseConnection {
+        charted_database::create_pool(&database::Config::SQLite(database::sqlite::Config {
+            common: Default::default(),
+            path: String::from(":memory:").into(),
+        }))
+        .await
+        .expect("failed to create database pool")
+    }
+
+    fn build_webhook(id: &str, url: &str, repository: Option<Ulid>, events: &[EventKind]) -> webhook::Model {
+        webhook::Model {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            repository,
+            events: Json::from(serde_json::to_value(events).unwrap()),
+            secret: Secret::generate().to_string(),
+            enabled: true,
+            owner: Ulid::new("01JQBR2V6V6MJ3D1JZ2GBKG9YW").unwrap(),
+            url: url.to_owned(),
+            id: Ulid::new(id).unwrap(),
+        }
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let config = Config {
+            backoff: Duration::from_secs(5).into(),
+            max_backoff: Duration::from_secs(60).into(),
+            ..config()
+        };
+
+        assert_eq!(backoff(&config, 1), Duration::from_secs(5));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(10));
+        assert_eq!(backoff(&config, 3), Duration::from_secs(20));
+        assert_eq!(backoff(&config, 4), Duration::from_secs(40));
+        assert_eq!(backoff(&config, 5), Duration::from_secs(60));
+        assert_eq!(backoff(&config, 64), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn subscribers() {
+        let db = create_db().await;
+        let repository_id = Ulid::new("01JQBR3C9WZ2C3XJ5QGCZV4T4Q").unwrap();
+        let dispatcher = Dispatcher::new(config(), db.clone(), reqwest::Client::new());
+
+        RepositoryEntity::insert(
+            repository::Model {
+                description: None,
+                deprecated: false,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                icon_hash: None,
+                private: false,
+                creator: None,
+                owner: Ulid::new("01JQBR2V6V6MJ3D1JZ2GBKG9YW").unwrap(),
+                latest_version: None,
+                latest_prerelease: None,
+                name: "hello-world".parse().unwrap(),
+                type_: Default::default(),
+                id: repository_id,
+            }
+            .into_active_model(),
+        )
+        .exec(&db)
+        .await
+        .unwrap();
+
+        for model in [
+            build_webhook("01JQBR4A3C5T4Y9N9B1F2K6Z7M", "http://localhost/owner", None, &[
+                EventKind::ReleaseCreated,
+            ]),
+            build_webhook(
+                "01JQBR4A3C5T4Y9N9B1F2K6Z7N",
+                "http://localhost/repo",
+                Some(repository_id),
+                &[EventKind::ReleaseCreated],
+            ),
+            build_webhook("01JQBR4A3C5T4Y9N9B1F2K6Z7P", "http://localhost/members", None, &[
+                EventKind::MemberAdded,
+            ]),
+        ] {
+            WebhookEntity::insert(model.into_active_model())
+                .exec(&db)
+                .await
+                .unwrap();
+        }
+
+        let owner = Ulid::new("01JQBR2V6V6MJ3D1JZ2GBKG9YW").unwrap();
+        let subscribers = |repository, kind| {
+            let dispatcher = dispatcher.clone();
+            async move {
+                let mut urls = dispatcher
+                    .subscribers(Target { owner, repository }, kind)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|webhook| webhook.url)
+                    .collect::<Vec<_>>();
+
+                urls.sort();
+                urls
+            }
+        };
+
+        assert_eq!(subscribers(Some(repository_id), EventKind::ReleaseCreated).await, [
+            "http://localhost/owner",
+            "http://localhost/repo"
+        ]);
+
+        assert_eq!(subscribers(None, EventKind::ReleaseCreated).await, [
+            "http://localhost/owner"
+        ]);
+        assert_eq!(subscribers(None, EventKind::MemberAdded).await, [
+            "http://localhost/members"
+        ]);
+        assert!(subscribers(None, EventKind::RepositoryDeleted).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn deliver_with_retries() {
+        let db = create_db().await;
+        let (url, received) = endpoint().await;
+        let dispatcher = Dispatcher::new(config(), db.clone(), reqwest::Client::new());
+
+        let model = build_webhook("01JQBR4A3C5T4Y9N9B1F2K6Z7M", &url, None, &[EventKind::ReleaseCreated]);
+        WebhookEntity::insert(model.clone().into_active_model())
+            .exec(&db)
+            .await
+            .unwrap();
+
+        let payload: Arc<[u8]> = br#"{"type":"release.created"}"#.to_vec().into();
+        dispatcher
+            .deliver(model.clone(), EventKind::ReleaseCreated, payload.clone())
+            .await;
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+
+        let secret = Secret::parse(&model.secret).unwrap();
+        let (headers, body) = &received[1];
+        let id = headers[WEBHOOK_ID].to_str().unwrap();
+        let timestamp = headers[WEBHOOK_TIMESTAMP].to_str().unwrap().parse::<i64>().unwrap();
+
+        assert_eq!(body.as_ref(), payload.as_ref());
+        assert_eq!(
+            received[0].0[WEBHOOK_ID], id,
+            "retries should reuse the same message id"
+        );
+        assert_eq!(headers[WEBHOOK_SIGNATURE], secret.sign(id, timestamp, &payload));
+
+        let deliveries = WebhookDeliveryEntity::find()
+            .filter(webhook::delivery::Column::Webhook.eq(model.id))
+            .order_by_asc(webhook::delivery::Column::Attempt)
+            .all(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(deliveries.len(), 2);
+        assert!(!deliveries[0].success);
+        assert_eq!(deliveries[0].status_code, Some(500));
+        assert!(deliveries[1].success);
+        assert_eq!(deliveries[1].status_code, Some(204));
+        assert_eq!(deliveries[1].attempt, 2);
+        assert_eq!(deliveries[1].message.to_string(), id);
+    }
+
+    #[tokio::test]
+    async fn deliver_refuses_non_public_hosts() {
+        let db = create_db().await;
+        let (url, received) = endpoint().await;
+        let config = Config {
+            max_attempts: 1,
+            allowed_hosts: Vec::new(),
+            ..config()
+        };
+
+        let dispatcher = Dispatcher::new(config, db.clone(), reqwest::Client::new());
+        let model = build_webhook("01JQBR4A3C5T4Y9N9B1F2K6Z7M", &url, None, &[EventKind::ReleaseCreated]);
+        WebhookEntity::insert(model.clone().into_active_model())
+            .exec(&db)
+            .await
+            .unwrap();
+
+        dispatcher
+            .deliver(model.clone(), EventKind::ReleaseCreated, b"{}".to_vec().into())
+            .await;
+
+        assert!(received.lock().unwrap().is_empty());
+
+        let deliveries = WebhookDeliveryEntity::find()
+            .filter(webhook::delivery::Column::Webhook.eq(model.id))
+            .all(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(deliveries.len(), 1);
+        assert!(!deliveries[0].success);
+        assert_eq!(deliveries[0].status_code, None);
+    }
+}
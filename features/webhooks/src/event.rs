@@ -0,0 +1,153 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Events that are delivered to webhooks.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt::Display, str::FromStr};
+use utoipa::ToSchema;
+
+macro_rules! mk_event_kinds {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident => $ty:literal;
+    )*) => {
+        /// Type of a [`Event`], which webhooks can subscribe to.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+        pub enum EventKind {
+            $(
+                $(#[$meta])*
+                #[serde(rename = $ty)]
+                $name,
+            )*
+        }
+
+        impl EventKind {
+            /// All the available event types.
+            pub const ALL: &[EventKind] = &[$(EventKind::$name,)*];
+
+            /// Returns the type of this event, as it appears in the payload's `type` field.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $(EventKind::$name => $ty,)*
+                }
+            }
+        }
+
+        impl FromStr for EventKind {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($ty => Ok(EventKind::$name),)*
+                    _ => Err(format!("unknown event type: {s}")),
+                }
+            }
+        }
+    };
+}
+
+mk_event_kinds! {
+    /// A repository was created.
+    RepositoryCreated => "repository.created";
+
+    /// A repository's metadata was updated.
+    RepositoryUpdated => "repository.updated";
+
+    /// A repository was deleted.
+    RepositoryDeleted => "repository.deleted";
+
+    /// A new release was published.
+    ReleaseCreated => "release.created";
+
+    /// A release's metadata was updated, or it was yanked or unyanked.
+    ReleaseUpdated => "release.updated";
+
+    /// A release was deleted.
+    ReleaseDeleted => "release.deleted";
+
+    /// A user became a member of a repository or organization.
+    MemberAdded => "member.added";
+
+    /// A member's permissions were updated.
+    MemberUpdated => "member.updated";
+
+    /// A member was removed from a repository or organization.
+    MemberRemoved => "member.removed";
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Payload of a webhook delivery, as described in the [Standard Webhooks specification].
+///
+/// [Standard Webhooks specification]: https://github.com/standard-webhooks/standard-webhooks/blob/main/spec/standard-webhooks.md#payload-structure
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    /// Type of this event.
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+
+    /// When this event happened.
+    pub timestamp: DateTime<Utc>,
+
+    /// Data of the resource that this event is about.
+    pub data: Value,
+}
+
+impl Event {
+    /// Creates a new [`Event`] that happened now. `data` is usually the resource that
+    /// the event is about, like a [`Repository`][charted_types::Repository].
+    pub fn new<T: Serialize>(kind: EventKind, data: T) -> Event {
+        Event {
+            kind,
+            timestamp: Utc::now(),
+            data: serde_json::to_value(data).unwrap_or(Value::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for kind in EventKind::ALL {
+            assert_eq!(kind.as_str().parse::<EventKind>(), Ok(*kind));
+            assert_eq!(
+                serde_json::to_value(kind).unwrap(),
+                Value::String(kind.as_str().to_owned())
+            );
+        }
+
+        assert!("release.published".parse::<EventKind>().is_err());
+    }
+
+    #[test]
+    fn payload() {
+        let event = Event::new(EventKind::ReleaseCreated, serde_json::json!({"tag": "1.0.0"}));
+        let payload = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(payload["type"], "release.created");
+        assert_eq!(payload["data"]["tag"], "1.0.0");
+        assert!(payload["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,67 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # 🐻‍❄️📦 `charted-feature-webhooks`
+//! This crate implements the **webhooks** server feature, which delivers events that
+//! happen in repositories and organizations to HTTP endpoints as described in the
+//! [Standard Webhooks Specification v1.0].
+//!
+//! Webhooks either belong to a single repository, or to a user or organization in
+//! which case they receive the events of everything that is owned by them. Every
+//! delivery attempt is recorded so that the owner can see why a delivery failed.
+//!
+//! [Standard Webhooks Specification v1.0]: https://github.com/standard-webhooks/standard-webhooks/blob/main/spec/standard-webhooks.md
+
+pub mod destination;
+pub mod event;
+pub mod signature;
+
+mod dispatcher;
+mod webhook;
+
+use charted_feature::Metadata;
+pub use dispatcher::*;
+pub use webhook::*;
+
+#[derive(Debug, Clone)]
+pub struct Feature {
+    dispatcher: Dispatcher,
+}
+
+impl Feature {
+    pub fn new(dispatcher: Dispatcher) -> Feature {
+        Feature { dispatcher }
+    }
+
+    /// Returns the [`Dispatcher`] that delivers events to webhooks.
+    pub fn dispatcher(&self) -> &Dispatcher {
+        &self.dispatcher
+    }
+}
+
+impl charted_feature::Feature for Feature {
+    fn metadata(&self) -> Metadata {
+        const METADATA: Metadata = Metadata {
+            name: "Webhooks",
+            config_key: "webhooks",
+            description: env!("CARGO_PKG_DESCRIPTION"),
+            authors: &["Noelware, LLC. <team@noelware.org>"],
+            since: "0.1.0",
+            deprecated: None,
+        };
+
+        METADATA
+    }
+}
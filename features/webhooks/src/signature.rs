@@ -0,0 +1,100 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signing of webhook payloads, as described in the [Standard Webhooks specification].
+//!
+//! [Standard Webhooks specification]: https://github.com/standard-webhooks/standard-webhooks/blob/main/spec/standard-webhooks.md#signature-scheme
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::fmt::Display;
+
+/// Prefix of a serialized [`Secret`].
+const PREFIX: &str = "whsec_";
+
+/// A symmetric secret that is shared between charted-server and the webhook's receiver.
+///
+/// Secrets are serialized as `whsec_` and the base64-encoded key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    /// Generates a new 32-byte secret.
+    pub fn generate() -> Secret {
+        let mut key = vec![0u8; 32];
+        rand::rng().fill_bytes(&mut key);
+
+        Secret(key)
+    }
+
+    /// Parses a secret from its serialized form. The `whsec_` prefix is optional.
+    pub fn parse(secret: &str) -> Result<Secret, base64::DecodeError> {
+        STANDARD
+            .decode(secret.strip_prefix(PREFIX).unwrap_or(secret))
+            .map(Secret)
+    }
+
+    /// Signs a payload and returns the value of the `webhook-signature` header.
+    ///
+    /// `id` and `timestamp` must be the same values as the `webhook-id` and
+    /// `webhook-timestamp` headers.
+    pub fn sign(&self, id: &str, timestamp: i64, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any size");
+        mac.update(format!("{id}.{timestamp}.").as_bytes());
+        mac.update(payload);
+
+        format!("v1,{}", STANDARD.encode(mac.finalize().into_bytes()))
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{PREFIX}{}", STANDARD.encode(&self.0))
+    }
+}
+
+// secrets shouldn't end up in logs
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vector from the Standard Webhooks reference implementations
+    #[test]
+    fn sign() {
+        let secret = Secret::parse("whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw").unwrap();
+        assert_eq!(
+            secret.sign("msg_p5jXN8AQM9LWM0D4loKWxJek", 1614265330, br#"{"test": 2432232314}"#),
+            "v1,g0hM9SsE+OTPJTGt/tmIKtSyZlE3uFJELVlNIOLJ1OE="
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let secret = Secret::generate();
+        let serialized = secret.to_string();
+
+        assert!(serialized.starts_with(PREFIX));
+        assert_eq!(Secret::parse(&serialized).unwrap(), secret);
+        assert_eq!(format!("{secret:?}"), "Secret(<redacted>)");
+    }
+}
@@ -0,0 +1,175 @@
+// 🐻‍❄️📦 charted-server: Free, open source, and reliable Helm Chart registry made in Rust
+// Copyright 2022-2025 Noelware, LLC. <team@noelware.org>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! API resources of the webhooks feature.
+
+use crate::event::EventKind;
+use charted_database::entities::webhook;
+use charted_types::{DateTime, Ulid};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A HTTP endpoint that receives events from a repository, or from all repositories that
+/// are owned by a user or organization.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Webhook {
+    /// datetime of when this webhook was created.
+    #[schema(read_only)]
+    pub created_at: DateTime,
+
+    /// datetime of when this webhook was last modified.
+    #[schema(read_only)]
+    pub updated_at: DateTime,
+
+    /// The repository that this webhook receives events from. If `null`, then it receives
+    /// events from everything that the owner owns.
+    #[serde(default)]
+    #[schema(read_only)]
+    pub repository: Option<Ulid>,
+
+    /// List of event types that this webhook is subscribed to.
+    pub events: Vec<EventKind>,
+
+    /// The secret that payloads are signed with. This is only a non-null value when the
+    /// webhook was first created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(read_only)]
+    pub secret: Option<String>,
+
+    /// whether if events are delivered to this webhook.
+    pub enabled: bool,
+
+    /// the user or organization that owns this webhook.
+    #[schema(read_only)]
+    pub owner: Ulid,
+
+    /// HTTP endpoint that events are delivered to.
+    pub url: String,
+
+    /// the webhook's unique identifier.
+    #[schema(read_only)]
+    pub id: Ulid,
+}
+
+impl Webhook {
+    /// Sanitizes the output of this [`Webhook`] by setting the [`secret`] field to
+    /// [`None`].
+    ///
+    /// [`secret`]: #structfield.secret
+    pub fn sanitize(self) -> Self {
+        Self { secret: None, ..self }
+    }
+}
+
+impl From<webhook::Model> for Webhook {
+    fn from(model: webhook::Model) -> Self {
+        Webhook {
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+            repository: model.repository,
+            events: serde_json::from_value(model.events).unwrap_or_default(),
+            secret: Some(model.secret),
+            enabled: model.enabled,
+            owner: model.owner,
+            url: model.url,
+            id: model.id,
+        }
+    }
+}
+
+/// A single attempt of delivering an event to a [`Webhook`].
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    /// datetime of when this attempt was made.
+    pub created_at: DateTime,
+
+    /// HTTP status code that the endpoint responded with, if it responded at all.
+    #[serde(default)]
+    pub status_code: Option<u16>,
+
+    /// Why the attempt failed, if it didn't succeed.
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// whether if the endpoint accepted the event.
+    pub success: bool,
+
+    /// Which attempt this was, starting from `1`.
+    pub attempt: u32,
+
+    /// The type of the event that was delivered.
+    pub event: EventKind,
+
+    /// The `webhook-id` that was sent. All attempts of the same event share it.
+    pub message: Ulid,
+
+    /// The webhook that the event was delivered to.
+    pub webhook: Ulid,
+
+    /// the attempt's unique identifier.
+    pub id: Ulid,
+}
+
+impl TryFrom<webhook::delivery::Model> for WebhookDelivery {
+    type Error = String;
+
+    fn try_from(model: webhook::delivery::Model) -> Result<Self, Self::Error> {
+        Ok(WebhookDelivery {
+            created_at: model.created_at.into(),
+            status_code: model.status_code.and_then(|code| code.try_into().ok()),
+            error: model.error,
+            success: model.success,
+            attempt: model.attempt.try_into().unwrap_or_default(),
+            event: model.event.parse()?,
+            message: model.message,
+            webhook: model.webhook,
+            id: model.id,
+        })
+    }
+}
+
+/// Payload object for creating a webhook.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhookPayload {
+    /// HTTP endpoint that events are delivered to.
+    pub url: url::Url,
+
+    /// List of event types that this webhook is subscribed to.
+    pub events: Vec<EventKind>,
+
+    /// whether if events are delivered to this webhook.
+    #[serde(default = "__truthy")]
+    pub enabled: bool,
+}
+
+/// Payload object for patching the metadata of a webhook.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct PatchWebhookPayload {
+    /// changes the HTTP endpoint that events are delivered to.
+    #[serde(default)]
+    pub url: Option<url::Url>,
+
+    /// changes the event types that this webhook is subscribed to.
+    #[serde(default)]
+    pub events: Option<Vec<EventKind>>,
+
+    /// enables or disables deliveries to this webhook.
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+const fn __truthy() -> bool {
+    true
+}